@@ -0,0 +1,391 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+use overlord::types::{
+    AggregatedVote, Proposal as OverlordProposal, SignedProposal, SignedVote, Vote, VoteType,
+};
+use overlord::Crypto;
+use parking_lot::Mutex;
+use rand::random;
+use rlp::Encodable;
+
+use common_crypto::{
+    BlsPrivateKey, PublicKey, Secp256k1PrivateKey, ToPublicKey, UncompressedPublicKey,
+};
+use core_consensus::message::{
+    END_GOSSIP_AGGREGATED_VOTE, END_GOSSIP_SIGNED_PROPOSAL, END_GOSSIP_SIGNED_VOTE,
+};
+use core_consensus::util::{time_now, OverlordCrypto};
+use core_consensus::{ConsensusError, ConsensusType};
+use core_mempool::MsgNewTxs;
+use protocol::codec::{hex_decode, ProtocolCodec};
+use protocol::traits::MessageTarget;
+use protocol::types::{
+    Address, Block, BlockNumber, Bytes, Hash, Hasher, Header, Proof, Proposal, Public,
+    SignatureComponents, SignedTransaction, Transaction, TransactionAction, UnverifiedTransaction,
+    H160, U256,
+};
+use protocol::ProtocolResult;
+
+use crate::config::{Behaviour, ByzantineConfig};
+
+/// The proposals and the quorum certificates are kept for this many heights.
+const HISTORY_WINDOW: u64 = 8;
+
+/// A consensus message to be sent by the byzantine node.
+#[derive(Clone, Debug)]
+pub struct Action {
+    pub end:    &'static str,
+    pub msg:    Vec<u8>,
+    pub target: MessageTarget,
+}
+
+/// Craft the byzantine messages in reaction to the consensus messages of the
+/// honest validators. It does not touch the network, so that the same logic
+/// drives both the byzantine node and the in-process tests.
+pub struct Attacker {
+    config:     ByzantineConfig,
+    crypto:     OverlordCrypto,
+    pub_key:    Bytes,
+    address:    H160,
+    /// The public keys of the other validators, sorted.
+    validators: Vec<Bytes>,
+
+    proposals: Mutex<BTreeMap<(u64, Bytes), Proposal>>,
+    qcs:       Mutex<BTreeMap<u64, Vec<AggregatedVote>>>,
+}
+
+impl Attacker {
+    pub fn new(config: ByzantineConfig) -> ProtocolResult<Self> {
+        let privkey = hex_decode(&config.privkey.as_string_trim0x())?;
+        let bls_privkey = BlsPrivateKey::try_from(privkey.as_ref())
+            .map_err(|e| ConsensusError::CryptoErr(Box::new(e)))?;
+        let pub_key = Secp256k1PrivateKey::try_from(privkey.as_ref())
+            .map_err(|e| ConsensusError::CryptoErr(Box::new(e)))?
+            .pub_key();
+        let address = Address::from_pubkey_bytes(pub_key.to_uncompressed_bytes())?.0;
+        let pub_key = pub_key.to_bytes();
+
+        let mut validators = config
+            .verifier_list
+            .iter()
+            .map(|v| v.pub_key.as_bytes())
+            .filter(|v| *v != pub_key)
+            .collect::<Vec<_>>();
+        validators.sort();
+
+        Ok(Attacker {
+            crypto: OverlordCrypto::new(bls_privkey, Default::default(), String::new()),
+            config,
+            pub_key,
+            address,
+            validators,
+            proposals: Mutex::new(BTreeMap::new()),
+            qcs: Mutex::new(BTreeMap::new()),
+        })
+    }
+
+    pub fn config(&self) -> &ByzantineConfig {
+        &self.config
+    }
+
+    pub fn pub_key(&self) -> Bytes {
+        self.pub_key.clone()
+    }
+
+    /// Decode a consensus message received from the endpoint and react to it.
+    /// The messages other than the proposals and the quorum certificates are
+    /// ignored.
+    pub fn react(&self, end: &str, msg: &[u8]) -> ProtocolResult<Vec<Action>> {
+        match end {
+            END_GOSSIP_SIGNED_PROPOSAL => {
+                let signed_proposal = SignedProposal::<Proposal>::decode(msg)
+                    .map_err(|_| ConsensusError::DecodeErr(ConsensusType::SignedProposal))?;
+                self.on_proposal(signed_proposal)
+            }
+            END_GOSSIP_AGGREGATED_VOTE => {
+                let qc = AggregatedVote::decode(msg)
+                    .map_err(|_| ConsensusError::DecodeErr(ConsensusType::AggregateVote))?;
+                self.on_qc(qc)
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Prevote for the proposal unless the votes are withheld, and propose two
+    /// conflicting blocks of the same content for the next round.
+    pub fn on_proposal(
+        &self,
+        signed_proposal: SignedProposal<Proposal>,
+    ) -> ProtocolResult<Vec<Action>> {
+        let proposal = signed_proposal.proposal;
+        let mut actions = Vec::new();
+
+        if !self.config.has(&Behaviour::WithholdVote) {
+            actions.push(self.vote(
+                proposal.height,
+                proposal.round,
+                VoteType::Prevote,
+                proposal.block_hash.clone(),
+                proposal.proposer.clone(),
+            )?);
+        }
+
+        if self.config.has(&Behaviour::ConflictingProposal) {
+            actions.extend(self.conflicting_proposals(
+                proposal.height,
+                proposal.round + 1,
+                proposal.content.clone(),
+            )?);
+        }
+
+        let mut proposals = self.proposals.lock();
+        proposals.insert((proposal.height, proposal.block_hash), proposal.content);
+        let boundary = proposal.height.saturating_sub(HISTORY_WINDOW);
+        *proposals = proposals.split_off(&(boundary, Bytes::new()));
+
+        Ok(actions)
+    }
+
+    /// Precommit for a prevote quorum certificate unless the votes are
+    /// withheld. A precommit quorum certificate commits the block, after which
+    /// two conflicting blocks are proposed for the next height. The quorum
+    /// certificates of the past heights are replayed on each new one.
+    ///
+    /// The node does not execute the blocks, so the previous hash of the
+    /// proposals of the next height is stale and they are expected to fail the
+    /// block check of the honest validators.
+    pub fn on_qc(&self, qc: AggregatedVote) -> ProtocolResult<Vec<Action>> {
+        let mut actions = Vec::new();
+        let is_nil = qc.block_hash.is_empty();
+
+        match qc.vote_type {
+            VoteType::Prevote if !is_nil && !self.config.has(&Behaviour::WithholdVote) => {
+                actions.push(self.vote(
+                    qc.height,
+                    qc.round,
+                    VoteType::Precommit,
+                    qc.block_hash.clone(),
+                    qc.leader.clone(),
+                )?);
+            }
+            VoteType::Precommit if !is_nil && self.config.has(&Behaviour::ConflictingProposal) => {
+                let content = self
+                    .proposals
+                    .lock()
+                    .get(&(qc.height, qc.block_hash.clone()))
+                    .cloned();
+                if let Some(mut content) = content {
+                    content.number = qc.height + 1;
+                    content.proof = Proof {
+                        number:     qc.height,
+                        round:      qc.round,
+                        block_hash: Hash::from_slice(qc.block_hash.as_ref()),
+                        signature:  qc.signature.signature.clone(),
+                        bitmap:     qc.signature.address_bitmap.clone(),
+                    };
+                    actions.extend(self.conflicting_proposals(qc.height + 1, 0, content)?);
+                }
+            }
+            _ => (),
+        }
+
+        if self.config.has(&Behaviour::ReplayQc) {
+            actions.extend(self.replay_qcs(qc.height));
+        }
+
+        let mut qcs = self.qcs.lock();
+        let height = qc.height;
+        qcs.entry(height).or_insert_with(Vec::new).push(qc);
+        *qcs = qcs.split_off(&height.saturating_sub(HISTORY_WINDOW));
+
+        Ok(actions)
+    }
+
+    /// The transactions whose signatures do not match the claimed public keys
+    /// and senders.
+    pub fn invalid_txs(&self, count: usize) -> MsgNewTxs {
+        let batch_stxs = (0..count)
+            .map(|_| {
+                let transaction = UnverifiedTransaction {
+                    unsigned:  Transaction {
+                        nonce:                    U256::from(random::<u32>()),
+                        max_priority_fee_per_gas: U256::one(),
+                        gas_price:                U256::one(),
+                        gas_limit:                U256::from(21000u64),
+                        action:                   TransactionAction::Call(H160::from_slice(
+                            &random_bytes(20),
+                        )),
+                        value:                    U256::zero(),
+                        data:                     Bytes::new(),
+                        access_list:              Vec::new(),
+                    },
+                    signature: Some(SignatureComponents {
+                        r:          random_bytes(32),
+                        s:          random_bytes(32),
+                        standard_v: 0,
+                    }),
+                    chain_id:  self.config.chain_id,
+                    hash:      Default::default(),
+                }
+                .calc_hash();
+
+                SignedTransaction {
+                    transaction,
+                    sender: H160::from_slice(&random_bytes(20)),
+                    public: Some(Public::from_slice(&random_bytes(64))),
+                }
+            })
+            .collect();
+
+        MsgNewTxs { batch_stxs }
+    }
+
+    /// A block that either has a wrong number or does not match the
+    /// transactions served along with it.
+    pub fn malformed_block(&self, number: BlockNumber) -> Block {
+        let mut header = Header {
+            number,
+            chain_id: self.config.chain_id,
+            proposer: self.address,
+            timestamp: time_now(),
+            ..Default::default()
+        };
+
+        if random::<bool>() {
+            header.number += 1;
+        } else {
+            header.signed_txs_hash = Hasher::digest(random_bytes(32));
+        }
+
+        Block {
+            header,
+            tx_hashes: Vec::new(),
+        }
+    }
+
+    pub fn malformed_txs(&self) -> Vec<SignedTransaction> {
+        self.invalid_txs(1).batch_stxs
+    }
+
+    /// A proof of the number with a garbage signature and bitmap.
+    pub fn malformed_proof(&self, number: BlockNumber) -> Proof {
+        Proof {
+            number,
+            round: 0,
+            block_hash: Hasher::digest(random_bytes(32)),
+            signature: random_bytes(96),
+            bitmap: random_bytes(1),
+        }
+    }
+
+    /// Sign two proposals of the content differing in the extra data and send
+    /// each of them to a half of the other validators.
+    fn conflicting_proposals(
+        &self,
+        height: u64,
+        round: u64,
+        content: Proposal,
+    ) -> ProtocolResult<Vec<Action>> {
+        let first = self.propose(height, round, content.clone(), 0)?;
+        let second = self.propose(height, round, content, 1)?;
+        let half = (self.validators.len() + 1) / 2;
+
+        Ok(self
+            .validators
+            .iter()
+            .enumerate()
+            .map(|(i, pub_key)| {
+                let signed_proposal = if i < half { &first } else { &second };
+                Action {
+                    end:    END_GOSSIP_SIGNED_PROPOSAL,
+                    msg:    signed_proposal.rlp_bytes().freeze().to_vec(),
+                    target: MessageTarget::Specified(pub_key.clone()),
+                }
+            })
+            .collect())
+    }
+
+    fn propose(
+        &self,
+        height: u64,
+        round: u64,
+        mut content: Proposal,
+        extra_data: u8,
+    ) -> ProtocolResult<SignedProposal<Proposal>> {
+        content.proposer = self.address;
+        content.timestamp = content.timestamp.max(time_now());
+        content.extra_data = Bytes::from(vec![extra_data]);
+
+        let block_hash = Bytes::from(Hasher::digest(content.encode()?).as_bytes().to_vec());
+        let proposal = OverlordProposal {
+            height,
+            round,
+            content,
+            block_hash,
+            lock: None,
+            proposer: self.pub_key.clone(),
+        };
+        let signature = self.sign(Bytes::from(rlp::encode(&proposal)))?;
+
+        Ok(SignedProposal {
+            signature,
+            proposal,
+        })
+    }
+
+    fn vote(
+        &self,
+        height: u64,
+        round: u64,
+        vote_type: VoteType,
+        block_hash: Bytes,
+        leader: Bytes,
+    ) -> ProtocolResult<Action> {
+        let vote = Vote {
+            height,
+            round,
+            vote_type,
+            block_hash,
+        };
+        let signature = self.sign(Bytes::from(rlp::encode(&vote)))?;
+        let signed_vote = SignedVote {
+            signature,
+            vote,
+            voter: self.pub_key.clone(),
+        };
+
+        Ok(Action {
+            end:    END_GOSSIP_SIGNED_VOTE,
+            msg:    signed_vote.rlp_bytes().freeze().to_vec(),
+            target: MessageTarget::Specified(leader),
+        })
+    }
+
+    fn replay_qcs(&self, height: u64) -> Vec<Action> {
+        let qcs = self.qcs.lock();
+        let end = match height.checked_sub(self.config.replay_lag) {
+            Some(end) => end,
+            None => return Vec::new(),
+        };
+
+        qcs.range(..=end)
+            .flat_map(|(_, qcs)| qcs.iter())
+            .map(|qc| Action {
+                end:    END_GOSSIP_AGGREGATED_VOTE,
+                msg:    qc.rlp_bytes().freeze().to_vec(),
+                target: MessageTarget::Broadcast,
+            })
+            .collect()
+    }
+
+    fn sign(&self, msg: Bytes) -> ProtocolResult<Bytes> {
+        self.crypto
+            .sign(self.crypto.hash(msg))
+            .map_err(|e| ConsensusError::Other(e.to_string()).into())
+    }
+}
+
+fn random_bytes(len: usize) -> Bytes {
+    (0..len).map(|_| random::<u8>()).collect::<Vec<_>>().into()
+}
@@ -0,0 +1,68 @@
+use std::path::PathBuf;
+
+use core_network::multiaddr::Multiaddr;
+use serde::Deserialize;
+
+use protocol::types::{Hex, ValidatorExtend};
+
+/// The number of milliseconds between two batches of invalid transactions.
+const DEFAULT_SPAM_INTERVAL: u64 = 1000;
+const DEFAULT_SPAM_BATCH: usize = 20;
+/// The quorum certificates of the heights at least this far behind the
+/// newest one are replayed.
+const DEFAULT_REPLAY_LAG: u64 = 1;
+
+/// A misbehaviour of the byzantine node. The node keeps following the
+/// consensus as an honest validator in the aspects not configured.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Behaviour {
+    /// Propose two different blocks for the same height and round, each of
+    /// them to a half of the validators.
+    ConflictingProposal,
+    /// Never send prevotes or precommits.
+    WithholdVote,
+    /// Broadcast the quorum certificates of the past heights again.
+    ReplayQc,
+    /// Gossip the transactions with broken signatures.
+    SpamInvalidTx,
+    /// Answer the synchronization requests with the blocks, transactions and
+    /// proofs that do not match each other.
+    MalformedSyncResponse,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ByzantineConfig {
+    pub privkey:       Hex,
+    pub chain_id:      u64,
+    pub data_path:     PathBuf,
+    pub listen_addr:   Multiaddr,
+    #[serde(default)]
+    pub bootstraps:    Vec<Multiaddr>,
+    pub verifier_list: Vec<ValidatorExtend>,
+    pub behaviours:    Vec<Behaviour>,
+    #[serde(default = "default_spam_interval")]
+    pub spam_interval: u64,
+    #[serde(default = "default_spam_batch")]
+    pub spam_batch:    usize,
+    #[serde(default = "default_replay_lag")]
+    pub replay_lag:    u64,
+}
+
+impl ByzantineConfig {
+    pub fn has(&self, behaviour: &Behaviour) -> bool {
+        self.behaviours.contains(behaviour)
+    }
+}
+
+fn default_spam_interval() -> u64 {
+    DEFAULT_SPAM_INTERVAL
+}
+
+fn default_spam_batch() -> usize {
+    DEFAULT_SPAM_BATCH
+}
+
+fn default_replay_lag() -> u64 {
+    DEFAULT_REPLAY_LAG
+}
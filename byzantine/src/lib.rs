@@ -1,8 +1,11 @@
+#![allow(clippy::mutable_key_type)]
+
+pub mod attacker;
+pub mod config;
+pub mod node;
 #[cfg(test)]
-mod tests {
-    #[test]
-    fn it_works() {
-        let result = 2 + 2;
-        assert_eq!(result, 4);
-    }
-}
+mod tests;
+
+pub use crate::attacker::{Action, Attacker};
+pub use crate::config::{Behaviour, ByzantineConfig};
+pub use crate::node::ByzantineNode;
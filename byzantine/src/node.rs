@@ -0,0 +1,257 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use core_consensus::message::{
+    Proposal as ProposalMessage, PullTxsRequest, END_GOSSIP_AGGREGATED_VOTE,
+    END_GOSSIP_SIGNED_PROPOSAL, QC, RPC_RESP_SYNC_PULL_BLOCK, RPC_RESP_SYNC_PULL_PROOF,
+    RPC_RESP_SYNC_PULL_TXS, RPC_SYNC_PULL_BLOCK, RPC_SYNC_PULL_PROOF, RPC_SYNC_PULL_TXS,
+};
+use core_mempool::END_GOSSIP_NEW_TXS;
+use core_network::{NetworkConfig, NetworkService, PeerId, PeerIdExt};
+use protocol::traits::{
+    Context, Gossip, MessageHandler, MessageTarget, Priority, Rpc, TrustFeedback,
+};
+use protocol::types::{BatchSignedTxs, BlockNumber};
+use protocol::{async_trait, tokio, ProtocolResult};
+
+use crate::attacker::{Action, Attacker};
+use crate::config::{Behaviour, ByzantineConfig};
+
+/// A validator node that joins the network with the honest ones and
+/// misbehaves as configured.
+pub struct ByzantineNode {
+    attacker: Arc<Attacker>,
+}
+
+impl ByzantineNode {
+    pub fn new(config: ByzantineConfig) -> ProtocolResult<Self> {
+        Ok(ByzantineNode {
+            attacker: Arc::new(Attacker::new(config)?),
+        })
+    }
+
+    pub async fn run(self) -> ProtocolResult<()> {
+        let config = self.attacker.config().clone();
+        let network_config = NetworkConfig::new()
+            .peer_store_dir(config.data_path.join("peer_store"))
+            .bootstraps(config.bootstraps.clone())
+            .listen_addr(config.listen_addr.clone())
+            .secio_keypair(&config.privkey.as_string_trim0x())?;
+
+        let mut network_service = NetworkService::new(network_config);
+        network_service.set_chain_id(config.chain_id.to_string());
+        let network = Arc::new(network_service.handle());
+
+        network_service.register_endpoint_handler(
+            END_GOSSIP_SIGNED_PROPOSAL,
+            ProposalHandler::new(Arc::clone(&self.attacker), Arc::clone(&network)),
+        )?;
+        network_service.register_endpoint_handler(
+            END_GOSSIP_AGGREGATED_VOTE,
+            QCHandler::new(Arc::clone(&self.attacker), Arc::clone(&network)),
+        )?;
+
+        if config.has(&Behaviour::MalformedSyncResponse) {
+            network_service.register_endpoint_handler(
+                RPC_SYNC_PULL_BLOCK,
+                PullBlockHandler::new(Arc::clone(&self.attacker), Arc::clone(&network)),
+            )?;
+            network_service.register_endpoint_handler(
+                RPC_SYNC_PULL_TXS,
+                PullTxsHandler::new(Arc::clone(&self.attacker), Arc::clone(&network)),
+            )?;
+            network_service.register_endpoint_handler(
+                RPC_SYNC_PULL_PROOF,
+                PullProofHandler::new(Arc::clone(&self.attacker), Arc::clone(&network)),
+            )?;
+        }
+
+        if config.has(&Behaviour::SpamInvalidTx) {
+            let attacker = Arc::clone(&self.attacker);
+            let network = Arc::clone(&network);
+            tokio::spawn(async move {
+                let interval = Duration::from_millis(config.spam_interval);
+                loop {
+                    tokio::time::sleep(interval).await;
+                    let msg = attacker.invalid_txs(config.spam_batch);
+                    if let Err(e) = network
+                        .broadcast(Context::new(), END_GOSSIP_NEW_TXS, msg, Priority::Normal)
+                        .await
+                    {
+                        log::warn!("[byzantine] spam invalid txs error {:?}", e);
+                    }
+                }
+            });
+        }
+
+        network_service.run().await;
+        Ok(())
+    }
+}
+
+async fn send<N: Gossip>(network: &N, ctx: Context, actions: Vec<Action>) {
+    for action in actions {
+        let res = match action.target {
+            MessageTarget::Broadcast => {
+                network
+                    .broadcast(ctx.clone(), action.end, action.msg, Priority::High)
+                    .await
+            }
+            MessageTarget::Specified(pub_key) => match PeerId::from_pubkey_bytes(pub_key) {
+                Ok(peer_id) => {
+                    network
+                        .multicast(
+                            ctx.clone(),
+                            action.end,
+                            [peer_id.into_bytes_ext()],
+                            action.msg,
+                            Priority::High,
+                        )
+                        .await
+                }
+                Err(e) => Err(e.into()),
+            },
+        };
+
+        if let Err(e) = res {
+            log::warn!("[byzantine] send {} error {:?}", action.end, e);
+        }
+    }
+}
+
+pub struct ProposalHandler<N> {
+    attacker: Arc<Attacker>,
+    network:  Arc<N>,
+}
+
+impl<N: Gossip + 'static> ProposalHandler<N> {
+    pub fn new(attacker: Arc<Attacker>, network: Arc<N>) -> Self {
+        ProposalHandler { attacker, network }
+    }
+}
+
+#[async_trait]
+impl<N: Gossip + 'static> MessageHandler for ProposalHandler<N> {
+    type Message = ProposalMessage;
+
+    async fn process(&self, ctx: Context, msg: Self::Message) -> TrustFeedback {
+        match self.attacker.react(END_GOSSIP_SIGNED_PROPOSAL, &msg.0) {
+            Ok(actions) => send(self.network.as_ref(), ctx, actions).await,
+            Err(e) => log::warn!("[byzantine] handle proposal error {:?}", e),
+        }
+
+        TrustFeedback::Neutral
+    }
+}
+
+pub struct QCHandler<N> {
+    attacker: Arc<Attacker>,
+    network:  Arc<N>,
+}
+
+impl<N: Gossip + 'static> QCHandler<N> {
+    pub fn new(attacker: Arc<Attacker>, network: Arc<N>) -> Self {
+        QCHandler { attacker, network }
+    }
+}
+
+#[async_trait]
+impl<N: Gossip + 'static> MessageHandler for QCHandler<N> {
+    type Message = QC;
+
+    async fn process(&self, ctx: Context, msg: Self::Message) -> TrustFeedback {
+        match self.attacker.react(END_GOSSIP_AGGREGATED_VOTE, &msg.0) {
+            Ok(actions) => send(self.network.as_ref(), ctx, actions).await,
+            Err(e) => log::warn!("[byzantine] handle qc error {:?}", e),
+        }
+
+        TrustFeedback::Neutral
+    }
+}
+
+pub struct PullBlockHandler<R> {
+    attacker: Arc<Attacker>,
+    rpc:      Arc<R>,
+}
+
+impl<R: Rpc + 'static> PullBlockHandler<R> {
+    pub fn new(attacker: Arc<Attacker>, rpc: Arc<R>) -> Self {
+        PullBlockHandler { attacker, rpc }
+    }
+}
+
+#[async_trait]
+impl<R: Rpc + 'static> MessageHandler for PullBlockHandler<R> {
+    type Message = BlockNumber;
+
+    async fn process(&self, ctx: Context, number: BlockNumber) -> TrustFeedback {
+        let block = self.attacker.malformed_block(number);
+        if let Err(e) = self
+            .rpc
+            .response(ctx, RPC_RESP_SYNC_PULL_BLOCK, Ok(block), Priority::High)
+            .await
+        {
+            log::warn!("[byzantine] push block {:?}", e);
+        }
+
+        TrustFeedback::Neutral
+    }
+}
+
+pub struct PullTxsHandler<R> {
+    attacker: Arc<Attacker>,
+    rpc:      Arc<R>,
+}
+
+impl<R: Rpc + 'static> PullTxsHandler<R> {
+    pub fn new(attacker: Arc<Attacker>, rpc: Arc<R>) -> Self {
+        PullTxsHandler { attacker, rpc }
+    }
+}
+
+#[async_trait]
+impl<R: Rpc + 'static> MessageHandler for PullTxsHandler<R> {
+    type Message = PullTxsRequest;
+
+    async fn process(&self, ctx: Context, _msg: PullTxsRequest) -> TrustFeedback {
+        let txs = BatchSignedTxs(self.attacker.malformed_txs());
+        if let Err(e) = self
+            .rpc
+            .response(ctx, RPC_RESP_SYNC_PULL_TXS, Ok(txs), Priority::High)
+            .await
+        {
+            log::warn!("[byzantine] push txs {:?}", e);
+        }
+
+        TrustFeedback::Neutral
+    }
+}
+
+pub struct PullProofHandler<R> {
+    attacker: Arc<Attacker>,
+    rpc:      Arc<R>,
+}
+
+impl<R: Rpc + 'static> PullProofHandler<R> {
+    pub fn new(attacker: Arc<Attacker>, rpc: Arc<R>) -> Self {
+        PullProofHandler { attacker, rpc }
+    }
+}
+
+#[async_trait]
+impl<R: Rpc + 'static> MessageHandler for PullProofHandler<R> {
+    type Message = BlockNumber;
+
+    async fn process(&self, ctx: Context, number: BlockNumber) -> TrustFeedback {
+        let proof = self.attacker.malformed_proof(number);
+        if let Err(e) = self
+            .rpc
+            .response(ctx, RPC_RESP_SYNC_PULL_PROOF, Ok(proof), Priority::High)
+            .await
+        {
+            log::warn!("[byzantine] push proof {:?}", e);
+        }
+
+        TrustFeedback::Neutral
+    }
+}
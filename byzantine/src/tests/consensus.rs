@@ -0,0 +1,193 @@
+use std::time::Duration;
+
+use common_crypto::BlsPublicKey;
+use core_consensus::status::{CurrentStatus, StatusAgent};
+use core_consensus::util::OverlordCrypto;
+use core_consensus::{ConsensusWal, DurationConfig, Node, OverlordConsensus, SignedTxsWAL};
+use protocol::tokio::{self, sync::Mutex as AsyncMutex, time::sleep};
+use protocol::traits::{Consensus, NodeInfo};
+
+use super::*;
+
+const TARGET_HEIGHT: u64 = 6;
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Start three honest validators and a byzantine one on the bus, and wait for
+/// the honest ones to commit the target height.
+async fn run_honest_nodes(
+    case: &str,
+    behaviours: Vec<Behaviour>,
+) -> (Arc<Bus>, Vec<Arc<MockAdapter>>) {
+    let keypairs = mock_keypairs();
+    let metadata = mock_metadata(keypairs.iter().map(|k| k.validator.clone()).collect());
+    let bus = Arc::new(Bus::default());
+
+    let attacker = Arc::new(mock_attacker(&keypairs[3], &metadata, behaviours));
+    bus.set_attacker(Arc::clone(&attacker));
+
+    let bls_pub_keys = metadata
+        .verifier_list
+        .iter()
+        .map(|v| {
+            let bls_pub_key = BlsPublicKey::try_from(v.bls_pub_key.as_bytes().as_ref()).unwrap();
+            (v.pub_key.as_bytes(), bls_pub_key)
+        })
+        .collect::<HashMap<_, _>>();
+    let mut authority_list = metadata
+        .verifier_list
+        .iter()
+        .map(|v| Node {
+            address:        v.pub_key.as_bytes(),
+            propose_weight: v.propose_weight,
+            vote_weight:    v.vote_weight,
+        })
+        .collect::<Vec<_>>();
+    authority_list.sort();
+
+    let mut adapters = Vec::new();
+    for (i, keypair) in keypairs.iter().take(3).enumerate() {
+        let path = format!("./free-space/byzantine/{}/{}", case, i);
+        let _ = std::fs::remove_dir_all(&path);
+
+        let pub_key = keypair.validator.pub_key.as_bytes();
+        let genesis = mock_chain(0);
+        let status = StatusAgent::new(CurrentStatus {
+            prev_hash: genesis[0].header_hash(),
+            last_number: 0,
+            tx_num_limit: metadata.tx_num_limit,
+            max_tx_size: metadata.max_tx_size.into(),
+            ..Default::default()
+        });
+        let adapter = Arc::new(MockAdapter::new(
+            pub_key.clone(),
+            metadata.clone(),
+            Arc::clone(&bus),
+            genesis,
+        ));
+        let crypto = Arc::new(OverlordCrypto::new(
+            BlsPrivateKey::try_from(keypair.private_key.as_ref()).unwrap(),
+            bls_pub_keys.clone(),
+            String::new(),
+        ));
+
+        let consensus = Arc::new(OverlordConsensus::new(
+            status,
+            H160::default(),
+            NodeInfo {
+                chain_id:     CHAIN_ID,
+                self_pub_key: pub_key.clone(),
+                self_address: Address(keypair.validator.address),
+            },
+            crypto,
+            Arc::new(SignedTxsWAL::new(format!("{}/txs", path))),
+            Arc::clone(&adapter),
+            Arc::new(AsyncMutex::new(())),
+            Arc::new(ConsensusWal::new(format!("{}/consensus", path))),
+            100,
+        ));
+        bus.register(pub_key, Arc::clone(&consensus) as Arc<dyn Consensus>);
+
+        let authority_list = authority_list.clone();
+        let timer_config = Some(DurationConfig::from(metadata.clone()));
+        tokio::spawn(async move {
+            consensus
+                .run(0, INTERVAL, authority_list, timer_config)
+                .await
+                .unwrap();
+        });
+        adapters.push(adapter);
+    }
+
+    if attacker.config().has(&Behaviour::SpamInvalidTx) {
+        let (bus, attacker) = (Arc::clone(&bus), Arc::clone(&attacker));
+        tokio::spawn(async move {
+            loop {
+                sleep(Duration::from_millis(attacker.config().spam_interval)).await;
+                bus.new_txs(attacker.invalid_txs(attacker.config().spam_batch));
+            }
+        });
+    }
+
+    let wait = async {
+        while adapters.iter().any(|a| a.latest_number() < TARGET_HEIGHT) {
+            sleep(Duration::from_millis(100)).await;
+        }
+    };
+    assert!(
+        tokio::time::timeout(TIMEOUT, wait).await.is_ok(),
+        "honest nodes lose liveness at heights {:?}",
+        adapters
+            .iter()
+            .map(|a| a.latest_number())
+            .collect::<Vec<_>>()
+    );
+
+    (bus, adapters)
+}
+
+/// The honest nodes commit the same block at each height, and no honest
+/// validator is found double signing.
+fn assert_safety(adapters: &[Arc<MockAdapter>]) {
+    for number in 1..=TARGET_HEIGHT {
+        let hashes = adapters
+            .iter()
+            .map(|a| a.block(number).unwrap().header_hash())
+            .collect::<Vec<_>>();
+        assert!(
+            hashes.iter().all(|h| *h == hashes[0]),
+            "honest nodes fork at height {}",
+            number
+        );
+    }
+
+    let honest = adapters
+        .iter()
+        .map(|a| a.pub_key.clone())
+        .collect::<Vec<_>>();
+    for adapter in adapters.iter() {
+        assert!(adapter
+            .evidences
+            .lock()
+            .iter()
+            .all(|e| !honest.contains(&e.voter)));
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_conflicting_proposal() {
+    let (_, adapters) =
+        run_honest_nodes("conflicting_proposal", vec![Behaviour::ConflictingProposal]).await;
+    assert_safety(&adapters);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_withhold_vote() {
+    let (_, adapters) = run_honest_nodes("withhold_vote", vec![Behaviour::WithholdVote]).await;
+    assert_safety(&adapters);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_replay_qc() {
+    let (_, adapters) = run_honest_nodes("replay_qc", vec![Behaviour::ReplayQc]).await;
+    assert_safety(&adapters);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_spam_invalid_tx() {
+    let (bus, adapters) = run_honest_nodes("spam_invalid_tx", vec![Behaviour::SpamInvalidTx]).await;
+    assert_safety(&adapters);
+    assert!(bus.accepted.lock().is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_all_behaviours() {
+    let (bus, adapters) = run_honest_nodes("all_behaviours", vec![
+        Behaviour::ConflictingProposal,
+        Behaviour::WithholdVote,
+        Behaviour::ReplayQc,
+        Behaviour::SpamInvalidTx,
+    ])
+    .await;
+    assert_safety(&adapters);
+    assert!(bus.accepted.lock().is_empty());
+}
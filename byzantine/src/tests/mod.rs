@@ -0,0 +1,613 @@
+mod consensus;
+mod sync;
+
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+use std::sync::Arc;
+
+use parking_lot::{Mutex, RwLock};
+
+use common_crypto::{
+    BlsPrivateKey, PublicKey, Secp256k1PrivateKey, ToBlsPublicKey, ToPublicKey,
+    UncompressedPublicKey,
+};
+use core_consensus::message::{
+    END_GOSSIP_AGGREGATED_VOTE, END_GOSSIP_SIGNED_CHOKE, END_GOSSIP_SIGNED_PROPOSAL,
+    END_GOSSIP_SIGNED_VOTE,
+};
+use core_consensus::util::digest_signed_transactions;
+use core_consensus::{BlockHeaderField, ConsensusError};
+use core_mempool::MsgNewTxs;
+use core_network::NetworkContext;
+use protocol::codec::{hex_decode, ProtocolCodec};
+use protocol::traits::{
+    CommonConsensusAdapter, Consensus, ConsensusAdapter, Context, MessageTarget,
+    SynchronizationAdapter, TrustFeedback,
+};
+use protocol::types::{
+    Address, Block, BlockNumber, Bytes, Evidence, ExecResp, Hash, Hasher, Header, Hex, Log,
+    MerkleRoot, Metadata, MetadataVersion, Proof, Proposal, Receipt, SignedTransaction, StateEntry,
+    StateRange, StateRangeRequest, Validator, ValidatorExtend, H160, H256, MAX_BLOCK_GAS_LIMIT,
+    U256,
+};
+use protocol::{async_trait, tokio, ProtocolResult};
+
+use crate::{Attacker, Behaviour, ByzantineConfig};
+
+const PRIVATE_KEYS: [&str; 4] = [
+    "37aa0f893d05914a4def0460c0a984d3611546cfb26924d7a7ca6e0db9950a2d",
+    "383fcff8683b8115e31613949be24254b4204ffbe43c227408a76334a2e3fb32",
+    "51ce21643b911347c5d5c85c323d9d5421810dc89f46b688720b2715f5e8e936",
+    "69ff51f4c22f30615f68b88efa740f8f1b9169e88842b83d189748d06f1a948e",
+];
+const CHAIN_ID: u64 = 5;
+const INTERVAL: u64 = 500;
+/// The session id of the byzantine peer in the synchronization.
+const BYZANTINE_SESSION: usize = 3;
+
+struct KeyPair {
+    private_key: Bytes,
+    validator:   ValidatorExtend,
+}
+
+fn mock_keypairs() -> Vec<KeyPair> {
+    PRIVATE_KEYS
+        .iter()
+        .map(|key| {
+            let private_key = Bytes::from(hex_decode(key).unwrap());
+            let pub_key = Secp256k1PrivateKey::try_from(private_key.as_ref())
+                .unwrap()
+                .pub_key();
+            let bls_pub_key = BlsPrivateKey::try_from(private_key.as_ref())
+                .unwrap()
+                .pub_key(&String::new());
+
+            KeyPair {
+                validator: ValidatorExtend {
+                    bls_pub_key:    Hex::encode(bls_pub_key.to_bytes()),
+                    pub_key:        Hex::encode(pub_key.to_bytes()),
+                    address:        Address::from_pubkey_bytes(pub_key.to_uncompressed_bytes())
+                        .unwrap()
+                        .0,
+                    propose_weight: 1,
+                    vote_weight:    1,
+                },
+                private_key,
+            }
+        })
+        .collect()
+}
+
+fn mock_metadata(verifier_list: Vec<ValidatorExtend>) -> Metadata {
+    Metadata {
+        version: MetadataVersion::new(0, u64::MAX),
+        epoch: 0,
+        gas_limit: MAX_BLOCK_GAS_LIMIT,
+        gas_price: 1,
+        interval: INTERVAL,
+        verifier_list,
+        propose_ratio: 15,
+        prevote_ratio: 10,
+        precommit_ratio: 10,
+        brake_ratio: 10,
+        tx_num_limit: 20000,
+        max_tx_size: 1024000,
+        last_checkpoint_block_hash: Hash::default(),
+    }
+}
+
+fn mock_attacker(keypair: &KeyPair, metadata: &Metadata, behaviours: Vec<Behaviour>) -> Attacker {
+    Attacker::new(ByzantineConfig {
+        privkey: Hex::encode(&keypair.private_key),
+        chain_id: CHAIN_ID,
+        data_path: "./free-space/byzantine".into(),
+        listen_addr: "/ip4/127.0.0.1/tcp/8000".parse().unwrap(),
+        bootstraps: Vec::new(),
+        verifier_list: metadata.verifier_list.clone(),
+        behaviours,
+        spam_interval: 100,
+        spam_batch: 10,
+        replay_lag: 1,
+    })
+    .unwrap()
+}
+
+fn mock_proof(block: &Block) -> Proof {
+    if block.header.number == 0 {
+        return Proof::default();
+    }
+
+    Proof {
+        number:     block.header.number,
+        round:      0,
+        block_hash: Hasher::digest(Proposal::from(block.clone()).encode().unwrap()),
+        signature:  Bytes::new(),
+        bitmap:     Bytes::new(),
+    }
+}
+
+fn mock_exec_resp(state_root: MerkleRoot) -> ExecResp {
+    ExecResp {
+        state_root,
+        receipt_root: MerkleRoot::default(),
+        gas_used: 0,
+        tx_resp: Vec::new(),
+    }
+}
+
+/// A chain of empty blocks, each of them carrying the proof of its parent.
+fn mock_chain(len: u64) -> Vec<Block> {
+    let mut chain = vec![Block::default()];
+
+    for number in 1..=len {
+        let parent = chain.last().unwrap();
+        let proposal = Proposal {
+            prev_hash: parent.header_hash(),
+            number,
+            timestamp: parent.header.timestamp + 1,
+            signed_txs_hash: digest_signed_transactions(&[]),
+            proof: mock_proof(parent),
+            chain_id: CHAIN_ID,
+            ..Default::default()
+        };
+        chain.push(Block::new(proposal, mock_exec_resp(MerkleRoot::default())));
+    }
+
+    chain
+}
+
+/// Deliver the consensus messages between the honest nodes and the attacker
+/// in process, each in a task of its own as the network does.
+#[derive(Default)]
+struct Bus {
+    nodes:    RwLock<Vec<(Bytes, Arc<dyn Consensus>)>>,
+    attacker: RwLock<Option<Arc<Attacker>>>,
+    /// The gossiped transactions that pass the signature check of the
+    /// mempool.
+    accepted: Mutex<Vec<SignedTransaction>>,
+}
+
+impl Bus {
+    fn register(&self, pub_key: Bytes, consensus: Arc<dyn Consensus>) {
+        self.nodes.write().push((pub_key, consensus));
+    }
+
+    fn set_attacker(&self, attacker: Arc<Attacker>) {
+        *self.attacker.write() = Some(attacker);
+    }
+
+    fn deliver(
+        self: &Arc<Self>,
+        from: Bytes,
+        end: &'static str,
+        msg: Vec<u8>,
+        target: MessageTarget,
+    ) {
+        let is_target = |pub_key: &Bytes| match &target {
+            MessageTarget::Broadcast => *pub_key != from,
+            MessageTarget::Specified(to) => pub_key == to,
+        };
+
+        for (pub_key, consensus) in self.nodes.read().iter() {
+            if !is_target(pub_key) {
+                continue;
+            }
+
+            let consensus = Arc::clone(consensus);
+            let msg = msg.clone();
+            tokio::spawn(async move {
+                let ctx = Context::new();
+                let _ = match end {
+                    END_GOSSIP_SIGNED_PROPOSAL => consensus.set_proposal(ctx, msg).await,
+                    END_GOSSIP_SIGNED_VOTE => consensus.set_vote(ctx, msg).await,
+                    END_GOSSIP_AGGREGATED_VOTE => consensus.set_qc(ctx, msg).await,
+                    END_GOSSIP_SIGNED_CHOKE => consensus.set_choke(ctx, msg).await,
+                    _ => Ok(()),
+                };
+            });
+        }
+
+        let attacker = match self.attacker.read().clone() {
+            Some(attacker) if is_target(&attacker.pub_key()) => attacker,
+            _ => return,
+        };
+        let bus = Arc::clone(self);
+        tokio::spawn(async move {
+            if let Ok(actions) = attacker.react(end, &msg) {
+                for action in actions {
+                    bus.deliver(attacker.pub_key(), action.end, action.msg, action.target);
+                }
+            }
+        });
+    }
+
+    /// Check the signatures of the gossiped transactions as the mempool does.
+    fn new_txs(&self, msg: MsgNewTxs) {
+        let valid = msg.batch_stxs.into_iter().filter(|stx| {
+            SignedTransaction::try_from(stx.transaction.clone())
+                .map(|verified| verified.sender == stx.sender && verified.public == stx.public)
+                .unwrap_or(false)
+        });
+        self.accepted.lock().extend(valid);
+    }
+}
+
+/// An adapter keeping the blocks in memory and executing nothing. The
+/// consensus messages go through the bus and the blocks and proofs of the
+/// synchronization come from the honest chain or from the attacker, chosen by
+/// the session id of the peer.
+struct MockAdapter {
+    pub_key:  Bytes,
+    metadata: Metadata,
+    bus:      Arc<Bus>,
+    attacker: Option<Arc<Attacker>>,
+
+    blocks:    RwLock<BTreeMap<BlockNumber, Block>>,
+    evidences: Mutex<Vec<Evidence>>,
+    reports:   Mutex<Vec<(usize, TrustFeedback)>>,
+}
+
+impl MockAdapter {
+    fn new(pub_key: Bytes, metadata: Metadata, bus: Arc<Bus>, chain: Vec<Block>) -> Self {
+        MockAdapter {
+            pub_key,
+            metadata,
+            bus,
+            attacker: None,
+            blocks: RwLock::new(chain.into_iter().map(|b| (b.header.number, b)).collect()),
+            evidences: Mutex::new(Vec::new()),
+            reports: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn with_attacker(mut self, attacker: Arc<Attacker>) -> Self {
+        self.attacker = Some(attacker);
+        self
+    }
+
+    fn block(&self, number: BlockNumber) -> ProtocolResult<Block> {
+        self.blocks
+            .read()
+            .get(&number)
+            .cloned()
+            .ok_or_else(|| ConsensusError::Other(format!("block {} not found", number)).into())
+    }
+
+    fn latest_number(&self) -> BlockNumber {
+        *self.blocks.read().keys().next_back().unwrap()
+    }
+
+    /// The attacker serving the synchronization request of the context.
+    fn byzantine_peer(&self, ctx: &Context) -> Option<&Arc<Attacker>> {
+        match ctx.session_id() {
+            Ok(sid) if sid.value() == BYZANTINE_SESSION => self.attacker.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+#[async_trait]
+impl CommonConsensusAdapter for MockAdapter {
+    async fn save_block(&self, _ctx: Context, block: Block) -> ProtocolResult<()> {
+        self.blocks.write().insert(block.header.number, block);
+        Ok(())
+    }
+
+    async fn save_proof(&self, _ctx: Context, _proof: Proof) -> ProtocolResult<()> {
+        Ok(())
+    }
+
+    async fn save_evidence(&self, _ctx: Context, evidence: Evidence) -> ProtocolResult<()> {
+        self.evidences.lock().push(evidence);
+        Ok(())
+    }
+
+    async fn save_signed_txs(
+        &self,
+        _ctx: Context,
+        _block_height: u64,
+        _signed_txs: Vec<SignedTransaction>,
+    ) -> ProtocolResult<()> {
+        Ok(())
+    }
+
+    async fn save_receipts(
+        &self,
+        _ctx: Context,
+        _height: u64,
+        _receipts: Vec<Receipt>,
+    ) -> ProtocolResult<()> {
+        Ok(())
+    }
+
+    async fn flush_mempool(
+        &self,
+        _ctx: Context,
+        _ordered_tx_hashes: &[Hash],
+        _current_number: BlockNumber,
+    ) -> ProtocolResult<()> {
+        Ok(())
+    }
+
+    async fn get_block_by_number(&self, _ctx: Context, height: u64) -> ProtocolResult<Block> {
+        self.block(height)
+    }
+
+    async fn get_block_header_by_number(
+        &self,
+        _ctx: Context,
+        height: u64,
+    ) -> ProtocolResult<Header> {
+        self.block(height).map(|b| b.header)
+    }
+
+    async fn get_current_number(&self, _ctx: Context) -> ProtocolResult<u64> {
+        Ok(self.latest_number())
+    }
+
+    async fn get_txs_from_storage(
+        &self,
+        _ctx: Context,
+        _tx_hashes: &[Hash],
+    ) -> ProtocolResult<Vec<SignedTransaction>> {
+        Ok(Vec::new())
+    }
+
+    async fn exec(
+        &self,
+        _ctx: Context,
+        last_state_root: Hash,
+        _proposal: &Proposal,
+        _signed_txs: Vec<SignedTransaction>,
+    ) -> ProtocolResult<ExecResp> {
+        Ok(mock_exec_resp(last_state_root))
+    }
+
+    fn need_change_metadata(&self, _block_number: u64) -> bool {
+        false
+    }
+
+    fn get_metadata_unchecked(&self, _ctx: Context, _block_number: u64) -> Metadata {
+        self.metadata.clone()
+    }
+
+    fn get_metadata(&self, _ctx: Context, _header: &Header) -> ProtocolResult<Metadata> {
+        Ok(self.metadata.clone())
+    }
+
+    fn update_metadata(&self, _ctx: Context, _header: &Header) -> ProtocolResult<()> {
+        Ok(())
+    }
+
+    async fn broadcast_number(&self, _ctx: Context, _height: u64) -> ProtocolResult<()> {
+        Ok(())
+    }
+
+    async fn broadcast_evidence(&self, _ctx: Context, _evidence: Evidence) -> ProtocolResult<()> {
+        Ok(())
+    }
+
+    fn set_args(
+        &self,
+        _context: Context,
+        _state_root: MerkleRoot,
+        _gas_limit: u64,
+        _max_tx_size: u64,
+    ) {
+    }
+
+    fn tag_consensus(&self, _ctx: Context, _peer_ids: Vec<Bytes>) -> ProtocolResult<()> {
+        Ok(())
+    }
+
+    async fn verify_proof(&self, _ctx: Context, block: Block, proof: Proof) -> ProtocolResult<()> {
+        let expect = mock_proof(&block);
+        if proof.number != expect.number || proof.block_hash != expect.block_hash {
+            return Err(ConsensusError::Other(format!(
+                "invalid proof of block {}",
+                block.header.number
+            ))
+            .into());
+        }
+
+        Ok(())
+    }
+
+    async fn verify_block_header(&self, _ctx: Context, block: &Proposal) -> ProtocolResult<()> {
+        let parent = self.block(block.number - 1)?;
+        if block.prev_hash != parent.header_hash() {
+            return Err(ConsensusError::VerifyBlockHeader(
+                block.number,
+                BlockHeaderField::PreviousBlockHash,
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+
+    async fn notify_block_logs(
+        &self,
+        _ctx: Context,
+        _block_number: u64,
+        _block_hash: Hash,
+        _logs: &[Vec<Log>],
+    ) {
+    }
+
+    async fn notify_checkpoint(&self, _ctx: Context, _block: Block, _proof: Proof) {}
+
+    fn verify_proof_signature(
+        &self,
+        _ctx: Context,
+        _block_height: u64,
+        _vote_hash: Bytes,
+        _aggregated_signature_bytes: Bytes,
+        _vote_pubkeys: Vec<Hex>,
+    ) -> ProtocolResult<()> {
+        Ok(())
+    }
+
+    fn verify_proof_weight(
+        &self,
+        _ctx: Context,
+        _block_height: u64,
+        _weight_map: HashMap<Bytes, u32>,
+        _signed_voters: Vec<Bytes>,
+    ) -> ProtocolResult<()> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ConsensusAdapter for MockAdapter {
+    async fn get_txs_from_mempool(
+        &self,
+        _ctx: Context,
+        _height: u64,
+        _gas_limit: U256,
+        _tx_num_limit: u64,
+    ) -> ProtocolResult<Vec<Hash>> {
+        Ok(Vec::new())
+    }
+
+    async fn get_full_txs(
+        &self,
+        _ctx: Context,
+        _order_txs: &[Hash],
+    ) -> ProtocolResult<Vec<SignedTransaction>> {
+        Ok(Vec::new())
+    }
+
+    async fn transmit(
+        &self,
+        _ctx: Context,
+        msg: Vec<u8>,
+        end: &str,
+        target: MessageTarget,
+    ) -> ProtocolResult<()> {
+        let end = match end {
+            END_GOSSIP_SIGNED_PROPOSAL => END_GOSSIP_SIGNED_PROPOSAL,
+            END_GOSSIP_SIGNED_VOTE => END_GOSSIP_SIGNED_VOTE,
+            END_GOSSIP_AGGREGATED_VOTE => END_GOSSIP_AGGREGATED_VOTE,
+            END_GOSSIP_SIGNED_CHOKE => END_GOSSIP_SIGNED_CHOKE,
+            _ => return Ok(()),
+        };
+
+        self.bus.deliver(self.pub_key.clone(), end, msg, target);
+        Ok(())
+    }
+
+    async fn get_current_number(&self, _ctx: Context) -> ProtocolResult<u64> {
+        Ok(self.latest_number())
+    }
+
+    async fn pull_block(&self, _ctx: Context, number: u64, _end: &str) -> ProtocolResult<Block> {
+        self.block(number)
+    }
+
+    async fn verify_txs(&self, _ctx: Context, _number: u64, _txs: &[Hash]) -> ProtocolResult<()> {
+        Ok(())
+    }
+
+    fn get_balances(
+        &self,
+        _ctx: Context,
+        _state_root: MerkleRoot,
+        addresses: &[H160],
+    ) -> ProtocolResult<Vec<U256>> {
+        Ok(vec![U256::max_value(); addresses.len()])
+    }
+}
+
+#[async_trait]
+impl SynchronizationAdapter for MockAdapter {
+    fn update_status(
+        &self,
+        _ctx: Context,
+        _height: u64,
+        _consensus_interval: u64,
+        _propose_ratio: u64,
+        _prevote_ratio: u64,
+        _precommit_ratio: u64,
+        _brake_ratio: u64,
+        _validators: Vec<Validator>,
+    ) -> ProtocolResult<()> {
+        Ok(())
+    }
+
+    async fn get_block_from_remote(
+        &self,
+        ctx: Context,
+        number: BlockNumber,
+    ) -> ProtocolResult<Block> {
+        match self.byzantine_peer(&ctx) {
+            Some(attacker) => Ok(attacker.malformed_block(number)),
+            None => self.block(number),
+        }
+    }
+
+    async fn get_txs_from_remote(
+        &self,
+        ctx: Context,
+        _number: BlockNumber,
+        _hashes: &[Hash],
+    ) -> ProtocolResult<Vec<SignedTransaction>> {
+        match self.byzantine_peer(&ctx) {
+            Some(attacker) => Ok(attacker.malformed_txs()),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    async fn get_proof_from_remote(
+        &self,
+        ctx: Context,
+        number: BlockNumber,
+    ) -> ProtocolResult<Proof> {
+        match self.byzantine_peer(&ctx) {
+            Some(attacker) => Ok(attacker.malformed_proof(number)),
+            None => self.block(number).map(|b| mock_proof(&b)),
+        }
+    }
+
+    async fn get_state_range_from_remote(
+        &self,
+        _ctx: Context,
+        _req: StateRangeRequest,
+    ) -> ProtocolResult<StateRange> {
+        Err(ConsensusError::Other("unsupported".to_string()).into())
+    }
+
+    async fn get_codes_from_remote(
+        &self,
+        _ctx: Context,
+        _hashes: &[Hash],
+    ) -> ProtocolResult<Vec<Bytes>> {
+        Err(ConsensusError::Other("unsupported".to_string()).into())
+    }
+
+    fn save_state_range(
+        &self,
+        _ctx: Context,
+        _root: MerkleRoot,
+        _entries: &[StateEntry],
+    ) -> ProtocolResult<MerkleRoot> {
+        Err(ConsensusError::Other("unsupported".to_string()).into())
+    }
+
+    async fn save_code(
+        &self,
+        _ctx: Context,
+        _address: H256,
+        _code_hash: Hash,
+        _code: Bytes,
+    ) -> ProtocolResult<()> {
+        Err(ConsensusError::Other("unsupported".to_string()).into())
+    }
+
+    fn report_peer(&self, ctx: Context, feedback: TrustFeedback) {
+        if let Ok(sid) = ctx.session_id() {
+            self.reports.lock().push((sid.value(), feedback));
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use core_consensus::sync_pipeline::SyncPipeline;
+use protocol::tokio::{self, sync::mpsc::channel};
+
+use super::*;
+
+const CHAIN_LEN: u64 = 20;
+const HONEST_SESSION: usize = 1;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_malformed_sync_response() {
+    let keypairs = mock_keypairs();
+    let metadata = mock_metadata(keypairs.iter().map(|k| k.validator.clone()).collect());
+    let attacker = Arc::new(mock_attacker(&keypairs[3], &metadata, vec![
+        Behaviour::MalformedSyncResponse,
+    ]));
+    let chain = mock_chain(CHAIN_LEN);
+    let adapter = Arc::new(
+        MockAdapter::new(
+            keypairs[0].validator.pub_key.as_bytes(),
+            metadata,
+            Arc::new(Bus::default()),
+            chain.clone(),
+        )
+        .with_attacker(attacker),
+    );
+
+    let peers = vec![
+        Context::new().with_value::<usize>("session_id", BYZANTINE_SESSION),
+        Context::new().with_value::<usize>("session_id", HONEST_SESSION),
+    ];
+    let (sender, mut receiver) = channel(CHAIN_LEN as usize);
+    let pipeline = SyncPipeline::new(Arc::clone(&adapter), peers, 10);
+    let sync = tokio::spawn(pipeline.run(chain[0].clone(), CHAIN_LEN, sender));
+
+    for block in chain.iter().skip(1) {
+        let (rich_block, proof) = receiver.recv().await.unwrap();
+        assert_eq!(rich_block.block.header_hash(), block.header_hash());
+        assert_eq!(proof, mock_proof(block));
+    }
+    sync.await.unwrap().unwrap();
+
+    let reports = adapter.reports.lock();
+    assert!(!reports.is_empty());
+    assert!(reports
+        .iter()
+        .all(|(sid, feedback)| *sid == BYZANTINE_SESSION
+            && matches!(feedback, TrustFeedback::Worse(_))));
+}
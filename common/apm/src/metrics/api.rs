@@ -19,6 +19,7 @@ make_auto_flush_static_metric! {
         eth_getBalance,
         eth_call,
         eth_estimateGas,
+        eth_createAccessList,
         eth_chainId,
         net_version,
         eth_getCode,
@@ -26,6 +27,7 @@ make_auto_flush_static_metric! {
         net_peerCount,
         net_listening,
         eth_gasPrice,
+        eth_maxPriorityFeePerGas,
         eth_syncing,
         eth_getLogs,
         get_block,
@@ -38,11 +40,33 @@ make_auto_flush_static_metric! {
         eth_getTransactionByBlockHashAndIndex,
         eth_getTransactionByBlockNumberAndIndex,
         eth_getStorageAt,
+        eth_getProof,
+        debug_traceTransaction,
+        debug_traceBlockByNumber,
+        debug_traceCall,
+        txpool_status,
+        txpool_content,
+        txpool_contentFrom,
+        txpool_inspect,
+        eth_sendTransaction,
+        eth_sign,
+        eth_signTransaction,
+        eth_signTypedData_v4,
+        personal_newAccount,
+        personal_unlockAccount,
+        personal_listAccounts,
+        admin_peers,
+        admin_nodeInfo,
+        admin_addPeer,
+        admin_removePeer,
+        admin_banPeer,
+        admin_unbanPeer,
     }
 
     pub label_enum Request_Result {
         success,
         failure,
+        rejected,
     }
 
     pub struct RequestResultCounterVec: LocalCounter {
@@ -77,3 +101,10 @@ lazy_static! {
     pub static ref API_REQUEST_TIME_HISTOGRAM_STATIC: RequestTimeHistogramVec =
         auto_flush_from!(API_REQUEST_TIME_HISTOGRAM_VEC, RequestTimeHistogramVec);
 }
+
+/// Count a request rejected by the rate limits before it is handled.
+pub fn on_request_rejected(method: &str) {
+    API_REQUEST_RESULT_COUNTER_VEC
+        .with_label_values(&[method, "rejected"])
+        .inc();
+}
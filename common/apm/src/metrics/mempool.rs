@@ -9,6 +9,7 @@ make_auto_flush_static_metric! {
     pub label_enum MempoolKind {
         insert_tx_from_p2p,
         package,
+        package_gas,
         current_size,
     }
 
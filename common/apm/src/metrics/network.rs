@@ -2,8 +2,8 @@ use lazy_static::lazy_static;
 
 use crate::metrics::{
     auto_flush_from, exponential_buckets, linear_buckets, make_auto_flush_static_metric,
-    register_counter_vec, register_histogram_vec, register_int_gauge, register_int_gauge_vec,
-    CounterVec, HistogramVec, IntGauge, IntGaugeVec,
+    register_counter_vec, register_histogram_vec, register_int_counter, register_int_gauge,
+    register_int_gauge_vec, CounterVec, HistogramVec, IntCounter, IntGauge, IntGaugeVec,
 };
 
 make_auto_flush_static_metric! {
@@ -144,6 +144,11 @@ lazy_static! {
         "Total number of connected consensus peers"
     )
     .expect("network connected consenss peers");
+    pub static ref NETWORK_BANNED_PEERS: IntCounter = register_int_counter!(
+        "axon_network_banned_peers",
+        "Total number of peers banned for misbehaviour"
+    )
+    .expect("network banned peers");
 }
 
 fn on_network_message(direction: &str, target: &str, url: &str, inc: f64) {
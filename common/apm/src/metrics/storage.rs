@@ -5,7 +5,8 @@ use protocol::traits::StorageCategory;
 
 use crate::metrics::{
     auto_flush_from, duration_to_sec, make_auto_flush_static_metric, register_counter_vec,
-    CounterVec,
+    register_histogram, register_int_counter, register_int_gauge, CounterVec, Histogram,
+    IntCounter, IntGauge,
 };
 
 make_auto_flush_static_metric! {
@@ -18,6 +19,8 @@ make_auto_flush_static_metric! {
     hash_height,
     state,
     code,
+    log_index,
+    evidence,
   }
 
   pub struct StoragePutCfTimeUsageVec: LocalCounter {
@@ -75,6 +78,30 @@ lazy_static! {
         auto_flush_from!(STORAGE_GET_CF_COUNTER_VEC, StorageGetCfTotalVec);
 }
 
+lazy_static! {
+    pub static ref STORAGE_TRIE_PRUNED_NODES_COUNTER: IntCounter = register_int_counter!(
+        "axon_storage_trie_pruned_nodes_total",
+        "Total stale trie nodes deleted by the pruner"
+    )
+    .unwrap();
+    pub static ref STORAGE_TRIE_PRUNED_HEIGHT_GAUGE: IntGauge = register_int_gauge!(
+        "axon_storage_trie_pruned_height",
+        "The latest block whose stale trie nodes have been pruned"
+    )
+    .unwrap();
+    pub static ref STORAGE_TRIE_PRUNE_TIME_HISTOGRAM: Histogram = register_histogram!(
+        "axon_storage_trie_prune_seconds",
+        "Time usage of pruning the stale trie nodes of a block"
+    )
+    .unwrap();
+}
+
+pub fn on_storage_prune_state(number: u64, nodes: u64, duration: Duration) {
+    STORAGE_TRIE_PRUNED_NODES_COUNTER.inc_by(nodes);
+    STORAGE_TRIE_PRUNED_HEIGHT_GAUGE.set(number as i64);
+    STORAGE_TRIE_PRUNE_TIME_HISTOGRAM.observe(duration_to_sec(duration));
+}
+
 pub fn on_storage_get_state(duration: Duration, keys: f64) {
     let seconds = duration_to_sec(duration);
 
@@ -117,6 +144,14 @@ pub fn on_storage_get_cf(sc: StorageCategory, duration: Duration, keys: f64) {
             STORAGE_GET_CF_TIME_USAGE.hash_height.inc_by(seconds);
             STORAGE_GET_CF_COUNTER.hash_height.inc_by(keys);
         }
+        StorageCategory::LogIndex => {
+            STORAGE_GET_CF_TIME_USAGE.log_index.inc_by(seconds);
+            STORAGE_GET_CF_COUNTER.log_index.inc_by(keys);
+        }
+        StorageCategory::Evidence => {
+            STORAGE_GET_CF_TIME_USAGE.evidence.inc_by(seconds);
+            STORAGE_GET_CF_COUNTER.evidence.inc_by(keys);
+        }
         _ => (),
     }
 }
@@ -149,6 +184,14 @@ pub fn on_storage_put_cf(sc: StorageCategory, duration: Duration, size: f64) {
             STORAGE_PUT_CF_TIME_USAGE.hash_height.inc_by(seconds);
             STORAGE_PUT_CF_BYTES_COUNTER.hash_height.inc_by(size);
         }
+        StorageCategory::LogIndex => {
+            STORAGE_PUT_CF_TIME_USAGE.log_index.inc_by(seconds);
+            STORAGE_PUT_CF_BYTES_COUNTER.log_index.inc_by(size);
+        }
+        StorageCategory::Evidence => {
+            STORAGE_PUT_CF_TIME_USAGE.evidence.inc_by(seconds);
+            STORAGE_PUT_CF_BYTES_COUNTER.evidence.inc_by(size);
+        }
         _ => (),
     }
 }
@@ -12,18 +12,127 @@ pub const DEFAULT_BROADCAST_TXS_INTERVAL: u64 = 200; // milliseconds
 pub const DEFAULT_OVERLORD_GAP: usize = 5;
 pub const DEFAULT_SYNC_TXS_CHUNK_SIZE: usize = 5000;
 pub const DEFAULT_CACHE_SIZE: usize = 128 << 20;
+pub const DEFAULT_GAS_ORACLE_BLOCKS: u64 = 20;
+pub const DEFAULT_GAS_ORACLE_PERCENTILE: u64 = 60;
+pub const DEFAULT_PRUNE_KEEP_BLOCKS: u64 = 128;
+pub const DEFAULT_PRICE_BUMP: u64 = 10; // percent
+pub const DEFAULT_PENDING_PER_ACCOUNT: u64 = 1024;
+pub const DEFAULT_QUEUED_SIZE: u64 = 4096;
+pub const DEFAULT_QUEUED_PER_ACCOUNT: u64 = 64;
+pub const DEFAULT_MAX_BATCH_SIZE: usize = 100;
+pub const DEFAULT_MAX_LOGS_RANGE: u64 = 10_000;
+pub const DEFAULT_MAX_LOGS_RESULTS: usize = 10_000;
+pub const DEFAULT_RPC_GAS_CAP: u64 = 50_000_000;
+
+fn default_gas_oracle_blocks() -> u64 {
+    DEFAULT_GAS_ORACLE_BLOCKS
+}
+
+fn default_gas_oracle_percentile() -> u64 {
+    DEFAULT_GAS_ORACLE_PERCENTILE
+}
+
+fn default_max_batch_size() -> usize {
+    DEFAULT_MAX_BATCH_SIZE
+}
+
+fn default_max_logs_range() -> u64 {
+    DEFAULT_MAX_LOGS_RANGE
+}
+
+fn default_rpc_gas_cap() -> u64 {
+    DEFAULT_RPC_GAS_CAP
+}
+
+fn default_max_logs_results() -> usize {
+    DEFAULT_MAX_LOGS_RESULTS
+}
+
+fn default_method_cost() -> u64 {
+    1
+}
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct ConfigApi {
-    pub http_listening_address: Option<SocketAddr>,
-    pub ws_listening_address:   Option<SocketAddr>,
+    pub http_listening_address:  Option<SocketAddr>,
+    pub ws_listening_address:    Option<SocketAddr>,
+    /// The address serving the `admin_*` methods, which are disabled if it is
+    /// not set. It should only be reachable by the operators.
+    pub admin_listening_address: Option<SocketAddr>,
+    #[serde(default)]
+    pub maxconn:                 usize,
     #[serde(default)]
-    pub maxconn:                usize,
+    pub max_payload_size:        usize,
+    pub enable_dump_profile:     Option<bool>,
+    #[serde(default)]
+    pub client_version:          String,
+    #[serde(default = "default_gas_oracle_blocks")]
+    pub gas_oracle_blocks:       u64,
+    #[serde(default = "default_gas_oracle_percentile")]
+    pub gas_oracle_percentile:   u64,
+    /// The directory of the encrypted keys used by `eth_sendTransaction` and
+    /// the other signing methods, which are disabled if it is not set. These
+    /// methods are only served on the admin listening address.
+    pub keystore_path:           Option<PathBuf>,
+    #[serde(default)]
+    pub rate_limit:              ConfigRateLimit,
+    /// The maximum number of blocks a log query may cover, 0 for no limit.
+    #[serde(default = "default_max_logs_range")]
+    pub max_logs_range:          u64,
+    /// The maximum number of logs a log query may return, 0 for no limit.
+    #[serde(default = "default_max_logs_results")]
+    pub max_logs_results:        usize,
+    /// The gas limit of `eth_call` and `eth_estimateGas`, which bounds the
+    /// work of a call, 0 for no limit.
+    #[serde(default = "default_rpc_gas_cap")]
+    pub gas_cap:                 u64,
+}
+
+/// The limits applied to the requests of the HTTP endpoint. The WebSocket
+/// endpoint applies the method rates and timeouts only. A rate of 0 disables
+/// the corresponding limit.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ConfigRateLimit {
+    /// The cost units an IP gains per second.
+    #[serde(default)]
+    pub ip_rate:        u64,
+    /// The cost units an IP can spend at once.
+    #[serde(default)]
+    pub ip_burst:       u64,
+    #[serde(default = "default_max_batch_size")]
+    pub max_batch_size: usize,
+    #[serde(default)]
+    pub methods:        Vec<ConfigMethodLimit>,
+}
+
+impl Default for ConfigRateLimit {
+    fn default() -> Self {
+        ConfigRateLimit {
+            ip_rate:        0,
+            ip_burst:       0,
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
+            methods:        Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ConfigMethodLimit {
+    pub name:    String,
+    /// The calls of the method from all the IPs allowed per second.
     #[serde(default)]
-    pub max_payload_size:       usize,
-    pub enable_dump_profile:    Option<bool>,
+    pub rate:    u64,
     #[serde(default)]
-    pub client_version:         String,
+    pub burst:   u64,
+    /// The cost units charged to the IP for each call.
+    #[serde(default = "default_method_cost")]
+    pub cost:    u64,
+    /// The milliseconds a call may run before it is aborted. The EVM of an
+    /// aborted call keeps running until it reaches the gas cap.
+    pub timeout: Option<u64>,
+    /// The gas limit of a call of the method running the EVM, below the
+    /// global gas cap.
+    pub gas_cap: Option<u64>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -74,6 +183,8 @@ pub struct ConfigConsensus {
     pub overlord_gap:        usize,
     #[serde(default = "default_sync_txs_chunk_size")]
     pub sync_txs_chunk_size: usize,
+    #[serde(default)]
+    pub snap_sync:           bool,
 }
 
 fn default_broadcast_txs_size() -> usize {
@@ -84,6 +195,22 @@ fn default_broadcast_txs_interval() -> u64 {
     DEFAULT_BROADCAST_TXS_INTERVAL
 }
 
+fn default_price_bump() -> u64 {
+    DEFAULT_PRICE_BUMP
+}
+
+fn default_pending_per_account() -> u64 {
+    DEFAULT_PENDING_PER_ACCOUNT
+}
+
+fn default_queued_size() -> u64 {
+    DEFAULT_QUEUED_SIZE
+}
+
+fn default_queued_per_account() -> u64 {
+    DEFAULT_QUEUED_PER_ACCOUNT
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct ConfigMempool {
     pub pool_size:   u64,
@@ -93,12 +220,49 @@ pub struct ConfigMempool {
     pub broadcast_txs_size:     usize,
     #[serde(default = "default_broadcast_txs_interval")]
     pub broadcast_txs_interval: u64,
+    #[serde(default = "default_price_bump")]
+    pub price_bump:             u64,
+    #[serde(default = "default_pending_per_account")]
+    pub pending_per_account:    u64,
+    #[serde(default = "default_queued_size")]
+    pub queued_size:            u64,
+    #[serde(default = "default_queued_per_account")]
+    pub queued_per_account:     u64,
+    /// Persist the admitted transactions so that they survive a restart.
+    #[serde(default)]
+    pub journal:                bool,
 }
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct ConfigExecutor {
     pub light:             bool,
     pub triedb_cache_size: usize,
+    #[serde(default)]
+    pub mode:              ExecutorMode,
+    /// The number of recent blocks whose state is kept by a pruned node.
+    #[serde(default = "default_prune_keep_blocks")]
+    pub prune_keep_blocks: u64,
+}
+
+fn default_prune_keep_blocks() -> u64 {
+    DEFAULT_PRUNE_KEEP_BLOCKS
+}
+
+/// Whether the trie nodes of the old states are kept. An archive node can
+/// answer state queries at any height while a pruned node only keeps the
+/// recent states. A non-empty archive node can not be switched to the pruned
+/// mode, it should be resynced or restored from a snapshot instead.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExecutorMode {
+    Archive,
+    Pruned,
+}
+
+impl Default for ExecutorMode {
+    fn default() -> Self {
+        ExecutorMode::Archive
+    }
 }
 
 fn default_cache_size() -> usize {
@@ -260,4 +424,10 @@ impl Config {
         path_state.push("consensus_wal");
         path_state
     }
+
+    pub fn data_path_for_mempool_journal(&self) -> PathBuf {
+        let mut path_state = self.data_path.clone();
+        path_state.push("mempool_journal");
+        path_state
+    }
 }
@@ -1,36 +1,62 @@
 use std::sync::Arc;
 
-use core_executor::{AxonExecutor, AxonExecutorAdapter, MPTTrie};
-use protocol::traits::{APIAdapter, Context, Executor, ExecutorAdapter, MemPool, Network, Storage};
+use core_executor::{account_proof, AxonExecutor, AxonExecutorAdapter, MPTTrie};
+use protocol::tokio::sync::broadcast::Receiver;
+use protocol::traits::{
+    APIAdapter, Context, ExecutorAdapter, MemPool, MetadataControl, Network, Storage,
+};
 use protocol::types::{
-    Account, BigEndianHash, Block, BlockNumber, Bytes, ExecutorContext, Hash, Header, Proposal,
-    Receipt, SignedTransaction, TxResp, H160, U256,
+    AccessList, Account, AccountProof, BigEndianHash, Block, BlockNumber, Bytes, ExecutorContext,
+    Hash, Header, MemPoolContent, NetworkNodeInfo, NetworkPeer, Proposal, Receipt,
+    SignedTransaction, TraceResult, TracerType, TxResp, H160, H256, RLP_NULL, U256,
 };
 use protocol::{async_trait, codec::ProtocolCodec, ProtocolResult};
 
 use crate::APIError;
 
 #[derive(Clone)]
-pub struct DefaultAPIAdapter<M, S, DB, Net> {
-    mempool: Arc<M>,
-    storage: Arc<S>,
-    trie_db: Arc<DB>,
-    net:     Arc<Net>,
+pub struct DefaultAPIAdapter<M, S, DB, Net, MT> {
+    mempool:  Arc<M>,
+    storage:  Arc<S>,
+    trie_db:  Arc<DB>,
+    net:      Arc<Net>,
+    metadata: Arc<MT>,
 }
 
-impl<M, S, DB, Net> DefaultAPIAdapter<M, S, DB, Net>
+impl<M, S, DB, Net, MT> DefaultAPIAdapter<M, S, DB, Net, MT>
 where
     M: MemPool + 'static,
     S: Storage + 'static,
     DB: cita_trie::DB + 'static,
     Net: Network + 'static,
+    MT: MetadataControl + 'static,
 {
-    pub fn new(mempool: Arc<M>, storage: Arc<S>, trie_db: Arc<DB>, net: Arc<Net>) -> Self {
+    pub fn new(
+        mempool: Arc<M>,
+        storage: Arc<S>,
+        trie_db: Arc<DB>,
+        net: Arc<Net>,
+        metadata: Arc<MT>,
+    ) -> Self {
         Self {
             mempool,
             storage,
             trie_db,
             net,
+            metadata,
+        }
+    }
+
+    /// Check whether the trie nodes of the given state root are still kept.
+    /// The state of an old block is not available once it is pruned.
+    fn ensure_state(&self, state_root: Hash) -> ProtocolResult<()> {
+        if state_root == RLP_NULL {
+            return Ok(());
+        }
+
+        match self.trie_db.contains(state_root.as_bytes()) {
+            Ok(true) => Ok(()),
+            _ => Err(APIError::StateNotAvailable(state_root).into()),
         }
     }
 
@@ -44,6 +70,7 @@ where
             .ok_or_else(|| APIError::Adapter(format!("Cannot get {:?} block", number)))?;
         let state_root = block.header.state_root;
         let proposal: Proposal = block.into();
+        self.ensure_state(state_root)?;
 
         AxonExecutorAdapter::from_root(
             state_root,
@@ -51,16 +78,18 @@ where
             Arc::clone(&self.storage),
             ExecutorContext::from(proposal),
         )
+        .await
     }
 }
 
 #[async_trait]
-impl<M, S, DB, Net> APIAdapter for DefaultAPIAdapter<M, S, DB, Net>
+impl<M, S, DB, Net, MT> APIAdapter for DefaultAPIAdapter<M, S, DB, Net, MT>
 where
     M: MemPool + 'static,
     S: Storage + 'static,
     DB: cita_trie::DB + 'static,
     Net: Network + 'static,
+    MT: MetadataControl + 'static,
 {
     async fn insert_signed_txs(
         &self,
@@ -119,6 +148,19 @@ where
             .await
     }
 
+    async fn get_log_block_numbers(
+        &self,
+        ctx: Context,
+        from: BlockNumber,
+        to: BlockNumber,
+        addresses: &[H160],
+        topics: &[H256],
+    ) -> ProtocolResult<Vec<BlockNumber>> {
+        self.storage
+            .get_log_block_numbers(ctx, from, to, addresses, topics)
+            .await
+    }
+
     async fn get_transaction_by_hash(
         &self,
         ctx: Context,
@@ -159,23 +201,69 @@ where
             .map(U256::from)
     }
 
+    async fn get_mempool_content(
+        &self,
+        ctx: Context,
+        from: Option<H160>,
+    ) -> ProtocolResult<MemPoolContent> {
+        self.mempool.get_content(ctx, from).await
+    }
+
+    fn subscribe_pending_txs(&self) -> Receiver<SignedTransaction> {
+        self.mempool.subscribe_new_txs()
+    }
+
+    fn subscribe_new_blocks(&self) -> Receiver<BlockNumber> {
+        self.mempool.subscribe_committed()
+    }
+
+    fn get_gas_limit(&self, ctx: Context, number: BlockNumber) -> u64 {
+        self.metadata.get_metadata_unchecked(ctx, number).gas_limit
+    }
+
     async fn evm_call(
         &self,
         _ctx: Context,
         from: Option<H160>,
         to: Option<H160>,
         data: Vec<u8>,
+        gas_limit: u64,
+        access_list: AccessList,
         state_root: Hash,
         mock_header: Proposal,
     ) -> ProtocolResult<TxResp> {
+        self.ensure_state(state_root)?;
         let mut backend = AxonExecutorAdapter::from_root(
             state_root,
             Arc::clone(&self.trie_db),
             Arc::clone(&self.storage),
             ExecutorContext::from(mock_header),
-        )?;
+        )
+        .await?;
 
-        Ok(AxonExecutor::default().call(&mut backend, from, to, data))
+        Ok(AxonExecutor::default().call_with(&mut backend, from, to, data, gas_limit, access_list))
+    }
+
+    async fn create_access_list(
+        &self,
+        _ctx: Context,
+        from: Option<H160>,
+        to: Option<H160>,
+        data: Vec<u8>,
+        gas_limit: u64,
+        state_root: Hash,
+        mock_header: Proposal,
+    ) -> ProtocolResult<(AccessList, TxResp)> {
+        self.ensure_state(state_root)?;
+        let mut backend = AxonExecutorAdapter::from_root(
+            state_root,
+            Arc::clone(&self.trie_db),
+            Arc::clone(&self.storage),
+            ExecutorContext::from(mock_header),
+        )
+        .await?;
+
+        Ok(AxonExecutor::default().create_access_list(&mut backend, from, to, data, gas_limit))
     }
 
     async fn get_code_by_hash(&self, ctx: Context, hash: &Hash) -> ProtocolResult<Option<Bytes>> {
@@ -186,6 +274,30 @@ where
         self.net.peer_count(ctx).map(Into::into)
     }
 
+    async fn get_peers(&self, ctx: Context) -> ProtocolResult<Vec<NetworkPeer>> {
+        self.net.peers(ctx)
+    }
+
+    async fn get_node_info(&self, ctx: Context) -> ProtocolResult<NetworkNodeInfo> {
+        self.net.node_info(ctx)
+    }
+
+    async fn add_peer(&self, ctx: Context, addr: &str) -> ProtocolResult<()> {
+        self.net.add_peer(ctx, addr)
+    }
+
+    async fn remove_peer(&self, ctx: Context, peer_id: &str) -> ProtocolResult<()> {
+        self.net.remove_peer(ctx, peer_id)
+    }
+
+    async fn ban_peer(&self, ctx: Context, peer_id: &str, duration: u64) -> ProtocolResult<()> {
+        self.net.ban_peer(ctx, peer_id, duration)
+    }
+
+    async fn unban_peer(&self, ctx: Context, peer: &str) -> ProtocolResult<()> {
+        self.net.unban_peer(ctx, peer)
+    }
+
     async fn get_storage_at(
         &self,
         _ctx: Context,
@@ -193,13 +305,14 @@ where
         position: U256,
         state_root: Hash,
     ) -> ProtocolResult<Bytes> {
+        self.ensure_state(state_root)?;
         let state_mpt_tree = MPTTrie::from_root(state_root, Arc::clone(&self.trie_db))?;
 
         let raw_account = state_mpt_tree
             .get(address.as_bytes())?
             .ok_or_else(|| APIError::Adapter("Can't find this address".to_string()))?;
 
-        let account = Account::decode(raw_account).unwrap();
+        let account = Account::decode(raw_account)?;
 
         let storage_mpt_tree = MPTTrie::from_root(account.storage_root, Arc::clone(&self.trie_db))?;
 
@@ -208,4 +321,85 @@ where
             .get(hash.as_bytes())?
             .ok_or_else(|| APIError::Adapter("Can't find this position".to_string()).into())
     }
+
+    async fn get_proof(
+        &self,
+        _ctx: Context,
+        address: H160,
+        storage_keys: Vec<H256>,
+        state_root: Hash,
+    ) -> ProtocolResult<AccountProof> {
+        self.ensure_state(state_root)?;
+        account_proof(
+            Arc::clone(&self.trie_db),
+            state_root,
+            address,
+            &storage_keys,
+        )
+    }
+
+    async fn trace_block(
+        &self,
+        ctx: Context,
+        number: BlockNumber,
+        tx_hash: Option<Hash>,
+        tracer: TracerType,
+    ) -> ProtocolResult<Vec<(Hash, TraceResult)>> {
+        if number == 0 {
+            return Err(APIError::Adapter("Cannot trace the genesis block".to_string()).into());
+        }
+
+        let block = self
+            .storage
+            .get_block(ctx.clone(), number)
+            .await?
+            .ok_or_else(|| APIError::Adapter(format!("Cannot get {:?} block", number)))?;
+        let parent = self
+            .storage
+            .get_block_header(ctx.clone(), number - 1)
+            .await?
+            .ok_or_else(|| APIError::Adapter(format!("Cannot get {:?} block", number - 1)))?;
+        let txs = self
+            .storage
+            .get_transactions(ctx, number, &block.tx_hashes)
+            .await?
+            .into_iter()
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| {
+                APIError::Adapter(format!("Cannot get transactions of {:?} block", number))
+            })?;
+
+        self.ensure_state(parent.state_root)?;
+        let mut backend = AxonExecutorAdapter::from_root(
+            parent.state_root,
+            Arc::clone(&self.trie_db),
+            Arc::clone(&self.storage),
+            ExecutorContext::from(Proposal::from(block)),
+        )
+        .await?;
+
+        Ok(AxonExecutor::default().trace(&mut backend, txs, tx_hash, &tracer))
+    }
+
+    async fn trace_call(
+        &self,
+        _ctx: Context,
+        from: Option<H160>,
+        to: Option<H160>,
+        data: Vec<u8>,
+        state_root: Hash,
+        mock_header: Proposal,
+        tracer: TracerType,
+    ) -> ProtocolResult<TraceResult> {
+        self.ensure_state(state_root)?;
+        let mut backend = AxonExecutorAdapter::from_root(
+            state_root,
+            Arc::clone(&self.trie_db),
+            Arc::clone(&self.storage),
+            ExecutorContext::from(mock_header),
+        )
+        .await?;
+
+        Ok(AxonExecutor::default().trace_call(&mut backend, from, to, data, &tracer))
+    }
 }
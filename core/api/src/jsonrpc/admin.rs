@@ -0,0 +1,119 @@
+use std::sync::Arc;
+
+use jsonrpsee::{core::Error, proc_macros::rpc};
+
+use common_apm::metrics_rpc;
+use protocol::async_trait;
+use protocol::traits::{APIAdapter, Context};
+
+use crate::jsonrpc::web3_types::{Web3NodeInfo, Web3PeerInfo};
+use crate::jsonrpc::RpcResult;
+
+/// The seconds a peer is banned for if the duration is not given.
+const DEFAULT_BAN_DURATION: u64 = 24 * 60 * 60;
+
+/// The peer management methods. They are only served on the admin listening
+/// address, which should not be exposed to the public.
+#[rpc(server)]
+pub trait AxonAdmin {
+    /// Returns the connected peers with their scores and tags.
+    #[method(name = "admin_peers")]
+    async fn peers(&self) -> RpcResult<Vec<Web3PeerInfo>>;
+
+    /// Returns the peer id and the listening addresses of the node, with the
+    /// banned addresses.
+    #[method(name = "admin_nodeInfo")]
+    async fn node_info(&self) -> RpcResult<Web3NodeInfo>;
+
+    /// Keeps connecting to the peer at the multiaddr, which must end with
+    /// `/p2p/<peer id>`.
+    #[method(name = "admin_addPeer")]
+    async fn add_peer(&self, addr: String) -> RpcResult<bool>;
+
+    /// Forgets the addresses of the peer and disconnects it.
+    #[method(name = "admin_removePeer")]
+    async fn remove_peer(&self, peer_id: String) -> RpcResult<bool>;
+
+    /// Bans the peer and the IPs of its known addresses for the given seconds,
+    /// one day by default, and disconnects it if it is connected.
+    #[method(name = "admin_banPeer")]
+    async fn ban_peer(&self, peer_id: String, duration: Option<u64>) -> RpcResult<bool>;
+
+    /// Lifts the bans on a peer by its id, or on an IP network listed in
+    /// `admin_nodeInfo`.
+    #[method(name = "admin_unbanPeer")]
+    async fn unban_peer(&self, peer: String) -> RpcResult<bool>;
+}
+
+pub struct AdminRpcImpl<Adapter> {
+    adapter: Arc<Adapter>,
+}
+
+impl<Adapter: APIAdapter> AdminRpcImpl<Adapter> {
+    pub fn new(adapter: Arc<Adapter>) -> Self {
+        Self { adapter }
+    }
+}
+
+#[async_trait]
+impl<Adapter: APIAdapter + 'static> AxonAdminServer for AdminRpcImpl<Adapter> {
+    #[metrics_rpc("admin_peers")]
+    async fn peers(&self) -> RpcResult<Vec<Web3PeerInfo>> {
+        let peers = self
+            .adapter
+            .get_peers(Context::new())
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?;
+        Ok(peers.into_iter().map(Into::into).collect())
+    }
+
+    #[metrics_rpc("admin_nodeInfo")]
+    async fn node_info(&self) -> RpcResult<Web3NodeInfo> {
+        let info = self
+            .adapter
+            .get_node_info(Context::new())
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?;
+        Ok(info.into())
+    }
+
+    #[metrics_rpc("admin_addPeer")]
+    async fn add_peer(&self, addr: String) -> RpcResult<bool> {
+        self.adapter
+            .add_peer(Context::new(), &addr)
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?;
+        Ok(true)
+    }
+
+    #[metrics_rpc("admin_removePeer")]
+    async fn remove_peer(&self, peer_id: String) -> RpcResult<bool> {
+        self.adapter
+            .remove_peer(Context::new(), &peer_id)
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?;
+        Ok(true)
+    }
+
+    #[metrics_rpc("admin_banPeer")]
+    async fn ban_peer(&self, peer_id: String, duration: Option<u64>) -> RpcResult<bool> {
+        self.adapter
+            .ban_peer(
+                Context::new(),
+                &peer_id,
+                duration.unwrap_or(DEFAULT_BAN_DURATION),
+            )
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?;
+        Ok(true)
+    }
+
+    #[metrics_rpc("admin_unbanPeer")]
+    async fn unban_peer(&self, peer: String) -> RpcResult<bool> {
+        self.adapter
+            .unban_peer(Context::new(), &peer)
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?;
+        Ok(true)
+    }
+}
@@ -0,0 +1,153 @@
+use std::sync::Arc;
+
+use jsonrpsee::{core::Error, proc_macros::rpc};
+
+use common_apm::metrics_rpc;
+use protocol::traits::{APIAdapter, Context};
+use protocol::types::{BlockNumber, TraceResult, TracerType, H256};
+use protocol::{async_trait, ProtocolResult};
+
+use crate::jsonrpc::r#impl::mock_header_by_call_req;
+use crate::jsonrpc::web3_types::{BlockId, Web3CallRequest, Web3TraceConfig, Web3TraceTransaction};
+use crate::jsonrpc::RpcResult;
+use crate::APIError;
+
+#[rpc(server)]
+pub trait AxonDebug {
+    /// Replays the transaction with the given hash and returns its trace.
+    #[method(name = "debug_traceTransaction")]
+    async fn trace_transaction(
+        &self,
+        hash: H256,
+        config: Option<Web3TraceConfig>,
+    ) -> RpcResult<TraceResult>;
+
+    /// Replays all the transactions of the block with the given number and
+    /// returns their traces.
+    #[method(name = "debug_traceBlockByNumber")]
+    async fn trace_block_by_number(
+        &self,
+        number: BlockId,
+        config: Option<Web3TraceConfig>,
+    ) -> RpcResult<Vec<Web3TraceTransaction>>;
+
+    /// Executes a call on top of the given block and returns its trace.
+    #[method(name = "debug_traceCall")]
+    async fn trace_call(
+        &self,
+        req: Web3CallRequest,
+        number: BlockId,
+        config: Option<Web3TraceConfig>,
+    ) -> RpcResult<TraceResult>;
+}
+
+pub struct DebugRpcImpl<Adapter> {
+    adapter: Arc<Adapter>,
+}
+
+impl<Adapter: APIAdapter> DebugRpcImpl<Adapter> {
+    pub fn new(adapter: Arc<Adapter>) -> Self {
+        Self { adapter }
+    }
+
+    async fn block_number(&self, number: BlockId) -> ProtocolResult<BlockNumber> {
+        match number {
+            BlockId::Num(n) => Ok(n),
+            BlockId::Latest => self
+                .adapter
+                .get_block_header_by_number(Context::new(), None)
+                .await?
+                .map(|h| h.number)
+                .ok_or_else(|| APIError::Storage("Cannot get latest header".to_string()).into()),
+        }
+    }
+}
+
+fn tracer_type(config: Option<Web3TraceConfig>) -> RpcResult<TracerType> {
+    TracerType::try_from(config.unwrap_or_default()).map_err(|e| Error::Custom(e.to_string()))
+}
+
+#[async_trait]
+impl<Adapter: APIAdapter + 'static> AxonDebugServer for DebugRpcImpl<Adapter> {
+    #[metrics_rpc("debug_traceTransaction")]
+    async fn trace_transaction(
+        &self,
+        hash: H256,
+        config: Option<Web3TraceConfig>,
+    ) -> RpcResult<TraceResult> {
+        let tracer = tracer_type(config)?;
+        let receipt = self
+            .adapter
+            .get_receipt_by_tx_hash(Context::new(), hash)
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?
+            .ok_or_else(|| Error::Custom(format!("Cannot get transaction {:?}", hash)))?;
+
+        self.adapter
+            .trace_block(Context::new(), receipt.block_number, Some(hash), tracer)
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?
+            .pop()
+            .map(|(_, result)| result)
+            .ok_or_else(|| Error::Custom(format!("Cannot trace transaction {:?}", hash)))
+    }
+
+    #[metrics_rpc("debug_traceBlockByNumber")]
+    async fn trace_block_by_number(
+        &self,
+        number: BlockId,
+        config: Option<Web3TraceConfig>,
+    ) -> RpcResult<Vec<Web3TraceTransaction>> {
+        let tracer = tracer_type(config)?;
+        let number = self
+            .block_number(number)
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?;
+
+        let traces = self
+            .adapter
+            .trace_block(Context::new(), number, None, tracer)
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?;
+
+        Ok(traces
+            .into_iter()
+            .map(|(tx_hash, result)| Web3TraceTransaction { tx_hash, result })
+            .collect())
+    }
+
+    #[metrics_rpc("debug_traceCall")]
+    async fn trace_call(
+        &self,
+        req: Web3CallRequest,
+        number: BlockId,
+        config: Option<Web3TraceConfig>,
+    ) -> RpcResult<TraceResult> {
+        if req.from.is_none() && req.to.is_none() {
+            return Err(Error::Custom("from and to are both None".to_string()));
+        }
+
+        let tracer = tracer_type(config)?;
+        let number: Option<BlockNumber> = number.into();
+        let header = self
+            .adapter
+            .get_block_header_by_number(Context::new(), number)
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?
+            .ok_or_else(|| Error::Custom(format!("Cannot get {:?} header", number)))?;
+        let mock_header = mock_header_by_call_req(header, &req);
+
+        self.adapter
+            .trace_call(
+                Context::new(),
+                req.from,
+                req.to,
+                req.data.as_bytes().to_vec(),
+                mock_header.state_root,
+                mock_header.into(),
+                tracer,
+            )
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))
+    }
+}
@@ -0,0 +1,290 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use protocol::types::{Hash, Hasher, Hex, H160, U256};
+use protocol::ProtocolResult;
+
+use crate::APIError;
+
+const DOMAIN_TYPE: &str = "EIP712Domain";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TypedDataField {
+    pub name:  String,
+    #[serde(rename = "type")]
+    pub type_: String,
+}
+
+/// The structured data to be signed by `eth_signTypedData_v4`, see EIP-712.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TypedData {
+    pub types:        BTreeMap<String, Vec<TypedDataField>>,
+    pub primary_type: String,
+    pub domain:       Value,
+    pub message:      Value,
+}
+
+impl TypedData {
+    /// Calculate `keccak256("\x19\x01" ‖ domainSeparator ‖
+    /// hashStruct(message))`.
+    pub fn sign_hash(&self) -> ProtocolResult<Hash> {
+        let mut data = vec![0x19, 0x01];
+        data.extend_from_slice(self.hash_struct(DOMAIN_TYPE, &self.domain)?.as_bytes());
+        if self.primary_type != DOMAIN_TYPE {
+            data.extend_from_slice(
+                self.hash_struct(&self.primary_type, &self.message)?
+                    .as_bytes(),
+            );
+        }
+
+        Ok(Hasher::digest(data))
+    }
+
+    fn hash_struct(&self, name: &str, value: &Value) -> ProtocolResult<Hash> {
+        Ok(Hasher::digest(self.encode_data(name, value)?))
+    }
+
+    fn fields(&self, name: &str) -> ProtocolResult<&Vec<TypedDataField>> {
+        self.types
+            .get(name)
+            .ok_or_else(|| invalid(format!("unknown type {}", name)))
+    }
+
+    /// Encode the type as `Name(type1 name1,...)` followed by the types it
+    /// refers to in alphabetical order.
+    fn encode_type(&self, name: &str) -> ProtocolResult<String> {
+        let mut deps = BTreeSet::new();
+        self.collect_deps(name, &mut deps)?;
+        deps.remove(name);
+
+        let mut ret = self.encode_single_type(name)?;
+        for dep in deps.iter() {
+            ret.push_str(&self.encode_single_type(dep)?);
+        }
+        Ok(ret)
+    }
+
+    fn encode_single_type(&self, name: &str) -> ProtocolResult<String> {
+        let fields = self
+            .fields(name)?
+            .iter()
+            .map(|field| format!("{} {}", field.type_, field.name))
+            .collect::<Vec<_>>();
+        Ok(format!("{}({})", name, fields.join(",")))
+    }
+
+    fn collect_deps(&self, name: &str, deps: &mut BTreeSet<String>) -> ProtocolResult<()> {
+        if !deps.insert(name.to_string()) {
+            return Ok(());
+        }
+
+        for field in self.fields(name)?.iter() {
+            let base = base_type(&field.type_);
+            if self.types.contains_key(base) {
+                self.collect_deps(base, deps)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn encode_data(&self, name: &str, value: &Value) -> ProtocolResult<Vec<u8>> {
+        let obj = value
+            .as_object()
+            .ok_or_else(|| invalid(format!("{} is not an object", name)))?;
+
+        let mut ret = Hasher::digest(self.encode_type(name)?).as_bytes().to_vec();
+        for field in self.fields(name)?.iter() {
+            let value = obj
+                .get(&field.name)
+                .ok_or_else(|| invalid(format!("missing field {} of {}", field.name, name)))?;
+            ret.extend_from_slice(&self.encode_value(&field.type_, value)?);
+        }
+        Ok(ret)
+    }
+
+    fn encode_value(&self, type_: &str, value: &Value) -> ProtocolResult<[u8; 32]> {
+        if let Some(item_type) = array_item_type(type_) {
+            let items = value
+                .as_array()
+                .ok_or_else(|| invalid(format!("{} is not an array", type_)))?;
+            let mut data = Vec::with_capacity(items.len() * 32);
+            for item in items.iter() {
+                data.extend_from_slice(&self.encode_value(item_type, item)?);
+            }
+            return Ok(Hasher::digest(data).0);
+        }
+
+        if self.types.contains_key(type_) {
+            return Ok(self.hash_struct(type_, value)?.0);
+        }
+
+        let mut ret = [0u8; 32];
+        match type_ {
+            "string" => {
+                let s = value
+                    .as_str()
+                    .ok_or_else(|| invalid("string is expected".to_string()))?;
+                ret = Hasher::digest(s).0;
+            }
+            "bytes" => ret = Hasher::digest(parse_bytes(value)?).0,
+            "bool" => {
+                let b = value
+                    .as_bool()
+                    .ok_or_else(|| invalid("bool is expected".to_string()))?;
+                ret[31] = b as u8;
+            }
+            "address" => {
+                let s = value
+                    .as_str()
+                    .ok_or_else(|| invalid("address is expected".to_string()))?;
+                let address = H160::from_str(s.trim_start_matches("0x"))
+                    .map_err(|e| invalid(e.to_string()))?;
+                ret[12..].copy_from_slice(address.as_bytes());
+            }
+            _ if type_.starts_with("bytes") => {
+                let bytes = parse_bytes(value)?;
+                if bytes.len() > 32 {
+                    return Err(invalid(format!("too long value of {}", type_)));
+                }
+                ret[..bytes.len()].copy_from_slice(&bytes);
+            }
+            _ if type_.starts_with("uint") => parse_int(value, false)?.to_big_endian(&mut ret),
+            _ if type_.starts_with("int") => parse_int(value, true)?.to_big_endian(&mut ret),
+            _ => return Err(invalid(format!("unknown type {}", type_))),
+        }
+
+        Ok(ret)
+    }
+}
+
+fn invalid(msg: String) -> protocol::ProtocolError {
+    APIError::RequestPayload(msg).into()
+}
+
+fn base_type(type_: &str) -> &str {
+    type_.split('[').next().unwrap_or(type_)
+}
+
+fn array_item_type(type_: &str) -> Option<&str> {
+    if type_.ends_with(']') {
+        type_.rfind('[').map(|idx| &type_[..idx])
+    } else {
+        None
+    }
+}
+
+fn parse_bytes(value: &Value) -> ProtocolResult<Vec<u8>> {
+    let s = value
+        .as_str()
+        .ok_or_else(|| invalid("hex string is expected".to_string()))?;
+    Ok(Hex::decode(s.to_string())?.to_vec())
+}
+
+/// Parse a number given as either a JSON number, a decimal string or a hex
+/// string. A negative one is encoded as two's complement.
+fn parse_int(value: &Value, signed: bool) -> ProtocolResult<U256> {
+    let (negative, abs) = match value {
+        Value::Number(n) => match (n.as_u64(), n.as_i64()) {
+            (Some(n), _) => (false, U256::from(n)),
+            (None, Some(n)) => (true, U256::from(n.unsigned_abs())),
+            _ => return Err(invalid(format!("invalid integer {}", n))),
+        },
+        Value::String(s) => {
+            let (negative, s) = match s.strip_prefix('-') {
+                Some(s) => (true, s),
+                None => (false, s.as_str()),
+            };
+            let abs = match s.strip_prefix("0x") {
+                Some(hex) => U256::from_str_radix(hex, 16).map_err(|e| invalid(e.to_string()))?,
+                None => U256::from_dec_str(s).map_err(|e| invalid(format!("{:?}", e)))?,
+            };
+            (negative, abs)
+        }
+        _ => return Err(invalid("integer is expected".to_string())),
+    };
+
+    if negative && !signed {
+        return Err(invalid(format!("negative unsigned integer -{}", abs)));
+    }
+
+    Ok(if negative {
+        U256::zero().overflowing_sub(abs).0
+    } else {
+        abs
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eip712_mail() {
+        let typed_data: TypedData = serde_json::from_str(
+            r#"{
+                "types": {
+                    "EIP712Domain": [
+                        {"name": "name", "type": "string"},
+                        {"name": "version", "type": "string"},
+                        {"name": "chainId", "type": "uint256"},
+                        {"name": "verifyingContract", "type": "address"}
+                    ],
+                    "Person": [
+                        {"name": "name", "type": "string"},
+                        {"name": "wallet", "type": "address"}
+                    ],
+                    "Mail": [
+                        {"name": "from", "type": "Person"},
+                        {"name": "to", "type": "Person"},
+                        {"name": "contents", "type": "string"}
+                    ]
+                },
+                "primaryType": "Mail",
+                "domain": {
+                    "name": "Ether Mail",
+                    "version": "1",
+                    "chainId": 1,
+                    "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+                },
+                "message": {
+                    "from": {
+                        "name": "Cow",
+                        "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"
+                    },
+                    "to": {
+                        "name": "Bob",
+                        "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB"
+                    },
+                    "contents": "Hello, Bob!"
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            typed_data.encode_type("Mail").unwrap(),
+            "Mail(Person from,Person to,string contents)Person(string name,address wallet)"
+        );
+        assert_eq!(
+            typed_data.sign_hash().unwrap(),
+            Hash::from_str("be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_int() {
+        let minus_one = parse_int(&Value::from(-1), true).unwrap();
+        assert_eq!(minus_one, U256::MAX);
+        assert_eq!(parse_int(&Value::from("-0x1"), true).unwrap(), minus_one);
+        assert_eq!(
+            parse_int(&Value::from("0x10"), false).unwrap(),
+            U256::from(16u64)
+        );
+        assert!(parse_int(&Value::from("-1"), false).is_err());
+    }
+}
@@ -12,28 +12,29 @@ use protocol::{
     tokio::{
         self, select,
         sync::{
+            broadcast::{self, error::RecvError},
             mpsc::{channel, Receiver, Sender},
             oneshot,
         },
         time::interval,
     },
     traits::{APIAdapter, Context},
-    types::{BlockNumber, Hash, Receipt, H160, H256, U256},
+    types::{BlockNumber, Hash, SignedTransaction, H160, H256, U256},
 };
 
 use crate::jsonrpc::{
-    r#impl::from_receipt_to_web3_log,
+    logs::{search_logs, LogsLimit},
     web3_types::{BlockId, MultiType, Web3Log},
     RpcResult,
 };
 
-pub fn filter_module<Adapter>(adapter: Arc<Adapter>) -> JsonRpcFilter
+pub fn filter_module<Adapter>(adapter: Arc<Adapter>, logs_limit: LogsLimit) -> JsonRpcFilter
 where
     Adapter: APIAdapter + 'static,
 {
     let (tx, rx) = channel(128);
 
-    tokio::spawn(FilterHub::new(adapter, rx).run());
+    tokio::spawn(FilterHub::new(adapter, rx, logs_limit).run());
 
     JsonRpcFilter { sender: tx }
 }
@@ -46,6 +47,9 @@ pub trait AxonFilter {
     #[method(name = "eth_newBlockFilter")]
     async fn block_filter(&self) -> RpcResult<U256>;
 
+    #[method(name = "eth_newPendingTransactionFilter")]
+    async fn pending_transaction_filter(&self) -> RpcResult<U256>;
+
     #[method(name = "eth_getFilterLogs")]
     async fn get_filter_logs(&self, id: U256) -> RpcResult<FilterChanges>;
 
@@ -61,6 +65,7 @@ pub trait AxonFilter {
 pub enum FilterChanges {
     Blocks(Vec<H256>),
     Logs(Vec<Web3Log>),
+    Transactions(Vec<Hash>),
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
@@ -119,6 +124,17 @@ impl AxonFilterServer for JsonRpcFilter {
         Ok(rx.await.unwrap())
     }
 
+    async fn pending_transaction_filter(&self) -> RpcResult<U256> {
+        let (tx, rx) = oneshot::channel();
+
+        self.sender
+            .send(Command::NewPendingTransactions(tx))
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?;
+
+        Ok(rx.await.unwrap())
+    }
+
     async fn get_filter_logs(&self, id: U256) -> RpcResult<FilterChanges> {
         let (tx, rx) = oneshot::channel();
 
@@ -156,29 +172,36 @@ impl AxonFilterServer for JsonRpcFilter {
 pub enum Command {
     NewLogs((LoggerFilter, oneshot::Sender<U256>)),
     NewBlocks(oneshot::Sender<U256>),
+    NewPendingTransactions(oneshot::Sender<U256>),
     FilterRequest((U256, oneshot::Sender<RpcResult<FilterChanges>>)),
     Uninstall((U256, oneshot::Sender<bool>)),
 }
 
 pub struct FilterHub<Adapter> {
-    logs_hub:   HashMap<U256, (LoggerFilter, Instant)>,
-    blocks_hub: HashMap<U256, (BlockNumber, Instant)>,
-    id:         u64,
-    recv:       Receiver<Command>,
-    adapter:    Arc<Adapter>,
+    logs_hub:        HashMap<U256, (LoggerFilter, Instant)>,
+    blocks_hub:      HashMap<U256, (BlockNumber, Instant)>,
+    pending_txs_hub: HashMap<U256, (Vec<Hash>, Instant)>,
+    id:              u64,
+    recv:            Receiver<Command>,
+    pending_txs:     broadcast::Receiver<SignedTransaction>,
+    adapter:         Arc<Adapter>,
+    logs_limit:      LogsLimit,
 }
 
 impl<Adapter> FilterHub<Adapter>
 where
     Adapter: APIAdapter + 'static,
 {
-    pub fn new(adapter: Arc<Adapter>, recv: Receiver<Command>) -> Self {
+    pub fn new(adapter: Arc<Adapter>, recv: Receiver<Command>, logs_limit: LogsLimit) -> Self {
         Self {
             logs_hub: HashMap::new(),
             blocks_hub: HashMap::new(),
+            pending_txs_hub: HashMap::new(),
             id: 0,
             recv,
+            pending_txs: adapter.subscribe_pending_txs(),
             adapter,
+            logs_limit,
         }
     }
 
@@ -196,6 +219,21 @@ where
                         }
                     }
                 }
+                stx = self.pending_txs.recv() => {
+                    match stx {
+                        Ok(stx) => {
+                            for (hashes, _) in self.pending_txs_hub.values_mut() {
+                                hashes.push(stx.transaction.hash);
+                            }
+                        }
+                        Err(RecvError::Lagged(n)) => {
+                            log::warn!("[api]: pending tx filter misses {} txs", n)
+                        }
+                        Err(RecvError::Closed) => {
+                            break
+                        }
+                    }
+                }
                 _ = time_internal.tick() => {
                     self.check_hubs();
                 }
@@ -211,6 +249,8 @@ where
         self.blocks_hub
             .retain(|_, (_, time)| now.saturating_duration_since(*time) < Duration::from_secs(40));
         self.logs_hub
+            .retain(|_, (_, time)| now.saturating_duration_since(*time) < Duration::from_secs(40));
+        self.pending_txs_hub
             .retain(|_, (_, time)| now.saturating_duration_since(*time) < Duration::from_secs(40))
     }
 
@@ -234,10 +274,17 @@ where
                     .insert(self.id.into(), (header.number, Instant::now()));
                 sender.send(self.id.into()).unwrap()
             }
+            Command::NewPendingTransactions(sender) => {
+                self.id += 1;
+                self.pending_txs_hub
+                    .insert(self.id.into(), (Vec::new(), Instant::now()));
+                sender.send(self.id.into()).unwrap()
+            }
             Command::FilterRequest((id, sender)) => self.impl_filter(id, sender).await,
             Command::Uninstall((id, sender)) => {
-                let removed =
-                    self.blocks_hub.remove(&id).is_some() || self.logs_hub.remove(&id).is_some();
+                let removed = self.blocks_hub.remove(&id).is_some()
+                    || self.logs_hub.remove(&id).is_some()
+                    || self.pending_txs_hub.remove(&id).is_some();
                 sender.send(removed).unwrap()
             }
         }
@@ -253,6 +300,10 @@ where
                 self.logs_hub.remove(&id);
             }
             sender.send(res).unwrap()
+        } else if let Some((hashes, time)) = self.pending_txs_hub.get_mut(&id) {
+            *time = Instant::now();
+            let res = Ok(FilterChanges::Transactions(std::mem::take(hashes)));
+            sender.send(res).unwrap()
         } else {
             sender
                 .send(Err(Error::Custom(format!(
@@ -299,8 +350,6 @@ where
         let default_topic = Vec::new();
         let topics = filter.topics.as_ref().unwrap_or(&default_topic);
 
-        let mut all_logs = Vec::new();
-
         let latest_block = self
             .adapter
             .get_block_by_number(Context::new(), None)
@@ -335,55 +384,16 @@ where
             return Err(Error::Custom(format!("Invalid from_block {}", start)));
         }
 
-        let extend_logs = |logs: &mut Vec<Web3Log>, receipts: Vec<Option<Receipt>>| {
-            let mut index = 0;
-            for receipt in receipts.into_iter().flatten() {
-                let log_len = receipt.logs.len();
-                match filter.address {
-                    Some(ref s) if s.contains(&receipt.sender) => {
-                        from_receipt_to_web3_log(index, topics, &receipt, logs)
-                    }
-                    None => from_receipt_to_web3_log(index, topics, &receipt, logs),
-                    _ => (),
-                }
-                index += log_len;
-            }
-        };
-
-        let mut visiter_last_block = false;
-        for n in start..=end {
-            if n == latest_number {
-                visiter_last_block = true;
-            } else {
-                let block = self
-                    .adapter
-                    .get_block_by_number(Context::new(), Some(n))
-                    .await
-                    .map_err(|e| Error::Custom(e.to_string()))?
-                    .unwrap();
-                let receipts = self
-                    .adapter
-                    .get_receipts_by_hashes(Context::new(), block.header.number, &block.tx_hashes)
-                    .await
-                    .map_err(|e| Error::Custom(e.to_string()))?;
-
-                extend_logs(&mut all_logs, receipts);
-            }
-        }
-
-        if visiter_last_block {
-            let receipts = self
-                .adapter
-                .get_receipts_by_hashes(
-                    Context::new(),
-                    latest_block.header.number,
-                    &latest_block.tx_hashes,
-                )
-                .await
-                .map_err(|e| Error::Custom(e.to_string()))?;
-
-            extend_logs(&mut all_logs, receipts);
-        }
+        let all_logs = search_logs(
+            &*self.adapter,
+            self.logs_limit,
+            &latest_block,
+            start,
+            end,
+            filter.address.as_ref(),
+            topics,
+        )
+        .await?;
 
         if let Some(BlockId::Num(ref mut n)) = filter.from_block {
             *n = latest_number + 1
@@ -0,0 +1,371 @@
+use std::{collections::VecDeque, sync::Arc};
+
+use parking_lot::RwLock;
+
+use protocol::tokio::sync::broadcast::error::RecvError;
+use protocol::traits::{APIAdapter, Context};
+use protocol::types::{BlockNumber, Header, SignedTransaction, U256};
+use protocol::ProtocolResult;
+
+use crate::jsonrpc::web3_types::Web3FeeHistory;
+use crate::APIError;
+
+/// The max number of blocks can be queried by `eth_feeHistory` at once, which
+/// is also the size of the cached window.
+pub const MAX_FEE_HISTORY_BLOCKS: u64 = 1024;
+/// The number of the lowest tips sampled from each block when suggesting the
+/// priority fee.
+const SAMPLE_TIPS_PER_BLOCK: usize = 3;
+
+/// The fee data of a committed block.
+#[derive(Clone, Debug, PartialEq)]
+struct BlockFees {
+    number:         BlockNumber,
+    base_fee:       U256,
+    gas_used:       U256,
+    gas_used_ratio: f64,
+    /// The effective tip and the gas used of each transaction, sorted by the
+    /// tip in ascending order.
+    tips:           Vec<(U256, U256)>,
+}
+
+impl BlockFees {
+    fn reward(&self, percentile: f64) -> U256 {
+        if self.tips.is_empty() {
+            return U256::zero();
+        }
+
+        let threshold = self.gas_used.as_u128() as f64 * percentile / 100.0;
+        let mut sum = U256::zero();
+        for (tip, gas_used) in self.tips.iter() {
+            sum += *gas_used;
+            if sum.as_u128() as f64 >= threshold {
+                return *tip;
+            }
+        }
+
+        self.tips.last().map(|(tip, _)| *tip).unwrap_or_default()
+    }
+}
+
+/// The gas price oracle samples the base fee, the gas usage and the effective
+/// tips of the recent blocks to answer `eth_gasPrice`,
+/// `eth_maxPriorityFeePerGas` and `eth_feeHistory`. The fees of the latest
+/// blocks are cached and the window is moved forward once a new block is
+/// committed.
+pub struct GasPriceOracle<Adapter> {
+    adapter:       Arc<Adapter>,
+    sample_blocks: u64,
+    percentile:    u64,
+    cache:         RwLock<VecDeque<BlockFees>>,
+}
+
+impl<Adapter: APIAdapter> GasPriceOracle<Adapter> {
+    pub fn new(adapter: Arc<Adapter>, sample_blocks: u64, percentile: u64) -> Self {
+        GasPriceOracle {
+            adapter,
+            sample_blocks: sample_blocks.clamp(1, MAX_FEE_HISTORY_BLOCKS),
+            percentile: percentile.min(100),
+            cache: RwLock::new(VecDeque::new()),
+        }
+    }
+
+    /// Suggest the gas price of a legacy transaction, which is the base fee of
+    /// the latest block plus the suggested priority fee.
+    pub async fn gas_price(&self) -> ProtocolResult<U256> {
+        let latest = self.latest().await?;
+        let base_fee = self
+            .cached(latest)
+            .map(|fees| fees.base_fee)
+            .unwrap_or_default();
+
+        Ok(base_fee.saturating_add(self.suggest_tip(latest)))
+    }
+
+    /// Suggest the priority fee by the configured percentile of the lowest
+    /// tips in the sampled blocks.
+    pub async fn max_priority_fee_per_gas(&self) -> ProtocolResult<U256> {
+        let latest = self.latest().await?;
+        Ok(self.suggest_tip(latest))
+    }
+
+    pub async fn fee_history(
+        &self,
+        block_count: u64,
+        newest_block: Option<BlockNumber>,
+        reward_percentiles: Option<Vec<f64>>,
+    ) -> ProtocolResult<Web3FeeHistory> {
+        if let Some(percentiles) = reward_percentiles.as_ref() {
+            check_percentiles(percentiles)?;
+        }
+
+        let latest = self.latest().await?;
+        let newest = newest_block.unwrap_or(latest).min(latest);
+        let block_count = block_count.min(MAX_FEE_HISTORY_BLOCKS).min(newest + 1);
+        let oldest = newest + 1 - block_count;
+
+        let mut history = Web3FeeHistory {
+            oldest_block:     oldest.into(),
+            reward:           reward_percentiles.as_ref().map(|_| Vec::new()),
+            base_fee_per_gas: Vec::with_capacity(block_count as usize + 1),
+            gas_used_ratio:   Vec::with_capacity(block_count as usize),
+        };
+
+        if block_count == 0 {
+            return Ok(history);
+        }
+
+        for number in oldest..=newest {
+            let fees = match self.cached(number) {
+                Some(fees) => fees,
+                None => self.load_block_fees(number).await?,
+            };
+
+            history.base_fee_per_gas.push(fees.base_fee);
+            history.gas_used_ratio.push(fees.gas_used_ratio);
+
+            if let (Some(rewards), Some(percentiles)) =
+                (history.reward.as_mut(), reward_percentiles.as_ref())
+            {
+                rewards.push(percentiles.iter().map(|p| fees.reward(*p)).collect());
+            }
+        }
+
+        // The base fee of the block next to the newest one, which is calculated
+        // by EIP-1559 if the newest one is the latest block.
+        let next_base_fee = if newest < latest {
+            match self.cached(newest + 1) {
+                Some(fees) => fees.base_fee,
+                None => self.header(newest + 1).await?.base_fee_per_gas,
+            }
+        } else {
+            let gas_limit = self.adapter.get_gas_limit(Context::new(), newest + 1);
+            self.header(newest).await?.next_base_fee_per_gas(gas_limit)
+        };
+        history.base_fee_per_gas.push(next_base_fee);
+
+        Ok(history)
+    }
+
+    /// Move the cached window forward whenever a new block is committed.
+    pub async fn run(self: Arc<Self>) {
+        let mut new_blocks = self.adapter.subscribe_new_blocks();
+        if let Err(e) = self.update().await {
+            log::warn!("[api]: update gas price oracle failed {:?}", e);
+        }
+
+        loop {
+            match new_blocks.recv().await {
+                Ok(_) | Err(RecvError::Lagged(_)) => {
+                    if let Err(e) = self.update().await {
+                        log::warn!("[api]: update gas price oracle failed {:?}", e);
+                    }
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    }
+
+    /// Return the latest block in the cached window. The window is only filled
+    /// here if it has not been filled by [`GasPriceOracle::run`] yet.
+    async fn latest(&self) -> ProtocolResult<BlockNumber> {
+        let cached_tip = self.cache.read().back().map(|fees| fees.number);
+        match cached_tip {
+            Some(tip) => Ok(tip),
+            None => self.update().await,
+        }
+    }
+
+    /// Move the cached window forward to the latest committed block and return
+    /// the latest block number.
+    async fn update(&self) -> ProtocolResult<BlockNumber> {
+        let latest = self
+            .adapter
+            .get_block_header_by_number(Context::new(), None)
+            .await?
+            .ok_or_else(|| APIError::Storage("Cannot get latest header".to_string()))?
+            .number;
+
+        let cached_tip = self.cache.read().back().map(|fees| fees.number);
+        let start = match cached_tip {
+            Some(tip) if tip >= latest => return Ok(latest),
+            Some(tip) => (tip + 1).max(latest.saturating_sub(MAX_FEE_HISTORY_BLOCKS - 1)),
+            None => latest.saturating_sub(self.sample_blocks - 1),
+        };
+
+        let mut new_fees = Vec::with_capacity((latest - start + 1) as usize);
+        for number in start..=latest {
+            new_fees.push(self.load_block_fees(number).await?);
+        }
+
+        let mut cache = self.cache.write();
+        for fees in new_fees.into_iter() {
+            // The window may have been moved forward meanwhile.
+            if cache
+                .back()
+                .map(|f| f.number >= fees.number)
+                .unwrap_or(false)
+            {
+                continue;
+            }
+
+            // Drop the whole window if it is not continuous with the new block.
+            if cache
+                .back()
+                .map(|f| f.number + 1 != fees.number)
+                .unwrap_or(false)
+            {
+                cache.clear();
+            }
+
+            cache.push_back(fees);
+            if cache.len() as u64 > MAX_FEE_HISTORY_BLOCKS {
+                cache.pop_front();
+            }
+        }
+
+        Ok(latest)
+    }
+
+    fn cached(&self, number: BlockNumber) -> Option<BlockFees> {
+        let cache = self.cache.read();
+        let front = cache.front()?.number;
+        if number < front {
+            return None;
+        }
+
+        cache.get((number - front) as usize).cloned()
+    }
+
+    fn suggest_tip(&self, latest: BlockNumber) -> U256 {
+        let oldest = latest.saturating_sub(self.sample_blocks - 1);
+        let cache = self.cache.read();
+        let mut tips = cache
+            .iter()
+            .filter(|fees| fees.number >= oldest && fees.number <= latest)
+            .flat_map(|fees| {
+                fees.tips
+                    .iter()
+                    .take(SAMPLE_TIPS_PER_BLOCK)
+                    .map(|(tip, _)| *tip)
+            })
+            .collect::<Vec<_>>();
+
+        if tips.is_empty() {
+            return U256::zero();
+        }
+
+        tips.sort_unstable();
+        let index = (tips.len() - 1) * self.percentile as usize / 100;
+        tips[index]
+    }
+
+    async fn header(&self, number: BlockNumber) -> ProtocolResult<Header> {
+        self.adapter
+            .get_block_header_by_number(Context::new(), Some(number))
+            .await?
+            .ok_or_else(|| APIError::Storage(format!("Cannot get {:?} header", number)).into())
+    }
+
+    async fn load_block_fees(&self, number: BlockNumber) -> ProtocolResult<BlockFees> {
+        let block = self
+            .adapter
+            .get_block_by_number(Context::new(), Some(number))
+            .await?
+            .ok_or_else(|| APIError::Storage(format!("Cannot get {:?} block", number)))?;
+        let header = &block.header;
+
+        let txs = self
+            .adapter
+            .get_transactions_by_hashes(Context::new(), number, &block.tx_hashes)
+            .await?;
+        let receipts = self
+            .adapter
+            .get_receipts_by_hashes(Context::new(), number, &block.tx_hashes)
+            .await?;
+
+        let mut tips = txs
+            .into_iter()
+            .zip(receipts.into_iter())
+            .filter_map(|(tx, receipt)| {
+                let (tx, receipt) = (tx?, receipt?);
+                Some((
+                    effective_tip(&tx, header.base_fee_per_gas),
+                    receipt.used_gas,
+                ))
+            })
+            .collect::<Vec<_>>();
+        tips.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+
+        let gas_used_ratio = if header.gas_limit.is_zero() {
+            0.0
+        } else {
+            header.gas_used.as_u128() as f64 / header.gas_limit.as_u128() as f64
+        };
+
+        Ok(BlockFees {
+            number,
+            base_fee: header.base_fee_per_gas,
+            gas_used: header.gas_used,
+            gas_used_ratio,
+            tips,
+        })
+    }
+}
+
+/// The tip paid to the proposer per gas, which is capped by the max fee minus
+/// the base fee.
+fn effective_tip(tx: &SignedTransaction, base_fee: U256) -> U256 {
+    let tx = &tx.transaction.unsigned;
+    tx.max_priority_fee_per_gas
+        .min(tx.gas_price.saturating_sub(base_fee))
+}
+
+fn check_percentiles(percentiles: &[f64]) -> ProtocolResult<()> {
+    let mut prev = 0.0;
+    for p in percentiles.iter() {
+        if !(0.0..=100.0).contains(p) || *p < prev {
+            return Err(APIError::RequestPayload(format!(
+                "Invalid reward percentiles {:?}",
+                percentiles
+            ))
+            .into());
+        }
+        prev = *p;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_reward() {
+        let fees = BlockFees {
+            number:         1,
+            base_fee:       U256::from(10u64),
+            gas_used:       U256::from(100u64),
+            gas_used_ratio: 0.5,
+            tips:           vec![
+                (U256::from(1u64), U256::from(50u64)),
+                (U256::from(2u64), U256::from(30u64)),
+                (U256::from(3u64), U256::from(20u64)),
+            ],
+        };
+
+        assert_eq!(fees.reward(0.0), U256::from(1u64));
+        assert_eq!(fees.reward(50.0), U256::from(1u64));
+        assert_eq!(fees.reward(60.0), U256::from(2u64));
+        assert_eq!(fees.reward(100.0), U256::from(3u64));
+    }
+
+    #[test]
+    fn test_check_percentiles() {
+        assert!(check_percentiles(&[]).is_ok());
+        assert!(check_percentiles(&[10.0, 50.0, 90.0]).is_ok());
+        assert!(check_percentiles(&[50.0, 10.0]).is_err());
+        assert!(check_percentiles(&[101.0]).is_err());
+    }
+}
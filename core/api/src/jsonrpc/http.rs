@@ -0,0 +1,207 @@
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+use futures::future::join_all;
+use hyper::body::HttpBody;
+use hyper::server::conn::AddrStream;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{header, Body, Method, Request, Response, Server, StatusCode};
+use jsonrpsee::RpcModule;
+use serde_json::{json, Value};
+
+use common_apm::metrics::api::on_request_rejected;
+use protocol::tokio::{self, task::JoinHandle};
+use protocol::ProtocolResult;
+
+use crate::jsonrpc::limiter::RateLimiter;
+use crate::APIError;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const LIMIT_EXCEEDED: i64 = -32005;
+const REQUEST_TIMEOUT: i64 = -32002;
+
+pub type HttpServerHandle = JoinHandle<()>;
+
+/// The HTTP endpoint of the JSON-RPC methods. The requests are checked against
+/// the rate limits by the IP of the connection before they are dispatched to
+/// the RPC module.
+struct HttpHandler {
+    rpc:              RpcModule<()>,
+    methods:          HashSet<&'static str>,
+    limiter:          RateLimiter,
+    max_payload_size: usize,
+}
+
+pub async fn run_http_server(
+    addr: SocketAddr,
+    rpc: RpcModule<()>,
+    limiter: RateLimiter,
+    max_payload_size: usize,
+) -> ProtocolResult<HttpServerHandle> {
+    let handler = Arc::new(HttpHandler {
+        methods: rpc.method_names().collect(),
+        rpc,
+        limiter,
+        max_payload_size,
+    });
+
+    let make_service = make_service_fn(move |conn: &AddrStream| {
+        let ip = conn.remote_addr().ip();
+        let handler = Arc::clone(&handler);
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req| {
+                let handler = Arc::clone(&handler);
+                async move { Ok::<_, Infallible>(handler.handle(ip, req).await) }
+            }))
+        }
+    });
+
+    let server = Server::try_bind(&addr)
+        .map_err(|e| APIError::HttpServer(e.to_string()))?
+        .serve(make_service);
+
+    Ok(tokio::spawn(async move {
+        if let Err(e) = server.await {
+            log::error!("[api]: http server error {:?}", e);
+        }
+    }))
+}
+
+impl HttpHandler {
+    async fn handle(&self, ip: IpAddr, req: Request<Body>) -> Response<Body> {
+        if req.method() != Method::POST {
+            return status_response(StatusCode::METHOD_NOT_ALLOWED);
+        }
+
+        let mut body = req.into_body();
+        let mut payload = Vec::new();
+        while let Some(chunk) = body.data().await {
+            match chunk {
+                Ok(chunk) if payload.len() + chunk.len() <= self.max_payload_size => {
+                    payload.extend_from_slice(&chunk)
+                }
+                Ok(_) => return status_response(StatusCode::PAYLOAD_TOO_LARGE),
+                Err(_) => return status_response(StatusCode::BAD_REQUEST),
+            }
+        }
+
+        let resp = match serde_json::from_slice::<Value>(&payload) {
+            Ok(Value::Array(calls)) => self.handle_batch(ip, calls).await,
+            Ok(call) => self.handle_call(ip, call).await,
+            Err(e) => Some(error_response(&Value::Null, PARSE_ERROR, &e.to_string())),
+        };
+
+        // The notifications are not answered.
+        let resp = match resp {
+            Some(resp) => resp,
+            None => return status_response(StatusCode::NO_CONTENT),
+        };
+
+        Response::builder()
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(resp))
+            .unwrap()
+    }
+
+    async fn handle_batch(&self, ip: IpAddr, calls: Vec<Value>) -> Option<String> {
+        if calls.is_empty() {
+            return Some(error_response(&Value::Null, INVALID_REQUEST, "empty batch"));
+        }
+        if let Err(e) = self.limiter.check_batch(calls.len()) {
+            on_request_rejected("batch");
+            return Some(error_response(&Value::Null, LIMIT_EXCEEDED, &e.to_string()));
+        }
+
+        let resps = join_all(calls.into_iter().map(|call| self.handle_call(ip, call)))
+            .await
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        if resps.is_empty() {
+            return None;
+        }
+
+        Some(format!("[{}]", resps.join(",")))
+    }
+
+    /// Handle a call and return its response, or `None` if it is a
+    /// notification which has no `id`. A notification is still dispatched,
+    /// but its result and errors are not sent back.
+    async fn handle_call(&self, ip: IpAddr, mut call: Value) -> Option<String> {
+        let method = call
+            .get("method")
+            .and_then(Value::as_str)
+            .map(str::to_owned);
+        let is_notification = method.is_some() && call.get("id").is_none();
+        if is_notification {
+            call["id"] = Value::Null;
+        }
+
+        let id = call.get("id").cloned().unwrap_or(Value::Null);
+        let resp = match method {
+            Some(method) => self.dispatch(ip, &id, &method, &call).await,
+            None => error_response(&id, INVALID_REQUEST, "method is missing"),
+        };
+
+        (!is_notification).then(|| resp)
+    }
+
+    async fn dispatch(&self, ip: IpAddr, id: &Value, method: &str, call: &Value) -> String {
+        if let Err(e) = self.limiter.check_call(ip, method) {
+            on_request_rejected(self.metric_label(method));
+            log::debug!("[api]: reject {} request: {}", method, e);
+            return error_response(id, LIMIT_EXCEEDED, &e.to_string());
+        }
+
+        let req = call.to_string();
+        let resp = match self.limiter.timeout(method) {
+            Some(timeout) => {
+                match tokio::time::timeout(timeout, self.rpc.raw_json_request(&req)).await {
+                    Ok(resp) => resp,
+                    Err(_) => {
+                        on_request_rejected(self.metric_label(method));
+                        return error_response(id, REQUEST_TIMEOUT, "request timed out");
+                    }
+                }
+            }
+            None => self.rpc.raw_json_request(&req).await,
+        };
+
+        match resp {
+            Ok((resp, _)) => resp,
+            Err(e) => error_response(id, INVALID_REQUEST, &e.to_string()),
+        }
+    }
+
+    /// Only the registered methods are used as metric labels, so that the
+    /// callers cannot create labels at will.
+    fn metric_label<'a>(&self, method: &'a str) -> &'a str {
+        if self.methods.contains(method) {
+            method
+        } else {
+            "unknown"
+        }
+    }
+}
+
+fn error_response(id: &Value, code: i64, message: &str) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "error": {
+            "code": code,
+            "message": message,
+        },
+        "id": id,
+    })
+    .to_string()
+}
+
+fn status_response(status: StatusCode) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .body(Body::empty())
+        .unwrap()
+}
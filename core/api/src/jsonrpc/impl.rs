@@ -1,4 +1,5 @@
 use std::{
+    collections::HashMap,
     path::PathBuf,
     sync::{
         atomic::{AtomicBool, Ordering},
@@ -9,68 +10,295 @@ use std::{
 use jsonrpsee::core::Error;
 
 use common_apm::metrics_rpc;
+use common_config_parser::types::ConfigMethodLimit;
 use core_consensus::SYNC_STATUS;
+use core_executor::tracer::exit_error;
 use protocol::traits::{APIAdapter, Context};
 use protocol::types::{
-    Block, BlockNumber, Bytes, Hash, Hasher, Header, Hex, Receipt, SignedTransaction, TxResp,
-    UnverifiedTransaction, H160, H256, H64, U256,
+    BlockNumber, Bytes, Hash, Hasher, Header, Hex, Receipt, SignedTransaction, Transaction,
+    TransactionAction, TxResp, UnverifiedTransaction, H160, H256, H64, U256,
 };
-use protocol::{async_trait, codec::ProtocolCodec, ProtocolResult};
+use protocol::{async_trait, codec::ProtocolCodec, tokio, ProtocolResult};
 
 use crate::jsonrpc::web3_types::{
-    BlockId, BlockIdWithPending, RichTransactionOrHash, Web3Block, Web3CallRequest, Web3FeeHistory,
-    Web3Filter, Web3Log, Web3Receipt, Web3SyncStatus, Web3Transaction,
+    BlockId, BlockIdOrHash, BlockIdWithPending, RichTransactionOrHash, Web3AccessListResult,
+    Web3AccountProof, Web3Block, Web3CallRequest, Web3FeeHistory, Web3Filter, Web3Log, Web3Receipt,
+    Web3SignedTransaction, Web3SyncStatus, Web3Transaction,
 };
 
-use crate::jsonrpc::{AxonJsonRpcServer, RpcResult};
+use crate::jsonrpc::{
+    eip712::TypedData,
+    gas_oracle::GasPriceOracle,
+    keystore::KeyStore,
+    logs::{extend_logs, search_logs, LogsLimit},
+    AxonJsonRpcServer, RpcResult,
+};
 use crate::APIError;
 
+/// The intrinsic gas of a transaction, no transaction succeeds with less.
+const MIN_TRANSACTION_GAS: u64 = 21_000;
+
+/// The gas limits of the calls running the EVM. The timeout of a request does
+/// not stop the EVM, so these bound the work left running after it. A method
+/// with its own cap is bounded by the lower one.
+#[derive(Clone, Debug)]
+pub struct GasCap {
+    global:  u64,
+    methods: HashMap<String, u64>,
+}
+
+impl GasCap {
+    /// A cap of 0 is no limit.
+    pub fn new(global: u64, limits: &[ConfigMethodLimit]) -> Self {
+        let global = match global {
+            0 => u64::MAX,
+            cap => cap,
+        };
+        let methods = limits
+            .iter()
+            .filter_map(|limit| {
+                let cap = limit.gas_cap.filter(|cap| *cap != 0)?;
+                Some((limit.name.clone(), cap))
+            })
+            .collect();
+
+        GasCap { global, methods }
+    }
+
+    fn of(&self, method: &str) -> u64 {
+        self.methods
+            .get(method)
+            .map_or(self.global, |cap| self.global.min(*cap))
+    }
+}
+
 #[allow(dead_code)]
 pub struct JsonRpcImpl<Adapter> {
-    adapter: Arc<Adapter>,
-    version: String,
-    pprof:   Arc<AtomicBool>,
-    path:    PathBuf,
+    adapter:    Arc<Adapter>,
+    gas_oracle: Arc<GasPriceOracle<Adapter>>,
+    version:    String,
+    pprof:      Arc<AtomicBool>,
+    path:       PathBuf,
+    keystore:   Option<Arc<KeyStore>>,
+    logs_limit: LogsLimit,
+    gas_cap:    GasCap,
 }
 
-impl<Adapter: APIAdapter> JsonRpcImpl<Adapter> {
-    pub fn new(adapter: Arc<Adapter>, version: &str, path: PathBuf) -> Self {
+impl<Adapter: APIAdapter + 'static> JsonRpcImpl<Adapter> {
+    pub fn new(
+        adapter: Arc<Adapter>,
+        gas_oracle: Arc<GasPriceOracle<Adapter>>,
+        version: &str,
+        path: PathBuf,
+        keystore: Option<Arc<KeyStore>>,
+        logs_limit: LogsLimit,
+        gas_cap: GasCap,
+    ) -> Self {
         Self {
             adapter,
+            gas_oracle,
             version: version.to_string(),
             pprof: Arc::new(AtomicBool::default()),
             path: path.join("api"),
+            keystore,
+            logs_limit,
+            gas_cap,
         }
     }
 
+    fn keystore(&self) -> ProtocolResult<&KeyStore> {
+        self.keystore
+            .as_deref()
+            .ok_or_else(|| APIError::KeyStore("keystore is not enabled".to_string()).into())
+    }
+
+    /// Sign the hash of a message with the unlocked key of the account. The
+    /// returned signature ends with a `v` of 27 or 28.
+    fn sign_message(&self, address: &H160, hash: &Hash) -> ProtocolResult<Hex> {
+        let mut signature = self.keystore()?.sign_hash(address, hash)?.to_vec();
+        signature[64] += 27;
+        Ok(Hex::encode(signature))
+    }
+
+    /// Fill the missing fields of the request and sign it with the unlocked
+    /// key of its sender.
+    async fn sign_call_request(&self, req: Web3CallRequest) -> ProtocolResult<SignedTransaction> {
+        let from = req
+            .from
+            .ok_or_else(|| APIError::RequestPayload("from is None".to_string()))?;
+
+        let nonce = match req.nonce {
+            Some(nonce) => nonce,
+            None => {
+                let account = self.adapter.get_account(Context::new(), from, None).await?;
+                let pending = self
+                    .adapter
+                    .get_pending_tx_count(Context::new(), from)
+                    .await?;
+                account.nonce + pending
+            }
+        };
+        let gas_price = match req.max_fee_per_gas.or(req.gas_price) {
+            Some(price) => price,
+            None => self.gas_oracle.gas_price().await?,
+        };
+        let max_priority_fee_per_gas = match req.max_priority_fee_per_gas {
+            Some(fee) => fee,
+            None => self.gas_oracle.max_priority_fee_per_gas().await?,
+        };
+        let gas_limit = match req.gas {
+            Some(gas) => gas,
+            None => self
+                .estimate_gas_limit(
+                    req.clone(),
+                    BlockIdOrHash::default(),
+                    self.gas_cap.of("eth_estimateGas"),
+                )
+                .await?
+                .into(),
+        };
+        let chain_id = self
+            .adapter
+            .get_block_header_by_number(Context::new(), None)
+            .await?
+            .map(|h| h.chain_id)
+            .ok_or_else(|| APIError::Storage("Cannot get latest block header".to_string()))?;
+
+        let mut utx = UnverifiedTransaction {
+            unsigned: Transaction {
+                nonce,
+                max_priority_fee_per_gas,
+                gas_price,
+                gas_limit,
+                action: req
+                    .to
+                    .map(TransactionAction::Call)
+                    .unwrap_or(TransactionAction::Create),
+                value: req.value.unwrap_or_default(),
+                data: req.data.as_bytes(),
+                access_list: req.access_list.unwrap_or_default(),
+            },
+            signature: None,
+            chain_id,
+            hash: Default::default(),
+        };
+        let signature = self.keystore()?.sign_hash(&from, &utx.signature_hash())?;
+        utx.signature = Some(signature.into());
+
+        SignedTransaction::try_from(utx.calc_hash()).map_err(Into::into)
+    }
+
+    /// Get the header of the block whose state is queried, by either its
+    /// number or its hash.
+    async fn header_by_id(&self, id: BlockIdOrHash) -> ProtocolResult<Header> {
+        let header = match id.clone() {
+            BlockIdOrHash::Id(number) => {
+                self.adapter
+                    .get_block_header_by_number(Context::new(), number.into())
+                    .await?
+            }
+            BlockIdOrHash::Hash(hash) => self
+                .adapter
+                .get_block_by_hash(Context::new(), hash)
+                .await?
+                .map(|block| block.header),
+        };
+
+        header.ok_or_else(|| APIError::Storage(format!("Cannot get {:?} header", id)).into())
+    }
+
     async fn call_evm(
         &self,
         req: Web3CallRequest,
         data: Bytes,
-        number: Option<u64>,
+        number: BlockIdOrHash,
+        gas_cap: u64,
     ) -> ProtocolResult<TxResp> {
         if req.from.is_none() && req.to.is_none() {
             return Err(APIError::RequestPayload("from and to are both None".to_string()).into());
         }
 
-        let header = self
-            .adapter
-            .get_block_header_by_number(Context::new(), number)
-            .await?
-            .ok_or_else(|| APIError::Storage(format!("Cannot get {:?} header", number)))?;
+        let header = self.header_by_id(number).await?;
+        let gas_limit = req.gas.map(gas_to_u64).unwrap_or(gas_cap);
+        self.call_evm_with_gas(&req, data, header, gas_limit.min(gas_cap))
+            .await
+    }
 
-        let mock_header = mock_header_by_call_req(header, &req);
+    /// The EVM runs synchronously, so the call is moved to a blocking thread
+    /// for the timeout of the request to fire. The gas cap of the method
+    /// bounds the work of the call left running after the timeout.
+    async fn call_evm_with_gas(
+        &self,
+        req: &Web3CallRequest,
+        data: Bytes,
+        header: Header,
+        gas_limit: u64,
+    ) -> ProtocolResult<TxResp> {
+        let mock_header = mock_header_by_call_req(header, req);
+        let adapter = Arc::clone(&self.adapter);
+        let (from, to) = (req.from, req.to);
+        let access_list = req.access_list.clone().unwrap_or_default();
 
-        self.adapter
-            .evm_call(
+        tokio::task::spawn_blocking(move || {
+            tokio::runtime::Handle::current().block_on(adapter.evm_call(
                 Context::new(),
-                req.from,
-                req.to,
+                from,
+                to,
                 data.to_vec(),
+                gas_limit,
+                access_list,
                 mock_header.state_root,
                 mock_header.into(),
-            )
-            .await
+            ))
+        })
+        .await
+        .map_err(|e| APIError::Adapter(e.to_string()))?
+    }
+
+    /// Find the lowest gas limit the call succeeds with by a binary search
+    /// below the gas of the request, or the block gas limit if it is not set.
+    async fn estimate_gas_limit(
+        &self,
+        req: Web3CallRequest,
+        number: BlockIdOrHash,
+        gas_cap: u64,
+    ) -> ProtocolResult<u64> {
+        if req.from.is_none() && req.to.is_none() {
+            return Err(APIError::RequestPayload("from and to are both None".to_string()).into());
+        }
+
+        let header = self.header_by_id(number).await?;
+        let data = req.data.as_bytes();
+        let cap = gas_to_u64(req.gas.unwrap_or(header.gas_limit)).min(gas_cap);
+
+        let resp = self
+            .call_evm_with_gas(&req, data.clone(), header.clone(), cap)
+            .await?;
+        if let Some(err) = exit_error(&resp.exit_reason) {
+            return Err(APIError::Adapter(format!(
+                "gas required exceeds allowance {} or the call always fails: {}",
+                cap, err
+            ))
+            .into());
+        }
+
+        // The call fails with a gas limit of `lo` and succeeds with `hi`. Any
+        // gas limit less than the gas used fails.
+        let mut lo = resp.gas_used.max(MIN_TRANSACTION_GAS) - 1;
+        let mut hi = cap;
+        while lo + 1 < hi {
+            let mid = lo + (hi - lo) / 2;
+            let resp = self
+                .call_evm_with_gas(&req, data.clone(), header.clone(), mid)
+                .await?;
+            if resp.exit_reason.is_succeed() {
+                hi = mid;
+            } else {
+                lo = mid;
+            }
+        }
+
+        Ok(hi)
     }
 }
 
@@ -228,10 +456,14 @@ impl<Adapter: APIAdapter + 'static> AxonJsonRpcServer for JsonRpcImpl<Adapter> {
     }
 
     #[metrics_rpc("eth_getBalance")]
-    async fn get_balance(&self, address: H160, number: BlockId) -> RpcResult<U256> {
+    async fn get_balance(&self, address: H160, number: BlockIdOrHash) -> RpcResult<U256> {
+        let header = self
+            .header_by_id(number)
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?;
         let account = self
             .adapter
-            .get_account(Context::new(), address, number.into())
+            .get_account(Context::new(), address, Some(header.number))
             .await
             .map_err(|e| Error::Custom(e.to_string()))?;
 
@@ -254,10 +486,10 @@ impl<Adapter: APIAdapter + 'static> AxonJsonRpcServer for JsonRpcImpl<Adapter> {
     }
 
     #[metrics_rpc("eth_call")]
-    async fn call(&self, req: Web3CallRequest, number: BlockId) -> RpcResult<Hex> {
+    async fn call(&self, req: Web3CallRequest, number: BlockIdOrHash) -> RpcResult<Hex> {
         let data_bytes = req.data.as_bytes();
         let resp = self
-            .call_evm(req, data_bytes, number.into())
+            .call_evm(req, data_bytes, number, self.gas_cap.of("eth_call"))
             .await
             .map_err(|e| Error::Custom(e.to_string()))?;
         let call_hex_result = Hex::encode(resp.ret);
@@ -265,25 +497,68 @@ impl<Adapter: APIAdapter + 'static> AxonJsonRpcServer for JsonRpcImpl<Adapter> {
     }
 
     #[metrics_rpc("eth_estimateGas")]
-    async fn estimate_gas(&self, req: Web3CallRequest, number: Option<BlockId>) -> RpcResult<U256> {
-        let num = match number {
-            Some(BlockId::Num(n)) => Some(n),
-            _ => None,
-        };
-        let data_bytes = req.data.as_bytes();
-        let resp = self
-            .call_evm(req, data_bytes, num)
+    async fn estimate_gas(
+        &self,
+        req: Web3CallRequest,
+        number: Option<BlockIdOrHash>,
+    ) -> RpcResult<U256> {
+        self.estimate_gas_limit(
+            req,
+            number.unwrap_or_default(),
+            self.gas_cap.of("eth_estimateGas"),
+        )
+        .await
+        .map(Into::into)
+        .map_err(|e| Error::Custom(e.to_string()))
+    }
+
+    #[metrics_rpc("eth_createAccessList")]
+    async fn create_access_list(
+        &self,
+        req: Web3CallRequest,
+        number: Option<BlockIdOrHash>,
+    ) -> RpcResult<Web3AccessListResult> {
+        if req.from.is_none() && req.to.is_none() {
+            return Err(Error::Custom("from and to are both None".to_string()));
+        }
+
+        let header = self
+            .header_by_id(number.unwrap_or_default())
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?;
+        let gas_limit = gas_to_u64(req.gas.unwrap_or(header.gas_limit));
+        let mock_header = mock_header_by_call_req(header, &req);
+
+        let (access_list, resp) = self
+            .adapter
+            .create_access_list(
+                Context::new(),
+                req.from,
+                req.to,
+                req.data.as_bytes().to_vec(),
+                gas_limit,
+                mock_header.state_root,
+                mock_header.into(),
+            )
             .await
             .map_err(|e| Error::Custom(e.to_string()))?;
 
-        Ok(resp.gas_used.into())
+        Ok(Web3AccessListResult {
+            access_list,
+            gas_used: resp.gas_used.into(),
+            error: exit_error(&resp.exit_reason),
+        })
     }
 
     #[metrics_rpc("eth_getCode")]
-    async fn get_code(&self, address: H160, number: BlockId) -> RpcResult<Hex> {
+    async fn get_code(&self, address: H160, number: BlockIdOrHash) -> RpcResult<Hex> {
+        let header = self
+            .header_by_id(number)
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?;
         let account = self
             .adapter
-            .get_account(Context::new(), address, number.into())
+            .get_account(Context::new(), address, Some(header.number))
             .await
             .map_err(|e| Error::Custom(e.to_string()))?;
 
@@ -342,7 +617,18 @@ impl<Adapter: APIAdapter + 'static> AxonJsonRpcServer for JsonRpcImpl<Adapter> {
 
     #[metrics_rpc("eth_gasPrice")]
     async fn gas_price(&self) -> RpcResult<U256> {
-        Ok(U256::from(8u64))
+        self.gas_oracle
+            .gas_price()
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))
+    }
+
+    #[metrics_rpc("eth_maxPriorityFeePerGas")]
+    async fn max_priority_fee_per_gas(&self) -> RpcResult<U256> {
+        self.gas_oracle
+            .max_priority_fee_per_gas()
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))
     }
 
     #[metrics_rpc("net_listening")]
@@ -371,179 +657,74 @@ impl<Adapter: APIAdapter + 'static> AxonJsonRpcServer for JsonRpcImpl<Adapter> {
     #[metrics_rpc("eth_getLogs")]
     async fn get_logs(&self, filter: Web3Filter) -> RpcResult<Vec<Web3Log>> {
         let topics = filter.topics.unwrap_or_default();
+        let address_filter: Option<Vec<H160>> = filter.address.into();
 
-        #[allow(clippy::large_enum_variant)]
-        enum BlockPosition {
-            Hash(H256),
-            Num(BlockNumber),
-            Block(Block),
-        }
-
-        async fn get_logs<T: APIAdapter>(
-            adapter: &T,
-            position: BlockPosition,
-            topics: &[H256],
-            logs: &mut Vec<Web3Log>,
-            address: Option<&Vec<H160>>,
-        ) -> RpcResult<()> {
-            let extend_logs = |logs: &mut Vec<Web3Log>, receipts: Vec<Option<Receipt>>| {
-                let mut index = 0;
-                for receipt in receipts.into_iter().flatten() {
-                    let log_len = receipt.logs.len();
-                    match address {
-                        Some(s) if s.contains(&receipt.sender) => {
-                            from_receipt_to_web3_log(index, topics, &receipt, logs)
-                        }
-                        None => from_receipt_to_web3_log(index, topics, &receipt, logs),
-                        _ => (),
-                    }
-                    index += log_len;
-                }
-            };
+        if let Some(hash) = filter.block_hash {
+            let block = self
+                .adapter
+                .get_block_by_hash(Context::new(), hash)
+                .await
+                .map_err(|e| Error::Custom(e.to_string()))?
+                .ok_or_else(|| Error::Custom(format!("Invalid block hash {}", hash)))?;
+            let receipts = self
+                .adapter
+                .get_receipts_by_hashes(Context::new(), block.header.number, &block.tx_hashes)
+                .await
+                .map_err(|e| Error::Custom(e.to_string()))?;
 
-            match position {
-                BlockPosition::Hash(hash) => {
-                    match adapter
-                        .get_block_by_hash(Context::new(), hash)
-                        .await
-                        .map_err(|e| Error::Custom(e.to_string()))?
-                    {
-                        Some(block) => {
-                            let receipts = adapter
-                                .get_receipts_by_hashes(
-                                    Context::new(),
-                                    block.header.number,
-                                    &block.tx_hashes,
-                                )
-                                .await
-                                .map_err(|e| Error::Custom(e.to_string()))?;
-                            extend_logs(logs, receipts);
-                            Ok(())
-                        }
-                        None => Err(Error::Custom(format!(
-                            "Invalid block hash
-                    {}",
-                            hash
-                        ))),
-                    }
-                }
-                BlockPosition::Num(n) => {
-                    let block = adapter
-                        .get_block_by_number(Context::new(), Some(n))
-                        .await
-                        .map_err(|e| Error::Custom(e.to_string()))?
-                        .unwrap();
-                    let receipts = adapter
-                        .get_receipts_by_hashes(
-                            Context::new(),
-                            block.header.number,
-                            &block.tx_hashes,
-                        )
-                        .await
-                        .map_err(|e| Error::Custom(e.to_string()))?;
-
-                    extend_logs(logs, receipts);
-                    Ok(())
-                }
-                BlockPosition::Block(block) => {
-                    let receipts = adapter
-                        .get_receipts_by_hashes(
-                            Context::new(),
-                            block.header.number,
-                            &block.tx_hashes,
-                        )
-                        .await
-                        .map_err(|e| Error::Custom(e.to_string()))?;
-
-                    extend_logs(logs, receipts);
-                    Ok(())
-                }
-            }
+            let mut logs = Vec::new();
+            extend_logs(&mut logs, receipts, address_filter.as_ref(), &topics);
+            return Ok(logs);
         }
 
-        let address_filter: Option<Vec<H160>> = filter.address.into();
-        let mut all_logs = Vec::new();
-        match filter.block_hash {
-            Some(hash) => {
-                get_logs(
-                    &*self.adapter,
-                    BlockPosition::Hash(hash),
-                    &topics,
-                    &mut all_logs,
-                    address_filter.as_ref(),
-                )
-                .await?;
-            }
-            None => {
-                let latest_block = self
-                    .adapter
-                    .get_block_by_number(Context::new(), None)
-                    .await
-                    .map_err(|e| Error::Custom(e.to_string()))?
-                    .unwrap();
-                let latest_number = latest_block.header.number;
-                let (start, end) = {
-                    let convert = |id: BlockId| -> BlockNumber {
-                        match id {
-                            BlockId::Num(n) => n,
-                            BlockId::Latest => latest_number,
-                        }
-                    };
-
-                    (
-                        filter.from_block.map(convert).unwrap_or(latest_number),
-                        filter.to_block.map(convert).unwrap_or(latest_number),
-                    )
-                };
-
-                if start > latest_number {
-                    return Err(Error::Custom(format!("Invalid from_block {}", start)));
+        let latest_block = self
+            .adapter
+            .get_block_by_number(Context::new(), None)
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?
+            .unwrap();
+        let latest_number = latest_block.header.number;
+        let (start, end) = {
+            let convert = |id: BlockId| -> BlockNumber {
+                match id {
+                    BlockId::Num(n) => n,
+                    BlockId::Latest => latest_number,
                 }
+            };
 
-                let mut visiter_last_block = false;
-                for n in start..=end {
-                    if n == latest_number {
-                        visiter_last_block = true;
-                    } else {
-                        get_logs(
-                            &*self.adapter,
-                            BlockPosition::Num(n),
-                            &topics,
-                            &mut all_logs,
-                            address_filter.as_ref(),
-                        )
-                        .await?;
-                    }
-                }
+            (
+                filter.from_block.map(convert).unwrap_or(latest_number),
+                filter.to_block.map(convert).unwrap_or(latest_number),
+            )
+        };
 
-                if visiter_last_block {
-                    get_logs(
-                        &*self.adapter,
-                        BlockPosition::Block(latest_block),
-                        &topics,
-                        &mut all_logs,
-                        address_filter.as_ref(),
-                    )
-                    .await?;
-                }
-            }
+        if start > latest_number {
+            return Err(Error::Custom(format!("Invalid from_block {}", start)));
         }
-        Ok(all_logs)
+
+        search_logs(
+            &*self.adapter,
+            self.logs_limit,
+            &latest_block,
+            start,
+            end,
+            address_filter.as_ref(),
+            &topics,
+        )
+        .await
     }
 
     #[metrics_rpc("eth_feeHistory")]
     async fn fee_history(
         &self,
-        _block_count: u64,
-        _newest_block: BlockId,
-        _reward_percentiles: Option<Vec<u64>>,
+        block_count: u64,
+        newest_block: BlockId,
+        reward_percentiles: Option<Vec<f64>>,
     ) -> RpcResult<Web3FeeHistory> {
-        Ok(Web3FeeHistory {
-            oldest_block:     U256::from(0),
-            reward:           None,
-            base_fee_per_gas: Vec::new(),
-            gas_used_ratio:   Vec::new(),
-        })
+        self.gas_oracle
+            .fee_history(block_count, newest_block.into(), reward_percentiles)
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))
     }
 
     #[metrics_rpc("web3_clientVersion")]
@@ -552,8 +733,61 @@ impl<Adapter: APIAdapter + 'static> AxonJsonRpcServer for JsonRpcImpl<Adapter> {
     }
 
     #[metrics_rpc("eth_accounts")]
-    async fn accounts(&self) -> RpcResult<Vec<Hex>> {
-        Ok(vec![])
+    async fn accounts(&self) -> RpcResult<Vec<H160>> {
+        Ok(self
+            .keystore
+            .as_ref()
+            .map(|keystore| keystore.accounts())
+            .unwrap_or_default())
+    }
+
+    #[metrics_rpc("eth_sendTransaction")]
+    async fn send_transaction(&self, req: Web3CallRequest) -> RpcResult<H256> {
+        let stx = self
+            .sign_call_request(req)
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?;
+        let hash = stx.transaction.hash;
+
+        self.adapter
+            .insert_signed_txs(Context::new(), stx)
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?;
+
+        Ok(hash)
+    }
+
+    #[metrics_rpc("eth_sign")]
+    async fn sign(&self, address: H160, data: Hex) -> RpcResult<Hex> {
+        let data = data.as_bytes();
+        let mut msg = format!("\x19Ethereum Signed Message:\n{}", data.len()).into_bytes();
+        msg.extend_from_slice(&data);
+
+        self.sign_message(&address, &Hasher::digest(msg))
+            .map_err(|e| Error::Custom(e.to_string()))
+    }
+
+    #[metrics_rpc("eth_signTransaction")]
+    async fn sign_transaction(&self, req: Web3CallRequest) -> RpcResult<Web3SignedTransaction> {
+        self.sign_call_request(req)
+            .await
+            .map(Into::into)
+            .map_err(|e| Error::Custom(e.to_string()))
+    }
+
+    #[metrics_rpc("eth_signTypedData_v4")]
+    async fn sign_typed_data_v4(&self, address: H160, data: serde_json::Value) -> RpcResult<Hex> {
+        // Some wallets send the typed data as a JSON string.
+        let typed_data: TypedData = match data {
+            serde_json::Value::String(s) => serde_json::from_str(&s),
+            data => serde_json::from_value(data),
+        }
+        .map_err(|e| Error::Custom(e.to_string()))?;
+
+        typed_data
+            .sign_hash()
+            .and_then(|hash| self.sign_message(&address, &hash))
+            .map_err(|e| Error::Custom(e.to_string()))
     }
 
     #[metrics_rpc("web3_sha3")]
@@ -643,23 +877,41 @@ impl<Adapter: APIAdapter + 'static> AxonJsonRpcServer for JsonRpcImpl<Adapter> {
         &self,
         address: H160,
         position: U256,
-        number: BlockId,
+        number: BlockIdOrHash,
     ) -> RpcResult<Hex> {
-        let block = self
-            .adapter
-            .get_block_by_number(Context::new(), number.into())
+        let header = self
+            .header_by_id(number)
             .await
-            .map_err(|e| Error::Custom(e.to_string()))?
-            .ok_or_else(|| Error::Custom("Can't find this block".to_string()))?;
+            .map_err(|e| Error::Custom(e.to_string()))?;
         let value = self
             .adapter
-            .get_storage_at(Context::new(), address, position, block.header.state_root)
+            .get_storage_at(Context::new(), address, position, header.state_root)
             .await
             .map_err(|e| Error::Custom(e.to_string()))?;
 
         Ok(Hex::encode(&value))
     }
 
+    #[metrics_rpc("eth_getProof")]
+    async fn get_proof(
+        &self,
+        address: H160,
+        storage_keys: Vec<H256>,
+        number: BlockIdOrHash,
+    ) -> RpcResult<Web3AccountProof> {
+        let header = self
+            .header_by_id(number)
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?;
+        let proof = self
+            .adapter
+            .get_proof(Context::new(), address, storage_keys, header.state_root)
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?;
+
+        Ok(proof.into())
+    }
+
     async fn coinbase(&self) -> RpcResult<H160> {
         // fixme: how to get the the coinbase value
         Ok(H160::default())
@@ -725,7 +977,11 @@ impl<Adapter: APIAdapter + 'static> AxonJsonRpcServer for JsonRpcImpl<Adapter> {
     }
 }
 
-fn mock_header_by_call_req(latest_header: Header, call_req: &Web3CallRequest) -> Header {
+fn gas_to_u64(gas: U256) -> u64 {
+    gas.min(U256::from(u64::MAX)).as_u64()
+}
+
+pub(crate) fn mock_header_by_call_req(latest_header: Header, call_req: &Web3CallRequest) -> Header {
     Header {
         prev_hash:                  latest_header.prev_hash,
         proposer:                   latest_header.proposer,
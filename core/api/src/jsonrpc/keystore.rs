@@ -0,0 +1,193 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant, SystemTime};
+
+use parking_lot::{Mutex, RwLock};
+use rand::{rngs::OsRng, RngCore};
+
+use common_crypto::{
+    Crypto, Secp256k1Recoverable, Secp256k1RecoverablePrivateKey, Signature, ToPublicKey,
+    UncompressedPublicKey,
+};
+use protocol::types::{public_to_address, Bytes, Hash, Public, H160};
+use protocol::ProtocolResult;
+
+use crate::APIError;
+
+const KEY_FILE_PREFIX: &str = "UTC--";
+const KEY_FILE_ADDRESS_SEPARATOR: &str = "--";
+
+/// A directory of private keys encrypted in the Web3 Secret Storage v3
+/// format. A key has to be unlocked with its password before signing.
+pub struct KeyStore {
+    dir:      PathBuf,
+    accounts: RwLock<BTreeMap<H160, PathBuf>>,
+    unlocked: Mutex<HashMap<H160, UnlockedKey>>,
+}
+
+struct UnlockedKey {
+    key:    Bytes,
+    expire: Option<Instant>,
+}
+
+impl KeyStore {
+    /// Open the keystore directory, the key files are expected to be named as
+    /// `UTC--<time>--<address>` like the ones generated by geth.
+    pub fn open<P: AsRef<Path>>(dir: P) -> ProtocolResult<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        if !dir.exists() {
+            fs::create_dir_all(&dir).map_err(|e| APIError::KeyStore(e.to_string()))?;
+        }
+
+        let mut accounts = BTreeMap::new();
+        for entry in fs::read_dir(&dir).map_err(|e| APIError::KeyStore(e.to_string()))? {
+            let path = entry.map_err(|e| APIError::KeyStore(e.to_string()))?.path();
+            match path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(parse_address)
+            {
+                Some(address) => {
+                    accounts.insert(address, path);
+                }
+                None => log::warn!("[api]: ignore unknown keystore file {:?}", path),
+            }
+        }
+
+        Ok(KeyStore {
+            dir,
+            accounts: RwLock::new(accounts),
+            unlocked: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn accounts(&self) -> Vec<H160> {
+        self.accounts.read().keys().copied().collect()
+    }
+
+    /// Generate a new key and store it encrypted with the password.
+    pub fn new_account(&self, password: &str) -> ProtocolResult<H160> {
+        let (key, address) = loop {
+            let mut key = [0u8; 32];
+            OsRng.fill_bytes(&mut key);
+            if let Ok(address) = key_to_address(&key) {
+                break (key, address);
+            }
+        };
+
+        let timestamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_err(|e| APIError::KeyStore(e.to_string()))?
+            .as_secs();
+        let name = format!(
+            "{}{}{}{:x}",
+            KEY_FILE_PREFIX, timestamp, KEY_FILE_ADDRESS_SEPARATOR, address
+        );
+        eth_keystore::encrypt_key(&self.dir, &mut OsRng, key, password, Some(&name))
+            .map_err(|e| APIError::KeyStore(e.to_string()))?;
+
+        self.accounts.write().insert(address, self.dir.join(name));
+        Ok(address)
+    }
+
+    /// Decrypt the key of the account so that it can sign for the given
+    /// duration, or until the node stops if there is no duration.
+    pub fn unlock(
+        &self,
+        address: &H160,
+        password: &str,
+        duration: Option<Duration>,
+    ) -> ProtocolResult<()> {
+        let path = self
+            .accounts
+            .read()
+            .get(address)
+            .cloned()
+            .ok_or(APIError::UnknownAccount(*address))?;
+        let key = eth_keystore::decrypt_key(path, password)
+            .map_err(|e| APIError::KeyStore(e.to_string()))?;
+
+        if key_to_address(&key)? != *address {
+            return Err(APIError::KeyStore(format!("key file of {:?} mismatches", address)).into());
+        }
+
+        self.unlocked.lock().insert(*address, UnlockedKey {
+            key:    Bytes::from(key),
+            expire: duration.map(|d| Instant::now() + d),
+        });
+        Ok(())
+    }
+
+    /// Sign the hash with the unlocked key of the account, the recovery id of
+    /// the returned signature is 0 or 1.
+    pub fn sign_hash(&self, address: &H160, hash: &Hash) -> ProtocolResult<Bytes> {
+        let mut unlocked = self.unlocked.lock();
+        let expired = unlocked
+            .get(address)
+            .and_then(|key| key.expire)
+            .map(|expire| expire <= Instant::now())
+            .unwrap_or_default();
+        if expired {
+            unlocked.remove(address);
+        }
+
+        let key = unlocked
+            .get(address)
+            .ok_or(APIError::AccountLocked(*address))?;
+
+        Secp256k1Recoverable::sign_message(hash.as_bytes(), &key.key)
+            .map(|sig| sig.to_bytes())
+            .map_err(|e| APIError::KeyStore(e.to_string()).into())
+    }
+}
+
+fn parse_address(file_name: &str) -> Option<H160> {
+    if !file_name.starts_with(KEY_FILE_PREFIX) {
+        return None;
+    }
+
+    let (_, address) = file_name.rsplit_once(KEY_FILE_ADDRESS_SEPARATOR)?;
+    H160::from_str(address.trim_start_matches("0x")).ok()
+}
+
+fn key_to_address(key: &[u8]) -> ProtocolResult<H160> {
+    let key = Secp256k1RecoverablePrivateKey::try_from(key)
+        .map_err(|e| APIError::KeyStore(e.to_string()))?;
+    let public = Public::from_slice(&key.pub_key().to_uncompressed_bytes()[1..65]);
+    Ok(public_to_address(&public))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use protocol::types::Hasher;
+
+    static KEYSTORE_PATH: &str = "./free-space/keystore";
+
+    #[test]
+    fn test_keystore() {
+        let _ = fs::remove_dir_all(KEYSTORE_PATH);
+        let keystore = KeyStore::open(KEYSTORE_PATH).unwrap();
+        let address = keystore.new_account("password").unwrap();
+        assert_eq!(keystore.accounts(), vec![address]);
+
+        let hash = Hasher::digest("axon");
+        assert!(keystore.sign_hash(&address, &hash).is_err());
+        assert!(keystore.unlock(&address, "wrong", None).is_err());
+        keystore.unlock(&address, "password", None).unwrap();
+        assert_eq!(keystore.sign_hash(&address, &hash).unwrap().len(), 65);
+
+        // the key is found again after a restart
+        let keystore = KeyStore::open(KEYSTORE_PATH).unwrap();
+        assert_eq!(keystore.accounts(), vec![address]);
+        keystore
+            .unlock(&address, "password", Some(Duration::ZERO))
+            .unwrap();
+        assert!(keystore.sign_hash(&address, &hash).is_err());
+
+        fs::remove_dir_all(KEYSTORE_PATH).unwrap();
+    }
+}
@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+use common_config_parser::types::ConfigRateLimit;
+use protocol::Display;
+
+/// The buckets of the IPs idle for this long are dropped once there are more
+/// than `MAX_IP_BUCKETS` of them.
+const IP_BUCKET_TTL: Duration = Duration::from_secs(600);
+const MAX_IP_BUCKETS: usize = 10_000;
+
+#[derive(Debug, Display, PartialEq, Eq)]
+pub enum Rejection {
+    #[display(fmt = "too many requests from {}", _0)]
+    Ip(IpAddr),
+
+    #[display(fmt = "too many {} requests", _0)]
+    Method(String),
+
+    #[display(fmt = "batch of {} requests exceeds the limit {}", len, limit)]
+    Batch { len: usize, limit: usize },
+}
+
+/// A bucket holding at most `capacity` tokens and refilled at `rate` tokens
+/// per second.
+struct TokenBucket {
+    capacity: f64,
+    rate:     f64,
+    tokens:   f64,
+    last:     Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: u64, burst: u64, now: Instant) -> Self {
+        let capacity = burst.max(rate).max(1) as f64;
+        TokenBucket {
+            capacity,
+            rate: rate as f64,
+            tokens: capacity,
+            last: now,
+        }
+    }
+
+    fn try_take(&mut self, cost: u64, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.last = now;
+
+        if self.tokens >= cost as f64 {
+            self.tokens -= cost as f64;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+struct MethodLimit {
+    bucket:  Option<Mutex<TokenBucket>>,
+    cost:    u64,
+    timeout: Option<Duration>,
+}
+
+/// Token bucket rate limits of the JSON-RPC requests. Every call is charged
+/// the cost of its method to the bucket of the caller's IP, and the methods
+/// with a rate take a token from their own bucket shared by all the IPs.
+pub struct RateLimiter {
+    ip_rate:        u64,
+    ip_burst:       u64,
+    ips:            Mutex<HashMap<IpAddr, TokenBucket>>,
+    methods:        HashMap<String, MethodLimit>,
+    max_batch_size: usize,
+}
+
+impl RateLimiter {
+    pub fn new(config: &ConfigRateLimit) -> Self {
+        let now = Instant::now();
+        let methods = config
+            .methods
+            .iter()
+            .map(|limit| {
+                let bucket = (limit.rate > 0)
+                    .then(|| Mutex::new(TokenBucket::new(limit.rate, limit.burst, now)));
+                (limit.name.clone(), MethodLimit {
+                    bucket,
+                    cost: limit.cost,
+                    timeout: limit.timeout.map(Duration::from_millis),
+                })
+            })
+            .collect();
+
+        RateLimiter {
+            ip_rate: config.ip_rate,
+            ip_burst: config.ip_burst,
+            ips: Mutex::new(HashMap::new()),
+            methods,
+            max_batch_size: config.max_batch_size,
+        }
+    }
+
+    pub fn check_batch(&self, len: usize) -> Result<(), Rejection> {
+        if len > self.max_batch_size {
+            return Err(Rejection::Batch {
+                len,
+                limit: self.max_batch_size,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn check_call(&self, ip: IpAddr, method: &str) -> Result<(), Rejection> {
+        self.check_method(method)?;
+
+        let now = Instant::now();
+        let limit = self.methods.get(method);
+        if self.ip_rate > 0 {
+            let cost = limit.map(|l| l.cost).unwrap_or(1);
+            let mut ips = self.ips.lock();
+            if ips.len() > MAX_IP_BUCKETS {
+                ips.retain(|_, bucket| now.saturating_duration_since(bucket.last) < IP_BUCKET_TTL);
+            }
+
+            let bucket = ips
+                .entry(ip)
+                .or_insert_with(|| TokenBucket::new(self.ip_rate, self.ip_burst, now));
+            if !bucket.try_take(cost, now) {
+                return Err(Rejection::Ip(ip));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Take a token from the bucket of the method shared by all the callers.
+    pub fn check_method(&self, method: &str) -> Result<(), Rejection> {
+        let bucket = self.methods.get(method).and_then(|l| l.bucket.as_ref());
+        match bucket {
+            Some(bucket) if !bucket.lock().try_take(1, Instant::now()) => {
+                Err(Rejection::Method(method.to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The time a call of the method may run before it is aborted.
+    pub fn timeout(&self, method: &str) -> Option<Duration> {
+        self.methods.get(method).and_then(|l| l.timeout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use common_config_parser::types::ConfigMethodLimit;
+
+    #[test]
+    fn test_rate_limiter() {
+        let limiter = RateLimiter::new(&ConfigRateLimit {
+            ip_rate:        1,
+            ip_burst:       10,
+            max_batch_size: 2,
+            methods:        vec![ConfigMethodLimit {
+                name:    "eth_getLogs".to_string(),
+                rate:    1,
+                burst:   1,
+                cost:    5,
+                timeout: Some(1000),
+                gas_cap: None,
+            }],
+        });
+        let ip = IpAddr::from([127, 0, 0, 1]);
+        let other = IpAddr::from([127, 0, 0, 2]);
+
+        assert!(limiter.check_batch(2).is_ok());
+        assert!(limiter.check_batch(3).is_err());
+
+        assert!(limiter.check_call(ip, "eth_getLogs").is_ok());
+        assert_eq!(
+            limiter.check_call(other, "eth_getLogs"),
+            Err(Rejection::Method("eth_getLogs".to_string()))
+        );
+
+        // the getLogs call costs 5 of the 10 units
+        for _ in 0..5 {
+            assert!(limiter.check_call(ip, "eth_blockNumber").is_ok());
+        }
+        assert_eq!(
+            limiter.check_call(ip, "eth_blockNumber"),
+            Err(Rejection::Ip(ip))
+        );
+        assert!(limiter.check_call(other, "eth_blockNumber").is_ok());
+
+        assert_eq!(limiter.timeout("eth_getLogs"), Some(Duration::from_secs(1)));
+        assert_eq!(limiter.timeout("eth_blockNumber"), None);
+    }
+}
@@ -0,0 +1,105 @@
+use jsonrpsee::core::Error;
+
+use protocol::traits::{APIAdapter, Context};
+use protocol::types::{Block, BlockNumber, Receipt, H160, H256};
+
+use crate::jsonrpc::{r#impl::from_receipt_to_web3_log, web3_types::Web3Log, RpcResult};
+use crate::APIError;
+
+/// The limits of a log query, 0 for no limit.
+#[derive(Clone, Copy, Debug)]
+pub struct LogsLimit {
+    pub max_range:   u64,
+    pub max_results: usize,
+}
+
+/// Search the logs in the blocks `[from, to]` matching the address and the
+/// topics. Only the blocks found in the log index are read, and the query is
+/// aborted once it covers or returns more than the limit.
+pub async fn search_logs<Adapter: APIAdapter>(
+    adapter: &Adapter,
+    limit: LogsLimit,
+    latest_block: &Block,
+    from: BlockNumber,
+    to: BlockNumber,
+    address: Option<&Vec<H160>>,
+    topics: &[H256],
+) -> RpcResult<Vec<Web3Log>> {
+    let latest_number = latest_block.header.number;
+    let to = to.min(latest_number);
+    if from > to {
+        return Ok(Vec::new());
+    }
+
+    let range = to - from + 1;
+    if limit.max_range != 0 && range > limit.max_range {
+        return Err(Error::Custom(
+            APIError::LogsRangeExceeded {
+                range,
+                limit: limit.max_range,
+            }
+            .to_string(),
+        ));
+    }
+
+    let addresses = match address {
+        Some(addresses) if addresses.is_empty() => return Ok(Vec::new()),
+        Some(addresses) => addresses.as_slice(),
+        None => &[],
+    };
+    let numbers = adapter
+        .get_log_block_numbers(Context::new(), from, to, addresses, topics)
+        .await
+        .map_err(|e| Error::Custom(e.to_string()))?;
+
+    let mut logs = Vec::new();
+    for number in numbers {
+        let fetched;
+        let block = if number == latest_number {
+            latest_block
+        } else {
+            fetched = adapter
+                .get_block_by_number(Context::new(), Some(number))
+                .await
+                .map_err(|e| Error::Custom(e.to_string()))?
+                .ok_or_else(|| Error::Custom(format!("Cannot find block {}", number)))?;
+            &fetched
+        };
+
+        let receipts = adapter
+            .get_receipts_by_hashes(Context::new(), number, &block.tx_hashes)
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?;
+        extend_logs(&mut logs, receipts, address, topics);
+
+        if limit.max_results != 0 && logs.len() > limit.max_results {
+            return Err(Error::Custom(
+                APIError::LogsResultsExceeded(limit.max_results).to_string(),
+            ));
+        }
+    }
+
+    Ok(logs)
+}
+
+/// Append the logs of the receipts of a block matching the address and the
+/// topics.
+pub fn extend_logs(
+    logs: &mut Vec<Web3Log>,
+    receipts: Vec<Option<Receipt>>,
+    address: Option<&Vec<H160>>,
+    topics: &[H256],
+) {
+    let mut index = 0;
+    for receipt in receipts.into_iter().flatten() {
+        let log_len = receipt.logs.len();
+        match address {
+            Some(s) if s.contains(&receipt.sender) => {
+                from_receipt_to_web3_log(index, topics, &receipt, logs)
+            }
+            None => from_receipt_to_web3_log(index, topics, &receipt, logs),
+            _ => (),
+        }
+        index += log_len;
+    }
+}
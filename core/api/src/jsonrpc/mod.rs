@@ -1,25 +1,46 @@
+mod admin;
+mod debug;
+mod eip712;
 mod filter;
+mod gas_oracle;
+mod http;
 mod r#impl;
+mod keystore;
+mod limiter;
+mod logs;
+mod personal;
+mod txpool;
 mod web3_types;
+mod ws;
 mod ws_subscription;
 
 use std::sync::Arc;
 
-use jsonrpsee::http_server::{HttpServerBuilder, HttpServerHandle};
 use jsonrpsee::ws_server::{WsServerBuilder, WsServerHandle};
 use jsonrpsee::{core::Error, proc_macros::rpc};
 
 use common_config_parser::types::Config;
+use protocol::tokio;
 use protocol::traits::APIAdapter;
 use protocol::types::{Hash, Hex, H160, H256, U256};
 use protocol::ProtocolResult;
 
 use crate::jsonrpc::{
+    admin::AxonAdminServer,
+    debug::AxonDebugServer,
     filter::AxonFilterServer,
+    http::{run_http_server, HttpServerHandle},
+    keystore::KeyStore,
+    limiter::RateLimiter,
+    logs::LogsLimit,
+    personal::AxonPersonalServer,
+    txpool::AxonTxPoolServer,
     web3_types::{
-        BlockId, BlockIdWithPending, Web3Block, Web3CallRequest, Web3FeeHistory, Web3Filter,
-        Web3Log, Web3Receipt, Web3SyncStatus, Web3Transaction,
+        BlockId, BlockIdOrHash, BlockIdWithPending, Web3AccessListResult, Web3AccountProof,
+        Web3Block, Web3CallRequest, Web3FeeHistory, Web3Filter, Web3Log, Web3Receipt,
+        Web3SignedTransaction, Web3SyncStatus, Web3Transaction,
     },
+    ws::WsLimited,
     ws_subscription::{ws_subscription_module, HexIdProvider},
 };
 use crate::APIError;
@@ -65,13 +86,26 @@ pub trait AxonJsonRpc {
     async fn get_transaction_count_by_number(&self, number: BlockId) -> RpcResult<U256>;
 
     #[method(name = "eth_getBalance")]
-    async fn get_balance(&self, address: H160, number: BlockId) -> RpcResult<U256>;
+    async fn get_balance(&self, address: H160, number: BlockIdOrHash) -> RpcResult<U256>;
 
     #[method(name = "eth_call")]
-    async fn call(&self, req: Web3CallRequest, number: BlockId) -> RpcResult<Hex>;
+    async fn call(&self, req: Web3CallRequest, number: BlockIdOrHash) -> RpcResult<Hex>;
 
     #[method(name = "eth_estimateGas")]
-    async fn estimate_gas(&self, req: Web3CallRequest, number: Option<BlockId>) -> RpcResult<U256>;
+    async fn estimate_gas(
+        &self,
+        req: Web3CallRequest,
+        number: Option<BlockIdOrHash>,
+    ) -> RpcResult<U256>;
+
+    /// Returns the addresses and storage slots the call accesses, and the gas
+    /// it uses with that access list.
+    #[method(name = "eth_createAccessList")]
+    async fn create_access_list(
+        &self,
+        req: Web3CallRequest,
+        number: Option<BlockIdOrHash>,
+    ) -> RpcResult<Web3AccessListResult>;
 
     #[method(name = "eth_chainId")]
     async fn chain_id(&self) -> RpcResult<U256>;
@@ -80,7 +114,7 @@ pub trait AxonJsonRpc {
     async fn net_version(&self) -> RpcResult<U256>;
 
     #[method(name = "eth_getCode")]
-    async fn get_code(&self, address: H160, number: BlockId) -> RpcResult<Hex>;
+    async fn get_code(&self, address: H160, number: BlockIdOrHash) -> RpcResult<Hex>;
 
     #[method(name = "eth_getTransactionReceipt")]
     async fn get_transaction_receipt(&self, hash: H256) -> RpcResult<Option<Web3Receipt>>;
@@ -100,6 +134,9 @@ pub trait AxonJsonRpc {
     #[method(name = "eth_gasPrice")]
     async fn gas_price(&self) -> RpcResult<U256>;
 
+    #[method(name = "eth_maxPriorityFeePerGas")]
+    async fn max_priority_fee_per_gas(&self) -> RpcResult<U256>;
+
     #[method(name = "eth_getLogs")]
     async fn get_logs(&self, filter: Web3Filter) -> RpcResult<Vec<Web3Log>>;
 
@@ -108,14 +145,26 @@ pub trait AxonJsonRpc {
         &self,
         block_count: u64,
         newest_block: BlockId,
-        reward_percentiles: Option<Vec<u64>>,
+        reward_percentiles: Option<Vec<f64>>,
     ) -> RpcResult<Web3FeeHistory>;
 
     #[method(name = "web3_clientVersion")]
     async fn client_version(&self) -> RpcResult<String>;
 
     #[method(name = "eth_accounts")]
-    async fn accounts(&self) -> RpcResult<Vec<Hex>>;
+    async fn accounts(&self) -> RpcResult<Vec<H160>>;
+
+    #[method(name = "eth_sendTransaction")]
+    async fn send_transaction(&self, req: Web3CallRequest) -> RpcResult<H256>;
+
+    #[method(name = "eth_sign")]
+    async fn sign(&self, address: H160, data: Hex) -> RpcResult<Hex>;
+
+    #[method(name = "eth_signTransaction")]
+    async fn sign_transaction(&self, req: Web3CallRequest) -> RpcResult<Web3SignedTransaction>;
+
+    #[method(name = "eth_signTypedData_v4")]
+    async fn sign_typed_data_v4(&self, address: H160, data: serde_json::Value) -> RpcResult<Hex>;
 
     #[method(name = "web3_sha3")]
     async fn sha3(&self, data: Hex) -> RpcResult<Hash>;
@@ -142,9 +191,19 @@ pub trait AxonJsonRpc {
         &self,
         address: H160,
         position: U256,
-        number: BlockId,
+        number: BlockIdOrHash,
     ) -> RpcResult<Hex>;
 
+    /// Returns the Merkle proofs of the account and the given storage slots,
+    /// see EIP-1186.
+    #[method(name = "eth_getProof")]
+    async fn get_proof(
+        &self,
+        address: H160,
+        storage_keys: Vec<H256>,
+        number: BlockIdOrHash,
+    ) -> RpcResult<Web3AccountProof>;
+
     #[method(name = "eth_coinbase")]
     async fn coinbase(&self) -> RpcResult<H160>;
 
@@ -164,30 +223,91 @@ pub trait AxonJsonRpc {
 pub async fn run_jsonrpc_server<Adapter: APIAdapter + 'static>(
     config: Config,
     adapter: Arc<Adapter>,
-) -> ProtocolResult<(Option<HttpServerHandle>, Option<WsServerHandle>)> {
-    let mut ret = (None, None);
-
-    let filter = filter::filter_module(Arc::clone(&adapter)).into_rpc();
+) -> ProtocolResult<(
+    Option<HttpServerHandle>,
+    Option<WsServerHandle>,
+    Option<HttpServerHandle>,
+)> {
+    let mut ret = (None, None, None);
+
+    let logs_limit = LogsLimit {
+        max_range:   config.rpc.max_logs_range,
+        max_results: config.rpc.max_logs_results,
+    };
+    let gas_cap = r#impl::GasCap::new(config.rpc.gas_cap, &config.rpc.rate_limit.methods);
+    let filter = filter::filter_module(Arc::clone(&adapter), logs_limit).into_rpc();
+    let debug = debug::DebugRpcImpl::new(Arc::clone(&adapter)).into_rpc();
+    let txpool = txpool::TxPoolRpcImpl::new(Arc::clone(&adapter)).into_rpc();
+    let keystore = config
+        .rpc
+        .keystore_path
+        .as_ref()
+        .map(KeyStore::open)
+        .transpose()?
+        .map(Arc::new);
+    let gas_oracle = Arc::new(gas_oracle::GasPriceOracle::new(
+        Arc::clone(&adapter),
+        config.rpc.gas_oracle_blocks,
+        config.rpc.gas_oracle_percentile,
+    ));
+    tokio::spawn(Arc::clone(&gas_oracle).run());
     let mut rpc = r#impl::JsonRpcImpl::new(
         Arc::clone(&adapter),
+        Arc::clone(&gas_oracle),
         &config.rpc.client_version,
         config.data_path.clone(),
+        None,
+        logs_limit,
+        gas_cap.clone(),
     )
     .into_rpc();
     rpc.merge(filter).unwrap();
+    rpc.merge(debug).unwrap();
+    rpc.merge(txpool).unwrap();
 
     if let Some(addr) = config.rpc.http_listening_address {
-        let server = HttpServerBuilder::new()
-            .max_request_body_size(config.rpc.max_payload_size as u32)
-            .build(addr)
-            .await
-            .map_err(|e| APIError::HttpServer(e.to_string()))?;
-
         ret.0 = Some(
-            server
-                .start(rpc.clone())
-                .map_err(|e| APIError::HttpServer(e.to_string()))?,
+            run_http_server(
+                addr,
+                rpc.clone(),
+                RateLimiter::new(&config.rpc.rate_limit),
+                config.rpc.max_payload_size,
+            )
+            .await?,
+        );
+    }
+
+    // The admin methods are kept apart from the public ones, so are the
+    // methods signing with the keystore
+    if let Some(addr) = config.rpc.admin_listening_address {
+        let mut admin_rpc = admin::AdminRpcImpl::new(Arc::clone(&adapter)).into_rpc();
+        if let Some(keystore) = keystore {
+            let signer = r#impl::JsonRpcImpl::new(
+                Arc::clone(&adapter),
+                gas_oracle,
+                &config.rpc.client_version,
+                config.data_path.clone(),
+                Some(Arc::clone(&keystore)),
+                logs_limit,
+                gas_cap,
+            );
+            admin_rpc.merge(signer.into_rpc()).unwrap();
+            admin_rpc
+                .merge(personal::PersonalRpcImpl::new(keystore).into_rpc())
+                .unwrap();
+        }
+
+        ret.2 = Some(
+            run_http_server(
+                addr,
+                admin_rpc,
+                RateLimiter::new(&Default::default()),
+                config.rpc.max_payload_size,
+            )
+            .await?,
         );
+    } else if keystore.is_some() {
+        log::warn!("[api]: keystore is disabled since the admin listening address is not set");
     }
 
     if let Some(addr) = config.rpc.ws_listening_address {
@@ -199,11 +319,12 @@ pub async fn run_jsonrpc_server<Adapter: APIAdapter + 'static>(
             .await
             .map_err(|e| APIError::WebSocketServer(e.to_string()))?;
 
-        rpc.merge(ws_subscription_module(adapter).await).unwrap();
+        let mut ws_rpc = WsLimited::into_rpc(rpc, RateLimiter::new(&config.rpc.rate_limit));
+        ws_rpc.merge(ws_subscription_module(adapter).await).unwrap();
 
         ret.1 = Some(
             server
-                .start(rpc)
+                .start(ws_rpc)
                 .map_err(|e| APIError::WebSocketServer(e.to_string()))?,
         )
     }
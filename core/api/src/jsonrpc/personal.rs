@@ -0,0 +1,80 @@
+use std::{sync::Arc, time::Duration};
+
+use jsonrpsee::{core::Error, proc_macros::rpc};
+
+use common_apm::metrics_rpc;
+use protocol::async_trait;
+use protocol::types::H160;
+
+use crate::jsonrpc::{keystore::KeyStore, RpcResult};
+
+/// The seconds an account stays unlocked if the duration is not given.
+const DEFAULT_UNLOCK_DURATION: u64 = 300;
+
+#[rpc(server)]
+pub trait AxonPersonal {
+    /// Generates a new key encrypted with the password and returns its
+    /// address.
+    #[method(name = "personal_newAccount")]
+    async fn new_account(&self, password: String) -> RpcResult<H160>;
+
+    /// Unlocks the account for the given seconds, 300 by default. An account
+    /// cannot be unlocked until the node stops, so a duration of 0 is
+    /// rejected.
+    #[method(name = "personal_unlockAccount")]
+    async fn unlock_account(
+        &self,
+        address: H160,
+        password: String,
+        duration: Option<u64>,
+    ) -> RpcResult<bool>;
+
+    /// Returns the addresses of the keys in the keystore.
+    #[method(name = "personal_listAccounts")]
+    async fn list_accounts(&self) -> RpcResult<Vec<H160>>;
+}
+
+pub struct PersonalRpcImpl {
+    keystore: Arc<KeyStore>,
+}
+
+impl PersonalRpcImpl {
+    pub fn new(keystore: Arc<KeyStore>) -> Self {
+        Self { keystore }
+    }
+}
+
+#[async_trait]
+impl AxonPersonalServer for PersonalRpcImpl {
+    #[metrics_rpc("personal_newAccount")]
+    async fn new_account(&self, password: String) -> RpcResult<H160> {
+        self.keystore
+            .new_account(&password)
+            .map_err(|e| Error::Custom(e.to_string()))
+    }
+
+    #[metrics_rpc("personal_unlockAccount")]
+    async fn unlock_account(
+        &self,
+        address: H160,
+        password: String,
+        duration: Option<u64>,
+    ) -> RpcResult<bool> {
+        let secs = duration.unwrap_or(DEFAULT_UNLOCK_DURATION);
+        if secs == 0 {
+            return Err(Error::Custom(
+                "unlock duration must be positive".to_string(),
+            ));
+        }
+
+        self.keystore
+            .unlock(&address, &password, Some(Duration::from_secs(secs)))
+            .map_err(|e| Error::Custom(e.to_string()))?;
+        Ok(true)
+    }
+
+    #[metrics_rpc("personal_listAccounts")]
+    async fn list_accounts(&self) -> RpcResult<Vec<H160>> {
+        Ok(self.keystore.accounts())
+    }
+}
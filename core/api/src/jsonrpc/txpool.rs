@@ -0,0 +1,102 @@
+use std::sync::Arc;
+
+use jsonrpsee::{core::Error, proc_macros::rpc};
+
+use common_apm::metrics_rpc;
+use protocol::traits::{APIAdapter, Context};
+use protocol::types::{MemPoolContent, SignedTransaction, TransactionAction, H160};
+use protocol::{async_trait, ProtocolResult};
+
+use crate::jsonrpc::web3_types::{
+    Web3Transaction, Web3TxPoolContent, Web3TxPoolContentFrom, Web3TxPoolStatus,
+};
+use crate::jsonrpc::RpcResult;
+
+#[rpc(server)]
+pub trait AxonTxPool {
+    /// Returns the number of the pending, queued and system script
+    /// transactions in the mempool.
+    #[method(name = "txpool_status")]
+    async fn status(&self) -> RpcResult<Web3TxPoolStatus>;
+
+    /// Returns all the transactions in the mempool, grouped by sender and
+    /// nonce.
+    #[method(name = "txpool_content")]
+    async fn content(&self) -> RpcResult<Web3TxPoolContent<Web3Transaction>>;
+
+    /// Returns the transactions in the mempool sent by the given address.
+    #[method(name = "txpool_contentFrom")]
+    async fn content_from(&self, address: H160) -> RpcResult<Web3TxPoolContentFrom>;
+
+    /// Returns a textual summary of all the transactions in the mempool.
+    #[method(name = "txpool_inspect")]
+    async fn inspect(&self) -> RpcResult<Web3TxPoolContent<String>>;
+}
+
+pub struct TxPoolRpcImpl<Adapter> {
+    adapter: Arc<Adapter>,
+}
+
+impl<Adapter: APIAdapter> TxPoolRpcImpl<Adapter> {
+    pub fn new(adapter: Arc<Adapter>) -> Self {
+        Self { adapter }
+    }
+
+    async fn mempool_content(&self, from: Option<H160>) -> ProtocolResult<MemPoolContent> {
+        self.adapter.get_mempool_content(Context::new(), from).await
+    }
+}
+
+#[async_trait]
+impl<Adapter: APIAdapter + 'static> AxonTxPoolServer for TxPoolRpcImpl<Adapter> {
+    #[metrics_rpc("txpool_status")]
+    async fn status(&self) -> RpcResult<Web3TxPoolStatus> {
+        let content = self
+            .mempool_content(None)
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?;
+        Ok((&content).into())
+    }
+
+    #[metrics_rpc("txpool_content")]
+    async fn content(&self) -> RpcResult<Web3TxPoolContent<Web3Transaction>> {
+        let content = self
+            .mempool_content(None)
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?;
+        Ok(Web3TxPoolContent::new(content, Web3Transaction::from))
+    }
+
+    #[metrics_rpc("txpool_contentFrom")]
+    async fn content_from(&self, address: H160) -> RpcResult<Web3TxPoolContentFrom> {
+        let content = self
+            .mempool_content(Some(address))
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?;
+        Ok(Web3TxPoolContentFrom::new(&address, content))
+    }
+
+    #[metrics_rpc("txpool_inspect")]
+    async fn inspect(&self) -> RpcResult<Web3TxPoolContent<String>> {
+        let content = self
+            .mempool_content(None)
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?;
+        Ok(Web3TxPoolContent::new(content, |stx| inspect_tx(&stx)))
+    }
+}
+
+/// Summarize a transaction in the form of geth's `txpool_inspect`, such as
+/// `0x...: 0 wei + 21000 gas × 1000000000 wei`.
+fn inspect_tx(stx: &SignedTransaction) -> String {
+    let tx = &stx.transaction.unsigned;
+    let to = match tx.action {
+        TransactionAction::Call(to) => format!("{:?}", to),
+        TransactionAction::Create => "contract creation".to_string(),
+    };
+
+    format!(
+        "{}: {} wei + {} gas × {} wei",
+        to, tx.value, tx.gas_limit, tx.gas_price
+    )
+}
@@ -1,4 +1,4 @@
-use std::fmt;
+use std::{collections::BTreeMap, fmt};
 
 use serde::de::{Error, MapAccess, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
@@ -6,10 +6,13 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use core_consensus::SyncStatus as InnerSyncStatus;
 use protocol::codec::ProtocolCodec;
 use protocol::types::{
-    AccessList, Block, Bloom, Bytes, Hash, Header, Hex, Public, Receipt, SignedTransaction, H160,
-    H256, U256, U64,
+    AccessList, AccountProof, AccountTxs, Block, Bloom, Bytes, Hash, Header, Hex, MemPoolContent,
+    NetworkBan, NetworkNodeInfo, NetworkPeer, Public, Receipt, SignedTransaction,
+    StructLoggerConfig, TraceResult, TracerType, H160, H256, U256, U64,
 };
 
+use crate::APIError;
+
 const EIP1559_TX_TYPE: u64 = 0x02;
 
 #[allow(clippy::large_enum_variant)]
@@ -87,7 +90,7 @@ impl From<SignedTransaction> for Web3Transaction {
             from:                     stx.sender,
             to:                       stx.get_to(),
             input:                    Hex::encode(stx.transaction.unsigned.data),
-            nonce:                    stx.transaction.unsigned.value,
+            nonce:                    stx.transaction.unsigned.nonce,
             transaction_index:        None,
             value:                    stx.transaction.unsigned.value,
             access_list:              Some(stx.transaction.unsigned.access_list.clone()),
@@ -117,7 +120,7 @@ impl From<(SignedTransaction, Receipt)> for Web3Transaction {
             from:                     stx.sender,
             to:                       stx.get_to(),
             input:                    Hex::encode(stx.transaction.unsigned.data),
-            nonce:                    stx.transaction.unsigned.value,
+            nonce:                    stx.transaction.unsigned.nonce,
             transaction_index:        Some(receipt.tx_index.into()),
             value:                    stx.transaction.unsigned.value,
             access_list:              Some(stx.transaction.unsigned.access_list.clone()),
@@ -406,6 +409,7 @@ impl<'a> Visitor<'a> for BlockIdVisitor {
     {
         match value {
             "latest" => Ok(BlockId::Latest),
+            "earliest" => Ok(BlockId::Num(0)),
             _ if value.starts_with("0x") => u64::from_str_radix(&value[2..], 16)
                 .map(BlockId::Num)
                 .map_err(|e| Error::custom(format!("Invalid block number: {}", e))),
@@ -423,6 +427,63 @@ impl<'a> Visitor<'a> for BlockIdVisitor {
     }
 }
 
+/// The block parameter of the state queries, which can also be a block hash as
+/// defined in EIP-1898.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BlockIdOrHash {
+    Id(BlockId),
+    Hash(Hash),
+}
+
+impl Default for BlockIdOrHash {
+    fn default() -> Self {
+        BlockIdOrHash::Id(BlockId::Latest)
+    }
+}
+
+impl From<BlockId> for BlockIdOrHash {
+    fn from(id: BlockId) -> Self {
+        BlockIdOrHash::Id(id)
+    }
+}
+
+impl<'a> Deserialize<'a> for BlockIdOrHash {
+    fn deserialize<D>(deserializer: D) -> Result<BlockIdOrHash, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        let value = serde_json::Value::deserialize(deserializer)?;
+
+        if let Some(hash) = value.get("blockHash") {
+            return serde_json::from_value(hash.clone())
+                .map(BlockIdOrHash::Hash)
+                .map_err(|e| Error::custom(format!("Invalid block hash: {}", e)));
+        }
+
+        BlockId::deserialize(value)
+            .map(BlockIdOrHash::Id)
+            .map_err(|e| Error::custom(e.to_string()))
+    }
+}
+
+impl Serialize for BlockIdOrHash {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use serde::ser::SerializeMap;
+
+        match self {
+            BlockIdOrHash::Id(id) => id.serialize(serializer),
+            BlockIdOrHash::Hash(hash) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("blockHash", hash)?;
+                map.end()
+            }
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum BlockIdWithPending {
     BlockId(BlockId),
@@ -659,13 +720,14 @@ pub struct SyncStatus {
     pub pulled_states:  U256,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(deny_unknown_fields, rename_all = "camelCase")]
 pub struct Web3FeeHistory {
     pub oldest_block:     U256,
-    pub reward:           Option<Vec<U256>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reward:           Option<Vec<Vec<U256>>>,
     pub base_fee_per_gas: Vec<U256>,
-    pub gas_used_ratio:   Vec<U256>,
+    pub gas_used_ratio:   Vec<f64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
@@ -709,6 +771,265 @@ impl From<Header> for Web3Header {
     }
 }
 
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Web3TraceConfig {
+    pub tracer:          Option<String>,
+    pub tracer_config:   Option<Web3TracerConfig>,
+    #[serde(default)]
+    pub disable_storage: bool,
+    #[serde(default)]
+    pub disable_stack:   bool,
+    #[serde(default)]
+    pub enable_memory:   bool,
+    pub timeout:         Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Web3TracerConfig {
+    #[serde(default)]
+    pub only_top_call: bool,
+}
+
+impl TryFrom<Web3TraceConfig> for TracerType {
+    type Error = APIError;
+
+    fn try_from(config: Web3TraceConfig) -> Result<Self, Self::Error> {
+        match config.tracer.as_deref() {
+            None => Ok(TracerType::StructLog(StructLoggerConfig {
+                disable_storage: config.disable_storage,
+                disable_stack:   config.disable_stack,
+                enable_memory:   config.enable_memory,
+            })),
+            Some("callTracer") => Ok(TracerType::Call {
+                only_top_call: config.tracer_config.unwrap_or_default().only_top_call,
+            }),
+            Some("prestateTracer") => Ok(TracerType::Prestate),
+            Some(tracer) => Err(APIError::RequestPayload(format!(
+                "Unsupported tracer {:?}",
+                tracer
+            ))),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Web3TraceTransaction {
+    pub tx_hash: Hash,
+    pub result:  TraceResult,
+}
+
+/// The result of `eth_signTransaction`, the raw transaction can be sent by
+/// `eth_sendRawTransaction`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Web3SignedTransaction {
+    pub raw: Hex,
+    pub tx:  Web3Transaction,
+}
+
+impl From<SignedTransaction> for Web3SignedTransaction {
+    fn from(stx: SignedTransaction) -> Self {
+        let mut raw = vec![EIP1559_TX_TYPE as u8];
+        raw.extend_from_slice(&stx.transaction.encode().unwrap());
+        Web3SignedTransaction {
+            raw: Hex::encode(raw),
+            tx:  stx.into(),
+        }
+    }
+}
+
+/// The result of `eth_createAccessList`, the error is set if the call fails.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Web3AccessListResult {
+    pub access_list: AccessList,
+    pub gas_used:    U256,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error:       Option<String>,
+}
+
+/// The result of `eth_getProof`, see EIP-1186.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Web3AccountProof {
+    pub address:       H160,
+    pub account_proof: Vec<Hex>,
+    pub balance:       U256,
+    pub code_hash:     Hash,
+    pub nonce:         U256,
+    pub storage_hash:  Hash,
+    pub storage_proof: Vec<Web3StorageProof>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Web3StorageProof {
+    pub key:   H256,
+    pub value: U256,
+    pub proof: Vec<Hex>,
+}
+
+impl From<AccountProof> for Web3AccountProof {
+    fn from(proof: AccountProof) -> Self {
+        Web3AccountProof {
+            address:       proof.address,
+            account_proof: proof.account_proof.into_iter().map(Hex::encode).collect(),
+            balance:       proof.account.balance,
+            code_hash:     proof.account.code_hash,
+            nonce:         proof.account.nonce,
+            storage_hash:  proof.account.storage_root,
+            storage_proof: proof
+                .storage_proof
+                .into_iter()
+                .map(|slot| Web3StorageProof {
+                    key:   slot.key,
+                    value: U256::from_big_endian(slot.value.as_bytes()),
+                    proof: slot.proof.into_iter().map(Hex::encode).collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Web3TxPoolStatus {
+    pub pending: U64,
+    pub queued:  U64,
+    pub system:  U64,
+}
+
+impl From<&MemPoolContent> for Web3TxPoolStatus {
+    fn from(content: &MemPoolContent) -> Self {
+        Web3TxPoolStatus {
+            pending: (content.pending_len() as u64).into(),
+            queued:  (content.queued_len() as u64).into(),
+            system:  (content.system.len() as u64).into(),
+        }
+    }
+}
+
+/// The transactions of a sender keyed by the decimal string of their nonces,
+/// which is the layout of geth's `txpool` namespace.
+pub type Web3AccountTxs<T> = BTreeMap<String, T>;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Web3TxPoolContent<T> {
+    pub pending: BTreeMap<H160, Web3AccountTxs<T>>,
+    pub queued:  BTreeMap<H160, Web3AccountTxs<T>>,
+    pub system:  Vec<T>,
+}
+
+impl<T> Web3TxPoolContent<T> {
+    pub fn new<F: Fn(SignedTransaction) -> T>(content: MemPoolContent, f: F) -> Self {
+        let convert = |txs: BTreeMap<H160, AccountTxs>| {
+            txs.into_iter()
+                .map(|(sender, txs)| (sender, account_txs(txs, &f)))
+                .collect()
+        };
+
+        Web3TxPoolContent {
+            pending: convert(content.pending),
+            queued:  convert(content.queued),
+            system:  content.system.into_iter().map(&f).collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Web3TxPoolContentFrom {
+    pub pending: Web3AccountTxs<Web3Transaction>,
+    pub queued:  Web3AccountTxs<Web3Transaction>,
+    pub system:  Vec<Web3Transaction>,
+}
+
+impl Web3TxPoolContentFrom {
+    pub fn new(sender: &H160, mut content: MemPoolContent) -> Self {
+        let take = |txs: &mut BTreeMap<H160, AccountTxs>| {
+            account_txs(
+                txs.remove(sender).unwrap_or_default(),
+                &Web3Transaction::from,
+            )
+        };
+
+        Web3TxPoolContentFrom {
+            pending: take(&mut content.pending),
+            queued:  take(&mut content.queued),
+            system:  content.system.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+fn account_txs<T, F: Fn(SignedTransaction) -> T>(txs: AccountTxs, f: &F) -> Web3AccountTxs<T> {
+    txs.into_iter()
+        .map(|(nonce, stx)| (nonce.to_string(), f(stx)))
+        .collect()
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Web3PeerInfo {
+    pub id:             String,
+    pub connected_addr: String,
+    pub listen_addrs:   Vec<String>,
+    pub inbound:        bool,
+    pub score:          i32,
+    pub tags:           Vec<String>,
+}
+
+impl From<NetworkPeer> for Web3PeerInfo {
+    fn from(peer: NetworkPeer) -> Self {
+        Web3PeerInfo {
+            id:             peer.peer_id,
+            connected_addr: peer.connected_addr,
+            listen_addrs:   peer.listen_addrs,
+            inbound:        peer.inbound,
+            score:          peer.score,
+            tags:           peer.tags,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Web3BannedAddress {
+    pub address:   String,
+    pub ban_until: U64,
+    pub reason:    String,
+}
+
+impl From<NetworkBan> for Web3BannedAddress {
+    fn from(ban: NetworkBan) -> Self {
+        Web3BannedAddress {
+            address:   ban.address,
+            ban_until: ban.ban_until.into(),
+            reason:    ban.reason,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Web3NodeInfo {
+    pub id:              String,
+    pub listen_addrs:    Vec<String>,
+    pub chain_id:        String,
+    pub connected_peers: U64,
+    pub banned:          Vec<Web3BannedAddress>,
+}
+
+impl From<NetworkNodeInfo> for Web3NodeInfo {
+    fn from(info: NetworkNodeInfo) -> Self {
+        Web3NodeInfo {
+            id:              info.peer_id,
+            listen_addrs:    info.listen_addrs,
+            chain_id:        info.chain_id,
+            connected_peers: (info.connected_peers as u64).into(),
+            banned:          info.banned.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -729,4 +1050,46 @@ mod tests {
         let json = json::parse(&serde_json::to_string(&status).unwrap()).unwrap();
         assert!(json.is_object());
     }
+
+    #[test]
+    fn test_trace_config() {
+        let config: Web3TraceConfig =
+            serde_json::from_str(r#"{"tracer":"callTracer","tracerConfig":{"onlyTopCall":true}}"#)
+                .unwrap();
+        assert_eq!(TracerType::try_from(config).unwrap(), TracerType::Call {
+            only_top_call: true,
+        });
+
+        let config: Web3TraceConfig =
+            serde_json::from_str(r#"{"disableStorage":true,"enableMemory":true}"#).unwrap();
+        assert_eq!(
+            TracerType::try_from(config).unwrap(),
+            TracerType::StructLog(StructLoggerConfig {
+                disable_storage: true,
+                disable_stack:   false,
+                enable_memory:   true,
+            })
+        );
+
+        let config: Web3TraceConfig = serde_json::from_str(r#"{"tracer":"4byteTracer"}"#).unwrap();
+        assert!(TracerType::try_from(config).is_err());
+    }
+
+    #[test]
+    fn test_block_id_or_hash() {
+        let id: BlockIdOrHash = serde_json::from_str(r#""latest""#).unwrap();
+        assert_eq!(id, BlockIdOrHash::Id(BlockId::Latest));
+
+        let id: BlockIdOrHash = serde_json::from_str(r#""earliest""#).unwrap();
+        assert_eq!(id, BlockIdOrHash::Id(BlockId::Num(0)));
+
+        let id: BlockIdOrHash = serde_json::from_str(r#"{"blockNumber":"0x10"}"#).unwrap();
+        assert_eq!(id, BlockIdOrHash::Id(BlockId::Num(16)));
+
+        let hash = Hash::repeat_byte(1);
+        let id: BlockIdOrHash =
+            serde_json::from_str(&serde_json::to_string(&BlockIdOrHash::Hash(hash)).unwrap())
+                .unwrap();
+        assert_eq!(id, BlockIdOrHash::Hash(hash));
+    }
 }
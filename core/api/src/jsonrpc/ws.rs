@@ -0,0 +1,79 @@
+use jsonrpsee::core::Error;
+use jsonrpsee::types::Params;
+use jsonrpsee::ws_server::RpcModule;
+use serde_json::{json, Value};
+
+use common_apm::metrics::api::on_request_rejected;
+use protocol::tokio;
+
+use crate::jsonrpc::limiter::RateLimiter;
+
+/// The methods served on the WebSocket endpoint, each call of which is
+/// checked against the rate limits before it is forwarded to the inner
+/// module. The WebSocket server cannot tell the IP of a call, so only the
+/// method rates and timeouts apply.
+pub struct WsLimited {
+    inner:   RpcModule<()>,
+    limiter: RateLimiter,
+}
+
+impl WsLimited {
+    pub fn into_rpc(rpc: RpcModule<()>, limiter: RateLimiter) -> RpcModule<WsLimited> {
+        let methods = rpc.method_names().collect::<Vec<_>>();
+        let mut limited = RpcModule::new(WsLimited {
+            inner: rpc,
+            limiter,
+        });
+
+        for method in methods.into_iter() {
+            limited
+                .register_async_method(method, move |params, ctx| async move {
+                    ctx.forward(method, params).await
+                })
+                .unwrap();
+        }
+
+        limited
+    }
+
+    async fn forward(&self, method: &'static str, params: Params<'static>) -> Result<Value, Error> {
+        if let Err(e) = self.limiter.check_method(method) {
+            on_request_rejected(method);
+            log::debug!("[api]: reject {} ws request: {}", method, e);
+            return Err(Error::Custom(e.to_string()));
+        }
+
+        let mut req = json!({
+            "jsonrpc": "2.0",
+            "id": 0,
+            "method": method,
+        });
+        if let Some(params) = params.as_str() {
+            req["params"] =
+                serde_json::from_str(params).map_err(|e| Error::Custom(e.to_string()))?;
+        }
+
+        let req = req.to_string();
+        let (resp, _) = match self.limiter.timeout(method) {
+            Some(timeout) => tokio::time::timeout(timeout, self.inner.raw_json_request(&req))
+                .await
+                .map_err(|_| {
+                    on_request_rejected(method);
+                    Error::Custom("request timed out".to_string())
+                })??,
+            None => self.inner.raw_json_request(&req).await?,
+        };
+
+        let mut resp =
+            serde_json::from_str::<Value>(&resp).map_err(|e| Error::Custom(e.to_string()))?;
+        match resp.get_mut("result") {
+            Some(result) => Ok(result.take()),
+            None => Err(Error::Custom(
+                resp["error"]["message"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+            )),
+        }
+    }
+}
@@ -18,16 +18,19 @@ use core_consensus::SYNC_STATUS;
 use protocol::{
     tokio::{
         self, select,
-        sync::mpsc::{channel, Receiver, Sender},
+        sync::{
+            broadcast::{self, error::RecvError},
+            mpsc::{channel, Receiver, Sender},
+        },
         time::interval,
     },
     traits::{APIAdapter, Context},
-    types::{BigEndianHash, Hash, Hex, H160, U256},
+    types::{BigEndianHash, Hash, Hex, SignedTransaction, H160, U256},
 };
 
 use crate::jsonrpc::{
     r#impl::from_receipt_to_web3_log,
-    web3_types::{MultiType, Web3Header, Web3SyncStatus},
+    web3_types::{MultiType, Web3Header, Web3SyncStatus, Web3Transaction},
 };
 
 pub async fn ws_subscription_module<Adapter>(adapter: Arc<Adapter>) -> RpcModule<Sender<RawHub>>
@@ -66,12 +69,15 @@ where
 }
 
 pub struct Subscription<Adapter> {
-    log_hubs:       Vec<Hub<LoggerFilter>>,
-    header_hubs:    Vec<Hub<()>>,
-    sync_hubs:      Vec<Hub<()>>,
-    adapter:        Arc<Adapter>,
-    current_number: u64,
-    recv:           Receiver<RawHub>,
+    log_hubs:        Vec<Hub<LoggerFilter>>,
+    header_hubs:     Vec<Hub<()>>,
+    sync_hubs:       Vec<Hub<()>>,
+    /// The filter tells whether to send the full transactions.
+    pending_tx_hubs: Vec<Hub<bool>>,
+    adapter:         Arc<Adapter>,
+    current_number:  u64,
+    recv:            Receiver<RawHub>,
+    pending_txs:     broadcast::Receiver<SignedTransaction>,
 }
 
 impl<Adapter> Subscription<Adapter>
@@ -89,12 +95,33 @@ where
             log_hubs: Vec::new(),
             header_hubs: Vec::new(),
             sync_hubs: Vec::new(),
+            pending_tx_hubs: Vec::new(),
+            pending_txs: adapter.subscribe_pending_txs(),
             adapter,
             current_number: latest.number,
             recv,
         }
     }
 
+    fn notify_pending_tx(&mut self, stx: SignedTransaction) {
+        self.pending_tx_hubs.retain(|hub| !hub.sink.is_closed());
+
+        let hash = stx.transaction.hash;
+        let web3_tx = if self.pending_tx_hubs.iter().any(|hub| hub.filter) {
+            Some(Web3Transaction::from(stx))
+        } else {
+            None
+        };
+
+        for hub in self.pending_tx_hubs.iter_mut() {
+            // unbound sender can ignore it's return
+            let _ignore = match web3_tx {
+                Some(ref tx) if hub.filter => hub.sink.send(tx),
+                _ => hub.sink.send(&hash),
+            };
+        }
+    }
+
     async fn notify(&mut self) {
         self.header_hubs.retain(|hub| !hub.sink.is_closed());
         self.sync_hubs.retain(|hub| !hub.sink.is_closed());
@@ -216,7 +243,8 @@ where
                             match hub.typ {
                                 Type::NewHeads => self.header_hubs.push(Hub{filter: (), sink: hub.sink}),
                                 Type::Logs(filter) => self.log_hubs.push(Hub{filter, sink: hub.sink}),
-                                Type::Syncing => self.sync_hubs.push(Hub{filter: (), sink: hub.sink})
+                                Type::Syncing => self.sync_hubs.push(Hub{filter: (), sink: hub.sink}),
+                                Type::NewPendingTransactions(full) => self.pending_tx_hubs.push(Hub{filter: full, sink: hub.sink}),
                             }
                         },
                         None => {
@@ -224,6 +252,17 @@ where
                         }
                     }
                 }
+                stx = self.pending_txs.recv() => {
+                    match stx {
+                        Ok(stx) => self.notify_pending_tx(stx),
+                        Err(RecvError::Lagged(n)) => {
+                            log::warn!("[api]: pending tx subscription misses {} txs", n)
+                        }
+                        Err(RecvError::Closed) => {
+                            break
+                        }
+                    }
+                }
                 _ = time_internal.tick() => {
                     self.notify().await;
                 }
@@ -239,6 +278,8 @@ enum Type {
     NewHeads,
     Logs(LoggerFilter),
     Syncing,
+    /// Whether to send the full transactions instead of their hashes.
+    NewPendingTransactions(bool),
 }
 
 impl<'a> TryFrom<Params<'a>> for Type {
@@ -252,6 +293,10 @@ impl<'a> TryFrom<Params<'a>> for Type {
         match method {
             "newHeads" => Ok(Type::NewHeads),
             "syncing" => Ok(Type::Syncing),
+            "newPendingTransactions" => {
+                let full: Option<bool> = iter.optional_next()?;
+                Ok(Type::NewPendingTransactions(full.unwrap_or_default()))
+            }
             "logs" => {
                 let filter: RawLoggerFilter = iter.next()?;
                 Ok(Type::Logs(filter.into()))
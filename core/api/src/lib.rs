@@ -6,6 +6,7 @@ pub use adapter::DefaultAPIAdapter;
 
 use std::error::Error;
 
+use protocol::types::{Hash, H160};
 use protocol::{Display, ProtocolError, ProtocolErrorKind};
 
 #[derive(Debug, Display)]
@@ -24,6 +25,31 @@ pub enum APIError {
 
     #[display(fmt = "Invalid request payload {:?}", _0)]
     RequestPayload(String),
+
+    #[display(fmt = "state {:?} is not available, it may have been pruned", _0)]
+    StateNotAvailable(Hash),
+
+    #[display(fmt = "keystore error {:?}", _0)]
+    KeyStore(String),
+
+    #[display(fmt = "account {:?} is not in the keystore", _0)]
+    UnknownAccount(H160),
+
+    #[display(fmt = "account {:?} is locked", _0)]
+    AccountLocked(H160),
+
+    #[display(
+        fmt = "query over {} blocks exceeds the limit of {} blocks, narrow the block range",
+        range,
+        limit
+    )]
+    LogsRangeExceeded { range: u64, limit: u64 },
+
+    #[display(
+        fmt = "query returns more than {} logs, narrow the block range or the filter",
+        _0
+    )]
+    LogsResultsExceeded(usize),
 }
 
 impl Error for APIError {}
@@ -26,11 +26,109 @@ impl AxonCli {
                 Arg::new("genesis_path")
                     .short('g')
                     .long("genesis")
-                    .help("Axon genesis path")
-                    .required(true)
+                    .help("Axon genesis path, required by run and init")
                     .takes_value(true),
             )
             .subcommand(Command::new("run").about("Run axon process"))
+            .subcommand(Command::new("init").about("Initialize the data directory with genesis"))
+            .subcommand(
+                Command::new("rollback")
+                    .about("Rewind the chain to a block whose state is still available")
+                    .arg(
+                        Arg::new("to")
+                            .long("to")
+                            .help("The block number to rewind to")
+                            .required(true)
+                            .takes_value(true),
+                    ),
+            )
+            .subcommand(
+                Command::new("db")
+                    .about("Inspect the block database")
+                    .subcommand_required(true)
+                    .subcommand(
+                        Command::new("stats").about("Print the key count and size of each column"),
+                    ),
+            )
+            .subcommand(
+                Command::new("verify-chain")
+                    .about("Check the parent links and proofs of all stored blocks"),
+            )
+            .subcommand(
+                Command::new("reindex-logs")
+                    .about("Index the logs of the blocks stored before the log index was built"),
+            )
+            .subcommand(
+                Command::new("export-blocks")
+                    .about("Export blocks into an RLP block stream")
+                    .arg(
+                        Arg::new("from")
+                            .long("from")
+                            .help("The first block number to export, defaults to 0")
+                            .takes_value(true),
+                    )
+                    .arg(
+                        Arg::new("to")
+                            .long("to")
+                            .help("The last block number to export, defaults to the latest")
+                            .takes_value(true),
+                    )
+                    .arg(
+                        Arg::new("output")
+                            .short('o')
+                            .long("output")
+                            .help("The block stream file path")
+                            .required(true)
+                            .takes_value(true),
+                    ),
+            )
+            .subcommand(
+                Command::new("import-blocks")
+                    .about("Execute and import blocks from an RLP block stream")
+                    .arg(
+                        Arg::new("input")
+                            .short('i')
+                            .long("input")
+                            .help("The block stream file path")
+                            .required(true)
+                            .takes_value(true),
+                    ),
+            )
+            .subcommand(
+                Command::new("snapshot")
+                    .about("Export or import a state snapshot")
+                    .subcommand_required(true)
+                    .subcommand(
+                        Command::new("export")
+                            .about("Export the state at a committed height")
+                            .arg(
+                                Arg::new("height")
+                                    .long("height")
+                                    .help("The block height to export, defaults to the latest")
+                                    .takes_value(true),
+                            )
+                            .arg(
+                                Arg::new("output")
+                                    .short('o')
+                                    .long("output")
+                                    .help("The snapshot file path")
+                                    .required(true)
+                                    .takes_value(true),
+                            ),
+                    )
+                    .subcommand(
+                        Command::new("import")
+                            .about("Bootstrap an empty node from a snapshot")
+                            .arg(
+                                Arg::new("input")
+                                    .short('i')
+                                    .long("input")
+                                    .help("The snapshot file path")
+                                    .required(true)
+                                    .takes_value(true),
+                            ),
+                    ),
+            )
             .get_matches();
 
         AxonCli { matches }
@@ -45,15 +143,82 @@ impl AxonCli {
         if let Some(ref mut f) = config.rocksdb.options_file {
             *f = path.join(&f)
         }
-        let genesis: RichBlock =
-            parse_file(self.matches.value_of("genesis_path").unwrap(), true).unwrap();
 
         register_log(&config);
 
-        Axon::new(config, genesis).run().unwrap();
+        match self.matches.subcommand() {
+            Some(("snapshot", matches)) => Self::snapshot(&config, matches),
+            Some(("rollback", matches)) => {
+                let to = parse_number(matches, "to").unwrap();
+                Axon::rollback(&config, to).unwrap();
+                println!("rolled back to block {}", to);
+            }
+            Some(("db", _)) => {
+                for (category, stats) in Axon::db_stats(&config).unwrap() {
+                    println!(
+                        "{:<20} keys: {:<12} size: {} bytes",
+                        category.to_string(),
+                        stats.keys,
+                        stats.size
+                    );
+                }
+            }
+            Some(("verify-chain", _)) => {
+                let latest = Axon::verify_chain(&config).unwrap();
+                println!("verified blocks 0 to {}", latest);
+            }
+            Some(("reindex-logs", _)) => {
+                let start = Axon::reindex_logs(&config).unwrap();
+                println!("indexed the logs from block {}", start);
+            }
+            Some(("export-blocks", matches)) => {
+                let from = parse_number(matches, "from").unwrap_or_default();
+                let to = parse_number(matches, "to");
+                let path = Path::new(matches.value_of("output").unwrap());
+                let count = Axon::export_blocks(&config, from, to, path).unwrap();
+                println!("exported {} blocks to {:?}", count, path);
+            }
+            Some(("import-blocks", matches)) => {
+                let path = Path::new(matches.value_of("input").unwrap());
+                let count = Axon::import_blocks(&config, path).unwrap();
+                println!("imported {} blocks from {:?}", count, path);
+            }
+            Some(("init", _)) => Axon::new(config, self.genesis()).init().unwrap(),
+            _ => Axon::new(config, self.genesis()).run().unwrap(),
+        }
+    }
+
+    fn genesis(&self) -> RichBlock {
+        let genesis_path = self
+            .matches
+            .value_of("genesis_path")
+            .expect("--genesis is required to run or init axon");
+        parse_file(genesis_path, true).unwrap()
+    }
+
+    fn snapshot(config: &Config, matches: &ArgMatches) {
+        match matches.subcommand() {
+            Some(("export", matches)) => {
+                let height = parse_number(matches, "height");
+                let path = Path::new(matches.value_of("output").unwrap());
+                Axon::export_snapshot(config, height, path).unwrap();
+            }
+            Some(("import", matches)) => {
+                let path = Path::new(matches.value_of("input").unwrap());
+                Axon::import_snapshot(config, path).unwrap();
+            }
+            _ => unreachable!(),
+        }
     }
 }
 
+fn parse_number(matches: &ArgMatches, name: &str) -> Option<u64> {
+    matches.value_of(name).map(|n| {
+        n.parse::<u64>()
+            .unwrap_or_else(|_| panic!("invalid {}", name))
+    })
+}
+
 fn register_log(config: &Config) {
     common_logger::init(
         config.logger.filter.clone(),
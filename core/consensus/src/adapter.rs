@@ -1,33 +1,35 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use overlord::types::{Node, OverlordMsg, Vote, VoteType};
-use overlord::{extract_voters, Crypto, OverlordHandler};
+use overlord::types::OverlordMsg;
+use overlord::OverlordHandler;
 use parking_lot::RwLock;
 
 use common_apm::Instant;
 use common_apm_derive::trace_span;
-use core_executor::{AxonExecutor, AxonExecutorAdapter};
+use core_executor::{AxonExecutor, AxonExecutorAdapter, JournalTrieDB, MPTTrie, TrieJournal};
 use core_network::{PeerId, PeerIdExt};
 use protocol::traits::{
     CommonConsensusAdapter, ConsensusAdapter, Context, CrossClient, Executor, Gossip, MemPool,
     MessageTarget, MetadataControl, Network, PeerTrust, Priority, Rpc, Storage,
-    SynchronizationAdapter,
+    SynchronizationAdapter, TrustFeedback,
 };
 use protocol::types::{
-    BatchSignedTxs, Block, BlockNumber, Bytes, ExecResp, Hash, Hasher, Header, Hex, Log,
-    MerkleRoot, Metadata, Proof, Proposal, Receipt, SignedTransaction, Validator, U256,
+    BatchCodes, BatchSignedTxs, Block, BlockNumber, Bytes, CodeRequest, Evidence, ExecResp, Hash,
+    Header, Hex, Log, MerkleRoot, Metadata, Proof, Proposal, Receipt, SignedTransaction,
+    StateEntry, StateRange, StateRangeRequest, Validator, H160, H256, RLP_NULL, U256,
 };
-use protocol::{async_trait, codec::ProtocolCodec, tokio::task, ProtocolResult};
+use protocol::{async_trait, tokio::task, ProtocolResult};
 
 use crate::consensus::gen_overlord_status;
 use crate::message::{
-    BROADCAST_HEIGHT, RPC_SYNC_PULL_BLOCK, RPC_SYNC_PULL_PROOF, RPC_SYNC_PULL_TXS,
+    BROADCAST_HEIGHT, END_GOSSIP_EVIDENCE, RPC_SYNC_PULL_BLOCK, RPC_SYNC_PULL_CODE,
+    RPC_SYNC_PULL_PROOF, RPC_SYNC_PULL_STATE, RPC_SYNC_PULL_TXS,
 };
 use crate::types::PullTxsRequest;
-use crate::util::{convert_hex_to_bls_pubkeys, OverlordCrypto};
+use crate::util::{convert_hex_to_bls_pubkeys, verify_proof_with_metadata, OverlordCrypto};
 use crate::BlockHeaderField::PreviousBlockHash;
-use crate::BlockProofField::{BitMap, HashMismatch, HeightMismatch, Signature, WeightNotFound};
+use crate::BlockProofField::{Signature, WeightNotFound};
 use crate::{BlockProofField, ConsensusError};
 
 pub struct OverlordConsensusAdapter<
@@ -134,6 +136,26 @@ where
 
         Ok(())
     }
+
+    fn get_balances(
+        &self,
+        _ctx: Context,
+        state_root: MerkleRoot,
+        addresses: &[H160],
+    ) -> ProtocolResult<Vec<U256>> {
+        let backend = AxonExecutorAdapter::without_block_hashes(
+            state_root,
+            Arc::clone(&self.trie_db),
+            Arc::clone(&self.storage),
+            Default::default(),
+        )?;
+        let executor = AxonExecutor::default();
+
+        Ok(addresses
+            .iter()
+            .map(|address| executor.get_account(&backend, address).balance)
+            .collect())
+    }
 }
 
 #[async_trait]
@@ -233,6 +255,79 @@ where
             .await?;
         Ok(ret)
     }
+
+    /// Pull a range of the trie entries with Merkle proofs from other nodes
+    #[trace_span(kind = "consensus.adapter")]
+    async fn get_state_range_from_remote(
+        &self,
+        ctx: Context,
+        req: StateRangeRequest,
+    ) -> ProtocolResult<StateRange> {
+        let ret = self
+            .network
+            .call::<StateRangeRequest, StateRange>(ctx, RPC_SYNC_PULL_STATE, req, Priority::High)
+            .await?;
+        Ok(ret)
+    }
+
+    /// Pull contract codes corresponding to the given code hashes from other
+    /// nodes
+    #[trace_span(kind = "consensus.adapter", logs = "{codes_len: hashes.len()}")]
+    async fn get_codes_from_remote(
+        &self,
+        ctx: Context,
+        hashes: &[Hash],
+    ) -> ProtocolResult<Vec<Bytes>> {
+        let res = self
+            .network
+            .call::<CodeRequest, BatchCodes>(
+                ctx,
+                RPC_SYNC_PULL_CODE,
+                CodeRequest {
+                    hashes: hashes.to_vec(),
+                },
+                Priority::High,
+            )
+            .await?;
+        Ok(res.inner())
+    }
+
+    #[trace_span(kind = "consensus.adapter", logs = "{entries_len: entries.len()}")]
+    fn save_state_range(
+        &self,
+        ctx: Context,
+        root: MerkleRoot,
+        entries: &[StateEntry],
+    ) -> ProtocolResult<MerkleRoot> {
+        let mut trie = if root == RLP_NULL {
+            MPTTrie::new(Arc::clone(&self.trie_db))
+        } else {
+            MPTTrie::from_root(root, Arc::clone(&self.trie_db))?
+        };
+
+        for entry in entries.iter() {
+            trie.insert(&entry.key, &entry.value)?;
+        }
+
+        trie.commit()
+    }
+
+    #[trace_span(kind = "consensus.adapter")]
+    async fn save_code(
+        &self,
+        ctx: Context,
+        address: H256,
+        code_hash: Hash,
+        code: Bytes,
+    ) -> ProtocolResult<()> {
+        self.storage
+            .insert_code(ctx, address, code_hash, code)
+            .await
+    }
+
+    fn report_peer(&self, ctx: Context, feedback: TrustFeedback) {
+        self.network.report(ctx, feedback);
+    }
 }
 
 #[async_trait]
@@ -243,7 +338,7 @@ where
     S: Storage + 'static,
     CS: CrossClient + 'static,
     MT: MetadataControl + 'static,
-    DB: cita_trie::DB + 'static,
+    DB: TrieJournal + 'static,
 {
     /// Save a block to the database.
     #[trace_span(kind = "consensus.adapter", logs = "{txs_len: block.tx_hashes.len()}")]
@@ -256,6 +351,15 @@ where
         self.storage.update_latest_proof(ctx, proof).await
     }
 
+    #[trace_span(kind = "consensus.adapter")]
+    async fn save_evidence(&self, ctx: Context, evidence: Evidence) -> ProtocolResult<()> {
+        self.storage
+            .insert_evidence(ctx.clone(), evidence.clone())
+            .await?;
+        self.metadata.record_evidence(ctx, evidence);
+        Ok(())
+    }
+
     /// Save some signed transactions to the database.
     #[trace_span(kind = "consensus.adapter", logs = "{txs_len: signed_txs.len()}")]
     async fn save_signed_txs(
@@ -346,14 +450,16 @@ where
         proposal: &Proposal,
         signed_txs: Vec<SignedTransaction>,
     ) -> ProtocolResult<ExecResp> {
+        let journal = Arc::new(JournalTrieDB::new(Arc::clone(&self.trie_db)));
         let mut backend = AxonExecutorAdapter::from_root(
             last_state_root,
-            Arc::clone(&self.trie_db),
+            Arc::clone(&journal),
             Arc::clone(&self.storage),
             proposal.clone().into(),
-        )?;
+        )
+        .await?;
 
-        Ok(task::block_in_place(|| {
+        let res = task::block_in_place(|| {
             let time = Instant::now();
             let res = AxonExecutor::default().exec(&mut backend, signed_txs);
             common_apm::metrics::consensus::CONSENSUS_TIME_HISTOGRAM_VEC_STATIC
@@ -361,7 +467,10 @@ where
                 .observe(common_apm::metrics::duration_to_sec(time.elapsed()));
 
             res
-        }))
+        });
+
+        journal.commit(proposal.number)?;
+        Ok(res)
     }
 
     fn need_change_metadata(&self, block_number: u64) -> bool {
@@ -387,6 +496,12 @@ where
             .await
     }
 
+    async fn broadcast_evidence(&self, ctx: Context, evidence: Evidence) -> ProtocolResult<()> {
+        self.network
+            .broadcast(ctx, END_GOSSIP_EVIDENCE, evidence, Priority::High)
+            .await
+    }
+
     fn set_args(&self, context: Context, state_root: MerkleRoot, gas_limit: u64, max_tx_size: u64) {
         self.mempool
             .set_args(context, state_root, gas_limit, max_tx_size);
@@ -438,106 +553,18 @@ where
             return Ok(());
         };
 
-        if block.header.number != proof.number {
-            log::error!(
-                "[consensus] verify_proof, block_header.number: {}, proof.number: {}",
-                block.header.number,
-                proof.number
-            );
-            return Err(ConsensusError::VerifyProof(
-                block.header.number,
-                HeightMismatch(block.header.number, proof.number),
-            )
-            .into());
-        }
-
-        // Todo: impl From<&Block> for Proposal
-        let proposal_hash = Hasher::digest(Proposal::from(block.clone()).encode()?);
-
-        if proposal_hash != proof.block_hash {
-            log::error!(
-                "[consensus] verify_proof, blockhash: {:?}, proof.block_hash: {:?}",
-                proposal_hash,
-                proof.block_hash
-            );
-            return Err(ConsensusError::VerifyProof(block.header.number, HashMismatch).into());
-        }
-
         // the auth_list for the target should comes from previous number
-        let metadata = self.metadata.get_metadata(ctx.clone(), &block.header)?;
-
-        if !metadata.version.contains(block.header.number) {
-            return Err(ConsensusError::ConfusedMetadata(
-                metadata.version.start,
-                metadata.version.end,
-            )
-            .into());
-        }
+        let metadata = self.metadata.get_metadata(ctx, &block.header)?;
 
-        let mut authority_list = metadata
-            .verifier_list
-            .iter()
-            .map(|v| Node {
-                address:        v.pub_key.as_bytes(),
-                propose_weight: v.propose_weight,
-                vote_weight:    v.vote_weight,
-            })
-            .collect::<Vec<Node>>();
-
-        let signed_voters = extract_voters(&mut authority_list, &proof.bitmap).map_err(|_| {
-            log::error!("[consensus] extract_voters fails, bitmap error");
-            ConsensusError::VerifyProof(block.header.number, BitMap)
-        })?;
-
-        let vote = Vote {
-            height:     proof.number,
-            round:      proof.round,
-            vote_type:  VoteType::Precommit,
-            block_hash: Bytes::from(proof.block_hash.as_bytes().to_vec()),
-        };
-
-        let weight_map = authority_list
-            .iter()
-            .map(|node| (node.address.clone(), node.vote_weight))
-            .collect::<HashMap<overlord::types::Address, u32>>();
-        self.verify_proof_weight(
-            ctx.clone(),
-            block.header.number,
-            weight_map,
-            signed_voters.clone(),
-        )?;
-
-        let vote_hash = self.crypto.hash(Bytes::from(rlp::encode(&vote)));
-        let hex_pubkeys = metadata
-            .verifier_list
-            .iter()
-            .filter_map(|v| {
-                if signed_voters.contains(&v.pub_key.as_bytes()) {
-                    Some(v.bls_pub_key.clone())
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<_>>();
-
-        self.verify_proof_signature(
-            ctx,
-            block.header.number,
-            vote_hash.clone(),
-            proof.signature.clone(),
-            hex_pubkeys,
-        ).map_err(|e| {
-            log::error!("[consensus] verify_proof_signature error, number {}, vote: {:?}, vote_hash:{:?}, sig:{:?}, signed_voter:{:?}",
-            block.header.number,
-            vote,
-            vote_hash,
-            proof.signature,
-            signed_voters,
+        verify_proof_with_metadata(&block, &proof, &metadata).map_err(|e| {
+            log::error!(
+                "[consensus] verify_proof error, number {}, proof: {:?}, error: {}",
+                block.header.number,
+                proof,
+                e
             );
             e
-        })?;
-
-        Ok(())
+        })
     }
 
     async fn notify_block_logs(
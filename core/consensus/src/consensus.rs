@@ -7,9 +7,12 @@ use overlord::types::{
 use overlord::{DurationConfig, Overlord, OverlordHandler};
 
 use protocol::traits::{Consensus, ConsensusAdapter, NodeInfo};
-use protocol::types::{Proposal, Validator, H160};
+use protocol::types::{Evidence, Proposal, Validator, H160};
 use protocol::{
-    async_trait, codec::ProtocolCodec, tokio::sync::Mutex as AsyncMutex, ProtocolResult,
+    async_trait,
+    codec::ProtocolCodec,
+    tokio::{self, sync::Mutex as AsyncMutex},
+    ProtocolResult,
 };
 
 use common_apm::tracing::{AxonTracer, Tag};
@@ -17,8 +20,8 @@ use common_apm_derive::trace_span;
 
 use crate::wal::{ConsensusWal, SignedTxsWAL};
 use crate::{
-    engine::ConsensusEngine, status::StatusAgent, util::OverlordCrypto, ConsensusError,
-    ConsensusType,
+    engine::ConsensusEngine, evidence::EvidencePool, status::StatusAgent, util::OverlordCrypto,
+    ConsensusError, ConsensusType,
 };
 
 /// Provide consensus
@@ -27,7 +30,9 @@ pub struct OverlordConsensus<Adapter: ConsensusAdapter + 'static> {
     inner:
         Arc<Overlord<Proposal, ConsensusEngine<Adapter>, OverlordCrypto, ConsensusEngine<Adapter>>>,
     /// An overlord consensus protocol handler.
-    handler: OverlordHandler<Proposal>,
+    handler:  OverlordHandler<Proposal>,
+    /// The pool finding the validators that double sign.
+    evidence: Arc<EvidencePool<Adapter>>,
 }
 
 #[async_trait]
@@ -62,12 +67,19 @@ impl<Adapter: ConsensusAdapter + 'static> Consensus for OverlordConsensus<Adapte
         let signed_vote = SignedVote::decode(&vote)
             .map_err(|_| ConsensusError::DecodeErr(ConsensusType::SignedVote))?;
 
+        let evidence = Arc::clone(&self.evidence);
+        let (observe_ctx, observed) = (ctx.clone(), signed_vote.clone());
+
         let msg = OverlordMsg::SignedVote(signed_vote);
         tracing_overlord_message(ctx.clone(), &msg);
 
         self.handler
             .send_msg(ctx, msg)
             .expect("Overlord handler disconnect");
+
+        // The signature is verified again by the evidence pool, which is kept
+        // off the consensus path.
+        tokio::spawn(async move { evidence.observe_vote(observe_ctx, &observed).await });
         Ok(())
     }
 
@@ -76,12 +88,17 @@ impl<Adapter: ConsensusAdapter + 'static> Consensus for OverlordConsensus<Adapte
         let aggregated_vote = AggregatedVote::decode(&qc)
             .map_err(|_| ConsensusError::DecodeErr(ConsensusType::AggregateVote))?;
 
+        let evidence = Arc::clone(&self.evidence);
+        let (observe_ctx, observed) = (ctx.clone(), aggregated_vote.clone());
+
         let msg = OverlordMsg::AggregatedVote(aggregated_vote);
         tracing_overlord_message(ctx.clone(), &msg);
 
         self.handler
             .send_msg(ctx, msg)
             .expect("Overlord handler disconnect");
+
+        tokio::spawn(async move { evidence.observe_qc(observe_ctx, &observed).await });
         Ok(())
     }
 
@@ -98,6 +115,10 @@ impl<Adapter: ConsensusAdapter + 'static> Consensus for OverlordConsensus<Adapte
             .expect("Overlord handler disconnect");
         Ok(())
     }
+
+    async fn set_evidence(&self, ctx: Context, evidence: Evidence) -> ProtocolResult<()> {
+        self.evidence.receive(ctx, evidence).await
+    }
 }
 
 impl<Adapter: ConsensusAdapter + 'static> OverlordConsensus<Adapter> {
@@ -112,6 +133,11 @@ impl<Adapter: ConsensusAdapter + 'static> OverlordConsensus<Adapter> {
         consensus_wal: Arc<ConsensusWal>,
         cross_period_interval: u64,
     ) -> Self {
+        let evidence = Arc::new(EvidencePool::new(
+            Arc::clone(&adapter),
+            Arc::clone(&crypto),
+            status.clone(),
+        ));
         let engine = Arc::new(ConsensusEngine::new(
             status,
             metadata_address,
@@ -147,8 +173,9 @@ impl<Adapter: ConsensusAdapter + 'static> OverlordConsensus<Adapter> {
         }
 
         Self {
-            inner:   Arc::new(overlord),
+            inner: Arc::new(overlord),
             handler: overlord_handler,
+            evidence,
         }
     }
 
@@ -16,11 +16,12 @@ use common_apm_derive::trace_span;
 use common_crypto::BlsPublicKey;
 use common_logger::{json, log};
 use common_merkle::Merkle;
+use core_executor::{effective_gas_price, is_call_system_script};
 use protocol::codec::ProtocolCodec;
 use protocol::traits::{ConsensusAdapter, Context, MessageTarget, NodeInfo};
 use protocol::types::{
-    Block, Bloom, BloomInput, Bytes, ExecResp, Hash, Hasher, Hex, Log, MerkleRoot, Metadata, Proof,
-    Proposal, Receipt, SignedTransaction, TransactionAction, ValidatorExtend, BASE_FEE_PER_GAS,
+    Block, Bloom, BloomInput, Bytes, ExecResp, Hash, Hasher, Header, Hex, Log, MerkleRoot,
+    Metadata, Proof, Proposal, Receipt, SignedTransaction, TransactionAction, ValidatorExtend,
     H160, MAX_BLOCK_GAS_LIMIT, U256,
 };
 use protocol::{
@@ -65,16 +66,51 @@ impl<Adapter: ConsensusAdapter + 'static> Engine<Proposal> for ConsensusEngine<A
         next_number: u64,
     ) -> Result<(Proposal, Bytes), Box<dyn Error + Send>> {
         let status = self.status.inner();
+        let gas_limit = self
+            .adapter
+            .get_metadata_unchecked(ctx.clone(), next_number)
+            .gas_limit
+            .min(MAX_BLOCK_GAS_LIMIT);
         let txs = self
             .adapter
             .get_txs_from_mempool(
                 ctx.clone(),
                 next_number,
-                MAX_BLOCK_GAS_LIMIT.into(),
+                gas_limit.into(),
                 status.tx_num_limit,
             )
             .await?;
         let signed_txs = self.adapter.get_full_txs(ctx.clone(), &txs).await?;
+        let parent = self
+            .adapter
+            .get_block_header_by_number(ctx.clone(), next_number - 1)
+            .await?;
+        let base_fee_per_gas = self.next_base_fee_per_gas(ctx.clone(), &parent);
+
+        let unpayable = self
+            .unpayable_txs(
+                ctx.clone(),
+                parent.state_root,
+                base_fee_per_gas,
+                &signed_txs,
+            )?
+            .into_iter()
+            .collect::<HashSet<_>>();
+        if !unpayable.is_empty() {
+            log::warn!(
+                "[consensus] get_block, skip {} unpayable txs",
+                unpayable.len()
+            );
+        }
+        let signed_txs = signed_txs
+            .into_iter()
+            .filter(|tx| !unpayable.contains(&tx.transaction.hash))
+            .collect::<Vec<_>>();
+        let txs = signed_txs
+            .iter()
+            .map(|tx| tx.transaction.hash)
+            .collect::<Vec<_>>();
+
         let order_root = Merkle::from_hashes(txs.clone())
             .get_root_hash()
             .unwrap_or_default();
@@ -89,7 +125,7 @@ impl<Adapter: ConsensusAdapter + 'static> Engine<Proposal> for ConsensusEngine<A
             gas_limit:                  MAX_BLOCK_GAS_LIMIT.into(),
             extra_data:                 Default::default(),
             mixed_hash:                 None,
-            base_fee_per_gas:           BASE_FEE_PER_GAS.into(),
+            base_fee_per_gas,
             proof:                      status.proof,
             last_checkpoint_block_hash: status.last_checkpoint_block_hash,
             chain_id:                   self.node_info.chain_id,
@@ -404,7 +440,15 @@ impl<Adapter: ConsensusAdapter + 'static> Engine<Proposal> for ConsensusEngine<A
         Ok(old_validators)
     }
 
-    fn report_error(&self, _ctx: Context, _err: OverlordError) {}
+    fn report_error(&self, ctx: Context, err: OverlordError) {
+        log(
+            log::Level::Warn,
+            "consensus",
+            "cons001",
+            &ctx,
+            json!({"error", err.to_string()}),
+        );
+    }
 
     fn report_view_change(&self, cx: Context, number: u64, round: u64, reason: ViewChangeReason) {
         let view_change_reason = match reason {
@@ -472,6 +516,72 @@ impl<Adapter: ConsensusAdapter + 'static> ConsensusEngine<Adapter> {
         self.status.inner()
     }
 
+    /// The gas target of the next block is half of the gas limit in its
+    /// metadata.
+    fn next_base_fee_per_gas(&self, ctx: Context, parent: &Header) -> U256 {
+        let metadata = self.adapter.get_metadata_unchecked(ctx, parent.number + 1);
+        parent.next_base_fee_per_gas(metadata.gas_limit)
+    }
+
+    /// Find the transactions that can not be executed in a block of the given
+    /// base fee on the state of its parent. A transaction is unpayable if its
+    /// gas price is less than the base fee, or its sender can not prepay its
+    /// fee and value along with its previous transactions in the block. A
+    /// sender only spends its balance by its own transactions, so a payable
+    /// transaction never fails to prepay in the execution. The following
+    /// transactions of the sender of an unpayable one are unpayable too, which
+    /// keeps the nonces continuous.
+    fn unpayable_txs(
+        &self,
+        ctx: Context,
+        state_root: MerkleRoot,
+        base_fee: U256,
+        txs: &[SignedTransaction],
+    ) -> ProtocolResult<Vec<Hash>> {
+        let senders = txs
+            .iter()
+            .filter(|tx| !is_call_system_script(&tx.transaction.unsigned.action))
+            .map(|tx| tx.sender)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        if senders.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let balances = self.adapter.get_balances(ctx, state_root, &senders)?;
+        let mut balances = senders.into_iter().zip(balances).collect::<HashMap<_, _>>();
+        let mut blocked = HashSet::new();
+        let mut unpayable = Vec::new();
+
+        for tx in txs.iter() {
+            let unsigned = &tx.transaction.unsigned;
+            if is_call_system_script(&unsigned.action) {
+                continue;
+            }
+
+            let balance = balances.entry(tx.sender).or_default();
+            let cost = effective_gas_price(unsigned, base_fee)
+                .checked_mul(unsigned.gas_limit)
+                .and_then(|fee| fee.checked_add(unsigned.value));
+            match cost {
+                Some(cost)
+                    if unsigned.gas_price >= base_fee
+                        && cost <= *balance
+                        && !blocked.contains(&tx.sender) =>
+                {
+                    *balance -= cost;
+                }
+                _ => {
+                    blocked.insert(tx.sender);
+                    unpayable.push(tx.transaction.hash);
+                }
+            }
+        }
+
+        Ok(unpayable)
+    }
+
     fn contains_change_metadata(&self, txs: &[SignedTransaction]) -> bool {
         let action = TransactionAction::Call(self.metadata_address);
         txs.iter()
@@ -500,6 +610,15 @@ impl<Adapter: ConsensusAdapter + 'static> ConsensusEngine<Adapter> {
             .get_block_by_number(ctx.clone(), proposal.number - 1)
             .await?;
 
+        let base_fee_per_gas = self.next_base_fee_per_gas(ctx.clone(), &previous_block.header);
+        if proposal.base_fee_per_gas != base_fee_per_gas {
+            return Err(ConsensusError::InvalidBaseFee {
+                expect: base_fee_per_gas,
+                actual: proposal.base_fee_per_gas,
+            }
+            .into());
+        }
+
         // verify block timestamp.
         if !validate_timestamp(
             current_timestamp,
@@ -537,7 +656,19 @@ impl<Adapter: ConsensusAdapter + 'static> ConsensusEngine<Adapter> {
             .adapter
             .get_full_txs(ctx.clone(), &proposal.tx_hashes)
             .await?;
-        self.check_order_transactions(ctx.clone(), proposal, &signed_txs)
+        self.check_order_transactions(ctx.clone(), proposal, &signed_txs)?;
+
+        let unpayable = self.unpayable_txs(
+            ctx,
+            previous_block.header.state_root,
+            proposal.base_fee_per_gas,
+            &signed_txs,
+        )?;
+        if !unpayable.is_empty() {
+            return Err(ConsensusError::UnpayableTransactions(unpayable).into());
+        }
+
+        Ok(())
     }
 
     #[trace_span(kind = "consensus.engine", logs = "{txs_len: signed_txs.len()}")]
@@ -0,0 +1,422 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+
+use overlord::types::{AggregatedVote, Node, SignedVote, Vote, VoteType};
+use overlord::{extract_voters, Crypto};
+use parking_lot::Mutex;
+
+use protocol::traits::{ConsensusAdapter, Context};
+use protocol::types::{Bytes, Evidence, EvidenceVote, Hash, Hex};
+use protocol::ProtocolResult;
+
+use crate::status::StatusAgent;
+use crate::util::OverlordCrypto;
+use crate::ConsensusError;
+
+/// The votes are kept for the heights in `[last_number - EVIDENCE_WINDOW,
+/// last_number + 1]`, the older ones can not be verified once the validators
+/// change.
+const EVIDENCE_WINDOW: u64 = 8;
+
+/// The votes kept for a height are capped, so that the voters can not grow the
+/// pool by voting in endless rounds.
+const MAX_VOTES_PER_HEIGHT: usize = 1024;
+
+/// The height, the round and the vote type of a vote.
+type VoteKey = (u64, u64, u8);
+
+/// Collect the votes and the quorum certificates passing through consensus and
+/// find the validators that signed two of them for different blocks with the
+/// same height, round and vote type. The evidence is verified, saved and
+/// gossiped to the other nodes.
+pub struct EvidencePool<Adapter> {
+    adapter: Arc<Adapter>,
+    crypto:  Arc<OverlordCrypto>,
+    status:  StatusAgent,
+
+    votes: Mutex<BTreeMap<VoteKey, Vec<EvidenceVote>>>,
+    found: Mutex<BTreeSet<(u64, Hash)>>,
+}
+
+impl<Adapter: ConsensusAdapter + 'static> EvidencePool<Adapter> {
+    pub fn new(adapter: Arc<Adapter>, crypto: Arc<OverlordCrypto>, status: StatusAgent) -> Self {
+        EvidencePool {
+            adapter,
+            crypto,
+            status,
+            votes: Mutex::new(BTreeMap::new()),
+            found: Mutex::new(BTreeSet::new()),
+        }
+    }
+
+    pub async fn observe_vote(&self, ctx: Context, signed_vote: &SignedVote) {
+        let vote = &signed_vote.vote;
+        let key = (vote.height, vote.round, vote_type_to_u8(&vote.vote_type));
+
+        self.observe(ctx, key, EvidenceVote {
+            block_hash: vote.block_hash.clone(),
+            signature:  signed_vote.signature.clone(),
+            voters:     vec![signed_vote.voter.clone()],
+        })
+        .await
+    }
+
+    pub async fn observe_qc(&self, ctx: Context, qc: &AggregatedVote) {
+        let key = (qc.height, qc.round, vote_type_to_u8(&qc.vote_type));
+        let voters = match self.qc_voters(ctx.clone(), qc) {
+            Some(voters) => voters,
+            None => return,
+        };
+
+        self.observe(ctx, key, EvidenceVote {
+            block_hash: qc.block_hash.clone(),
+            signature: qc.signature.signature.clone(),
+            voters,
+        })
+        .await
+    }
+
+    /// Verify and save the evidence gossiped by another node. The evidence out
+    /// of the window or the current epoch is ignored since the voters may be
+    /// unknown.
+    pub async fn receive(&self, ctx: Context, evidence: Evidence) -> ProtocolResult<()> {
+        if !self.in_window(evidence.height) || self.is_found(&evidence) {
+            return Ok(());
+        }
+
+        let verifiers = match self.verifiers(ctx.clone(), evidence.height) {
+            Some(verifiers) => verifiers,
+            None => return Ok(()),
+        };
+
+        verify_evidence(&self.crypto, &evidence, &verifiers)?;
+        self.save(ctx, evidence, false).await;
+        Ok(())
+    }
+
+    async fn observe(&self, ctx: Context, key: VoteKey, vote: EvidenceVote) {
+        if !self.in_window(key.0) {
+            return;
+        }
+
+        let verifiers = match self.verifiers(ctx.clone(), key.0) {
+            Some(verifiers) => verifiers,
+            None => return,
+        };
+
+        let known = {
+            let votes = self.votes.lock();
+            votes.get(&key).cloned().unwrap_or_default()
+        };
+        if known
+            .iter()
+            .any(|k| k.block_hash == vote.block_hash && k.voters == vote.voters)
+        {
+            return;
+        }
+
+        if let Err(e) = verify_vote(&self.crypto, key, &vote, &verifiers) {
+            log::debug!("[evidence] drop invalid vote at {:?}: {}", key, e);
+            return;
+        }
+
+        for evidence in find_evidences(key, &known, &vote) {
+            self.save(ctx.clone(), evidence, true).await;
+        }
+
+        let mut votes = self.votes.lock();
+        let height_votes = votes
+            .range((key.0, 0, 0)..=(key.0, u64::MAX, u8::MAX))
+            .map(|(_, v)| v.len())
+            .sum::<usize>();
+        if height_votes >= MAX_VOTES_PER_HEIGHT {
+            log::debug!("[evidence] drop vote at {:?}, too many votes", key);
+            return;
+        }
+
+        votes.entry(key).or_insert_with(Vec::new).push(vote);
+        self.prune(&mut votes);
+    }
+
+    /// Save the evidence unless it is found before. The evidence is marked as
+    /// found under the same lock as the check, so that the concurrent
+    /// deliveries of it are only recorded once.
+    async fn save(&self, ctx: Context, evidence: Evidence, broadcast: bool) {
+        if !self.found.lock().insert((evidence.height, evidence.hash())) {
+            return;
+        }
+
+        log::warn!(
+            "[evidence] validator {} double signed at height {} round {} vote type {}",
+            Hex::encode(&evidence.voter).as_string(),
+            evidence.height,
+            evidence.round,
+            evidence.vote_type
+        );
+
+        if let Err(e) = self
+            .adapter
+            .save_evidence(ctx.clone(), evidence.clone())
+            .await
+        {
+            log::error!("[evidence] save evidence error {:?}", e);
+        }
+
+        if broadcast {
+            if let Err(e) = self.adapter.broadcast_evidence(ctx, evidence).await {
+                log::warn!("[evidence] broadcast evidence error {:?}", e);
+            }
+        }
+    }
+
+    /// The verifiers of the height if it is in the current epoch.
+    fn verifiers(&self, ctx: Context, height: u64) -> Option<Vec<Bytes>> {
+        let next_number = self.status.inner().last_number + 1;
+        let metadata = self.adapter.get_metadata_unchecked(ctx, next_number);
+        if !metadata.version.contains(height) {
+            return None;
+        }
+
+        Some(
+            metadata
+                .verifier_list
+                .iter()
+                .map(|v| v.pub_key.as_bytes())
+                .collect(),
+        )
+    }
+
+    /// The voters of a quorum certificate in the current epoch. The bitmap is
+    /// ordered by the sorted verifier list.
+    fn qc_voters(&self, ctx: Context, qc: &AggregatedVote) -> Option<Vec<Bytes>> {
+        let next_number = self.status.inner().last_number + 1;
+        let metadata = self.adapter.get_metadata_unchecked(ctx, next_number);
+        if !metadata.version.contains(qc.height) {
+            return None;
+        }
+
+        let mut authority_list = metadata
+            .verifier_list
+            .iter()
+            .map(|v| Node {
+                address:        v.pub_key.as_bytes(),
+                propose_weight: v.propose_weight,
+                vote_weight:    v.vote_weight,
+            })
+            .collect::<Vec<_>>();
+
+        extract_voters(&mut authority_list, &qc.signature.address_bitmap).ok()
+    }
+
+    fn in_window(&self, height: u64) -> bool {
+        let last_number = self.status.inner().last_number;
+        height + EVIDENCE_WINDOW >= last_number && height <= last_number + 1
+    }
+
+    fn is_found(&self, evidence: &Evidence) -> bool {
+        self.found
+            .lock()
+            .contains(&(evidence.height, evidence.hash()))
+    }
+
+    fn prune(&self, votes: &mut BTreeMap<VoteKey, Vec<EvidenceVote>>) {
+        let boundary = self
+            .status
+            .inner()
+            .last_number
+            .saturating_sub(EVIDENCE_WINDOW);
+
+        *votes = votes.split_off(&(boundary, 0, 0));
+        let mut found = self.found.lock();
+        *found = found.split_off(&(boundary, Hash::default()));
+    }
+}
+
+/// The evidences of the voters that signed both the vote and a known one for
+/// another block.
+fn find_evidences(key: VoteKey, known: &[EvidenceVote], vote: &EvidenceVote) -> Vec<Evidence> {
+    let mut evidences = Vec::new();
+
+    for other in known.iter().filter(|k| k.block_hash != vote.block_hash) {
+        for voter in vote.voters.iter().filter(|v| other.voters.contains(v)) {
+            evidences.push(Evidence::new(
+                key.0,
+                key.1,
+                key.2,
+                voter.clone(),
+                vote.clone(),
+                other.clone(),
+            ));
+        }
+    }
+
+    evidences
+}
+
+fn verify_evidence(
+    crypto: &OverlordCrypto,
+    evidence: &Evidence,
+    verifiers: &[Bytes],
+) -> ProtocolResult<()> {
+    let key = (evidence.height, evidence.round, evidence.vote_type);
+
+    if evidence.first.block_hash == evidence.second.block_hash
+        || !evidence.first.voters.contains(&evidence.voter)
+        || !evidence.second.voters.contains(&evidence.voter)
+    {
+        return Err(ConsensusError::InvalidEvidence("votes not conflict".to_string()).into());
+    }
+
+    verify_vote(crypto, key, &evidence.first, verifiers)?;
+    verify_vote(crypto, key, &evidence.second, verifiers)
+}
+
+/// The voters must be the verifiers of the height. A vote signed by a single
+/// voter is checked as a plain signature and the others as an aggregated
+/// signature.
+fn verify_vote(
+    crypto: &OverlordCrypto,
+    key: VoteKey,
+    vote: &EvidenceVote,
+    verifiers: &[Bytes],
+) -> ProtocolResult<()> {
+    if let Some(voter) = vote.voters.iter().find(|v| !verifiers.contains(v)) {
+        return Err(ConsensusError::InvalidEvidence(format!(
+            "voter {} is not a verifier",
+            Hex::encode(voter).as_string()
+        ))
+        .into());
+    }
+
+    let vote_type = vote_type_from_u8(key.2)
+        .ok_or_else(|| ConsensusError::InvalidEvidence(format!("vote type {}", key.2)))?;
+    let hash = crypto.hash(Bytes::from(rlp::encode(&Vote {
+        height: key.0,
+        round: key.1,
+        vote_type,
+        block_hash: vote.block_hash.clone(),
+    })));
+
+    let res = match vote.voters.as_slice() {
+        [] => return Err(ConsensusError::InvalidEvidence("no voter".to_string()).into()),
+        [voter] => crypto.verify_signature(vote.signature.clone(), hash, voter.clone()),
+        voters => crypto.verify_aggregated_signature(vote.signature.clone(), hash, voters.to_vec()),
+    };
+
+    res.map_err(|e| ConsensusError::InvalidEvidence(e.to_string()).into())
+}
+
+fn vote_type_to_u8(vote_type: &VoteType) -> u8 {
+    match vote_type {
+        VoteType::Prevote => 1,
+        VoteType::Precommit => 2,
+    }
+}
+
+fn vote_type_from_u8(vote_type: u8) -> Option<VoteType> {
+    match vote_type {
+        1 => Some(VoteType::Prevote),
+        2 => Some(VoteType::Precommit),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use common_crypto::{BlsPrivateKey, PrivateKey, ToBlsPublicKey};
+
+    use super::*;
+
+    const KEY: VoteKey = (10, 0, 1);
+
+    fn mock_signers(n: u8) -> (Vec<Bytes>, Vec<OverlordCrypto>) {
+        let private_keys = (0..n)
+            .map(|_| BlsPrivateKey::generate(&mut rand::rngs::OsRng))
+            .collect::<Vec<_>>();
+        let voters = (0..n).map(|i| Bytes::from(vec![i])).collect::<Vec<_>>();
+        let pub_keys = voters
+            .iter()
+            .cloned()
+            .zip(private_keys.iter().map(|k| k.pub_key(&String::new())))
+            .collect::<HashMap<_, _>>();
+
+        let cryptos = private_keys
+            .into_iter()
+            .map(|k| OverlordCrypto::new(k, pub_keys.clone(), String::new()))
+            .collect();
+        (voters, cryptos)
+    }
+
+    fn mock_vote(
+        crypto: &OverlordCrypto,
+        voter: &Bytes,
+        block_hash: &'static [u8],
+    ) -> EvidenceVote {
+        let block_hash = Bytes::from_static(block_hash);
+        let hash = crypto.hash(Bytes::from(rlp::encode(&Vote {
+            height:     KEY.0,
+            round:      KEY.1,
+            vote_type:  VoteType::Prevote,
+            block_hash: block_hash.clone(),
+        })));
+
+        EvidenceVote {
+            block_hash,
+            signature: crypto.sign(hash).unwrap(),
+            voters: vec![voter.clone()],
+        }
+    }
+
+    #[test]
+    fn test_find_evidences() {
+        let (voters, cryptos) = mock_signers(2);
+        let a = mock_vote(&cryptos[0], &voters[0], b"a");
+        let b = mock_vote(&cryptos[0], &voters[0], b"b");
+        let other = mock_vote(&cryptos[1], &voters[1], b"b");
+
+        assert!(find_evidences(KEY, &[a.clone()], &a).is_empty());
+        assert!(find_evidences(KEY, &[a.clone()], &other).is_empty());
+
+        let evidences = find_evidences(KEY, &[a.clone(), other], &b);
+        assert_eq!(evidences, vec![Evidence::new(
+            KEY.0,
+            KEY.1,
+            KEY.2,
+            voters[0].clone(),
+            a,
+            b
+        )]);
+    }
+
+    #[test]
+    fn test_verify_vote() {
+        let (voters, cryptos) = mock_signers(2);
+        let vote = mock_vote(&cryptos[0], &voters[0], b"a");
+        assert!(verify_vote(&cryptos[1], KEY, &vote, &voters).is_ok());
+
+        // The voter is not a verifier of the height.
+        assert!(verify_vote(&cryptos[1], KEY, &vote, &voters[1..]).is_err());
+
+        // The signature is for another vote or another voter.
+        assert!(verify_vote(&cryptos[1], (KEY.0, KEY.1 + 1, KEY.2), &vote, &voters).is_err());
+        let mut forged = vote;
+        forged.voters = vec![voters[1].clone()];
+        assert!(verify_vote(&cryptos[1], KEY, &forged, &voters).is_err());
+    }
+
+    #[test]
+    fn test_verify_evidence() {
+        let (voters, cryptos) = mock_signers(2);
+        let a = mock_vote(&cryptos[0], &voters[0], b"a");
+        let b = mock_vote(&cryptos[0], &voters[0], b"b");
+
+        let evidence = Evidence::new(KEY.0, KEY.1, KEY.2, voters[0].clone(), a.clone(), b);
+        assert!(verify_evidence(&cryptos[1], &evidence, &voters).is_ok());
+        assert!(verify_evidence(&cryptos[1], &evidence, &voters[1..]).is_err());
+
+        let evidence = Evidence::new(KEY.0, KEY.1, KEY.2, voters[0].clone(), a.clone(), a);
+        assert!(verify_evidence(&cryptos[1], &evidence, &voters).is_err());
+    }
+}
@@ -8,8 +8,11 @@
 pub mod adapter;
 pub mod consensus;
 pub mod engine;
+pub mod evidence;
 pub mod message;
+pub mod snap_sync;
 pub mod status;
+pub mod sync_pipeline;
 pub mod synchronization;
 pub mod types;
 pub mod util;
@@ -19,7 +22,7 @@ use std::error::Error;
 
 use common_crypto::Error as CryptoError;
 
-use protocol::types::{ExitReason, Hash, MerkleRoot};
+use protocol::types::{ExitReason, Hash, MerkleRoot, U256};
 use protocol::{Display, ProtocolError, ProtocolErrorKind};
 
 pub use crate::adapter::OverlordConsensusAdapter;
@@ -122,6 +125,16 @@ pub enum ConsensusError {
     #[display(fmt = "Consensus invalid timestamp")]
     InvalidTimestamp,
 
+    #[display(
+        fmt = "Check invalid base fee per gas, expect {:?} get {:?}",
+        expect,
+        actual
+    )]
+    InvalidBaseFee { expect: U256, actual: U256 },
+
+    #[display(fmt = "Consensus unpayable transactions {:?}", _0)]
+    UnpayableTransactions(Vec<Hash>),
+
     /// Consensus missed the block header.
     #[display(fmt = "Consensus missed block header of {} block", _0)]
     MissingBlockHeader(u64),
@@ -133,6 +146,9 @@ pub enum ConsensusError {
     #[display(fmt = "Synchronization {} block error", _0)]
     VerifyTransaction(u64),
 
+    #[display(fmt = "Synchronization requests block {} but gets {}", expect, actual)]
+    MismatchedSyncBlock { expect: u64, actual: u64 },
+
     #[display(fmt = "Synchronization/Consensus {} block error : {}", _0, _1)]
     VerifyBlockHeader(u64, BlockHeaderField),
 
@@ -183,6 +199,15 @@ pub enum ConsensusError {
 
     #[display(fmt = "Confused metadata range [{}, {})!", _0, _1)]
     ConfusedMetadata(u64, u64),
+
+    #[display(fmt = "Invalid state range of trie {:?}", _0)]
+    InvalidStateRange(MerkleRoot),
+
+    #[display(fmt = "Invalid code of hash {:?}", _0)]
+    InvalidCode(Hash),
+
+    #[display(fmt = "Invalid evidence {}", _0)]
+    InvalidEvidence(String),
 }
 
 #[derive(Debug, Display)]
@@ -10,17 +10,24 @@ use common_apm_derive::trace_span;
 use protocol::traits::{
     Consensus, Context, MessageHandler, Priority, Rpc, Storage, Synchronization, TrustFeedback,
 };
-use protocol::types::BatchSignedTxs;
-use protocol::{async_trait, types::BlockNumber, ProtocolError};
+use protocol::types::{
+    BatchCodes, BatchSignedTxs, CodeRequest, Evidence, StateRangeRequest, MAX_CODE_REQUEST_HASHES,
+    MAX_STATE_RANGE_ENTRIES,
+};
+use protocol::{async_trait, tokio::task, types::BlockNumber, ProtocolError};
 
+use core_executor::state_range;
 use core_storage::StorageError;
 
+use crate::ConsensusError;
+
 pub use crate::types::PullTxsRequest;
 
 pub const END_GOSSIP_SIGNED_PROPOSAL: &str = "/gossip/consensus/signed_proposal";
 pub const END_GOSSIP_SIGNED_VOTE: &str = "/gossip/consensus/signed_vote";
 pub const END_GOSSIP_AGGREGATED_VOTE: &str = "/gossip/consensus/qc";
 pub const END_GOSSIP_SIGNED_CHOKE: &str = "/gossip/consensus/signed_choke";
+pub const END_GOSSIP_EVIDENCE: &str = "/gossip/consensus/evidence";
 pub const RPC_SYNC_PULL_BLOCK: &str = "/rpc_call/consensus/sync_pull_block";
 pub const RPC_RESP_SYNC_PULL_BLOCK: &str = "/rpc_resp/consensus/sync_pull_block";
 pub const RPC_SYNC_PULL_TXS: &str = "/rpc_call/consensus/sync_pull_txs";
@@ -28,6 +35,10 @@ pub const RPC_RESP_SYNC_PULL_TXS: &str = "/rpc_resp/consensus/sync_pull_txs";
 pub const BROADCAST_HEIGHT: &str = "/gossip/consensus/broadcast_height";
 pub const RPC_SYNC_PULL_PROOF: &str = "/rpc_call/consensus/sync_pull_proof";
 pub const RPC_RESP_SYNC_PULL_PROOF: &str = "/rpc_resp/consensus/sync_pull_proof";
+pub const RPC_SYNC_PULL_STATE: &str = "/rpc_call/consensus/sync_pull_state";
+pub const RPC_RESP_SYNC_PULL_STATE: &str = "/rpc_resp/consensus/sync_pull_state";
+pub const RPC_SYNC_PULL_CODE: &str = "/rpc_call/consensus/sync_pull_code";
+pub const RPC_RESP_SYNC_PULL_CODE: &str = "/rpc_resp/consensus/sync_pull_code";
 
 macro_rules! overlord_message {
     ($msg_name: ident, $overlord_type_name: ident) => {
@@ -194,6 +205,31 @@ impl<C: Consensus + 'static> MessageHandler for ChokeMessageHandler<C> {
     }
 }
 
+pub struct EvidenceMessageHandler<C> {
+    consensus: Arc<C>,
+}
+
+impl<C: Consensus + 'static> EvidenceMessageHandler<C> {
+    pub fn new(consensus: Arc<C>) -> Self {
+        Self { consensus }
+    }
+}
+
+#[async_trait]
+impl<C: Consensus + 'static> MessageHandler for EvidenceMessageHandler<C> {
+    type Message = Evidence;
+
+    #[trace_span(name = "handle_evidence", kind = "consensus.message")]
+    async fn process(&self, ctx: Context, msg: Self::Message) -> TrustFeedback {
+        if let Err(e) = self.consensus.set_evidence(ctx, msg).await {
+            warn!("set evidence {:?}", e);
+            return TrustFeedback::Worse(e.to_string());
+        }
+
+        TrustFeedback::Good
+    }
+}
+
 pub struct RemoteHeightMessageHandler<Sy> {
     synchronization: Arc<Sy>,
 }
@@ -350,3 +386,94 @@ impl<R: Rpc + 'static, S: Storage + 'static> MessageHandler for PullTxsRpcHandle
         TrustFeedback::Neutral
     }
 }
+
+#[derive(Debug)]
+pub struct PullStateRpcHandler<R, DB> {
+    rpc:     Arc<R>,
+    trie_db: Arc<DB>,
+}
+
+impl<R, DB> PullStateRpcHandler<R, DB>
+where
+    R: Rpc + 'static,
+    DB: cita_trie::DB + 'static,
+{
+    pub fn new(rpc: Arc<R>, trie_db: Arc<DB>) -> Self {
+        PullStateRpcHandler { rpc, trie_db }
+    }
+}
+
+#[async_trait]
+impl<R: Rpc + 'static, DB: cita_trie::DB + 'static> MessageHandler for PullStateRpcHandler<R, DB> {
+    type Message = StateRangeRequest;
+
+    #[trace_span(name = "pull_state_rpc", kind = "consensus.message")]
+    async fn process(&self, ctx: Context, msg: StateRangeRequest) -> TrustFeedback {
+        let trie_db = Arc::clone(&self.trie_db);
+        let limit = msg.limit.min(MAX_STATE_RANGE_ENTRIES) as usize;
+
+        let ret = task::spawn_blocking(move || state_range(trie_db, msg.root, &msg.start, limit))
+            .await
+            .unwrap_or_else(|e| Err(ConsensusError::Other(e.to_string()).into()));
+
+        self.rpc
+            .response(ctx, RPC_RESP_SYNC_PULL_STATE, ret, Priority::High)
+            .unwrap_or_else(move |e: ProtocolError| warn!("[core_consensus] push state {:?}", e))
+            .await;
+
+        TrustFeedback::Neutral
+    }
+}
+
+#[derive(Debug)]
+pub struct PullCodeRpcHandler<R, S> {
+    rpc:     Arc<R>,
+    storage: Arc<S>,
+}
+
+impl<R, S> PullCodeRpcHandler<R, S>
+where
+    R: Rpc + 'static,
+    S: Storage + 'static,
+{
+    pub fn new(rpc: Arc<R>, storage: Arc<S>) -> Self {
+        PullCodeRpcHandler { rpc, storage }
+    }
+}
+
+#[async_trait]
+impl<R: Rpc + 'static, S: Storage + 'static> MessageHandler for PullCodeRpcHandler<R, S> {
+    type Message = CodeRequest;
+
+    #[trace_span(name = "pull_code_rpc", kind = "consensus.message")]
+    async fn process(&self, ctx: Context, msg: CodeRequest) -> TrustFeedback {
+        let mut codes = Vec::with_capacity(msg.hashes.len());
+        let mut ret = Ok(());
+
+        for hash in msg.hashes.iter().take(MAX_CODE_REQUEST_HASHES) {
+            match self.storage.get_code_by_hash(ctx.clone(), hash).await {
+                Ok(Some(code)) => codes.push(code),
+                Ok(None) => {
+                    ret = Err(StorageError::GetNone.into());
+                    break;
+                }
+                Err(e) => {
+                    ret = Err(e);
+                    break;
+                }
+            }
+        }
+
+        self.rpc
+            .response(
+                ctx,
+                RPC_RESP_SYNC_PULL_CODE,
+                ret.map(|_| BatchCodes(codes)),
+                Priority::High,
+            )
+            .unwrap_or_else(move |e: ProtocolError| warn!("[core_consensus] push code {:?}", e))
+            .await;
+
+        TrustFeedback::Neutral
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+
+use common_apm_derive::trace_span;
+use core_executor::MPTTrie;
+use protocol::codec::ProtocolCodec;
+use protocol::traits::{Context, SynchronizationAdapter};
+use protocol::types::{
+    Account, Bytes, Hash, Hasher, MerkleRoot, StateEntry, StateRange, StateRangeRequest, H160,
+    H256, MAX_CODE_REQUEST_HASHES, MAX_STATE_RANGE_ENTRIES, NIL_DATA, RLP_NULL,
+};
+use protocol::ProtocolResult;
+
+use crate::ConsensusError;
+
+/// Download the whole state committed by a trusted state root from other
+/// nodes. Each range is checked by the proofs of its boundary entries as soon
+/// as it arrives, and each rebuilt trie must match its expected root, so a
+/// faulty peer can not make the node accept any other state.
+pub struct SnapSync<Adapter> {
+    adapter: Arc<Adapter>,
+}
+
+impl<Adapter: SynchronizationAdapter> SnapSync<Adapter> {
+    pub fn new(adapter: Arc<Adapter>) -> Self {
+        SnapSync { adapter }
+    }
+
+    #[trace_span(kind = "consensus.snap_sync")]
+    pub async fn sync_state(&self, ctx: Context, state_root: MerkleRoot) -> ProtocolResult<()> {
+        let mut accounts = 0usize;
+        let mut storage_roots = BTreeSet::new();
+        let mut codes: BTreeMap<Hash, Vec<H256>> = BTreeMap::new();
+
+        // The collected roots and hashes are only used after the account trie
+        // is proved to match the state root.
+        self.sync_trie(ctx.clone(), state_root, |entry| {
+            if entry.key.len() != H160::len_bytes() {
+                return Err(ConsensusError::InvalidStateRange(state_root).into());
+            }
+
+            let account = <Account as ProtocolCodec>::decode(&entry.value)?;
+            if account.storage_root != RLP_NULL {
+                storage_roots.insert(account.storage_root);
+            }
+            if account.code_hash != NIL_DATA {
+                codes
+                    .entry(account.code_hash)
+                    .or_default()
+                    .push(H160::from_slice(&entry.key).into());
+            }
+
+            accounts += 1;
+            Ok(())
+        })
+        .await?;
+
+        log::info!(
+            "[snap_sync]: synced {} accounts, {} storage tries and {} codes to go",
+            accounts,
+            storage_roots.len(),
+            codes.len()
+        );
+
+        for root in storage_roots.into_iter() {
+            self.sync_trie(ctx.clone(), root, |_| Ok(())).await?;
+        }
+
+        self.sync_codes(ctx, codes).await
+    }
+
+    async fn sync_trie<F>(&self, ctx: Context, root: MerkleRoot, mut visit: F) -> ProtocolResult<()>
+    where
+        F: FnMut(&StateEntry) -> ProtocolResult<()> + Send,
+    {
+        let mut local_root = RLP_NULL;
+        let mut start = Bytes::new();
+
+        loop {
+            let range = self
+                .adapter
+                .get_state_range_from_remote(ctx.clone(), StateRangeRequest {
+                    root,
+                    start: start.clone(),
+                    limit: MAX_STATE_RANGE_ENTRIES,
+                })
+                .await?;
+            verify_range(root, &start, &range)?;
+
+            for entry in range.entries.iter() {
+                visit(entry)?;
+            }
+            local_root = self
+                .adapter
+                .save_state_range(ctx.clone(), local_root, &range.entries)?;
+
+            match range.entries.last() {
+                Some(last) if range.more => start = next_key(&last.key),
+                _ => break,
+            }
+        }
+
+        if local_root != root {
+            return Err(ConsensusError::InvalidStateRoot {
+                expect: root,
+                actual: local_root,
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+
+    async fn sync_codes(
+        &self,
+        ctx: Context,
+        codes: BTreeMap<Hash, Vec<H256>>,
+    ) -> ProtocolResult<()> {
+        let hashes = codes.keys().copied().collect::<Vec<_>>();
+
+        for chunk in hashes.chunks(MAX_CODE_REQUEST_HASHES) {
+            let remote_codes = self
+                .adapter
+                .get_codes_from_remote(ctx.clone(), chunk)
+                .await?;
+            if remote_codes.len() != chunk.len() {
+                return Err(ConsensusError::Other(format!(
+                    "expect {} codes, get {}",
+                    chunk.len(),
+                    remote_codes.len()
+                ))
+                .into());
+            }
+
+            for (hash, code) in chunk.iter().zip(remote_codes.into_iter()) {
+                if Hasher::digest(&code) != *hash {
+                    return Err(ConsensusError::InvalidCode(*hash).into());
+                }
+
+                for address in codes[hash].iter() {
+                    self.adapter
+                        .save_code(ctx.clone(), *address, *hash, code.clone())
+                        .await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Check that the entries are sorted, start from the requested key and that the
+/// boundary entries are proved against the root. Entries missed inside the
+/// range are caught when the rebuilt trie root is compared.
+fn verify_range(root: MerkleRoot, start: &[u8], range: &StateRange) -> ProtocolResult<()> {
+    let (first, last) = match (range.entries.first(), range.entries.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ if range.more => return Err(ConsensusError::InvalidStateRange(root).into()),
+        _ => return Ok(()),
+    };
+
+    let sorted = range.entries.windows(2).all(|w| w[0].key < w[1].key);
+    if !sorted || first.key.as_ref() < start {
+        return Err(ConsensusError::InvalidStateRange(root).into());
+    }
+
+    for (entry, proof) in [(first, &range.first_proof), (last, &range.last_proof)] {
+        if MPTTrie::verify_proof(root, &entry.key, proof)? != Some(entry.value.clone()) {
+            return Err(ConsensusError::InvalidStateRange(root).into());
+        }
+    }
+
+    Ok(())
+}
+
+/// The smallest key greater than the given key.
+fn next_key(key: &[u8]) -> Bytes {
+    let mut next = key.to_vec();
+    next.push(0);
+    next.into()
+}
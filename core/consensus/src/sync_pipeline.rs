@@ -0,0 +1,238 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Arc;
+
+use futures::future::try_join_all;
+use futures::stream::{FuturesOrdered, FuturesUnordered, StreamExt};
+
+use protocol::tokio::{self, sync::mpsc::Sender, task::JoinHandle};
+use protocol::traits::{Context, SynchronizationAdapter, TrustFeedback};
+use protocol::types::{Block, BlockNumber, Proof};
+use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
+
+use crate::synchronization::RichBlock;
+use crate::util::digest_signed_transactions;
+use crate::{BlockHeaderField, ConsensusError};
+
+/// The number of blocks being downloaded, buffered or verified at once.
+pub const SYNC_WINDOW: u64 = 64;
+/// A block is requested from this many peers in turn before the sync fails.
+const MAX_DOWNLOAD_ATTEMPTS: u64 = 3;
+
+/// A block that follows the previous one handed over, with its proof.
+pub type VerifiedBlock = (RichBlock, Proof);
+
+/// Download the blocks of a range from several peers at once and hand them
+/// over in order once they are verified, so that the receiver only has to
+/// execute and commit them.
+///
+/// A downloaded block is checked against its transactions and its proof
+/// before it is buffered, and a peer failing to serve a valid block is
+/// replaced by the next one. The buffered blocks are then checked in order to
+/// follow their parents.
+pub struct SyncPipeline<Adapter> {
+    adapter:             Arc<Adapter>,
+    peers:               Vec<Context>,
+    sync_txs_chunk_size: usize,
+}
+
+impl<Adapter: SynchronizationAdapter + 'static> SyncPipeline<Adapter> {
+    pub fn new(adapter: Arc<Adapter>, peers: Vec<Context>, sync_txs_chunk_size: usize) -> Self {
+        debug_assert!(!peers.is_empty());
+
+        SyncPipeline {
+            adapter,
+            peers,
+            sync_txs_chunk_size,
+        }
+    }
+
+    /// Run until the blocks after `parent` up to `to` are all handed over, or
+    /// the receiver is dropped. The blocks are verified with the metadata of
+    /// the epoch after `parent`, so `to` must not go beyond its end.
+    pub async fn run(
+        self,
+        parent: Block,
+        to: BlockNumber,
+        sender: Sender<VerifiedBlock>,
+    ) -> ProtocolResult<()> {
+        let from = parent.header.number + 1;
+        let mut parent = parent;
+        let (mut next_download, mut next_verify, mut next_send) = (from, from, from);
+        let mut buffer = BTreeMap::new();
+        let mut downloads = FuturesUnordered::new();
+        let mut verifications = FuturesOrdered::new();
+
+        while next_send <= to {
+            while next_download <= to && next_download < next_send + SYNC_WINDOW {
+                downloads.push(self.download(next_download, 0));
+                next_download += 1;
+            }
+
+            tokio::select! {
+                Some((number, attempt, res)) = downloads.next() => match res {
+                    Ok(block) => {
+                        buffer.insert(number, block);
+                    }
+                    Err(e) if attempt + 1 < MAX_DOWNLOAD_ATTEMPTS => {
+                        log::warn!(
+                            "[synchronization]: download block {} error {:?}, retry with another peer",
+                            number,
+                            e
+                        );
+                        downloads.push(self.download(number, attempt + 1));
+                    }
+                    Err(e) => {
+                        log::error!("[synchronization]: download block {} error {:?}", number, e);
+                        return Err(e);
+                    }
+                },
+                Some(res) = verifications.next() => {
+                    let verified: VerifiedBlock = res?;
+                    if sender.send(verified).await.is_err() {
+                        return Ok(());
+                    }
+                    next_send += 1;
+                }
+                else => break,
+            }
+
+            while let Some((rich_block, proof)) = buffer.remove(&next_verify) {
+                let block_parent = std::mem::replace(&mut parent, rich_block.block.clone());
+                let adapter = Arc::clone(&self.adapter);
+                verifications.push_back(join(tokio::spawn(verify_parent(
+                    adapter,
+                    block_parent,
+                    rich_block,
+                    proof,
+                ))));
+                next_verify += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn download(
+        &self,
+        number: BlockNumber,
+        attempt: u64,
+    ) -> impl Future<Output = (BlockNumber, u64, ProtocolResult<VerifiedBlock>)> {
+        let peer = (number + attempt) % self.peers.len() as u64;
+        let handle = tokio::spawn(download_block(
+            Arc::clone(&self.adapter),
+            self.peers[peer as usize].clone(),
+            number,
+            self.sync_txs_chunk_size,
+        ));
+
+        async move { (number, attempt, join(handle).await) }
+    }
+}
+
+async fn join<T>(handle: JoinHandle<ProtocolResult<T>>) -> ProtocolResult<T> {
+    handle
+        .await
+        .map_err(|e| ConsensusError::Other(e.to_string()))?
+}
+
+/// Download a block with its transactions and its proof from the peer, and
+/// check that they match each other. The peer serving a mismatched block or
+/// an invalid proof is reported.
+async fn download_block<Adapter: SynchronizationAdapter>(
+    adapter: Arc<Adapter>,
+    ctx: Context,
+    number: BlockNumber,
+    sync_txs_chunk_size: usize,
+) -> ProtocolResult<VerifiedBlock> {
+    let block = adapter.get_block_from_remote(ctx.clone(), number).await?;
+    if block.header.number != number {
+        let e: ProtocolError = ConsensusError::MismatchedSyncBlock {
+            expect: number,
+            actual: block.header.number,
+        }
+        .into();
+        adapter.report_peer(ctx, TrustFeedback::Worse(e.to_string()));
+        return Err(e);
+    }
+
+    let txs = try_join_all(
+        block
+            .tx_hashes
+            .chunks(sync_txs_chunk_size)
+            .map(|hashes| adapter.get_txs_from_remote(ctx.clone(), number, hashes)),
+    )
+    .await?
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+
+    let signed_txs_hash = digest_signed_transactions(&txs);
+    if signed_txs_hash != block.header.signed_txs_hash {
+        let e: ProtocolError = ConsensusError::InvalidOrderSignedTransactionsHash {
+            expect: signed_txs_hash,
+            actual: block.header.signed_txs_hash,
+        }
+        .into();
+        adapter.report_peer(ctx, TrustFeedback::Worse(e.to_string()));
+        return Err(e);
+    }
+
+    let proof = adapter.get_proof_from_remote(ctx.clone(), number).await?;
+    if let Err(e) = adapter
+        .verify_proof(ctx.clone(), block.clone(), proof.clone())
+        .await
+    {
+        // The verifiers are read from the local metadata and storage, whose
+        // errors are not the fault of the peer.
+        if matches!(e.kind(), ProtocolErrorKind::Consensus) {
+            adapter.report_peer(ctx, TrustFeedback::Worse(e.to_string()));
+        } else {
+            log::warn!(
+                "[synchronization]: verify the proof of block {} error {:?}",
+                number,
+                e
+            );
+        }
+        return Err(e);
+    }
+
+    Ok((RichBlock { block, txs }, proof))
+}
+
+/// Check that the block follows its parent, which is committed by the proof
+/// carried in the block.
+async fn verify_parent<Adapter: SynchronizationAdapter>(
+    adapter: Arc<Adapter>,
+    parent: Block,
+    rich_block: RichBlock,
+    proof: Proof,
+) -> ProtocolResult<VerifiedBlock> {
+    let header = &rich_block.block.header;
+    if header.prev_hash != parent.header_hash() {
+        log::error!(
+            "[synchronization]: block {} does not follow its parent {:?}",
+            header.number,
+            parent.header
+        );
+        return Err(ConsensusError::VerifyBlockHeader(
+            header.number,
+            BlockHeaderField::PreviousBlockHash,
+        )
+        .into());
+    }
+
+    adapter
+        .verify_proof(Context::new(), parent, header.proof.clone())
+        .await
+        .map_err(|e| {
+            log::error!(
+                "[synchronization]: verify_proof error, parent of block {}, proof: {:?}",
+                header.number,
+                header.proof
+            );
+            e
+        })?;
+
+    Ok((rich_block, proof))
+}
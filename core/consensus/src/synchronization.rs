@@ -1,21 +1,40 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 
+use futures::future::join;
 use parking_lot::RwLock;
 
 use common_apm::Instant;
 use common_apm_derive::trace_span;
-use protocol::tokio::{sync::Mutex, time::sleep};
-use protocol::traits::{Context, Synchronization, SynchronizationAdapter};
-use protocol::types::{Block, Proof, Proposal, Receipt, SignedTransaction, U256};
-use protocol::{async_trait, ProtocolResult};
-
+use core_executor::BLOCK_HASH_HISTORY;
+use core_network::NetworkContext;
+use protocol::tokio::{
+    sync::{mpsc, Mutex},
+    time::sleep,
+};
+use protocol::traits::{Context, Synchronization, SynchronizationAdapter, TrustFeedback};
+use protocol::types::{Block, Metadata, Proof, Receipt, SignedTransaction, U256};
+use protocol::{async_trait, ProtocolError, ProtocolResult};
+
+use crate::snap_sync::SnapSync;
 use crate::status::{CurrentStatus, StatusAgent};
-use crate::util::digest_signed_transactions;
+use crate::sync_pipeline::{SyncPipeline, SYNC_WINDOW};
+use crate::util::{digest_signed_transactions, verify_proof_with_metadata};
 use crate::{engine::generate_receipts_and_logs, ConsensusError};
 
 const POLLING_BROADCAST: u64 = 2000;
-const ONCE_SYNC_BLOCK_LIMIT: u64 = 50;
+const ONCE_SYNC_BLOCK_LIMIT: u64 = 512;
+/// The peers that have not broadcast their number for this long are not
+/// synced from.
+const SYNC_PEER_TIMEOUT: Duration = Duration::from_millis(POLLING_BROADCAST * 10);
+const MAX_SYNC_PEERS: usize = 8;
+/// A fresh node downloads the state instead of replaying the blocks if the
+/// remote is at least this far ahead.
+const SNAP_SYNC_MIN_BLOCKS: u64 = 1024;
+/// The pivot block stays this far behind the remote so that its state is not
+/// pruned by peers while being downloaded.
+const SNAP_SYNC_PIVOT_DISTANCE: u64 = 64;
 
 lazy_static::lazy_static! {
     pub static ref SYNC_STATUS: RwLock<SyncStatus> = RwLock::new(SyncStatus::default());
@@ -27,19 +46,33 @@ pub struct RichBlock {
     pub txs:   Vec<SignedTransaction>,
 }
 
+/// A peer that broadcasts its latest block number, with the context to call
+/// it back.
+struct RemotePeer {
+    ctx:    Context,
+    number: u64,
+    seen:   Instant,
+}
+
 pub struct OverlordSynchronization<Adapter: SynchronizationAdapter> {
     adapter: Arc<Adapter>,
     status:  StatusAgent,
     lock:    Arc<Mutex<()>>,
     syncing: Mutex<()>,
+    peers:   RwLock<HashMap<usize, RemotePeer>>,
 
     sync_txs_chunk_size: usize,
+    snap_sync:           bool,
 }
 
 #[async_trait]
-impl<Adapter: SynchronizationAdapter> Synchronization for OverlordSynchronization<Adapter> {
+impl<Adapter: SynchronizationAdapter + 'static> Synchronization
+    for OverlordSynchronization<Adapter>
+{
     #[trace_span(kind = "consensus.sync", logs = "{remote_number: remote_number}")]
     async fn receive_remote_block(&self, ctx: Context, remote_number: u64) -> ProtocolResult<()> {
+        self.record_peer(&ctx, remote_number);
+
         let syncing_lock = self.syncing.try_lock();
         if syncing_lock.is_err() {
             return Ok(());
@@ -54,7 +87,7 @@ impl<Adapter: SynchronizationAdapter> Synchronization for OverlordSynchronizatio
             return Ok(());
         }
 
-        let current_number = self.status.inner().last_number;
+        let mut current_number = self.status.inner().last_number;
 
         if remote_number <= current_number {
             return Ok(());
@@ -67,6 +100,26 @@ impl<Adapter: SynchronizationAdapter> Synchronization for OverlordSynchronizatio
         );
 
         let sync_status_agent = self.init_status_agent().await?;
+
+        if self.snap_sync && current_number == 0 && remote_number >= SNAP_SYNC_MIN_BLOCKS {
+            let pivot = remote_number - SNAP_SYNC_PIVOT_DISTANCE;
+            match self
+                .sync_state_to_pivot(ctx.clone(), sync_status_agent.clone(), pivot)
+                .await
+            {
+                Ok(pivot) => {
+                    self.update_status(ctx.clone(), sync_status_agent.clone())?;
+                    current_number = pivot;
+                }
+                // The blocks are replayed from the genesis instead.
+                Err(e) => log::error!(
+                    "[synchronization]: snap sync err, pivot {:?} err_msg: {:?}, fall back to full sync",
+                    pivot,
+                    e
+                ),
+            }
+        }
+
         let sync_resp = self
             .start_sync(
                 ctx.clone(),
@@ -99,9 +152,10 @@ impl<Adapter: SynchronizationAdapter> Synchronization for OverlordSynchronizatio
     }
 }
 
-impl<Adapter: SynchronizationAdapter> OverlordSynchronization<Adapter> {
+impl<Adapter: SynchronizationAdapter + 'static> OverlordSynchronization<Adapter> {
     pub fn new(
         sync_txs_chunk_size: usize,
+        snap_sync: bool,
         adapter: Arc<Adapter>,
         status: StatusAgent,
         lock: Arc<Mutex<()>>,
@@ -113,8 +167,10 @@ impl<Adapter: SynchronizationAdapter> OverlordSynchronization<Adapter> {
             status,
             lock,
             syncing,
+            peers: RwLock::new(HashMap::new()),
 
             sync_txs_chunk_size,
+            snap_sync,
         }
     }
 
@@ -144,154 +200,288 @@ impl<Adapter: SynchronizationAdapter> OverlordSynchronization<Adapter> {
         current_number: u64,
         remote_number: u64,
     ) -> ProtocolResult<()> {
-        let mut current_consented_number = current_number;
         let remote_number = if current_number + ONCE_SYNC_BLOCK_LIMIT > remote_number {
             remote_number
         } else {
             current_number + ONCE_SYNC_BLOCK_LIMIT
         };
+        // The proofs of the blocks in the next epoch can only be verified once
+        // its metadata is loaded by committing the last block of this epoch,
+        // so the sync stops there and the next round continues from it.
+        let epoch_end = self
+            .adapter
+            .get_metadata_unchecked(ctx.clone(), current_number + 1)
+            .version
+            .end;
+        let remote_number = remote_number.min(epoch_end);
 
         {
-            SYNC_STATUS
-                .write()
-                .start(current_consented_number, remote_number);
+            SYNC_STATUS.write().start(current_number, remote_number);
         }
 
-        while current_consented_number < remote_number {
-            let consenting_number = current_consented_number + 1;
-            log::info!(
-                "[synchronization]: try syncing block, current_consented_number:{},syncing_number:{}",
-                current_consented_number,
-                consenting_number
-            );
+        let parent = self
+            .adapter
+            .get_block_by_number(ctx.clone(), current_number)
+            .await?;
+        let peers = self.sync_peers(&ctx, remote_number);
+        log::info!(
+            "[synchronization]: sync blocks {} to {} from {} peers",
+            current_number + 1,
+            remote_number,
+            peers.len()
+        );
+
+        let (sender, mut receiver) = mpsc::channel(SYNC_WINDOW as usize);
+        let download = SyncPipeline::new(
+            Arc::clone(&self.adapter),
+            peers,
+            self.sync_txs_chunk_size,
+        )
+        .run(parent, remote_number, sender);
+
+        // The blocks are committed while the following ones are downloaded and
+        // verified. A failed commit drops the receiver and stops the download.
+        let commit = async {
+            while let Some((rich_block, proof)) = receiver.recv().await {
+                let consenting_number = rich_block.block.header.number;
+                log::info!(
+                    "[synchronization]: try syncing block, syncing_number:{}",
+                    consenting_number
+                );
+
+                let inst = Instant::now();
+                self.commit_block(ctx.clone(), rich_block, proof, sync_status_agent.clone())
+                    .await
+                    .map_err(|e| {
+                        log::error!(
+                            "[synchronization]: commit block {} error",
+                            consenting_number
+                        );
+                        e
+                    })?;
+
+                common_apm::metrics::consensus::ENGINE_SYNC_BLOCK_COUNTER.inc_by(1u64);
+                common_apm::metrics::consensus::ENGINE_SYNC_BLOCK_HISTOGRAM
+                    .observe(common_apm::metrics::duration_to_sec(inst.elapsed()));
+                SYNC_STATUS.write().add_one();
+            }
 
-            let consenting_rich_block: RichBlock = self
-                .get_rich_block_from_remote(ctx.clone(), consenting_number)
+            Ok::<_, ProtocolError>(())
+        };
+
+        let (downloaded, committed) = join(download, commit).await;
+        committed?;
+        downloaded
+    }
+
+    fn record_peer(&self, ctx: &Context, number: u64) {
+        let session_id = match ctx.session_id() {
+            Ok(session_id) => session_id.value(),
+            Err(_) => return,
+        };
+
+        let mut peers = self.peers.write();
+        peers.retain(|_, peer| peer.seen.elapsed() < SYNC_PEER_TIMEOUT);
+        peers.insert(session_id, RemotePeer {
+            ctx: ctx.clone(),
+            number,
+            seen: Instant::now(),
+        });
+    }
+
+    /// The peer that triggers the sync comes first, followed by the other
+    /// peers that have the blocks up to `number`.
+    fn sync_peers(&self, ctx: &Context, number: u64) -> Vec<Context> {
+        let session_id = ctx.session_id().ok().map(|sid| sid.value());
+        let mut peers = vec![ctx.clone()];
+
+        peers.extend(
+            self.peers
+                .read()
+                .iter()
+                .filter(|(sid, peer)| {
+                    Some(**sid) != session_id
+                        && peer.number >= number
+                        && peer.seen.elapsed() < SYNC_PEER_TIMEOUT
+                })
+                .map(|(_, peer)| peer.ctx.clone())
+                .take(MAX_SYNC_PEERS - 1),
+        );
+
+        peers
+    }
+
+    /// Download the state of the pivot block and jump to it without replaying
+    /// the blocks before. The receipts of these blocks are not available on
+    /// this node.
+    ///
+    /// The pivot block comes from the remote, so its proof is verified with the
+    /// verifiers in the local genesis state before the state root it carries is
+    /// trusted. The pivot is moved back to the last block of their epoch if it
+    /// is beyond. A peer failing to serve the pivot block or its state is
+    /// replaced by the next one. Return the number of the synced pivot.
+    #[trace_span(kind = "consensus.sync", logs = "{pivot: pivot}")]
+    async fn sync_state_to_pivot(
+        &self,
+        ctx: Context,
+        sync_status_agent: StatusAgent,
+        pivot: u64,
+    ) -> ProtocolResult<u64> {
+        let genesis = self
+            .adapter
+            .get_block_header_by_number(ctx.clone(), 0)
+            .await?;
+        let trusted_metadata = self.adapter.get_metadata(ctx.clone(), &genesis)?;
+        let pivot = pivot.min(trusted_metadata.version.end);
+        if pivot == 0 {
+            return Err(ConsensusError::Other(
+                "no trusted verifiers for a snap sync pivot".to_string(),
+            )
+            .into());
+        }
+
+        log::info!(
+            "[synchronization]: snap sync start, pivot block number {:?}",
+            pivot
+        );
+
+        let inst = Instant::now();
+        for peer in self.sync_peers(&ctx, pivot) {
+            let (rich_block, proof) = match self
+                .get_verified_pivot(peer.clone(), pivot, &trusted_metadata)
                 .await
-                .map_err(|e| {
-                    log::error!(
-                        "[synchronization]: get_rich_block_from_remote error, number: {:?}",
-                        consenting_number
+            {
+                Ok(res) => res,
+                Err(e) => {
+                    log::warn!(
+                        "[synchronization]: get pivot block {} error {:?}, retry with another peer",
+                        pivot,
+                        e
                     );
+                    continue;
+                }
+            };
+
+            let block = rich_block.block;
+            let ancestors = match self.get_verified_ancestors(peer.clone(), &block).await {
+                Ok(ancestors) => ancestors,
+                Err(e) => {
+                    log::warn!(
+                        "[synchronization]: get ancestors of pivot block {} error {:?}, retry with another peer",
+                        pivot,
+                        e
+                    );
+                    continue;
+                }
+            };
+
+            if let Err(e) = SnapSync::new(Arc::clone(&self.adapter))
+                .sync_state(peer.clone(), block.header.state_root)
+                .await
+            {
+                log::warn!(
+                    "[synchronization]: sync state of pivot block {} error {:?}, retry with another peer",
+                    pivot,
                     e
-                })?;
+                );
+                continue;
+            }
 
-            let consenting_proof = self
-                .verify_block(ctx.clone(), &consenting_rich_block)
+            let metadata = self.adapter.get_metadata(ctx.clone(), &block.header)?;
+            sync_status_agent.swap(CurrentStatus {
+                prev_hash:                  block.header_hash(),
+                last_number:                block.header.number,
+                last_state_root:            block.header.state_root,
+                tx_num_limit:               metadata.tx_num_limit,
+                max_tx_size:                metadata.max_tx_size.into(),
+                proof:                      proof.clone(),
+                last_checkpoint_block_hash: metadata.last_checkpoint_block_hash,
+            });
+
+            self.adapter
+                .save_signed_txs(ctx.clone(), pivot, rich_block.txs)
                 .await?;
+            self.adapter.save_proof(ctx.clone(), proof).await?;
+            for ancestor in ancestors.into_iter() {
+                self.adapter.save_block(ctx.clone(), ancestor).await?;
+            }
+            self.adapter.save_block(ctx.clone(), block).await?;
 
-            let inst = Instant::now();
-            self.commit_block(
-                ctx.clone(),
-                consenting_rich_block,
-                consenting_proof,
-                sync_status_agent.clone(),
-            )
-            .await
-            .map_err(|e| {
-                log::error!(
-                    "[synchronization]: commit block {} error",
-                    current_consented_number
-                );
-                e
-            })?;
-
-            current_consented_number += 1;
+            log::info!(
+                "[synchronization]: snap sync end, pivot block number {:?}, cost {:?}",
+                pivot,
+                inst.elapsed()
+            );
 
-            common_apm::metrics::consensus::ENGINE_SYNC_BLOCK_COUNTER.inc_by(1u64);
-            common_apm::metrics::consensus::ENGINE_SYNC_BLOCK_HISTOGRAM
-                .observe(common_apm::metrics::duration_to_sec(inst.elapsed()));
-            SYNC_STATUS.write().add_one();
+            return Ok(pivot);
         }
 
-        Ok(())
+        Err(ConsensusError::Other(format!(
+            "no peer serves a valid state of pivot block {}",
+            pivot
+        ))
+        .into())
     }
 
-    async fn verify_block(
+    /// Download the pivot block with its transactions and proof from the peer,
+    /// and verify them with the trusted metadata. The peer serving an invalid
+    /// pivot block is reported.
+    async fn get_verified_pivot(
         &self,
         ctx: Context,
-        consenting_rich_block: &RichBlock,
-    ) -> ProtocolResult<Proof> {
-        let consenting_number = consenting_rich_block.block.header.number;
-        let proposal: Proposal = consenting_rich_block.block.clone().into();
-
-        let consenting_proof: Proof = self
+        pivot: u64,
+        trusted_metadata: &Metadata,
+    ) -> ProtocolResult<(RichBlock, Proof)> {
+        let rich_block = self.get_rich_block_from_remote(ctx.clone(), pivot).await?;
+        let proof = self
             .adapter
-            .get_proof_from_remote(ctx.clone(), consenting_number)
-            .await
-            .map_err(|e| {
-                log::error!(
-                    "[synchronization]: get_proof_from_remote error, number: {:?}",
-                    consenting_number
-                );
-                e
-            })?;
-
-        self.adapter
-            .verify_proof(
-                ctx.clone(),
-                consenting_rich_block.block.clone(),
-                consenting_proof.clone(),
-            )
-            .await
-            .map_err(|e| {
-                log::error!(
-                    "[synchronization]: verify_proof error, syncing block header: {:?}, proof: {:?}",
-                    consenting_rich_block.block.header,
-                    consenting_proof,
-                );
-                e
-            })?;
+            .get_proof_from_remote(ctx.clone(), pivot)
+            .await?;
 
-        self.adapter
-            .verify_block_header(ctx.clone(), &proposal)
-            .await
-            .map_err(|e| {
-                log::error!(
-                    "[synchronization]: verify_block_header error, block header: {:?}",
-                    consenting_rich_block.block.header
-                );
-                e
-            })?;
+        if let Err(e) = verify_pivot(&rich_block, &proof, pivot, trusted_metadata) {
+            self.adapter
+                .report_peer(ctx, TrustFeedback::Worse(e.to_string()));
+            return Err(e);
+        }
 
-        let previous_block = self
-            .adapter
-            .get_block_by_number(ctx.clone(), consenting_rich_block.block.header.number - 1)
-            .await
-            .map_err(|e| {
-                log::error!(
-                    "[synchronization] get previous block {} error",
-                    consenting_rich_block.block.header.number - 1
-                );
-                e
-            })?;
+        Ok((rich_block, proof))
+    }
 
-        self.adapter
-            .verify_proof(
-                ctx.clone(),
-                previous_block.clone(),
-                consenting_rich_block.block.header.proof.clone(),
-            )
-            .await
-            .map_err(|e| {
-                log::error!(
-                    "[synchronization]: verify_proof error, previous block header: {:?}, proof: {:?}",
-                    previous_block.header,
-                    consenting_rich_block.block.header.proof
+    /// Download the blocks before the pivot block whose hashes can be read by
+    /// `BLOCKHASH`, oldest first. They are trusted by the hash links from the
+    /// verified pivot block, and the peer breaking the links is reported.
+    async fn get_verified_ancestors(
+        &self,
+        ctx: Context,
+        pivot: &Block,
+    ) -> ProtocolResult<Vec<Block>> {
+        let number = pivot.header.number;
+        let mut ancestors = Vec::new();
+        let mut prev_hash = pivot.header.prev_hash;
+
+        for height in (number.saturating_sub(BLOCK_HASH_HISTORY)..number).rev() {
+            let block = self
+                .adapter
+                .get_block_from_remote(ctx.clone(), height)
+                .await?;
+            if block.header.number != height || block.header_hash() != prev_hash {
+                self.adapter.report_peer(
+                    ctx,
+                    TrustFeedback::Worse(format!("mismatched ancestor block {}", height)),
                 );
-                e
-            })?;
-
-        let signed_txs_hash = digest_signed_transactions(&consenting_rich_block.txs);
-        if signed_txs_hash != consenting_rich_block.block.header.signed_txs_hash {
-            return Err(ConsensusError::InvalidOrderSignedTransactionsHash {
-                expect: signed_txs_hash,
-                actual: consenting_rich_block.block.header.signed_txs_hash,
+                return Err(ConsensusError::Other(format!(
+                    "ancestor block {} does not link to the pivot block",
+                    height
+                ))
+                .into());
             }
-            .into());
+
+            prev_hash = block.header.prev_hash;
+            ancestors.push(block);
         }
 
-        Ok(consenting_proof)
+        ancestors.reverse();
+        Ok(ancestors)
     }
 
     async fn init_status_agent(&self) -> ProtocolResult<StatusAgent> {
@@ -497,6 +687,35 @@ impl<Adapter: SynchronizationAdapter> OverlordSynchronization<Adapter> {
     }
 }
 
+/// Check that the pivot block matches its transactions and is committed by
+/// the trusted verifiers.
+fn verify_pivot(
+    rich_block: &RichBlock,
+    proof: &Proof,
+    pivot: u64,
+    trusted_metadata: &Metadata,
+) -> ProtocolResult<()> {
+    let header = &rich_block.block.header;
+    if header.number != pivot {
+        return Err(ConsensusError::MismatchedSyncBlock {
+            expect: pivot,
+            actual: header.number,
+        }
+        .into());
+    }
+
+    let signed_txs_hash = digest_signed_transactions(&rich_block.txs);
+    if signed_txs_hash != header.signed_txs_hash {
+        return Err(ConsensusError::InvalidOrderSignedTransactionsHash {
+            expect: signed_txs_hash,
+            actual: header.signed_txs_hash,
+        }
+        .into());
+    }
+
+    verify_proof_with_metadata(&rich_block.block, proof, trusted_metadata)
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum SyncStatus {
     False,
@@ -529,8 +748,10 @@ impl SyncStatus {
     pub fn add_one(&mut self) {
         match *self {
             SyncStatus::False => (),
-            SyncStatus::Syncing { mut current, .. } => {
-                current += U256::one();
+            SyncStatus::Syncing {
+                ref mut current, ..
+            } => {
+                *current += U256::one();
             }
         }
     }
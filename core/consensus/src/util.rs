@@ -3,15 +3,20 @@ use std::convert::TryFrom;
 use std::error::Error;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use overlord::Crypto;
+use overlord::types::{Node, Vote, VoteType};
+use overlord::{extract_voters, Crypto};
 use parking_lot::RwLock;
 
-use crate::ConsensusError;
+use crate::{BlockProofField, ConsensusError};
 use common_crypto::{
     BlsPrivateKey, BlsPublicKey, BlsSignature, BlsSignatureVerify, HashValue, PrivateKey, Signature,
 };
+use protocol::codec::ProtocolCodec;
 use protocol::traits::Context;
-use protocol::types::{Address, Bytes, Hash, Hasher, Hex, MerkleRoot, SignedTransaction};
+use protocol::types::{
+    Address, Block, Bytes, Hash, Hasher, Hex, MerkleRoot, Metadata, Proof, Proposal,
+    SignedTransaction,
+};
 use protocol::{ProtocolError, ProtocolResult};
 
 pub fn digest_signed_transactions(stxs: &[SignedTransaction]) -> Hash {
@@ -181,6 +186,86 @@ pub fn convert_hex_to_bls_pubkeys(hex: Hex) -> ProtocolResult<BlsPublicKey> {
     Ok(ret)
 }
 
+/// Verify the proof of a block with the verifiers in the metadata of its epoch.
+/// Unlike the consensus adapter it needs no running node, so that a stored
+/// chain can be checked offline.
+pub fn verify_proof_with_metadata(
+    block: &Block,
+    proof: &Proof,
+    metadata: &Metadata,
+) -> ProtocolResult<()> {
+    let number = block.header.number;
+    if number != proof.number {
+        return Err(ConsensusError::VerifyProof(
+            number,
+            BlockProofField::HeightMismatch(number, proof.number),
+        )
+        .into());
+    }
+
+    let proposal_hash = Hasher::digest(Proposal::from(block.clone()).encode()?);
+    if proposal_hash != proof.block_hash {
+        return Err(ConsensusError::VerifyProof(number, BlockProofField::HashMismatch).into());
+    }
+
+    if !metadata.version.contains(number) {
+        return Err(
+            ConsensusError::ConfusedMetadata(metadata.version.start, metadata.version.end).into(),
+        );
+    }
+
+    let mut authority_list = metadata
+        .verifier_list
+        .iter()
+        .map(|v| Node {
+            address:        v.pub_key.as_bytes(),
+            propose_weight: v.propose_weight,
+            vote_weight:    v.vote_weight,
+        })
+        .collect::<Vec<Node>>();
+    let signed_voters = extract_voters(&mut authority_list, &proof.bitmap)
+        .map_err(|_| ConsensusError::VerifyProof(number, BlockProofField::BitMap))?;
+
+    let total_weight: u64 = authority_list
+        .iter()
+        .map(|n| u64::from(n.vote_weight))
+        .sum();
+    let signed_weight: u64 = authority_list
+        .iter()
+        .filter(|n| signed_voters.contains(&n.address))
+        .map(|n| u64::from(n.vote_weight))
+        .sum();
+    if 3 * signed_weight <= 2 * total_weight {
+        return Err(ConsensusError::VerifyProof(number, BlockProofField::Weight).into());
+    }
+
+    let vote = Vote {
+        height:     proof.number,
+        round:      proof.round,
+        vote_type:  VoteType::Precommit,
+        block_hash: Bytes::from(proof.block_hash.as_bytes().to_vec()),
+    };
+    let vote_hash = Hasher::digest(Bytes::from(rlp::encode(&vote)));
+    let pub_keys = metadata
+        .verifier_list
+        .iter()
+        .filter(|v| signed_voters.contains(&v.pub_key.as_bytes()))
+        .map(|v| convert_hex_to_bls_pubkeys(v.bls_pub_key.clone()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let aggregate_key = BlsPublicKey::aggregate(pub_keys)
+        .map_err(|e| ConsensusError::CryptoErr(Box::new(e.into())))?;
+    let signature = BlsSignature::try_from(proof.signature.as_ref())
+        .map_err(|e| ConsensusError::CryptoErr(Box::new(e)))?;
+    let hash = HashValue::try_from(vote_hash.as_bytes())
+        .map_err(|_| ConsensusError::Other("failed to convert hash value".to_string()))?;
+    signature
+        .verify(&hash, &aggregate_key, &String::new())
+        .map_err(|_| ConsensusError::VerifyProof(number, BlockProofField::Signature))?;
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
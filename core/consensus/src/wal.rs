@@ -118,6 +118,16 @@ impl SignedTxsWAL {
     }
 
     pub fn remove(&self, committed_number: u64) -> ProtocolResult<()> {
+        self.remove_if(|number| number <= committed_number)
+    }
+
+    /// Remove the transactions of the given number and the ones above, which
+    /// belong to the blocks removed by a rollback.
+    pub fn remove_from(&self, number: u64) -> ProtocolResult<()> {
+        self.remove_if(|n| n >= number)
+    }
+
+    fn remove_if<F: Fn(u64) -> bool>(&self, f: F) -> ProtocolResult<()> {
         for entry in fs::read_dir(&self.path).map_err(ConsensusError::WALErr)? {
             let folder = entry.map_err(ConsensusError::WALErr)?.path();
             let folder_name = folder
@@ -132,7 +142,7 @@ impl SignedTxsWAL {
                 ConsensusError::Other(format!("parse folder name {:?} error {:?}", folder, err))
             })?;
 
-            if number <= committed_number {
+            if f(number) {
                 fs::remove_dir_all(folder).map_err(ConsensusError::WALErr)?;
             }
         }
@@ -333,7 +333,7 @@ where
     }
 
     fn get_nonce(&self, addr: &H160) -> U256 {
-        let backend = AxonExecutorAdapter::from_root(
+        let backend = AxonExecutorAdapter::without_block_hashes(
             **CURRENT_STATE_ROOT.load(),
             Arc::clone(&self.trie_db),
             Arc::clone(&self.storage),
@@ -34,7 +34,9 @@ struct BenchAdapter {
 impl BenchAdapter {
     fn new() -> Self {
         BenchAdapter {
-            trie_db: Arc::new(RocksTrieDB::new(STATE_PATH, Default::default(), 1000).unwrap()),
+            trie_db: Arc::new(
+                RocksTrieDB::new(STATE_PATH, Default::default(), 1000, Default::default()).unwrap(),
+            ),
             storage: Arc::new(ImplStorage::new(Arc::new(
                 RocksAdapter::new(DATA_PATH, Default::default()).unwrap(),
             ))),
@@ -60,7 +62,7 @@ impl BenchAdapter {
     }
 
     fn init_bachend(&self) -> AxonExecutorAdapter<ImplStorage<RocksAdapter>, RocksTrieDB> {
-        AxonExecutorAdapter::from_root(
+        AxonExecutorAdapter::without_block_hashes(
             self.init_mpt(),
             Arc::clone(&self.trie_db),
             Arc::clone(&self.storage),
@@ -0,0 +1,119 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+
+use protocol::traits::Backend;
+use protocol::types::{AccessList, AccessListItem, Basic, H160, H256, U256};
+
+use crate::tracer::is_precompile;
+
+/// A backend that records the addresses and the storage slots read through
+/// it, so that they can be turned into the access list of the call.
+pub struct AccessListBackend<'a, B> {
+    inner:    &'a B,
+    accessed: RefCell<BTreeMap<H160, BTreeSet<H256>>>,
+}
+
+impl<'a, B: Backend> AccessListBackend<'a, B> {
+    pub fn new(inner: &'a B) -> Self {
+        AccessListBackend {
+            inner,
+            accessed: RefCell::new(BTreeMap::new()),
+        }
+    }
+
+    fn touch(&self, address: H160) {
+        self.accessed.borrow_mut().entry(address).or_default();
+    }
+
+    fn touch_slot(&self, address: H160, index: H256) {
+        self.accessed
+            .borrow_mut()
+            .entry(address)
+            .or_default()
+            .insert(index);
+    }
+
+    /// Return the access list, leaving out the addresses that are warm
+    /// anyway unless some of their slots are accessed. These are the sender,
+    /// the callee or the created contract, and the precompiles.
+    pub fn into_access_list(self, excluded: &[H160]) -> AccessList {
+        self.accessed
+            .into_inner()
+            .into_iter()
+            .filter(|(address, slots)| {
+                !slots.is_empty() || !(excluded.contains(address) || is_precompile(address))
+            })
+            .map(|(address, slots)| AccessListItem {
+                address,
+                storage_keys: slots.into_iter().collect(),
+            })
+            .collect()
+    }
+}
+
+impl<'a, B: Backend> Backend for AccessListBackend<'a, B> {
+    fn gas_price(&self) -> U256 {
+        self.inner.gas_price()
+    }
+
+    fn origin(&self) -> H160 {
+        self.inner.origin()
+    }
+
+    fn block_hash(&self, number: U256) -> H256 {
+        self.inner.block_hash(number)
+    }
+
+    fn block_number(&self) -> U256 {
+        self.inner.block_number()
+    }
+
+    fn block_coinbase(&self) -> H160 {
+        self.inner.block_coinbase()
+    }
+
+    fn block_timestamp(&self) -> U256 {
+        self.inner.block_timestamp()
+    }
+
+    fn block_difficulty(&self) -> U256 {
+        self.inner.block_difficulty()
+    }
+
+    fn block_gas_limit(&self) -> U256 {
+        self.inner.block_gas_limit()
+    }
+
+    fn block_base_fee_per_gas(&self) -> U256 {
+        self.inner.block_base_fee_per_gas()
+    }
+
+    fn chain_id(&self) -> U256 {
+        self.inner.chain_id()
+    }
+
+    fn exists(&self, address: H160) -> bool {
+        self.touch(address);
+        self.inner.exists(address)
+    }
+
+    fn basic(&self, address: H160) -> Basic {
+        self.touch(address);
+        self.inner.basic(address)
+    }
+
+    fn code(&self, address: H160) -> Vec<u8> {
+        self.touch(address);
+        self.inner.code(address)
+    }
+
+    fn storage(&self, address: H160, index: H256) -> H256 {
+        self.touch_slot(address, index);
+        self.inner.storage(address, index)
+    }
+
+    fn original_storage(&self, address: H160, index: H256) -> Option<H256> {
+        self.touch_slot(address, index);
+        self.inner.original_storage(address, index)
+    }
+}
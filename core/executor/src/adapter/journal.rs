@@ -0,0 +1,69 @@
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use protocol::types::BlockNumber;
+use protocol::ProtocolResult;
+
+/// A trie db which keeps the nodes removed by each block in a journal, so that
+/// the stale nodes can be deleted once the block is old enough.
+pub trait TrieJournal: cita_trie::DB {
+    fn commit_journal(&self, number: BlockNumber, removed: Vec<Vec<u8>>) -> ProtocolResult<()>;
+}
+
+/// A view of the trie db used to execute a block. The removed nodes are
+/// recorded instead of being deleted, since they are still referenced by the
+/// states of the previous blocks.
+pub struct JournalTrieDB<DB> {
+    db:      Arc<DB>,
+    removed: Mutex<Vec<Vec<u8>>>,
+}
+
+impl<DB: TrieJournal> JournalTrieDB<DB> {
+    pub fn new(db: Arc<DB>) -> Self {
+        JournalTrieDB {
+            db,
+            removed: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Commit the nodes removed by executing the given block to the journal.
+    pub fn commit(&self, number: BlockNumber) -> ProtocolResult<()> {
+        let removed = std::mem::take(&mut *self.removed.lock());
+        self.db.commit_journal(number, removed)
+    }
+}
+
+impl<DB: TrieJournal> cita_trie::DB for JournalTrieDB<DB> {
+    type Error = DB::Error;
+
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        self.db.get(key)
+    }
+
+    fn contains(&self, key: &[u8]) -> Result<bool, Self::Error> {
+        self.db.contains(key)
+    }
+
+    fn insert(&self, key: Vec<u8>, value: Vec<u8>) -> Result<(), Self::Error> {
+        self.db.insert(key, value)
+    }
+
+    fn insert_batch(&self, keys: Vec<Vec<u8>>, values: Vec<Vec<u8>>) -> Result<(), Self::Error> {
+        self.db.insert_batch(keys, values)
+    }
+
+    fn remove(&self, key: &[u8]) -> Result<(), Self::Error> {
+        self.removed.lock().push(key.to_vec());
+        Ok(())
+    }
+
+    fn remove_batch(&self, keys: &[Vec<u8>]) -> Result<(), Self::Error> {
+        self.removed.lock().extend_from_slice(keys);
+        Ok(())
+    }
+
+    fn flush(&self) -> Result<(), Self::Error> {
+        self.db.flush()
+    }
+}
@@ -1,6 +1,14 @@
+mod journal;
+mod proof;
+mod pruner;
+mod range;
 mod trie;
 mod trie_db;
 
+pub use journal::{JournalTrieDB, TrieJournal};
+pub use proof::account_proof;
+pub use pruner::TriePruner;
+pub use range::state_range;
 pub use trie::MPTTrie;
 pub use trie_db::RocksTrieDB;
 
@@ -10,9 +18,10 @@ use evm::backend::{Apply, Basic};
 
 use protocol::traits::{ApplyBackend, Backend, Context, ExecutorAdapter, Storage};
 use protocol::types::{
-    Account, Bytes, ExecutorContext, Hasher, Log, MerkleRoot, H160, H256, NIL_DATA, RLP_NULL, U256,
+    Account, BlockNumber, Bytes, ExecutorContext, Hasher, Log, MerkleRoot, H160, H256, NIL_DATA,
+    RLP_NULL, U256,
 };
-use protocol::{codec::ProtocolCodec, ProtocolResult};
+use protocol::{codec::ProtocolCodec, Display, ProtocolError, ProtocolErrorKind, ProtocolResult};
 
 macro_rules! blocking_async {
     ($self_: ident, $adapter: ident, $method: ident$ (, $args: expr)*) => {{
@@ -25,11 +34,52 @@ macro_rules! blocking_async {
     }};
 }
 
+/// The number of the recent blocks whose hashes can be read by the
+/// `BLOCKHASH` opcode.
+pub const BLOCK_HASH_HISTORY: u64 = 256;
+
+/// A ring buffer of the recent block hashes, the hash of block `n` is stored
+/// in the slot `n % BLOCK_HASH_HISTORY`.
+struct BlockHashes([Option<(BlockNumber, H256)>; BLOCK_HASH_HISTORY as usize]);
+
+impl BlockHashes {
+    fn new() -> Self {
+        BlockHashes([None; BLOCK_HASH_HISTORY as usize])
+    }
+
+    /// Read the hashes of the recent complete blocks before `current` from the
+    /// storage.
+    async fn load<S: Storage>(storage: &S, current: BlockNumber) -> ProtocolResult<Self> {
+        let mut hashes = BlockHashes::new();
+        for number in current.saturating_sub(BLOCK_HASH_HISTORY)..current {
+            let header = storage
+                .get_block_header(Context::new(), number)
+                .await?
+                .ok_or(ExecutorAdapterError::MissingBlockHeader(number))?;
+            hashes.insert(number, header.hash());
+        }
+
+        Ok(hashes)
+    }
+
+    fn get(&self, number: BlockNumber) -> Option<H256> {
+        match self.0[(number % BLOCK_HASH_HISTORY) as usize] {
+            Some((n, hash)) if n == number => Some(hash),
+            _ => None,
+        }
+    }
+
+    fn insert(&mut self, number: BlockNumber, hash: H256) {
+        self.0[(number % BLOCK_HASH_HISTORY) as usize] = Some((number, hash));
+    }
+}
+
 pub struct AxonExecutorAdapter<S, DB: cita_trie::DB> {
-    exec_ctx: ExecutorContext,
-    trie:     MPTTrie<DB>,
-    storage:  Arc<S>,
-    db:       Arc<DB>,
+    exec_ctx:     ExecutorContext,
+    trie:         MPTTrie<DB>,
+    storage:      Arc<S>,
+    db:           Arc<DB>,
+    block_hashes: BlockHashes,
 }
 
 impl<S, DB> ExecutorAdapter for AxonExecutorAdapter<S, DB>
@@ -77,8 +127,15 @@ where
         self.exec_ctx.block_number
     }
 
-    fn block_hash(&self, _number: U256) -> H256 {
-        self.exec_ctx.block_hash
+    /// Return the hash of one of the 256 most recent complete blocks, and zero
+    /// for the others as the yellow paper required.
+    fn block_hash(&self, number: U256) -> H256 {
+        let current = self.exec_ctx.block_number;
+        if number >= current || current - number > BLOCK_HASH_HISTORY.into() {
+            return H256::default();
+        }
+
+        self.block_hashes.get(number.as_u64()).unwrap_or_default()
     }
 
     fn block_coinbase(&self) -> H160 {
@@ -217,17 +274,44 @@ where
     S: Storage + 'static,
     DB: cita_trie::DB + 'static,
 {
-    pub fn new(db: Arc<DB>, storage: Arc<S>, exec_ctx: ExecutorContext) -> ProtocolResult<Self> {
+    pub async fn new(
+        db: Arc<DB>,
+        storage: Arc<S>,
+        exec_ctx: ExecutorContext,
+    ) -> ProtocolResult<Self> {
         let trie = MPTTrie::new(Arc::clone(&db));
+        let block_hashes =
+            BlockHashes::load(storage.as_ref(), exec_ctx.block_number.as_u64()).await?;
+
         Ok(AxonExecutorAdapter {
             trie,
             db,
             storage,
             exec_ctx,
+            block_hashes,
         })
     }
 
-    pub fn from_root(
+    pub async fn from_root(
+        state_root: MerkleRoot,
+        db: Arc<DB>,
+        storage: Arc<S>,
+        exec_ctx: ExecutorContext,
+    ) -> ProtocolResult<Self> {
+        let mut adapter = Self::without_block_hashes(state_root, db, storage, exec_ctx)?;
+        adapter.block_hashes = BlockHashes::load(
+            adapter.storage.as_ref(),
+            adapter.exec_ctx.block_number.as_u64(),
+        )
+        .await?;
+
+        Ok(adapter)
+    }
+
+    /// Open the state without reading the recent block hashes, so that the
+    /// `BLOCKHASH` opcode always returns zero. It is only for reading the
+    /// state or calling the contracts which do not depend on the history.
+    pub fn without_block_hashes(
         state_root: MerkleRoot,
         db: Arc<DB>,
         storage: Arc<S>,
@@ -240,6 +324,7 @@ where
             db,
             storage,
             exec_ctx,
+            block_hashes: BlockHashes::new(),
         })
     }
 
@@ -314,3 +399,103 @@ where
             && new_account.code_hash.is_zero()
     }
 }
+
+#[derive(Debug, Display)]
+pub enum ExecutorAdapterError {
+    #[display(fmt = "Missing the header of block {}", _0)]
+    MissingBlockHeader(BlockNumber),
+}
+
+impl std::error::Error for ExecutorAdapterError {}
+
+impl From<ExecutorAdapterError> for ProtocolError {
+    fn from(err: ExecutorAdapterError) -> ProtocolError {
+        ProtocolError::new(ProtocolErrorKind::Executor, Box::new(err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cita_trie::MemoryDB;
+
+    use core_storage::{adapter::memory::MemoryAdapter, ImplStorage};
+    use protocol::tokio;
+    use protocol::types::{Block, Header};
+
+    use super::*;
+
+    #[test]
+    fn test_block_hashes_eviction() {
+        let mut hashes = BlockHashes::new();
+        hashes.insert(1, H256::repeat_byte(1));
+        assert_eq!(hashes.get(1), Some(H256::repeat_byte(1)));
+        assert_eq!(hashes.get(1 + BLOCK_HASH_HISTORY), None);
+
+        hashes.insert(1 + BLOCK_HASH_HISTORY, H256::repeat_byte(2));
+        assert_eq!(hashes.get(1), None);
+        assert_eq!(
+            hashes.get(1 + BLOCK_HASH_HISTORY),
+            Some(H256::repeat_byte(2))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_block_hash_window() {
+        let storage = Arc::new(ImplStorage::new(Arc::new(MemoryAdapter::new())));
+        let current = BLOCK_HASH_HISTORY + 10;
+        let mut headers = Vec::new();
+        for number in 0..current {
+            let header = Header {
+                number,
+                ..Default::default()
+            };
+            storage
+                .set_block(Context::new(), Block {
+                    header:    header.clone(),
+                    tx_hashes: Vec::new(),
+                })
+                .await
+                .unwrap();
+            headers.push(header);
+        }
+
+        let exec_ctx = ExecutorContext {
+            block_number: current.into(),
+            ..ExecutorContext::from(&Header::default())
+        };
+        let backend = AxonExecutorAdapter::new(
+            Arc::new(MemoryDB::new(true)),
+            Arc::clone(&storage),
+            exec_ctx,
+        )
+        .await
+        .unwrap();
+
+        // The current block and the future ones are not readable.
+        assert_eq!(backend.block_hash(current.into()), H256::zero());
+        assert_eq!(backend.block_hash((current + 1).into()), H256::zero());
+
+        // Only the 256 most recent complete blocks are readable.
+        let oldest = current - BLOCK_HASH_HISTORY;
+        for number in [oldest, oldest + 1, current - 1] {
+            assert_eq!(
+                backend.block_hash(number.into()),
+                headers[number as usize].hash()
+            );
+        }
+        assert_eq!(backend.block_hash((oldest - 1).into()), H256::zero());
+        assert_eq!(backend.block_hash(U256::zero()), H256::zero());
+
+        // A missing recent block fails the adapter instead of reading zero.
+        let res = AxonExecutorAdapter::new(
+            Arc::new(MemoryDB::new(true)),
+            Arc::new(ImplStorage::new(Arc::new(MemoryAdapter::new()))),
+            ExecutorContext {
+                block_number: current.into(),
+                ..ExecutorContext::from(&Header::default())
+            },
+        )
+        .await;
+        assert!(res.is_err());
+    }
+}
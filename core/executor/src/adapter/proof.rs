@@ -0,0 +1,172 @@
+use std::sync::Arc;
+
+use cita_trie::DB as TrieDB;
+
+use protocol::codec::ProtocolCodec;
+use protocol::types::{
+    Account, AccountProof, MerkleRoot, StorageProof, H160, H256, NIL_DATA, RLP_NULL, U256,
+};
+use protocol::ProtocolResult;
+
+use crate::adapter::trie::MPTTrie;
+
+/// Generate the proofs of the account and the given storage slots in the state
+/// with the given root. They can be checked by [`AccountProof::verify`].
+pub fn account_proof<DB: TrieDB>(
+    db: Arc<DB>,
+    state_root: MerkleRoot,
+    address: H160,
+    keys: &[H256],
+) -> ProtocolResult<AccountProof> {
+    let mut account = Account {
+        nonce:        U256::zero(),
+        balance:      U256::zero(),
+        storage_root: RLP_NULL,
+        code_hash:    NIL_DATA,
+    };
+    let mut account_proof = Vec::new();
+
+    if state_root != RLP_NULL {
+        let trie = MPTTrie::from_root(state_root, Arc::clone(&db))?;
+        if let Some(raw) = trie.get(address.as_bytes())? {
+            account = Account::decode(raw)?;
+        }
+        account_proof = trie.get_proof(address.as_bytes())?;
+    }
+
+    let storage_trie = if account.storage_root == RLP_NULL {
+        None
+    } else {
+        Some(MPTTrie::from_root(account.storage_root, db)?)
+    };
+
+    let storage_proof = keys
+        .iter()
+        .map(|key| match storage_trie.as_ref() {
+            Some(trie) => Ok(StorageProof {
+                key:   *key,
+                value: trie
+                    .get(key.as_bytes())?
+                    .map(|raw| H256::from_slice(&raw))
+                    .unwrap_or_default(),
+                proof: trie.get_proof(key.as_bytes())?,
+            }),
+            None => Ok(StorageProof {
+                key:   *key,
+                value: H256::default(),
+                proof: Vec::new(),
+            }),
+        })
+        .collect::<ProtocolResult<Vec<_>>>()?;
+
+    Ok(AccountProof {
+        address,
+        account,
+        account_proof,
+        storage_proof,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use core_storage::{adapter::memory::MemoryAdapter, ImplStorage};
+    use protocol::tokio;
+    use protocol::traits::Executor;
+    use protocol::types::{
+        Bytes, ExecutorContext, Public, SignatureComponents, SignedTransaction, Transaction,
+        TransactionAction, UnverifiedTransaction,
+    };
+
+    use crate::adapter::{AxonExecutorAdapter, RocksTrieDB};
+    use crate::AxonExecutor;
+
+    fn create_tx(sender: H160, nonce: u64, init_code: Vec<u8>) -> SignedTransaction {
+        SignedTransaction {
+            transaction: UnverifiedTransaction {
+                unsigned:  Transaction {
+                    nonce:                    nonce.into(),
+                    max_priority_fee_per_gas: U256::zero(),
+                    gas_price:                U256::zero(),
+                    gas_limit:                1_000_000u64.into(),
+                    action:                   TransactionAction::Create,
+                    value:                    U256::zero(),
+                    data:                     init_code.into(),
+                    access_list:              Vec::new(),
+                },
+                signature: Some(SignatureComponents {
+                    standard_v: 0,
+                    r:          Bytes::default(),
+                    s:          Bytes::default(),
+                }),
+                chain_id:  0u64,
+                hash:      H256::from_low_u64_be(nonce + 1),
+            },
+            sender,
+            public: Some(Public::default()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_account_proof() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Arc::new(
+            RocksTrieDB::new(dir.path(), Default::default(), 100, Default::default()).unwrap(),
+        );
+        let storage = Arc::new(ImplStorage::new(Arc::new(MemoryAdapter::new())));
+        let sender = H160::from_low_u64_be(0x1234);
+
+        let mut backend = AxonExecutorAdapter::new(
+            Arc::clone(&db),
+            Arc::clone(&storage),
+            ExecutorContext::default(),
+        )
+        .await
+        .unwrap();
+        // PUSH1 0x2a PUSH1 0x01 SSTORE STOP
+        let resp = AxonExecutor::default().exec(&mut backend, vec![create_tx(sender, 0, vec![
+            0x60, 0x2a, 0x60, 0x01, 0x55, 0x00,
+        ])]);
+        let contract = H160::from(resp.tx_resp[0].code_address.unwrap());
+        let first_root = resp.state_root;
+
+        // PUSH1 0x07 PUSH1 0x02 SSTORE STOP
+        let mut backend = AxonExecutorAdapter::from_root(
+            first_root,
+            Arc::clone(&db),
+            Arc::clone(&storage),
+            ExecutorContext::default(),
+        )
+        .await
+        .unwrap();
+        let resp = AxonExecutor::default().exec(&mut backend, vec![create_tx(sender, 1, vec![
+            0x60, 0x07, 0x60, 0x02, 0x55, 0x00,
+        ])]);
+        let second_root = resp.state_root;
+
+        let keys = [H256::from_low_u64_be(1), H256::from_low_u64_be(2)];
+        let proof = account_proof(Arc::clone(&db), first_root, contract, &keys).unwrap();
+        assert_eq!(proof.storage_proof[0].value, H256::from_low_u64_be(0x2a));
+        assert_eq!(proof.storage_proof[1].value, H256::zero());
+        proof.verify(first_root).unwrap();
+        // the proof does not hold in a later state
+        assert!(proof.verify(second_root).is_err());
+
+        let proof = account_proof(Arc::clone(&db), second_root, sender, &keys).unwrap();
+        assert_eq!(proof.account.nonce, U256::from(2u64));
+        proof.verify(second_root).unwrap();
+
+        // an absent account is proved to be empty
+        let absent = H160::from_low_u64_be(0x5678);
+        let proof = account_proof(Arc::clone(&db), second_root, absent, &keys).unwrap();
+        assert_eq!(proof.account.code_hash, NIL_DATA);
+        proof.verify(second_root).unwrap();
+
+        let mut tampered = account_proof(Arc::clone(&db), first_root, contract, &keys).unwrap();
+        tampered.storage_proof[0].value = H256::from_low_u64_be(0x2b);
+        assert!(tampered.verify(first_root).is_err());
+
+        dir.close().unwrap();
+    }
+}
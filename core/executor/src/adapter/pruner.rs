@@ -0,0 +1,74 @@
+use std::{sync::Arc, thread, time::Duration};
+
+use common_apm::metrics::storage::on_storage_prune_state;
+use common_apm::Instant;
+use protocol::types::BlockNumber;
+use protocol::ProtocolResult;
+
+use crate::adapter::RocksTrieDB;
+
+const PRUNE_INTERVAL: Duration = Duration::from_secs(3);
+/// The max number of blocks pruned in a round, so that a node which is far
+/// behind does not hold the refcount lock for too long.
+const MAX_PRUNE_BLOCKS: u64 = 100;
+
+/// The pruner deletes the stale trie nodes of the blocks older than the kept
+/// window in the background.
+pub struct TriePruner {
+    db:          Arc<RocksTrieDB>,
+    keep_blocks: u64,
+}
+
+impl TriePruner {
+    pub fn new(db: Arc<RocksTrieDB>, keep_blocks: u64) -> Self {
+        TriePruner {
+            db,
+            keep_blocks: keep_blocks.max(1),
+        }
+    }
+
+    pub fn spawn(self) -> thread::JoinHandle<()> {
+        thread::Builder::new()
+            .name("trie-pruner".to_string())
+            .spawn(move || loop {
+                thread::sleep(PRUNE_INTERVAL);
+
+                if let Err(e) = self.prune_round() {
+                    log::error!("[executor] prune trie error {:?}", e);
+                }
+            })
+            .expect("spawn trie pruner")
+    }
+
+    fn prune_round(&self) -> ProtocolResult<()> {
+        let latest = self.db.latest_journal();
+        if latest <= self.keep_blocks {
+            return Ok(());
+        }
+
+        let target = latest - self.keep_blocks;
+        let start = match self.db.pruned_number()? {
+            Some(pruned) => pruned + 1,
+            None => return Ok(()),
+        };
+
+        for number in start..=target.min(start + MAX_PRUNE_BLOCKS - 1) {
+            self.prune_block(number)?;
+        }
+
+        Ok(())
+    }
+
+    fn prune_block(&self, number: BlockNumber) -> ProtocolResult<()> {
+        let inst = Instant::now();
+        let deleted = self.db.prune(number)?;
+        on_storage_prune_state(number, deleted, inst.elapsed());
+
+        log::debug!(
+            "[executor] pruned {} stale trie nodes of block {}",
+            deleted,
+            number
+        );
+        Ok(())
+    }
+}
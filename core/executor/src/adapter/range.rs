@@ -0,0 +1,237 @@
+use std::sync::Arc;
+
+use cita_trie::{TrieError, DB as TrieDB};
+use rlp::{DecoderError, Prototype, Rlp};
+
+use protocol::types::{MerkleRoot, StateEntry, StateRange, H256, RLP_NULL};
+use protocol::ProtocolResult;
+
+use crate::adapter::trie::{MPTTrie, MPTTrieError};
+
+/// Collect at most `limit` entries of the trie with the given root whose keys
+/// are not less than `start`, in key order. The first and the last entries are
+/// proved against the root.
+pub fn state_range<DB: TrieDB>(
+    db: Arc<DB>,
+    root: MerkleRoot,
+    start: &[u8],
+    limit: usize,
+) -> ProtocolResult<StateRange> {
+    if root == RLP_NULL || limit == 0 {
+        return Ok(StateRange::default());
+    }
+
+    // Collect one more entry than required to know whether the range is over.
+    let mut walker = RangeWalker {
+        db:      db.as_ref(),
+        start:   to_nibbles(start),
+        limit:   limit + 1,
+        entries: Vec::new(),
+    };
+    let node = walker.load(root)?;
+    walker.walk(&node, &mut Vec::new())?;
+
+    let mut entries = walker.entries;
+    let more = entries.len() > limit;
+    entries.truncate(limit);
+
+    let (first_proof, last_proof) = match (entries.first(), entries.last()) {
+        (Some(first), Some(last)) => {
+            let trie = MPTTrie::from_root(root, db)?;
+            (trie.get_proof(&first.key)?, trie.get_proof(&last.key)?)
+        }
+        _ => (Vec::new(), Vec::new()),
+    };
+
+    Ok(StateRange {
+        entries,
+        more,
+        first_proof,
+        last_proof,
+    })
+}
+
+/// A depth-first walker over the raw trie nodes. Children are visited in
+/// nibble order so the entries come out sorted by key, and the subtrees whose
+/// keys are all less than the start key are never loaded.
+struct RangeWalker<'a, DB> {
+    db:      &'a DB,
+    start:   Vec<u8>,
+    limit:   usize,
+    entries: Vec<StateEntry>,
+}
+
+impl<'a, DB: TrieDB> RangeWalker<'a, DB> {
+    fn load(&self, hash: H256) -> Result<Vec<u8>, MPTTrieError> {
+        self.db
+            .get(hash.as_bytes())
+            .map_err(|e| MPTTrieError::Trie(TrieError::DB(e.to_string())))?
+            .ok_or(MPTTrieError::MissingNode(hash))
+    }
+
+    fn walk(&mut self, node: &[u8], path: &mut Vec<u8>) -> Result<(), MPTTrieError> {
+        if self.entries.len() >= self.limit || self.is_before_start(path) {
+            return Ok(());
+        }
+
+        let rlp = Rlp::new(node);
+        match rlp.prototype()? {
+            // A leaf or an extension node.
+            Prototype::List(2) => {
+                let (nibbles, is_leaf) = decode_path(rlp.at(0)?.data()?);
+                let len = path.len();
+                path.extend_from_slice(&nibbles);
+
+                if is_leaf {
+                    self.push(path, rlp.at(1)?.data()?);
+                } else {
+                    self.walk_child(&rlp.at(1)?, path)?;
+                }
+
+                path.truncate(len);
+            }
+
+            // A branch node, a key ending here sorts before all its children.
+            Prototype::List(17) => {
+                self.push(path, rlp.at(16)?.data()?);
+
+                for i in 0..16u8 {
+                    path.push(i);
+                    self.walk_child(&rlp.at(i as usize)?, path)?;
+                    path.pop();
+                }
+            }
+
+            _ => return Err(DecoderError::RlpExpectedToBeList.into()),
+        }
+
+        Ok(())
+    }
+
+    /// A child is either an inline node or the hash of a node.
+    fn walk_child(&mut self, item: &Rlp, path: &mut Vec<u8>) -> Result<(), MPTTrieError> {
+        if item.is_list() {
+            return self.walk(item.as_raw(), path);
+        }
+
+        match item.data()? {
+            [] => Ok(()),
+            hash if hash.len() == H256::len_bytes() => {
+                let node = self.load(H256::from_slice(hash))?;
+                self.walk(&node, path)
+            }
+            _ => Err(DecoderError::Custom("invalid child reference").into()),
+        }
+    }
+
+    fn push(&mut self, path: &[u8], value: &[u8]) {
+        if value.is_empty() || path < self.start.as_slice() || self.entries.len() >= self.limit {
+            return;
+        }
+
+        self.entries.push(StateEntry {
+            key:   from_nibbles(path).into(),
+            value: value.to_vec().into(),
+        });
+    }
+
+    /// Whether all the keys under the path are less than the start key.
+    fn is_before_start(&self, path: &[u8]) -> bool {
+        let len = path.len().min(self.start.len());
+        path[..len] < self.start[..len]
+    }
+}
+
+fn to_nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect()
+}
+
+fn from_nibbles(nibbles: &[u8]) -> Vec<u8> {
+    nibbles
+        .chunks(2)
+        .map(|n| (n[0] << 4) | n.get(1).copied().unwrap_or_default())
+        .collect()
+}
+
+/// Decode a hex-prefix encoded path into nibbles and the leaf flag.
+fn decode_path(compact: &[u8]) -> (Vec<u8>, bool) {
+    let flag = match compact.first() {
+        Some(b) => b >> 4,
+        None => return (Vec::new(), false),
+    };
+
+    let mut nibbles = Vec::with_capacity(compact.len() * 2);
+    if flag & 1 == 1 {
+        nibbles.push(compact[0] & 0x0f);
+    }
+    nibbles.extend(to_nibbles(&compact[1..]));
+
+    (nibbles, flag & 2 == 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapter::RocksTrieDB;
+    use getrandom::getrandom;
+    use protocol::types::Bytes;
+
+    fn rand_bytes(len: usize) -> Vec<u8> {
+        let mut ret = vec![0u8; len];
+        getrandom(&mut ret).unwrap();
+        ret
+    }
+
+    #[test]
+    fn test_state_range() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Arc::new(
+            RocksTrieDB::new(dir.path(), Default::default(), 100, Default::default()).unwrap(),
+        );
+        let mut mpt = MPTTrie::new(Arc::clone(&db));
+
+        let mut kvs = (0..300)
+            .map(|_| (rand_bytes(32), rand_bytes(40)))
+            .collect::<Vec<_>>();
+        for (k, v) in kvs.iter() {
+            mpt.insert(k, v).unwrap();
+        }
+        let root = mpt.commit().unwrap();
+        kvs.sort();
+
+        let mut start = Vec::new();
+        let mut collected = Vec::new();
+        loop {
+            let range = state_range(Arc::clone(&db), root, &start, 64).unwrap();
+            let first = range.entries.first().unwrap();
+            let last = range.entries.last().unwrap();
+            assert_eq!(
+                MPTTrie::verify_proof(root, &first.key, &range.first_proof).unwrap(),
+                Some(first.value.clone())
+            );
+            assert_eq!(
+                MPTTrie::verify_proof(root, &last.key, &range.last_proof).unwrap(),
+                Some(last.value.clone())
+            );
+
+            start = last.key.to_vec();
+            start.push(0);
+            collected.extend(
+                range
+                    .entries
+                    .iter()
+                    .map(|e| (e.key.to_vec(), e.value.to_vec())),
+            );
+
+            if !range.more {
+                break;
+            }
+        }
+        assert_eq!(collected, kvs);
+
+        let tampered = vec![Bytes::from(rand_bytes(100))];
+        assert!(MPTTrie::verify_proof(root, &kvs[0].0, &tampered).is_err());
+
+        dir.close().unwrap();
+    }
+}
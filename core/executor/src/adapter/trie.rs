@@ -1,10 +1,10 @@
 use std::sync::Arc;
 
-use cita_trie::{PatriciaTrie, Trie, TrieError, DB as TrieDB};
+use cita_trie::{MemoryDB, PatriciaTrie, Trie, TrieError, DB as TrieDB};
 use hasher::HasherKeccak;
 
 use protocol::codec::hex_encode;
-use protocol::types::{Bytes, MerkleRoot};
+use protocol::types::{Bytes, MerkleRoot, H256};
 use protocol::{Display, From, ProtocolError, ProtocolErrorKind, ProtocolResult};
 
 lazy_static::lazy_static! {
@@ -52,11 +52,46 @@ impl<DB: TrieDB> MPTTrie<DB> {
         }
     }
 
+    /// Iterate over all the key-value pairs in the trie.
+    pub fn iter(&self) -> impl Iterator<Item = (Vec<u8>, Vec<u8>)> + '_ {
+        self.0.iter()
+    }
+
     pub fn commit(&mut self) -> ProtocolResult<MerkleRoot> {
         Ok(MerkleRoot::from_slice(
             &self.0.root().map_err(MPTTrieError::from)?,
         ))
     }
+
+    /// Return the nodes on the path from the root to the given key. The proof
+    /// also works for absent keys.
+    pub fn get_proof(&self, key: &[u8]) -> ProtocolResult<Vec<Bytes>> {
+        Ok(self
+            .0
+            .get_proof(key)
+            .map_err(MPTTrieError::from)?
+            .into_iter()
+            .map(Bytes::from)
+            .collect())
+    }
+}
+
+impl MPTTrie<MemoryDB> {
+    /// Verify a proof generated by [`MPTTrie::get_proof`] and return the value
+    /// of the key, or `None` if the proof shows the key is absent.
+    pub fn verify_proof(
+        root: MerkleRoot,
+        key: &[u8],
+        proof: &[Bytes],
+    ) -> ProtocolResult<Option<Bytes>> {
+        let trie = PatriciaTrie::new(Arc::new(MemoryDB::new(true)), Arc::clone(&HASHER_INST));
+        let proof = proof.iter().map(|node| node.to_vec()).collect();
+
+        Ok(trie
+            .verify_proof(root.as_bytes(), key, proof)
+            .map_err(|_| MPTTrieError::InvalidProof(Bytes::copy_from_slice(key)))?
+            .map(Bytes::from))
+    }
 }
 
 #[derive(Debug, Display, From)]
@@ -66,6 +101,15 @@ pub enum MPTTrieError {
 
     #[display(fmt = "Remove {:?} failed", _0)]
     RemoveFailed(String),
+
+    #[display(fmt = "Invalid proof of key {:?}", _0)]
+    InvalidProof(Bytes),
+
+    #[display(fmt = "Missing trie node {:?}", _0)]
+    MissingNode(H256),
+
+    #[display(fmt = "Decode trie node {:?}", _0)]
+    DecodeNode(rlp::DecoderError),
 }
 
 impl std::error::Error for MPTTrieError {}
@@ -91,7 +135,7 @@ mod tests {
     #[test]
     fn test_mpt_cache() {
         let dir = tempfile::tempdir().unwrap();
-        let db = RocksTrieDB::new(dir.path(), Default::default(), 100).unwrap();
+        let db = RocksTrieDB::new(dir.path(), Default::default(), 100, Default::default()).unwrap();
         let mut mpt = MPTTrie::new(Arc::new(db));
 
         let key_1 = rand_bytes(5);
@@ -1,22 +1,38 @@
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::{collections::HashMap, fs, io, path::Path, sync::Arc};
 
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use rand::{rngs::SmallRng, Rng, SeedableRng};
-use rocksdb::ops::{Get, Open, Put, WriteOps};
-use rocksdb::{FullOptions, Options, WriteBatch, DB};
+use rlp::Rlp;
+use rocksdb::ops::{Get, Iterate, Open, Put, WriteOps};
+use rocksdb::{FullOptions, IteratorMode, Options, WriteBatch, DB};
 
 use common_apm::metrics::storage::{on_storage_get_state, on_storage_put_state};
 use common_apm::Instant;
-use common_config_parser::types::ConfigRocksDB;
+use common_config_parser::types::{ConfigRocksDB, ExecutorMode};
+use protocol::types::BlockNumber;
 use protocol::{Display, From, ProtocolError, ProtocolErrorKind, ProtocolResult};
 
+use crate::adapter::TrieJournal;
+
 // 49999 is the largest prime number within 50000.
 const RAND_SEED: u64 = 49999;
 
+// The trie nodes are stored by their 32 bytes hash, so the prefixed keys below
+// never collide with them.
+const REFCOUNT_PREFIX: &[u8] = b"refcount";
+const JOURNAL_PREFIX: &[u8] = b"journal";
+const PRUNED_NUMBER_KEY: &[u8] = b"pruned_number";
+const MODE_KEY: &[u8] = b"executor_mode";
+
 pub struct RocksTrieDB {
-    db:         Arc<DB>,
-    cache:      RwLock<HashMap<Vec<u8>, Vec<u8>>>,
-    cache_size: usize,
+    db:             Arc<DB>,
+    cache:          RwLock<HashMap<Vec<u8>, Vec<u8>>>,
+    cache_size:     usize,
+    mode:           ExecutorMode,
+    // Serializes the read-modify-write of the reference counts.
+    refcount_lock:  Mutex<()>,
+    latest_journal: AtomicU64,
 }
 
 impl RocksTrieDB {
@@ -24,6 +40,7 @@ impl RocksTrieDB {
         path: P,
         config: ConfigRocksDB,
         cache_size: usize,
+        mode: ExecutorMode,
     ) -> ProtocolResult<Self> {
         if !path.as_ref().is_dir() {
             fs::create_dir_all(&path).map_err(RocksTrieDBError::CreateDB)?;
@@ -31,15 +48,128 @@ impl RocksTrieDB {
 
         let opts = rocksdb_opts(config)?;
         let db = Arc::new(DB::open(&opts, path).map_err(RocksTrieDBError::from)?);
+        check_mode(&db, mode)?;
 
         // Init HashMap with capacity 2 * cache_size to avoid reallocate memory.
         Ok(RocksTrieDB {
             db,
             cache: RwLock::new(HashMap::with_capacity(cache_size + cache_size)),
             cache_size,
+            mode,
+            refcount_lock: Mutex::new(()),
+            latest_journal: AtomicU64::new(0),
         })
     }
 
+    pub fn mode(&self) -> ExecutorMode {
+        self.mode
+    }
+
+    /// The number of the latest block whose journal is committed since the
+    /// node started.
+    pub fn latest_journal(&self) -> BlockNumber {
+        self.latest_journal.load(Ordering::Acquire)
+    }
+
+    /// The number of the latest block whose stale trie nodes are pruned.
+    pub fn pruned_number(&self) -> ProtocolResult<Option<BlockNumber>> {
+        Ok(self.get_u64(PRUNED_NUMBER_KEY)?)
+    }
+
+    /// Delete the trie nodes recorded in the journal of the given block whose
+    /// reference count drops to zero, and return the number of deleted nodes.
+    pub fn prune(&self, number: BlockNumber) -> ProtocolResult<u64> {
+        let journal_key = journal_key(number);
+        let removed = match self.db.get(&journal_key).map_err(to_store_err)? {
+            Some(raw) => Rlp::new(&raw)
+                .as_list::<Vec<u8>>()
+                .map_err(RocksTrieDBError::from)?,
+            None => Vec::new(),
+        };
+
+        let mut counts: HashMap<&[u8], u64> = HashMap::new();
+        for key in removed.iter() {
+            *counts.entry(key.as_slice()).or_default() += 1;
+        }
+
+        let mut batch = WriteBatch::default();
+        let mut deleted = Vec::new();
+        {
+            let _guard = self.refcount_lock.lock();
+
+            for (key, n) in counts.into_iter() {
+                let refcount_key = refcount_key(key);
+                match self.get_u64(&refcount_key)? {
+                    // Every node of a pruned trie db is counted, see
+                    // `check_mode`, keep the unknown ones anyway.
+                    None => continue,
+                    Some(count) if count > n => {
+                        batch
+                            .put(&refcount_key, &(count - n).to_be_bytes())
+                            .map_err(RocksTrieDBError::from)?;
+                    }
+                    Some(_) => {
+                        batch
+                            .delete(&refcount_key)
+                            .map_err(RocksTrieDBError::from)?;
+                        batch.delete(key).map_err(RocksTrieDBError::from)?;
+                        deleted.push(key.to_vec());
+                    }
+                }
+            }
+
+            batch.delete(&journal_key).map_err(RocksTrieDBError::from)?;
+            batch
+                .put(PRUNED_NUMBER_KEY, &number.to_be_bytes())
+                .map_err(RocksTrieDBError::from)?;
+            self.db.write(&batch).map_err(to_store_err)?;
+        }
+
+        {
+            let mut cache = self.cache.write();
+            for key in deleted.iter() {
+                cache.remove(key);
+            }
+        }
+
+        Ok(deleted.len() as u64)
+    }
+
+    fn get_u64(&self, key: &[u8]) -> Result<Option<u64>, RocksTrieDBError> {
+        match self.db.get(key).map_err(to_store_err)? {
+            Some(raw) => {
+                let bytes: [u8; 8] = raw
+                    .as_ref()
+                    .try_into()
+                    .map_err(|_| RocksTrieDBError::InvalidCount(raw.to_vec()))?;
+                Ok(Some(u64::from_be_bytes(bytes)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Count the references of the inserted nodes, a node is deleted by the
+    /// pruner once its reference count drops to zero. The caller must hold the
+    /// `refcount_lock` until the batch is written.
+    fn increase_refcounts(
+        &self,
+        batch: &mut WriteBatch,
+        keys: &[Vec<u8>],
+    ) -> Result<(), RocksTrieDBError> {
+        let mut counts: HashMap<&[u8], u64> = HashMap::new();
+        for key in keys.iter() {
+            *counts.entry(key.as_slice()).or_default() += 1;
+        }
+
+        for (key, n) in counts.into_iter() {
+            let refcount_key = refcount_key(key);
+            let count = self.get_u64(&refcount_key)?.unwrap_or_default();
+            batch.put(&refcount_key, &(count + n).to_be_bytes())?;
+        }
+
+        Ok(())
+    }
+
     pub fn inner_db(&self) -> Arc<DB> {
         Arc::clone(&self.db)
     }
@@ -100,6 +230,10 @@ impl cita_trie::DB for RocksTrieDB {
     }
 
     fn insert(&self, key: Vec<u8>, value: Vec<u8>) -> Result<(), Self::Error> {
+        if self.mode == ExecutorMode::Pruned {
+            return self.insert_batch(vec![key], vec![value]);
+        }
+
         let inst = Instant::now();
         let size = key.len() + value.len();
 
@@ -122,6 +256,14 @@ impl cita_trie::DB for RocksTrieDB {
         let mut total_size = 0;
         let mut batch = WriteBatch::default();
 
+        let refcount_guard = if self.mode == ExecutorMode::Pruned {
+            let guard = self.refcount_lock.lock();
+            self.increase_refcounts(&mut batch, &keys)?;
+            Some(guard)
+        } else {
+            None
+        };
+
         {
             let mut cache = self.cache.write();
             for (key, val) in keys.into_iter().zip(values.into_iter()) {
@@ -134,11 +276,14 @@ impl cita_trie::DB for RocksTrieDB {
 
         let inst = Instant::now();
         self.db.write(&batch).map_err(to_store_err)?;
+        drop(refcount_guard);
         on_storage_put_state(inst.elapsed(), total_size as f64);
 
         self.flush()
     }
 
+    // The nodes shared by the old states can not be deleted at once, a pruned
+    // node deletes them in the background by the journals.
     fn remove(&self, _key: &[u8]) -> Result<(), Self::Error> {
         Ok(())
     }
@@ -169,6 +314,75 @@ impl cita_trie::DB for RocksTrieDB {
     }
 }
 
+impl TrieJournal for RocksTrieDB {
+    fn commit_journal(&self, number: BlockNumber, removed: Vec<Vec<u8>>) -> ProtocolResult<()> {
+        if self.mode == ExecutorMode::Archive {
+            return Ok(());
+        }
+
+        let mut batch = WriteBatch::default();
+        batch
+            .put(
+                &journal_key(number),
+                &rlp::encode_list::<Vec<u8>, _>(&removed),
+            )
+            .map_err(RocksTrieDBError::from)?;
+
+        // Start pruning from the first journaled block.
+        if self.get_u64(PRUNED_NUMBER_KEY)?.is_none() {
+            batch
+                .put(PRUNED_NUMBER_KEY, &number.saturating_sub(1).to_be_bytes())
+                .map_err(RocksTrieDBError::from)?;
+        }
+
+        self.db.write(&batch).map_err(to_store_err)?;
+        self.latest_journal.fetch_max(number, Ordering::AcqRel);
+
+        Ok(())
+    }
+}
+
+/// The trie nodes written in the archive mode are not counted, and a node
+/// inserted again after switching to the pruned mode would be deleted while
+/// the old states still reference it. So a non-empty trie db that has ever
+/// been opened in the archive mode can not be pruned.
+///
+/// The mode is persisted on the first open and compared against on the
+/// following ones, the keys are only read when the mode changes.
+fn check_mode(db: &DB, mode: ExecutorMode) -> Result<(), RocksTrieDBError> {
+    let flag: &[u8] = match mode {
+        ExecutorMode::Archive => &[0],
+        ExecutorMode::Pruned => &[1],
+    };
+    if db.get(MODE_KEY).map_err(to_store_err)?.as_deref() == Some(flag) {
+        return Ok(());
+    }
+
+    // The trie db of an old version has no mode flag, it was written in the
+    // archive mode.
+    if mode == ExecutorMode::Pruned && !is_empty(db) {
+        return Err(RocksTrieDBError::PruneArchive);
+    }
+
+    db.put(MODE_KEY, flag).map_err(to_store_err)
+}
+
+/// Whether the trie db holds nothing but the mode flag, which is told by its
+/// first two keys.
+fn is_empty(db: &DB) -> bool {
+    db.iterator(IteratorMode::Start)
+        .take(2)
+        .all(|(key, _)| key.as_ref() == MODE_KEY)
+}
+
+fn refcount_key(key: &[u8]) -> Vec<u8> {
+    [REFCOUNT_PREFIX, key].concat()
+}
+
+fn journal_key(number: BlockNumber) -> Vec<u8> {
+    [JOURNAL_PREFIX, &number.to_be_bytes()].concat()
+}
+
 fn rocksdb_opts(config: ConfigRocksDB) -> ProtocolResult<Options> {
     let mut opts = if let Some(ref file) = config.options_file {
         let cache_size = match config.cache_size {
@@ -222,6 +436,15 @@ pub enum RocksTrieDBError {
 
     #[display(fmt = "Create DB path {}", _0)]
     CreateDB(io::Error),
+
+    #[display(fmt = "Decode journal {:?}", _0)]
+    DecodeJournal(rlp::DecoderError),
+
+    #[display(fmt = "Invalid count {:?}", _0)]
+    InvalidCount(Vec<u8>),
+
+    #[display(fmt = "Cannot switch a non-empty archive trie db to the pruned mode")]
+    PruneArchive,
 }
 
 impl std::error::Error for RocksTrieDBError {}
@@ -271,7 +494,8 @@ mod tests {
         let val_2 = rand_bytes(256);
 
         let dir = tempfile::tempdir().unwrap();
-        let trie = RocksTrieDB::new(dir.path(), Default::default(), 100).unwrap();
+        let trie =
+            RocksTrieDB::new(dir.path(), Default::default(), 100, Default::default()).unwrap();
 
         trie.insert(key_1.clone(), val_1.clone()).unwrap();
         trie.insert(key_2.clone(), val_2.clone()).unwrap();
@@ -298,7 +522,8 @@ mod tests {
         let val_2 = rand_bytes(256);
 
         let dir = tempfile::tempdir().unwrap();
-        let trie = RocksTrieDB::new(dir.path(), Default::default(), 100).unwrap();
+        let trie =
+            RocksTrieDB::new(dir.path(), Default::default(), 100, Default::default()).unwrap();
 
         trie.insert(key_1.clone(), val_1.clone()).unwrap();
         trie.insert(key_2.clone(), val_2.clone()).unwrap();
@@ -334,6 +559,69 @@ mod tests {
         dir.close().unwrap();
     }
 
+    #[test]
+    fn test_trie_prune() {
+        let key_1 = rand_bytes(32);
+        let key_2 = rand_bytes(32);
+        let val = rand_bytes(64);
+
+        let dir = tempfile::tempdir().unwrap();
+        let trie =
+            RocksTrieDB::new(dir.path(), Default::default(), 100, ExecutorMode::Pruned).unwrap();
+
+        trie.insert(key_1.clone(), val.clone()).unwrap();
+        trie.insert_batch(vec![key_2.clone(), key_2.clone()], vec![
+            val.clone(),
+            val.clone(),
+        ])
+        .unwrap();
+        trie.commit_journal(1, vec![]).unwrap();
+        trie.commit_journal(2, vec![key_1.clone(), key_2.clone()])
+            .unwrap();
+        assert_eq!(trie.latest_journal(), 2);
+        assert_eq!(trie.pruned_number().unwrap(), Some(0));
+
+        // Removing a node does not delete it before the block is pruned.
+        trie.remove(&key_1).unwrap();
+        assert!(trie.contains(&key_1).unwrap());
+
+        assert_eq!(trie.prune(1).unwrap(), 0);
+        assert_eq!(trie.prune(2).unwrap(), 1);
+        assert_eq!(trie.pruned_number().unwrap(), Some(2));
+
+        // The second node is still referenced once.
+        assert!(trie.get(&key_1).unwrap().is_none());
+        assert_eq!(trie.get(&key_2).unwrap(), Some(val));
+
+        dir.close().unwrap();
+    }
+
+    #[test]
+    fn test_refuse_pruning_archive() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let trie = RocksTrieDB::new(dir.path(), Default::default(), 100, ExecutorMode::Archive)
+                .unwrap();
+            trie.insert(rand_bytes(32), rand_bytes(64)).unwrap();
+        }
+
+        assert!(
+            RocksTrieDB::new(dir.path(), Default::default(), 100, ExecutorMode::Pruned).is_err()
+        );
+        assert!(
+            RocksTrieDB::new(dir.path(), Default::default(), 100, ExecutorMode::Archive).is_ok()
+        );
+
+        // An empty trie db can be opened in either mode.
+        let dir = tempfile::tempdir().unwrap();
+        {
+            RocksTrieDB::new(dir.path(), Default::default(), 100, ExecutorMode::Archive).unwrap();
+        }
+        assert!(
+            RocksTrieDB::new(dir.path(), Default::default(), 100, ExecutorMode::Pruned).is_ok()
+        );
+    }
+
     #[bench]
     fn bench_rand(b: &mut Bencher) {
         b.iter(|| {
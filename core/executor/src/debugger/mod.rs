@@ -31,7 +31,9 @@ impl EvmDebugger {
         let rocks_adapter = Arc::new(RocksAdapter::new(db_data_path, Default::default()).unwrap());
         let mut db_state_path = db_path.to_string();
         db_state_path.push_str("/state");
-        let trie = Arc::new(RocksTrieDB::new(db_state_path, Default::default(), 1000).unwrap());
+        let trie = Arc::new(
+            RocksTrieDB::new(db_state_path, Default::default(), 1000, Default::default()).unwrap(),
+        );
 
         let mut mpt = MPTTrie::new(Arc::clone(&trie));
 
@@ -78,7 +80,7 @@ impl EvmDebugger {
             logs:                   vec![],
         };
 
-        AxonExecutorAdapter::from_root(
+        AxonExecutorAdapter::without_block_hashes(
             self.state_root,
             Arc::clone(&self.trie_db),
             Arc::clone(&self.storage),
@@ -1,5 +1,6 @@
 #![feature(test)]
 
+mod access_list;
 pub mod adapter;
 #[cfg(test)]
 mod debugger;
@@ -7,12 +8,19 @@ mod precompiles;
 mod system;
 #[cfg(test)]
 mod tests;
+pub mod tracer;
 mod vm;
 
-pub use crate::adapter::{AxonExecutorAdapter, MPTTrie, RocksTrieDB};
-pub use crate::{system::NATIVE_TOKEN_ISSUE_ADDRESS, vm::code_address};
+pub use crate::adapter::{
+    account_proof, state_range, AxonExecutorAdapter, JournalTrieDB, MPTTrie, RocksTrieDB,
+    TrieJournal, TriePruner, BLOCK_HASH_HISTORY,
+};
+pub use crate::{
+    system::NATIVE_TOKEN_ISSUE_ADDRESS,
+    vm::{code_address, effective_gas_price},
+};
 
-use std::collections::BTreeMap;
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
 
 use evm::executor::stack::{MemoryStackState, StackExecutor, StackSubstateMetadata};
 use evm::CreateScheme;
@@ -21,26 +29,100 @@ use common_merkle::Merkle;
 use protocol::codec::ProtocolCodec;
 use protocol::traits::{ApplyBackend, Backend, Executor, ExecutorAdapter as Adapter};
 use protocol::types::{
-    Account, Config, ExecResp, Hasher, SignedTransaction, TransactionAction, TxResp, H160,
-    NIL_DATA, RLP_NULL, U256,
+    AccessList, Account, Config, ExecResp, Hash, Hasher, SignedTransaction, TraceResult,
+    TracerType, TransactionAction, TxResp, H160, NIL_DATA, RLP_NULL, U256,
 };
 
+use crate::access_list::AccessListBackend;
+use crate::tracer::{into_inner, trace_using, AxonTracer, Tracer};
 use crate::{system::SystemExecutor, vm::EvmExecutor};
 
+/// The maximum number of the calls made to generate an access list. The list
+/// usually stays the same after the second call.
+const MAX_ACCESS_LIST_ROUNDS: usize = 8;
+
 #[derive(Default)]
 pub struct AxonExecutor;
 
-impl Executor for AxonExecutor {
-    // Used for query data API, this function will not modify the world state.
-    fn call<B: Backend>(
+impl AxonExecutor {
+    /// Replay the transactions of a block in order and trace them with the
+    /// given tracer. If `target` is set, only the transaction with that hash is
+    /// traced and the replay stops after it.
+    pub fn trace<B: Backend + ApplyBackend + Adapter>(
+        &self,
+        backend: &mut B,
+        txs: Vec<SignedTransaction>,
+        target: Option<Hash>,
+        tracer_type: &TracerType,
+    ) -> Vec<(Hash, TraceResult)> {
+        let mut res = Vec::new();
+        let evm_executor = EvmExecutor::new();
+        let sys_executor = SystemExecutor::new();
+
+        for tx in txs.into_iter() {
+            let tx_hash = tx.transaction.hash;
+            let is_target = target.is_none() || target == Some(tx_hash);
+            let base_fee = backend.block_base_fee_per_gas();
+            backend.set_gas_price(effective_gas_price(&tx.transaction.unsigned, base_fee));
+
+            if is_call_system_script(&tx.transaction.unsigned.action) {
+                let resp = sys_executor.inner_exec(backend, tx);
+                if is_target {
+                    res.push((tx_hash, Tracer::new(tracer_type).finish(&resp)));
+                }
+            } else if is_target {
+                let tracer = Rc::new(RefCell::new(Tracer::new(tracer_type)));
+                let resp = trace_using(&tracer, || {
+                    evm_executor
+                        .inner_exec_with_hook(backend, tx, |b| tracer.borrow_mut().before_apply(b))
+                });
+                res.push((tx_hash, into_inner(tracer).finish(&resp)));
+            } else {
+                evm_executor.inner_exec(backend, tx);
+            }
+
+            if target == Some(tx_hash) {
+                break;
+            }
+        }
+
+        res
+    }
+
+    /// Trace a call with the given tracer, the world state is not modified.
+    pub fn trace_call<B: Backend>(
+        &self,
+        backend: &mut B,
+        from: Option<H160>,
+        to: Option<H160>,
+        data: Vec<u8>,
+        tracer_type: &TracerType,
+    ) -> TraceResult {
+        let tracer = Rc::new(RefCell::new(Tracer::new(tracer_type)));
+        let resp = trace_using(&tracer, || self.call(backend, from, to, data));
+        // The call never applies its changes, so the backend still holds the
+        // state before the call.
+        tracer.borrow_mut().before_apply(backend);
+        into_inner(tracer).finish(&resp)
+    }
+
+    /// Make a call with the given gas limit and access list, the world state
+    /// is not modified.
+    pub fn call_with<B: Backend>(
         &self,
         backend: &mut B,
         from: Option<H160>,
         to: Option<H160>,
         data: Vec<u8>,
+        gas_limit: u64,
+        access_list: AccessList,
     ) -> TxResp {
+        let access_list = access_list
+            .into_iter()
+            .map(|item| (item.address, item.storage_keys))
+            .collect::<Vec<_>>();
         let config = Config::london();
-        let metadata = StackSubstateMetadata::new(u64::MAX, &config);
+        let metadata = StackSubstateMetadata::new(gas_limit, &config);
         let state = MemoryStackState::new(metadata, backend);
         let precompiles = BTreeMap::new();
         let mut executor = StackExecutor::new_with_precompiles(state, &config, &precompiles);
@@ -50,16 +132,16 @@ impl Executor for AxonExecutor {
                 *addr,
                 U256::default(),
                 data,
-                u64::MAX,
-                Vec::new(),
+                gas_limit,
+                access_list,
             )
         } else {
             executor.transact_create(
                 from.unwrap_or_default(),
                 U256::default(),
                 data,
-                u64::MAX,
-                Vec::new(),
+                gas_limit,
+                access_list,
             )
         };
 
@@ -84,6 +166,57 @@ impl Executor for AxonExecutor {
         }
     }
 
+    /// Generate the access list of a call by recording the addresses and the
+    /// storage slots it reads. The call is repeated with the generated list
+    /// until the list no longer changes, and the last response is returned
+    /// with the list.
+    pub fn create_access_list<B: Backend>(
+        &self,
+        backend: &mut B,
+        from: Option<H160>,
+        to: Option<H160>,
+        data: Vec<u8>,
+        gas_limit: u64,
+    ) -> (AccessList, TxResp) {
+        let mut access_list = AccessList::new();
+        let mut round = 0;
+
+        loop {
+            let mut tracking = AccessListBackend::new(&*backend);
+            let resp = self.call_with(
+                &mut tracking,
+                from,
+                to,
+                data.clone(),
+                gas_limit,
+                access_list.clone(),
+            );
+
+            let mut excluded = vec![from.unwrap_or_default()];
+            excluded.extend(to.or_else(|| resp.code_address.map(Into::into)));
+            let accessed = tracking.into_access_list(&excluded);
+
+            round += 1;
+            if accessed == access_list || round >= MAX_ACCESS_LIST_ROUNDS {
+                return (access_list, resp);
+            }
+            access_list = accessed;
+        }
+    }
+}
+
+impl Executor for AxonExecutor {
+    // Used for query data API, this function will not modify the world state.
+    fn call<B: Backend>(
+        &self,
+        backend: &mut B,
+        from: Option<H160>,
+        to: Option<H160>,
+        data: Vec<u8>,
+    ) -> TxResp {
+        self.call_with(backend, from, to, data, u64::MAX, Vec::new())
+    }
+
     // Function execute returns exit_reason, ret_data and remain_gas.
     fn exec<B: Backend + ApplyBackend + Adapter>(
         &self,
@@ -99,7 +232,8 @@ impl Executor for AxonExecutor {
         let sys_executor = SystemExecutor::new();
 
         for tx in txs.into_iter() {
-            backend.set_gas_price(tx.transaction.unsigned.gas_price);
+            let base_fee = backend.block_base_fee_per_gas();
+            backend.set_gas_price(effective_gas_price(&tx.transaction.unsigned, base_fee));
             let mut r = if is_call_system_script(&tx.transaction.unsigned.action) {
                 sys_executor.inner_exec(backend, tx)
             } else {
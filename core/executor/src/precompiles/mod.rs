@@ -22,8 +22,8 @@ use evm::{Context, ExitError};
 use protocol::types::H160;
 
 use crate::precompiles::{
-    blake2_f::Blake2F, ec_add::EcAdd, ec_mul::EcMul, ec_pairing::EcPairing, ecrecover::EcRecover,
-    identity::Identity, modexp::ModExp, ripemd160::Ripemd160, sha256::Sha256,
+    blake2_f::Blake2F, ckb_vm::CkbVM, ec_add::EcAdd, ec_mul::EcMul, ec_pairing::EcPairing,
+    ecrecover::EcRecover, identity::Identity, modexp::ModExp, ripemd160::Ripemd160, sha256::Sha256,
 };
 
 #[macro_export]
@@ -84,6 +84,14 @@ pub fn build_precompile_set() -> BTreeMap<H160, PrecompileFn> {
     precompiles!(EcRecover, Sha256, Ripemd160, Identity, ModExp, EcAdd, EcMul, EcPairing, Blake2F)
 }
 
+/// The addresses of all the precompile contracts, including the ones that
+/// are not part of the standard precompile set.
+pub fn precompile_addresses() -> Vec<H160> {
+    let mut ret = build_precompile_set().into_keys().collect::<Vec<_>>();
+    ret.push(CkbVM::ADDRESS);
+    ret
+}
+
 pub(crate) fn read_point(input: &[u8], start: usize) -> Result<G1, PrecompileFailure> {
     if input.len() < start + 64 {
         return err!("Invalid input length");
@@ -6,8 +6,8 @@ use std::str::FromStr;
 use evm::backend::{MemoryAccount, MemoryBackend, MemoryVicinity};
 
 use protocol::types::{
-    Bytes, ExitReason, ExitSucceed, Public, SignatureComponents, SignedTransaction, Transaction,
-    TransactionAction, UnverifiedTransaction, H160, H256, U256,
+    AccessListItem, Bytes, ExitReason, ExitSucceed, Public, SignatureComponents, SignedTransaction,
+    Transaction, TransactionAction, UnverifiedTransaction, H160, H256, U256,
 };
 use protocol::{codec::hex_decode, traits::Executor};
 
@@ -185,3 +185,85 @@ fn test_simplestorage() {
         0, 42
     ]);
 }
+
+#[test]
+fn test_create_access_list() {
+    let contract = H160::from_str("0x1000000000000000000000000000000000000000").unwrap();
+    let other = H160::from_str("0x2000000000000000000000000000000000000000").unwrap();
+    let sender = H160::from_str("0xf000000000000000000000000000000000000000").unwrap();
+
+    // PUSH1 0x00 SLOAD POP PUSH20 <other> BALANCE POP STOP
+    let mut code = hex_decode("6000545073").unwrap();
+    code.extend_from_slice(other.as_bytes());
+    code.extend_from_slice(&[0x31, 0x50, 0x00]);
+
+    let mut state = BTreeMap::new();
+    state.insert(contract, MemoryAccount {
+        nonce: U256::one(),
+        balance: U256::zero(),
+        storage: BTreeMap::new(),
+        code,
+    });
+    state.insert(sender, MemoryAccount {
+        nonce:   U256::one(),
+        balance: U256::max_value(),
+        storage: BTreeMap::new(),
+        code:    Vec::new(),
+    });
+
+    let vicinity = gen_vicinity();
+    let mut backend = MemoryBackend::new(&vicinity, state);
+    let executor = AxonExecutor::default();
+    let (access_list, r) = executor.create_access_list(
+        &mut backend,
+        Some(sender),
+        Some(contract),
+        Vec::new(),
+        100_000,
+    );
+    assert_eq!(r.exit_reason, ExitReason::Succeed(ExitSucceed::Stopped));
+    assert_eq!(access_list, vec![
+        AccessListItem {
+            address:      contract,
+            storage_keys: vec![H256::zero()],
+        },
+        AccessListItem {
+            address:      other,
+            storage_keys: Vec::new(),
+        },
+    ]);
+
+    // the gas limit is enforced
+    let r = executor.call_with(
+        &mut backend,
+        Some(sender),
+        Some(contract),
+        Vec::new(),
+        r.gas_used - 1,
+        access_list,
+    );
+    assert!(!r.exit_reason.is_succeed());
+}
+
+#[test]
+fn test_gas_price_below_base_fee() {
+    let sender = H160::from_str("0xf000000000000000000000000000000000000000").unwrap();
+    let mut state = BTreeMap::new();
+    state.insert(sender, MemoryAccount {
+        nonce:   U256::one(),
+        balance: U256::max_value(),
+        storage: BTreeMap::new(),
+        code:    Vec::new(),
+    });
+
+    let mut vicinity = gen_vicinity();
+    vicinity.block_base_fee_per_gas = U256::from(10);
+    let mut backend = MemoryBackend::new(&vicinity, state);
+    let mut tx = gen_tx(sender, H160::default(), 0, Vec::new());
+    tx.transaction.unsigned.gas_price = U256::from(9);
+
+    let r = EvmExecutor::new().inner_exec(&mut backend, tx);
+    assert!(r.exit_reason.is_error());
+    assert_eq!(r.gas_used, 0);
+    assert_eq!(backend.state()[&sender].balance, U256::max_value());
+}
@@ -0,0 +1,264 @@
+use evm::tracing::Event as EvmEvent;
+use evm::CreateScheme;
+use evm_gasometer::tracing::Event as GasometerEvent;
+use evm_runtime::tracing::Event as RuntimeEvent;
+
+use protocol::types::{CallFrame, Hex, TraceResult, TxResp, H160, U256};
+
+use crate::tracer::{exit_error, AxonTracer};
+
+/// The `callTracer` of geth, which records the call frames of a transaction
+/// as a tree.
+pub struct CallTracer {
+    only_top_call: bool,
+    depth:         usize,
+    stack:         Vec<CallFrame>,
+    root:          Option<CallFrame>,
+    transact:      Option<CallFrame>,
+}
+
+impl CallTracer {
+    pub fn new(only_top_call: bool) -> Self {
+        CallTracer {
+            only_top_call,
+            depth: 0,
+            stack: Vec::new(),
+            root: None,
+            transact: None,
+        }
+    }
+
+    fn push_frame(&mut self, frame: CallFrame) {
+        self.depth += 1;
+
+        // Only the frames of the top call are recorded if `only_top_call` is set.
+        if self.root.is_some() || (self.only_top_call && self.depth > 1) {
+            return;
+        }
+
+        self.stack.push(frame);
+    }
+
+    fn pop_frame(&mut self, output: Hex, error: Option<String>) {
+        let depth = self.depth;
+        self.depth = self.depth.saturating_sub(1);
+
+        if self.only_top_call && depth > 1 {
+            return;
+        }
+
+        let mut frame = match self.stack.pop() {
+            Some(frame) => frame,
+            None => return,
+        };
+
+        if frame.call_type == "CREATE" || frame.call_type == "CREATE2" {
+            if error.is_none() {
+                frame.output = Some(output);
+            }
+        } else {
+            frame.output = Some(output);
+        }
+        frame.error = error;
+
+        match self.stack.last_mut() {
+            Some(parent) => parent.calls.push(frame),
+            None => self.root = Some(frame),
+        }
+    }
+}
+
+impl AxonTracer for CallTracer {
+    fn on_evm_event(&mut self, event: EvmEvent) {
+        match event {
+            EvmEvent::TransactCall {
+                caller,
+                address,
+                value,
+                data,
+                gas_limit,
+            } => {
+                self.transact = Some(new_frame(
+                    "CALL",
+                    caller,
+                    Some(address),
+                    Some(value),
+                    gas_limit,
+                    data,
+                ))
+            }
+            EvmEvent::TransactCreate {
+                caller,
+                value,
+                init_code,
+                gas_limit,
+                address,
+            } => {
+                self.transact = Some(new_frame(
+                    "CREATE",
+                    caller,
+                    Some(address),
+                    Some(value),
+                    gas_limit,
+                    init_code,
+                ))
+            }
+            EvmEvent::TransactCreate2 {
+                caller,
+                value,
+                init_code,
+                gas_limit,
+                address,
+                ..
+            } => {
+                self.transact = Some(new_frame(
+                    "CREATE2",
+                    caller,
+                    Some(address),
+                    Some(value),
+                    gas_limit,
+                    init_code,
+                ))
+            }
+            EvmEvent::Call {
+                code_address,
+                transfer,
+                input,
+                target_gas,
+                is_static,
+                context,
+            } => {
+                let call_type = if is_static {
+                    "STATICCALL"
+                } else if context.address != code_address {
+                    if transfer.is_some() {
+                        "CALLCODE"
+                    } else {
+                        "DELEGATECALL"
+                    }
+                } else {
+                    "CALL"
+                };
+                let value = match call_type {
+                    "STATICCALL" => None,
+                    "DELEGATECALL" => Some(context.apparent_value),
+                    _ => Some(transfer.as_ref().map(|t| t.value).unwrap_or_default()),
+                };
+                let gas = target_gas
+                    .or_else(|| self.transact.as_ref().map(|t| t.gas.as_u64()))
+                    .unwrap_or_default();
+
+                self.push_frame(new_frame(
+                    call_type,
+                    context.caller,
+                    Some(code_address),
+                    value,
+                    gas,
+                    input,
+                ))
+            }
+            EvmEvent::Create {
+                caller,
+                address,
+                scheme,
+                value,
+                init_code,
+                target_gas,
+            } => {
+                let call_type = match scheme {
+                    CreateScheme::Create2 { .. } => "CREATE2",
+                    _ => "CREATE",
+                };
+                let gas = target_gas
+                    .or_else(|| self.transact.as_ref().map(|t| t.gas.as_u64()))
+                    .unwrap_or_default();
+
+                self.push_frame(new_frame(
+                    call_type,
+                    caller,
+                    Some(address),
+                    Some(value),
+                    gas,
+                    init_code,
+                ))
+            }
+            EvmEvent::Suicide {
+                address,
+                target,
+                balance,
+            } => {
+                if self.only_top_call {
+                    return;
+                }
+
+                if let Some(parent) = self.stack.last_mut() {
+                    parent.calls.push(new_frame(
+                        "SELFDESTRUCT",
+                        address,
+                        Some(target),
+                        Some(balance),
+                        0,
+                        &[],
+                    ));
+                }
+            }
+            EvmEvent::Exit {
+                reason,
+                return_value,
+            } => self.pop_frame(Hex::encode(return_value), exit_error(reason)),
+            _ => (),
+        }
+    }
+
+    fn on_runtime_event(&mut self, _event: RuntimeEvent) {}
+
+    fn on_gasometer_event(&mut self, event: GasometerEvent) {
+        let snapshot = match event {
+            GasometerEvent::RecordCost { snapshot, .. }
+            | GasometerEvent::RecordRefund { snapshot, .. }
+            | GasometerEvent::RecordStipend { snapshot, .. }
+            | GasometerEvent::RecordDynamicCost { snapshot, .. }
+            | GasometerEvent::RecordTransaction { snapshot, .. } => snapshot,
+        };
+
+        if let (Some(s), Some(frame)) = (snapshot, self.stack.last_mut()) {
+            frame.gas_used = (s.used_gas + s.memory_gas).into();
+        }
+    }
+
+    fn finish(self, resp: &TxResp) -> TraceResult {
+        // The transaction may fail before entering the first call frame, such as
+        // out of gas for the intrinsic cost.
+        let mut root = self
+            .root
+            .or(self.transact)
+            .unwrap_or_else(|| new_frame("CALL", H160::default(), None, None, 0, &[]));
+
+        root.gas_used = resp.gas_used.into();
+        root.output = Some(Hex::encode(&resp.ret));
+        root.error = exit_error(&resp.exit_reason);
+        TraceResult::Call(root)
+    }
+}
+
+fn new_frame(
+    call_type: &str,
+    from: H160,
+    to: Option<H160>,
+    value: Option<U256>,
+    gas: u64,
+    input: &[u8],
+) -> CallFrame {
+    CallFrame {
+        call_type: call_type.to_string(),
+        from,
+        to,
+        value,
+        gas: gas.into(),
+        gas_used: U256::zero(),
+        input: Hex::encode(input),
+        output: None,
+        error: None,
+        calls: Vec::new(),
+    }
+}
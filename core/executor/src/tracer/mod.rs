@@ -0,0 +1,145 @@
+mod call;
+mod prestate;
+mod struct_log;
+
+pub use call::CallTracer;
+pub use prestate::PrestateTracer;
+pub use struct_log::StructLogger;
+
+use std::{cell::RefCell, rc::Rc};
+
+use evm::tracing::{Event as EvmEvent, EventListener as EvmListener};
+use evm_gasometer::tracing::{Event as GasometerEvent, EventListener as GasometerListener};
+use evm_runtime::tracing::{Event as RuntimeEvent, EventListener as RuntimeListener};
+
+use protocol::traits::Backend;
+use protocol::types::{ExitReason, TraceResult, TracerType, TxResp, H160};
+
+pub trait AxonTracer: 'static {
+    fn on_evm_event(&mut self, event: EvmEvent);
+
+    fn on_runtime_event(&mut self, event: RuntimeEvent);
+
+    fn on_gasometer_event(&mut self, event: GasometerEvent);
+
+    /// Called after the execution but before the state changes are applied.
+    fn before_apply<B: Backend>(&mut self, _backend: &B) {}
+
+    fn finish(self, resp: &TxResp) -> TraceResult;
+}
+
+/// Dispatch the events to the tracer selected by `TracerType`.
+pub enum Tracer {
+    StructLog(StructLogger),
+    Call(CallTracer),
+    Prestate(PrestateTracer),
+}
+
+impl Tracer {
+    pub fn new(tracer_type: &TracerType) -> Self {
+        match tracer_type {
+            TracerType::StructLog(config) => Tracer::StructLog(StructLogger::new(config.clone())),
+            TracerType::Call { only_top_call } => Tracer::Call(CallTracer::new(*only_top_call)),
+            TracerType::Prestate => Tracer::Prestate(PrestateTracer::new()),
+        }
+    }
+}
+
+impl AxonTracer for Tracer {
+    fn on_evm_event(&mut self, event: EvmEvent) {
+        match self {
+            Tracer::StructLog(t) => t.on_evm_event(event),
+            Tracer::Call(t) => t.on_evm_event(event),
+            Tracer::Prestate(t) => t.on_evm_event(event),
+        }
+    }
+
+    fn on_runtime_event(&mut self, event: RuntimeEvent) {
+        match self {
+            Tracer::StructLog(t) => t.on_runtime_event(event),
+            Tracer::Call(t) => t.on_runtime_event(event),
+            Tracer::Prestate(t) => t.on_runtime_event(event),
+        }
+    }
+
+    fn on_gasometer_event(&mut self, event: GasometerEvent) {
+        match self {
+            Tracer::StructLog(t) => t.on_gasometer_event(event),
+            Tracer::Call(t) => t.on_gasometer_event(event),
+            Tracer::Prestate(t) => t.on_gasometer_event(event),
+        }
+    }
+
+    fn before_apply<B: Backend>(&mut self, backend: &B) {
+        match self {
+            Tracer::StructLog(t) => t.before_apply(backend),
+            Tracer::Call(t) => t.before_apply(backend),
+            Tracer::Prestate(t) => t.before_apply(backend),
+        }
+    }
+
+    fn finish(self, resp: &TxResp) -> TraceResult {
+        match self {
+            Tracer::StructLog(t) => t.finish(resp),
+            Tracer::Call(t) => t.finish(resp),
+            Tracer::Prestate(t) => t.finish(resp),
+        }
+    }
+}
+
+struct ListenerProxy<T>(Rc<RefCell<T>>);
+
+impl<T: AxonTracer> EvmListener for ListenerProxy<T> {
+    fn event(&mut self, event: EvmEvent) {
+        self.0.borrow_mut().on_evm_event(event)
+    }
+}
+
+impl<T: AxonTracer> RuntimeListener for ListenerProxy<T> {
+    fn event(&mut self, event: RuntimeEvent) {
+        self.0.borrow_mut().on_runtime_event(event)
+    }
+}
+
+impl<T: AxonTracer> GasometerListener for ListenerProxy<T> {
+    fn event(&mut self, event: GasometerEvent) {
+        self.0.borrow_mut().on_gasometer_event(event)
+    }
+}
+
+/// Run `f` with `tracer` registered as the listener of all the evm, runtime
+/// and gasometer events.
+pub fn trace_using<T: AxonTracer, R, F: FnOnce() -> R>(tracer: &Rc<RefCell<T>>, f: F) -> R {
+    let mut evm_proxy = ListenerProxy(Rc::clone(tracer));
+    let mut runtime_proxy = ListenerProxy(Rc::clone(tracer));
+    let mut gasometer_proxy = ListenerProxy(Rc::clone(tracer));
+
+    evm::tracing::using(&mut evm_proxy, || {
+        evm_runtime::tracing::using(&mut runtime_proxy, || {
+            evm_gasometer::tracing::using(&mut gasometer_proxy, f)
+        })
+    })
+}
+
+/// Take the tracer back once all the listeners registered by `trace_using`
+/// have been dropped.
+pub fn into_inner<T: AxonTracer>(tracer: Rc<RefCell<T>>) -> T {
+    match Rc::try_unwrap(tracer) {
+        Ok(t) => t.into_inner(),
+        Err(_) => unreachable!("tracer is still referenced by a listener"),
+    }
+}
+
+pub(crate) fn is_precompile(address: &H160) -> bool {
+    crate::precompiles::precompile_addresses().contains(address)
+}
+
+/// The error message of a failed execution in the form reported by geth.
+pub fn exit_error(reason: &ExitReason) -> Option<String> {
+    match reason {
+        ExitReason::Succeed(_) => None,
+        ExitReason::Revert(_) => Some("execution reverted".to_string()),
+        ExitReason::Error(e) => Some(format!("{:?}", e)),
+        ExitReason::Fatal(e) => Some(format!("{:?}", e)),
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use evm::tracing::Event as EvmEvent;
+use evm_gasometer::tracing::Event as GasometerEvent;
+use evm_runtime::tracing::Event as RuntimeEvent;
+
+use protocol::traits::Backend;
+use protocol::types::{Hex, PrestateAccount, TraceResult, TxResp, H160, H256};
+
+use crate::tracer::{is_precompile, AxonTracer};
+
+const OP_BALANCE: u8 = 0x31;
+const OP_EXTCODESIZE: u8 = 0x3b;
+const OP_EXTCODECOPY: u8 = 0x3c;
+const OP_EXTCODEHASH: u8 = 0x3f;
+
+/// The `prestateTracer` of geth, which returns the state of all the accounts
+/// touched by the transaction before it is executed.
+#[derive(Default)]
+pub struct PrestateTracer {
+    touched:  BTreeMap<H160, BTreeSet<H256>>,
+    prestate: BTreeMap<H160, PrestateAccount>,
+}
+
+impl PrestateTracer {
+    pub fn new() -> Self {
+        PrestateTracer::default()
+    }
+
+    fn touch_account(&mut self, address: H160) {
+        if !is_precompile(&address) {
+            self.touched.entry(address).or_default();
+        }
+    }
+
+    fn touch_storage(&mut self, address: H160, index: H256) {
+        self.touched.entry(address).or_default().insert(index);
+    }
+}
+
+impl AxonTracer for PrestateTracer {
+    fn on_evm_event(&mut self, event: EvmEvent) {
+        match event {
+            EvmEvent::TransactCall {
+                caller, address, ..
+            }
+            | EvmEvent::TransactCreate {
+                caller, address, ..
+            }
+            | EvmEvent::TransactCreate2 {
+                caller, address, ..
+            } => {
+                self.touch_account(caller);
+                self.touch_account(address);
+            }
+            EvmEvent::Call {
+                code_address,
+                context,
+                ..
+            } => {
+                self.touch_account(context.caller);
+                self.touch_account(context.address);
+                self.touch_account(code_address);
+            }
+            EvmEvent::Create {
+                caller, address, ..
+            } => {
+                self.touch_account(caller);
+                self.touch_account(address);
+            }
+            EvmEvent::Suicide {
+                address, target, ..
+            } => {
+                self.touch_account(address);
+                self.touch_account(target);
+            }
+            _ => (),
+        }
+    }
+
+    fn on_runtime_event(&mut self, event: RuntimeEvent) {
+        match event {
+            RuntimeEvent::Step { opcode, stack, .. } => {
+                if matches!(
+                    opcode.0,
+                    OP_BALANCE | OP_EXTCODESIZE | OP_EXTCODECOPY | OP_EXTCODEHASH
+                ) {
+                    if let Some(top) = stack.data().last() {
+                        self.touch_account(H160::from(*top));
+                    }
+                }
+            }
+            RuntimeEvent::SLoad { address, index, .. }
+            | RuntimeEvent::SStore { address, index, .. } => self.touch_storage(address, index),
+            _ => (),
+        }
+    }
+
+    fn on_gasometer_event(&mut self, _event: GasometerEvent) {}
+
+    fn before_apply<B: Backend>(&mut self, backend: &B) {
+        for (address, indexes) in self.touched.iter() {
+            let basic = backend.basic(*address);
+            let code = backend.code(*address);
+
+            self.prestate.insert(*address, PrestateAccount {
+                balance: basic.balance,
+                nonce:   basic.nonce,
+                code:    if code.is_empty() {
+                    None
+                } else {
+                    Some(Hex::encode(code))
+                },
+                storage: indexes
+                    .iter()
+                    .map(|index| (*index, backend.storage(*address, *index)))
+                    .collect(),
+            });
+        }
+    }
+
+    fn finish(self, _resp: &TxResp) -> TraceResult {
+        TraceResult::Prestate(self.prestate)
+    }
+}
@@ -2,7 +2,8 @@ use evm::executor::stack::{MemoryStackState, StackExecutor, StackSubstateMetadat
 
 use protocol::traits::{ApplyBackend, Backend};
 use protocol::types::{
-    Config, Hasher, SignedTransaction, TransactionAction, TxResp, H160, H256, U256,
+    Config, ExitError, ExitReason, Hasher, SignedTransaction, Transaction, TransactionAction,
+    TxResp, H160, H256, U256,
 };
 
 use crate::precompiles::build_precompile_set;
@@ -19,20 +20,63 @@ impl EvmExecutor {
         &self,
         backend: &mut B,
         tx: SignedTransaction,
+    ) -> TxResp {
+        self.inner_exec_with_hook(backend, tx, |_| ())
+    }
+
+    /// The `before_apply` hook is called with the backend after the
+    /// transaction has been executed but before its changes are applied, so
+    /// the backend still holds the state the transaction started from.
+    pub fn inner_exec_with_hook<B: Backend + ApplyBackend, F: FnOnce(&B)>(
+        &self,
+        backend: &mut B,
+        tx: SignedTransaction,
+        before_apply: F,
     ) -> TxResp {
         let old_nonce = backend.basic(tx.sender).nonce;
+        let gas_limit = tx.transaction.unsigned.gas_limit.as_u64();
+        let base_fee = backend.block_base_fee_per_gas();
+        let gas_price = effective_gas_price(&tx.transaction.unsigned, base_fee);
+        let tip = gas_price.saturating_sub(base_fee);
+        let coinbase = backend.block_coinbase();
+
         let config = Config::london();
         let metadata = StackSubstateMetadata::new(u64::MAX, &config);
         let state = MemoryStackState::new(metadata, backend);
         let precompiles = build_precompile_set();
         let mut executor = StackExecutor::new_with_precompiles(state, &config, &precompiles);
+
+        // Charge the fee of the whole gas limit in advance, the unused part is
+        // refunded after the execution. The transactions paying less than the
+        // base fee or unable to prepay are rejected before packaging, so this
+        // only guards the state of a faulty block.
+        let prepay = if tx.transaction.unsigned.gas_price < base_fee {
+            Err(ExitError::Other("gas price less than base fee".into()))
+        } else {
+            match gas_price.checked_mul(gas_limit.into()) {
+                Some(fee) => executor.state_mut().withdraw(tx.sender, fee).map(|_| fee),
+                None => Err(ExitError::OutOfFund),
+            }
+        };
+        if let Err(e) = prepay {
+            return TxResp {
+                exit_reason:  ExitReason::Error(e),
+                ret:          vec![],
+                remain_gas:   gas_limit,
+                gas_used:     0,
+                logs:         vec![],
+                code_address: None,
+                removed:      false,
+            };
+        }
+
         let (exit_reason, ret) = match tx.transaction.unsigned.action {
             TransactionAction::Call(addr) => executor.transact_call(
                 tx.sender,
                 addr,
                 tx.transaction.unsigned.value,
                 tx.transaction.unsigned.data.to_vec(),
-                tx.transaction.unsigned.gas_limit.as_u64(),
+                gas_limit,
                 tx.transaction
                     .unsigned
                     .access_list
@@ -44,7 +88,7 @@ impl EvmExecutor {
                 tx.sender,
                 tx.transaction.unsigned.value,
                 tx.transaction.unsigned.data.to_vec(),
-                tx.transaction.unsigned.gas_limit.as_u64(),
+                gas_limit,
                 tx.transaction
                     .unsigned
                     .access_list
@@ -56,7 +100,21 @@ impl EvmExecutor {
 
         let remain_gas = executor.gas();
         let gas_used = executor.used_gas();
+
+        // Refund the unused gas to the sender and pay the tip to the proposer,
+        // the base fee is burned.
+        let refund = gas_price * U256::from(gas_limit.saturating_sub(gas_used));
+        if !refund.is_zero() {
+            executor.state_mut().deposit(tx.sender, refund);
+        }
+
+        let reward = tip * U256::from(gas_used);
+        if !reward.is_zero() {
+            executor.state_mut().deposit(coinbase, reward);
+        }
+
         let (values, logs) = executor.into_state().deconstruct();
+        before_apply(backend);
         backend.apply(values, logs, true);
 
         let code_address = if tx.transaction.unsigned.action == TransactionAction::Create
@@ -79,6 +137,13 @@ impl EvmExecutor {
     }
 }
 
+/// The gas price actually paid by a transaction, which is
+/// `min(max_fee_per_gas, base_fee + max_priority_fee_per_gas)`.
+pub fn effective_gas_price(tx: &Transaction, base_fee: U256) -> U256 {
+    tx.gas_price
+        .min(base_fee.saturating_add(tx.max_priority_fee_per_gas))
+}
+
 pub fn code_address(sender: &H160, nonce: &U256) -> H256 {
     let mut stream = rlp::RlpStream::new_list(2);
     stream.append(sender);
@@ -202,6 +202,32 @@ where
         }
         .into())
     }
+
+    /// Get the nonce of the account at the latest state, a transaction is
+    /// valid only if its nonce is greater than it.
+    fn account_nonce(&self, address: &H160) -> ProtocolResult<U256> {
+        if let Some(nonce) = self.addr_nonce.get(address) {
+            return Ok(*nonce.value());
+        }
+
+        let backend = AxonExecutorAdapter::without_block_hashes(
+            **CURRENT_STATE_ROOT.load(),
+            Arc::clone(&self.trie_db),
+            Arc::clone(&self.storage),
+            Default::default(),
+        )?;
+
+        let account = AxonExecutor::default().get_account(&backend, address);
+        self.addr_nonce.insert(*address, account.nonce);
+
+        Ok(account.nonce)
+    }
+
+    async fn next_base_fee_per_gas(&self, ctx: Context) -> ProtocolResult<U256> {
+        let header = self.storage.get_latest_block_header(ctx.clone()).await?;
+        let metadata = self.metadata.get_metadata_unchecked(ctx, header.number + 1);
+        Ok(header.next_base_fee_per_gas(metadata.gas_limit))
+    }
 }
 
 #[async_trait]
@@ -264,32 +290,10 @@ where
             return self.check_system_script_tx_authorization(ctx, tx).await;
         }
 
-        let addr = &tx.sender;
-        if let Some(res) = self.addr_nonce.get(addr) {
-            if res.value() >= &tx.transaction.unsigned.nonce {
-                return Err(MemPoolError::InvalidNonce {
-                    current:  res.value().as_u64(),
-                    tx_nonce: tx.transaction.unsigned.nonce.as_u64(),
-                }
-                .into());
-            } else {
-                return Ok(());
-            }
-        }
-
-        let backend = AxonExecutorAdapter::from_root(
-            **CURRENT_STATE_ROOT.load(),
-            Arc::clone(&self.trie_db),
-            Arc::clone(&self.storage),
-            Default::default(),
-        )?;
-
-        let account = AxonExecutor::default().get_account(&backend, addr);
-        self.addr_nonce.insert(*addr, account.nonce);
-
-        if account.nonce >= tx.transaction.unsigned.nonce {
+        let current = self.account_nonce(&tx.sender)?;
+        if current >= tx.transaction.unsigned.nonce {
             return Err(MemPoolError::InvalidNonce {
-                current:  account.nonce.as_u64(),
+                current:  current.as_u64(),
                 tx_nonce: tx.transaction.unsigned.nonce.as_u64(),
             }
             .into());
@@ -298,6 +302,10 @@ where
         Ok(())
     }
 
+    async fn get_account_nonce(&self, _ctx: Context, address: &H160) -> ProtocolResult<U256> {
+        Ok(self.account_nonce(address)? + U256::one())
+    }
+
     async fn check_transaction(&self, ctx: Context, stx: &SignedTransaction) -> ProtocolResult<()> {
         if stx.transaction.signature.is_none() {
             return Err(AdapterError::VerifySignature("missing signature".to_string()).into());
@@ -343,6 +351,19 @@ where
             .into());
         }
 
+        // check the gas price covers the base fee of the next block
+        if !is_call_system_script(&stx.transaction.unsigned.action) {
+            let base_fee = self.next_base_fee_per_gas(ctx.clone()).await?;
+            if stx.transaction.unsigned.gas_price < base_fee {
+                return Err(MemPoolError::GasPriceBelowBaseFee {
+                    tx_hash,
+                    gas_price: stx.transaction.unsigned.gas_price,
+                    base_fee,
+                }
+                .into());
+            }
+        }
+
         // Verify chain id
         if self.chain_id != stx.transaction.chain_id {
             if ctx.is_network_origin_txs() {
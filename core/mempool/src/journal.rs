@@ -0,0 +1,242 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use parking_lot::Mutex;
+use rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream};
+
+use protocol::codec::ProtocolCodec;
+use protocol::types::{Hash, SignedTransaction};
+use protocol::ProtocolResult;
+
+use crate::MemPoolError;
+
+const JOURNAL_FILE_NAME: &str = "transactions.rlp";
+const JOURNAL_TMP_FILE_NAME: &str = "transactions.rlp.new";
+/// The journal is rewritten with the live transactions only once the number
+/// of its records exceeds twice the live ones plus this slack.
+const ROTATE_SLACK: usize = 1024;
+
+const INSERT_TAG: u8 = 0;
+const REMOVE_TAG: u8 = 1;
+
+/// An append-only log of the transactions admitted into the mempool and of
+/// their removal, so that the pending transactions survive a restart.
+pub struct TxJournal {
+    dir:   PathBuf,
+    inner: Mutex<JournalInner>,
+}
+
+struct JournalInner {
+    file:    fs::File,
+    live:    HashSet<Hash>,
+    records: usize,
+}
+
+impl TxJournal {
+    pub fn new<P: AsRef<Path>>(path: P) -> ProtocolResult<Self> {
+        let dir = path.as_ref().to_path_buf();
+        if !dir.exists() {
+            fs::create_dir_all(&dir).map_err(MemPoolError::Journal)?;
+        }
+
+        let file = open_append(&dir.join(JOURNAL_FILE_NAME))?;
+        Ok(TxJournal {
+            dir,
+            inner: Mutex::new(JournalInner {
+                file,
+                live: HashSet::new(),
+                records: 0,
+            }),
+        })
+    }
+
+    /// Replay the records and return the transactions that have not been
+    /// removed, in the order of their admission. A record truncated by a crash
+    /// ends the journal.
+    pub fn load(&self) -> ProtocolResult<Vec<SignedTransaction>> {
+        let mut buf = Vec::new();
+        fs::File::open(self.dir.join(JOURNAL_FILE_NAME))
+            .and_then(|mut file| file.read_to_end(&mut buf))
+            .map_err(MemPoolError::Journal)?;
+
+        let mut txs = Vec::new();
+        let mut index = HashMap::new();
+        let mut offset = 0;
+
+        while offset < buf.len() {
+            let len = match Rlp::new(&buf[offset..]).payload_info() {
+                Ok(info) if offset + info.total() <= buf.len() => info.total(),
+                _ => {
+                    log::warn!(
+                        "[mempool]: journal is truncated at {}, drop the rest",
+                        offset
+                    );
+                    break;
+                }
+            };
+
+            match <JournalRecord as ProtocolCodec>::decode(&buf[offset..offset + len])? {
+                JournalRecord::Insert(stx) => {
+                    let hash = stx.transaction.hash;
+                    if !index.contains_key(&hash) {
+                        index.insert(hash, txs.len());
+                        txs.push(Some(stx));
+                    }
+                }
+                JournalRecord::Remove(hash) => {
+                    if let Some(idx) = index.remove(&hash) {
+                        txs[idx] = None;
+                    }
+                }
+            }
+            offset += len;
+        }
+
+        Ok(txs.into_iter().flatten().collect())
+    }
+
+    pub fn insert(&self, stx: &SignedTransaction) -> ProtocolResult<()> {
+        let mut inner = self.inner.lock();
+        if inner.live.insert(stx.transaction.hash) {
+            inner.append(&JournalRecord::Insert(stx.clone()))?;
+        }
+        Ok(())
+    }
+
+    pub fn remove(&self, hashes: &[Hash]) -> ProtocolResult<()> {
+        let mut inner = self.inner.lock();
+        for hash in hashes.iter() {
+            if inner.live.remove(hash) {
+                inner.append(&JournalRecord::Remove(*hash))?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn live_hashes(&self) -> Vec<Hash> {
+        self.inner.lock().live.iter().copied().collect()
+    }
+
+    pub fn need_rotate(&self) -> bool {
+        let inner = self.inner.lock();
+        inner.records > inner.live.len() * 2 + ROTATE_SLACK
+    }
+
+    /// Replace the journal with one that only records the given transactions.
+    /// The new journal is written aside and then renamed so that a crash
+    /// leaves either the old or the new one.
+    pub fn rotate(&self, txs: &[SignedTransaction]) -> ProtocolResult<()> {
+        let mut inner = self.inner.lock();
+        let tmp_path = self.dir.join(JOURNAL_TMP_FILE_NAME);
+        let path = self.dir.join(JOURNAL_FILE_NAME);
+
+        let mut buf = Vec::new();
+        for stx in txs.iter() {
+            buf.extend_from_slice(&JournalRecord::Insert(stx.clone()).encode()?);
+        }
+
+        fs::File::create(&tmp_path)
+            .and_then(|mut file| {
+                file.write_all(&buf)?;
+                file.sync_all()
+            })
+            .and_then(|_| fs::rename(&tmp_path, &path))
+            .map_err(MemPoolError::Journal)?;
+
+        inner.file = open_append(&path)?;
+        inner.live = txs.iter().map(|stx| stx.transaction.hash).collect();
+        inner.records = txs.len();
+        Ok(())
+    }
+}
+
+impl JournalInner {
+    fn append(&mut self, record: &JournalRecord) -> ProtocolResult<()> {
+        self.file
+            .write_all(&record.encode()?)
+            .map_err(MemPoolError::Journal)?;
+        self.records += 1;
+        Ok(())
+    }
+}
+
+fn open_append(path: &Path) -> ProtocolResult<fs::File> {
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| MemPoolError::Journal(e).into())
+}
+
+#[allow(clippy::large_enum_variant)]
+enum JournalRecord {
+    Insert(SignedTransaction),
+    Remove(Hash),
+}
+
+impl Encodable for JournalRecord {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(2);
+        match self {
+            JournalRecord::Insert(stx) => s.append(&INSERT_TAG).append(stx),
+            JournalRecord::Remove(hash) => s.append(&REMOVE_TAG).append(hash),
+        };
+    }
+}
+
+impl Decodable for JournalRecord {
+    fn decode(r: &Rlp) -> Result<Self, DecoderError> {
+        match r.val_at::<u8>(0)? {
+            INSERT_TAG => Ok(JournalRecord::Insert(r.val_at(1)?)),
+            REMOVE_TAG => Ok(JournalRecord::Remove(r.val_at(1)?)),
+            _ => Err(DecoderError::Custom("invalid journal record tag")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::tests::default_mock_txs;
+
+    static JOURNAL_PATH: &str = "./free-space/mempool/journal";
+
+    #[test]
+    fn test_journal_replay() {
+        let _ = fs::remove_dir_all(JOURNAL_PATH);
+        let txs = default_mock_txs(4);
+
+        let journal = TxJournal::new(JOURNAL_PATH).unwrap();
+        for stx in txs.iter() {
+            journal.insert(stx).unwrap();
+        }
+        journal.remove(&[txs[1].transaction.hash]).unwrap();
+        assert_eq!(journal.load().unwrap(), vec![
+            txs[0].clone(),
+            txs[2].clone(),
+            txs[3].clone()
+        ]);
+
+        // a truncated record is dropped
+        journal.insert(&default_mock_txs(1)[0]).unwrap();
+        let path = Path::new(JOURNAL_PATH).join(JOURNAL_FILE_NAME);
+        let len = fs::metadata(&path).unwrap().len();
+        fs::OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(len - 1)
+            .unwrap();
+        let journal = TxJournal::new(JOURNAL_PATH).unwrap();
+        assert_eq!(journal.load().unwrap().len(), 3);
+
+        journal.rotate(&txs[..1]).unwrap();
+        assert_eq!(journal.load().unwrap(), vec![txs[0].clone()]);
+        assert_eq!(journal.live_hashes(), vec![txs[0].transaction.hash]);
+
+        fs::remove_dir_all(JOURNAL_PATH).unwrap();
+    }
+}
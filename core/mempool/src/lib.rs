@@ -3,6 +3,7 @@
 
 mod adapter;
 mod context;
+mod journal;
 mod pool;
 #[cfg(test)]
 mod tests;
@@ -16,6 +17,7 @@ pub use adapter::DefaultMemPoolAdapter;
 
 use std::collections::HashSet;
 use std::error::Error;
+use std::path::Path;
 use std::sync::Arc;
 
 use futures::future::try_join_all;
@@ -23,16 +25,27 @@ use futures::future::try_join_all;
 use common_apm::Instant;
 use core_executor::is_call_system_script;
 use core_network::NetworkContext;
+use protocol::tokio::sync::broadcast;
 use protocol::traits::{Context, MemPool, MemPoolAdapter};
-use protocol::types::{BlockNumber, Hash, SignedTransaction, H160, H256, U256};
+use protocol::types::{BlockNumber, Hash, MemPoolContent, SignedTransaction, H160, H256, U256};
 use protocol::{async_trait, tokio, Display, ProtocolError, ProtocolErrorKind, ProtocolResult};
 
 use crate::context::TxContext;
+use crate::journal::TxJournal;
 use crate::pool::PriorityPool;
 
+/// The number of admitted transactions buffered for a slow subscriber before
+/// it starts to miss them.
+const NEW_TXS_CHANNEL_SIZE: usize = 4096;
+/// The number of committed block numbers buffered for a slow subscriber.
+const COMMITTED_CHANNEL_SIZE: usize = 128;
+
 pub struct MemPoolImpl<Adapter> {
-    pool:    PriorityPool,
-    adapter: Arc<Adapter>,
+    pool:         PriorityPool,
+    adapter:      Arc<Adapter>,
+    journal:      Option<TxJournal>,
+    new_txs_tx:   broadcast::Sender<SignedTransaction>,
+    committed_tx: broadcast::Sender<BlockNumber>,
 }
 
 impl<Adapter> MemPoolImpl<Adapter>
@@ -42,12 +55,27 @@ where
     pub async fn new(
         pool_size: usize,
         timeout_gap: u64,
+        price_bump: u64,
+        pending_per_account: usize,
+        queued_size: usize,
+        queued_per_account: usize,
         adapter: Adapter,
         initial_txs: Vec<SignedTransaction>,
     ) -> Self {
         let mempool = MemPoolImpl {
-            pool:    PriorityPool::new(pool_size, timeout_gap).await,
-            adapter: Arc::new(adapter),
+            pool:         PriorityPool::new(
+                pool_size,
+                timeout_gap,
+                price_bump,
+                pending_per_account,
+                queued_size,
+                queued_per_account,
+            )
+            .await,
+            adapter:      Arc::new(adapter),
+            journal:      None,
+            new_txs_tx:   broadcast::channel(NEW_TXS_CHANNEL_SIZE).0,
+            committed_tx: broadcast::channel(COMMITTED_CHANNEL_SIZE).0,
         };
 
         for tx in initial_txs.into_iter() {
@@ -59,6 +87,45 @@ where
         mempool
     }
 
+    /// Persist the admitted transactions into a journal under the given
+    /// directory. The transactions left in the journal by the last run are
+    /// inserted again, except the ones which have been committed.
+    pub async fn with_journal<P: AsRef<Path>>(mut self, path: P) -> ProtocolResult<Self> {
+        let journal = TxJournal::new(path)?;
+        let txs = journal.load()?;
+        let total = txs.len();
+
+        let mut kept = Vec::with_capacity(total);
+        for tx in txs.into_iter() {
+            let hash = tx.transaction.hash;
+            if self
+                .adapter
+                .check_storage_exist(Context::new(), &hash)
+                .await
+                .is_err()
+            {
+                continue;
+            }
+
+            if let Err(e) = self.insert(Context::new(), tx.clone()).await {
+                log::warn!("[mempool]: replay journal tx {:?} failed {:?}", hash, e);
+            }
+
+            if self.pool.contains(&hash) {
+                kept.push(tx);
+            }
+        }
+
+        log::info!(
+            "[mempool]: replay {} of {} txs from journal",
+            kept.len(),
+            total
+        );
+        journal.rotate(&kept)?;
+        self.journal = Some(journal);
+        Ok(self)
+    }
+
     pub fn len(&self) -> usize {
         self.pool.len()
     }
@@ -67,6 +134,10 @@ where
         self.pool.co_queue_len()
     }
 
+    pub fn queued_len(&self) -> usize {
+        self.pool.queued_len()
+    }
+
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
@@ -118,9 +189,22 @@ where
             if is_system_script {
                 self.pool.insert_system_script_tx(tx.clone())?;
             } else {
-                self.pool.insert(tx.clone(), true)?;
+                let account_nonce = self
+                    .adapter
+                    .get_account_nonce(ctx.clone(), &tx.sender)
+                    .await?;
+                self.pool.insert_by_nonce(tx.clone(), account_nonce)?;
+            }
+
+            if let Some(journal) = self.journal.as_ref() {
+                if let Err(e) = journal.insert(&tx) {
+                    log::warn!("[mempool]: journal tx {:?} failed {:?}", tx_hash, e);
+                }
             }
 
+            // It only fails when there is no subscriber.
+            let _ = self.new_txs_tx.send(tx.clone());
+
             if !ctx.is_network_origin_txs() {
                 self.adapter.broadcast_tx(ctx, None, tx).await?;
             } else {
@@ -172,6 +256,33 @@ where
         Ok(())
     }
 
+    /// Record the removal of the journaled transactions which have left the
+    /// pool, and rewrite the journal once it is mostly made of stale records.
+    fn flush_journal(&self) -> ProtocolResult<()> {
+        let journal = match self.journal.as_ref() {
+            Some(journal) => journal,
+            None => return Ok(()),
+        };
+
+        let removed = journal
+            .live_hashes()
+            .into_iter()
+            .filter(|hash| !self.pool.contains(hash))
+            .collect::<Vec<_>>();
+        journal.remove(&removed)?;
+
+        if journal.need_rotate() {
+            let txs = journal
+                .live_hashes()
+                .iter()
+                .filter_map(|hash| self.pool.get_by_hash(hash))
+                .collect::<Vec<_>>();
+            journal.rotate(&txs)?;
+        }
+
+        Ok(())
+    }
+
     #[cfg(test)]
     pub fn get_tx_cache(&self) -> &PriorityPool {
         &self.pool
@@ -199,11 +310,14 @@ where
             self.pool.len(),
         );
         let inst = Instant::now();
-        let txs = self.pool.package(gas_limit, tx_num_limit as usize);
+        let (txs, gas_used) = self.pool.package(gas_limit, tx_num_limit as usize);
 
         common_apm::metrics::mempool::MEMPOOL_PACKAGE_SIZE_VEC_STATIC
             .package
             .observe((txs.len()) as f64);
+        common_apm::metrics::mempool::MEMPOOL_PACKAGE_SIZE_VEC_STATIC
+            .package_gas
+            .observe(gas_used.low_u64() as f64);
         common_apm::metrics::mempool::MEMPOOL_TIME_STATIC
             .package
             .observe(common_apm::metrics::duration_to_sec(inst.elapsed()));
@@ -227,7 +341,21 @@ where
                     .is_ok()
             })
         };
-        self.pool.flush(tx_hashes, nonce_check, current_number);
+        let account_nonce = |address: &H160| -> Option<U256> {
+            tokio::task::block_in_place(|| {
+                rt.block_on(self.adapter.get_account_nonce(Context::new(), address))
+                    .ok()
+            })
+        };
+        self.pool
+            .flush(tx_hashes, nonce_check, account_nonce, current_number);
+
+        if let Err(e) = self.flush_journal() {
+            log::warn!("[mempool]: flush journal failed {:?}", e);
+        }
+
+        // It only fails when there is no subscriber.
+        let _ = self.committed_tx.send(current_number);
         Ok(())
     }
 
@@ -321,10 +449,26 @@ where
         Ok(self.pool.get_tx_count_by_address(address))
     }
 
+    async fn get_content(
+        &self,
+        _ctx: Context,
+        from: Option<H160>,
+    ) -> ProtocolResult<MemPoolContent> {
+        Ok(self.pool.content(from.as_ref()))
+    }
+
     fn set_args(&self, context: Context, state_root: H256, gas_limit: u64, max_tx_size: u64) {
         self.adapter
             .set_args(context, state_root, gas_limit, max_tx_size);
     }
+
+    fn subscribe_new_txs(&self) -> broadcast::Receiver<SignedTransaction> {
+        self.new_txs_tx.subscribe()
+    }
+
+    fn subscribe_committed(&self) -> broadcast::Receiver<BlockNumber> {
+        self.committed_tx.subscribe()
+    }
 }
 
 fn check_dup_order_hashes(order_tx_hashes: &[Hash]) -> ProtocolResult<()> {
@@ -373,6 +517,18 @@ pub enum MemPoolError {
         gas_limit_tx:     u64,
     },
 
+    #[display(
+        fmt = "Tx: {:?} gas price {} is less than the base fee {}",
+        tx_hash,
+        gas_price,
+        base_fee
+    )]
+    GasPriceBelowBaseFee {
+        tx_hash:   Hash,
+        gas_price: U256,
+        base_fee:  U256,
+    },
+
     #[display(fmt = "Tx nonce {} is invalid current nonce {}", tx_nonce, current)]
     InvalidNonce { current: u64, tx_nonce: u64 },
 
@@ -418,6 +574,23 @@ pub enum MemPoolError {
     #[display(fmt = "Tx: {:?} invalid timeout", _0)]
     InvalidTimeout(Hash),
 
+    #[display(
+        fmt = "Account {:?} reaches the limit of {} queued txs",
+        address,
+        limit
+    )]
+    ExceedAccountLimit { address: H160, limit: usize },
+
+    #[display(
+        fmt = "Tx: {:?} replacement underpriced, require a price bump of {}%",
+        tx_hash,
+        price_bump
+    )]
+    ReplacementUnderpriced { tx_hash: Hash, price_bump: u64 },
+
+    #[display(fmt = "Mempool journal error {:?}", _0)]
+    Journal(std::io::Error),
+
     #[display(fmt = "Batch transaction validation failed")]
     VerifyBatchTransactions,
 
@@ -1,16 +1,16 @@
-use std::collections::{BTreeMap, BinaryHeap, HashSet};
+use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet, VecDeque};
 use std::sync::{
     atomic::{AtomicUsize, Ordering},
     Arc,
 };
-use std::time::Duration;
 
 use crossbeam_queue::ArrayQueue;
 use dashmap::DashMap;
 use parking_lot::{Mutex, RwLock};
 
-use protocol::tokio::{self, time::sleep};
-use protocol::types::{BlockNumber, Bytes, Hash, SignedTransaction, H160, U256};
+use protocol::types::{
+    AccountTxs, BlockNumber, Bytes, Hash, MemPoolContent, SignedTransaction, H160, U256,
+};
 use protocol::ProtocolResult;
 
 use crate::tx_wrapper::{TxPtr, TxWrapper};
@@ -20,47 +20,44 @@ pub struct PriorityPool {
     sys_tx_bucket:  SystemScriptTxBucket,
     occupied_nonce: DashMap<H160, BTreeMap<U256, TxPtr>>,
     co_queue:       Arc<ArrayQueue<TxPtr>>,
-    real_queue:     Arc<Mutex<BinaryHeap<TxPtr>>>,
     tx_map:         DashMap<Hash, SignedTransaction>,
     stock_len:      AtomicUsize,
     timeout_gap:    Mutex<BTreeMap<BlockNumber, HashSet<Hash>>>,
     timeout_config: u64,
+    price_bump:     u64,
+
+    queued:              Mutex<QueuedTxs>,
+    queued_size:         usize,
+    pending_per_account: usize,
+    queued_per_account:  usize,
 
     flush_lock: Arc<RwLock<()>>,
 }
 
 impl PriorityPool {
-    pub async fn new(size: usize, timeout_config: u64) -> Self {
-        let pool = PriorityPool {
+    pub async fn new(
+        size: usize,
+        timeout_config: u64,
+        price_bump: u64,
+        pending_per_account: usize,
+        queued_size: usize,
+        queued_per_account: usize,
+    ) -> Self {
+        PriorityPool {
             sys_tx_bucket: SystemScriptTxBucket::new(),
             occupied_nonce: DashMap::new(),
             co_queue: Arc::new(ArrayQueue::new(size)),
-            real_queue: Arc::new(Mutex::new(BinaryHeap::with_capacity(size * 2))),
             tx_map: DashMap::new(),
             stock_len: AtomicUsize::new(0),
             timeout_gap: Mutex::new(BTreeMap::new()),
             timeout_config,
+            price_bump,
+            queued: Mutex::new(QueuedTxs::default()),
+            queued_size,
+            pending_per_account,
+            queued_per_account,
             flush_lock: Arc::new(RwLock::new(())),
-        };
-
-        let co_queue = Arc::clone(&pool.co_queue);
-        let real_queue = Arc::clone(&pool.real_queue);
-        let flush_lock = Arc::clone(&pool.flush_lock);
-
-        tokio::spawn(async move {
-            loop {
-                if !co_queue.is_empty() {
-                    let _flushing = flush_lock.read();
-                    let mut q = real_queue.lock();
-                    let txs = pop_all_item(Arc::clone(&co_queue));
-                    txs.for_each(|p_tx| q.push(p_tx));
-                }
-
-                sleep(Duration::from_millis(50)).await;
-            }
-        });
-
-        pool
+        }
     }
 
     pub fn get_tx_count_by_address(&self, address: H160) -> usize {
@@ -80,6 +77,38 @@ impl PriorityPool {
     }
 
     pub fn insert(&self, stx: SignedTransaction, check_limit: bool) -> ProtocolResult<()> {
+        // This lock is necessary to avoid mismatch error triggered by the concurrent
+        // operation of tx insertion and flush.
+        let _flushing = self.flush_lock.read();
+        self.insert_pending(stx, check_limit)
+    }
+
+    /// Insert a transaction into the pending tier if it continues the pending
+    /// nonces of its sender, or into the queued tier otherwise. The queued
+    /// ones are promoted once the gap is filled.
+    pub fn insert_by_nonce(
+        &self,
+        stx: SignedTransaction,
+        account_nonce: U256,
+    ) -> ProtocolResult<()> {
+        let _flushing = self.flush_lock.read();
+
+        let sender = stx.sender;
+        let nonce = stx.transaction.unsigned.nonce;
+        let (next_nonce, pending_count) = self.next_nonce(&sender, account_nonce);
+
+        if nonce < next_nonce {
+            self.insert_pending(stx, true)
+        } else if nonce == next_nonce && pending_count < self.pending_per_account {
+            self.insert_pending(stx, true)?;
+            self.promote(&sender, next_nonce + 1, pending_count + 1);
+            Ok(())
+        } else {
+            self.insert_queued(stx).map_err(Into::into)
+        }
+    }
+
+    fn insert_pending(&self, stx: SignedTransaction, check_limit: bool) -> ProtocolResult<()> {
         if let Err(n) = self
             .stock_len
             .fetch_update(Ordering::AcqRel, Ordering::Acquire, |x| {
@@ -93,16 +122,13 @@ impl PriorityPool {
             return Err(MemPoolError::ReachLimit(n).into());
         }
 
-        // This lock is necessary to avoid mismatch error triggered by the concurrent
-        // operation of tx insertion and flush.
-        let _flushing = self.flush_lock.read();
-
-        // Must flush co_queue here when it's full, otherwise, this tx may can't package
-        // by self, because it will never insert to real_queue
-        if !check_limit && self.co_queue.is_full() {
-            let mut q = self.real_queue.lock();
-            let txs = pop_all_item(Arc::clone(&self.co_queue));
-            txs.for_each(|p_tx| q.push(p_tx));
+        // Transactions of a proposal are inserted without the limit check and
+        // must be kept, so only the local insertion is subject to the price bump.
+        if check_limit {
+            if let Err(e) = self.replace(&stx) {
+                self.stock_len.fetch_sub(1, Ordering::AcqRel);
+                return Err(e.into());
+            }
         }
 
         let (ptr, tx) = TxWrapper::from(stx).into_parts();
@@ -116,28 +142,180 @@ impl PriorityPool {
         Ok(())
     }
 
-    pub fn package(&self, _gas_limit: U256, limit: usize) -> Vec<Hash> {
+    fn insert_queued(&self, stx: SignedTransaction) -> Result<(), MemPoolError> {
+        let mut queued = self.queued.lock();
+        let sender = stx.sender;
+
+        let old_tx = queued
+            .get_by_nonce(&sender, &stx.transaction.unsigned.nonce)
+            .cloned();
+        if let Some(old_tx) = old_tx {
+            if old_tx.transaction.hash != stx.transaction.hash {
+                self.check_replacement(&old_tx, &stx)?;
+                queued.insert(stx);
+            }
+            return Ok(());
+        }
+
+        if queued.account_len(&sender) >= self.queued_per_account {
+            return Err(MemPoolError::ExceedAccountLimit {
+                address: sender,
+                limit:   self.queued_per_account,
+            });
+        }
+
+        if queued.len() >= self.queued_size {
+            match queued.lowest_price() {
+                Some((address, nonce, price)) if price < stx.transaction.unsigned.gas_price => {
+                    queued.remove(&address, &nonce);
+                }
+                _ => return Err(MemPoolError::ReachLimit(queued.len())),
+            }
+        }
+
+        queued.insert(stx);
+        Ok(())
+    }
+
+    /// Move the queued transactions of the sender which continue from the
+    /// given nonce into the pending tier, as long as the sender has room.
+    fn promote(&self, sender: &H160, from_nonce: U256, pending_count: usize) {
+        let room = self.pending_per_account.saturating_sub(pending_count);
+        let txs = self.queued.lock().take_from(sender, from_nonce, room);
+
+        for stx in txs.into_iter() {
+            // The transactions were verified on their way into the queued tier.
+            if let Err(e) = self.insert_pending(stx, false) {
+                log::warn!("[mempool]: promote queued tx failed {:?}", e);
+            }
+        }
+    }
+
+    /// Return the nonce which the next pending transaction of the sender should
+    /// use, and the count of the pending transactions before it.
+    fn next_nonce(&self, sender: &H160, account_nonce: U256) -> (U256, usize) {
+        let mut next_nonce = account_nonce;
+        let mut count = 0;
+
+        if let Some(nonces) = self.occupied_nonce.get(sender) {
+            for (nonce, ptr) in nonces.range(account_nonce..) {
+                if *nonce != next_nonce || ptr.is_dropped() {
+                    break;
+                }
+                next_nonce += U256::one();
+                count += 1;
+            }
+        }
+
+        (next_nonce, count)
+    }
+
+    /// Evict the pending transaction with the same sender and nonce as the
+    /// given one, if any.
+    fn replace(&self, stx: &SignedTransaction) -> Result<(), MemPoolError> {
+        let new_tx = &stx.transaction;
+        let old_ptr = self
+            .occupied_nonce
+            .get(&stx.sender)
+            .and_then(|nonces| nonces.get(&new_tx.unsigned.nonce).cloned());
+        let old_ptr = match old_ptr {
+            Some(ptr) if !ptr.is_dropped() && ptr.hash != new_tx.hash => ptr,
+            _ => return Ok(()),
+        };
+        match self.tx_map.get(&old_ptr.hash) {
+            Some(old_tx) => self.check_replacement(&old_tx, stx)?,
+            None => return Ok(()),
+        };
+
+        old_ptr.set_dropped();
+        if self.tx_map.remove(&old_ptr.hash).is_some() {
+            self.stock_len.fetch_sub(1, Ordering::AcqRel);
+        }
+
+        // The dropped flag keeps the evicted one from being packaged, and it is
+        // cleaned up from co_queue on the next flush.
+        Ok(())
+    }
+
+    /// The replacement must raise both the gas price and the priority fee by at
+    /// least `price_bump` percent.
+    fn check_replacement(
+        &self,
+        old_stx: &SignedTransaction,
+        new_stx: &SignedTransaction,
+    ) -> Result<(), MemPoolError> {
+        let old_tx = &old_stx.transaction.unsigned;
+        let new_tx = &new_stx.transaction.unsigned;
+
+        if new_tx.gas_price < bump_price(old_tx.gas_price, self.price_bump)
+            || new_tx.max_priority_fee_per_gas
+                < bump_price(old_tx.max_priority_fee_per_gas, self.price_bump)
+        {
+            return Err(MemPoolError::ReplacementUnderpriced {
+                tx_hash:    new_stx.transaction.hash,
+                price_bump: self.price_bump,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Select transactions within both the gas limit and the count limit,
+    /// return their hashes and the total gas limit of them. The lowest nonce
+    /// of each sender competes by gas price, and the next nonce of a sender is
+    /// available once the previous one is selected. A transaction that does
+    /// not fit is skipped together with the later nonces of its sender.
+    pub fn package(&self, gas_limit: U256, limit: usize) -> (Vec<Hash>, U256) {
         let _flushing = self.flush_lock.read();
 
-        let mut ret = self.sys_tx_bucket.package();
-        let mut q = self.real_queue.lock();
-        if !self.co_queue.is_empty() {
-            let txs = pop_all_item(Arc::clone(&self.co_queue));
-            txs.for_each(|p_tx| q.push(p_tx));
+        let mut ret = Vec::new();
+        let mut gas_used = U256::zero();
+        for (hash, tx_gas_limit) in self.sys_tx_bucket.package() {
+            let total = gas_used.saturating_add(tx_gas_limit);
+            if total <= gas_limit {
+                ret.push(hash);
+                gas_used = total;
+            }
         }
 
-        ret.extend(
-            q.iter()
-                .filter_map(|ptr| {
-                    if ptr.is_dropped() {
-                        None
-                    } else {
-                        Some(ptr.hash)
-                    }
-                })
-                .take(limit),
-        );
-        ret
+        let mut senders = self
+            .occupied_nonce
+            .iter()
+            .map(|kv| {
+                kv.value()
+                    .values()
+                    .filter(|ptr| !ptr.is_dropped())
+                    .cloned()
+                    .collect::<VecDeque<_>>()
+            })
+            .collect::<Vec<_>>();
+        let mut heads = senders
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(idx, ptrs)| ptrs.pop_front().map(|ptr| (ptr, idx)))
+            .collect::<BinaryHeap<_>>();
+
+        let mut count = 0;
+        while let Some((ptr, idx)) = heads.pop() {
+            if count >= limit {
+                break;
+            }
+
+            let total = gas_used.saturating_add(ptr.gas_limit);
+            if total > gas_limit {
+                continue;
+            }
+
+            ret.push(ptr.hash);
+            gas_used = total;
+            count += 1;
+
+            if let Some(next) = senders[idx].pop_front() {
+                heads.push((next, idx));
+            }
+        }
+
+        (ret, gas_used)
     }
 
     pub fn len(&self) -> usize {
@@ -148,9 +326,62 @@ impl PriorityPool {
         self.co_queue.len()
     }
 
+    pub fn queued_len(&self) -> usize {
+        self.queued.lock().len()
+    }
+
     pub fn contains(&self, hash: &Hash) -> bool {
         let _flushing = self.flush_lock.read();
-        self.tx_map.contains_key(hash) || self.sys_tx_bucket.contains(hash)
+        self.tx_map.contains_key(hash)
+            || self.sys_tx_bucket.contains(hash)
+            || self.queued.lock().contains(hash)
+    }
+
+    /// Take a snapshot of the pending, queued and system script transactions,
+    /// only the ones sent by `from` if it is given.
+    pub fn content(&self, from: Option<&H160>) -> MemPoolContent {
+        let _flushing = self.flush_lock.read();
+        let is_wanted = |sender: &H160| from.map(|addr| addr == sender).unwrap_or(true);
+
+        let mut pending = BTreeMap::new();
+        for kv in self.occupied_nonce.iter().filter(|kv| is_wanted(kv.key())) {
+            let txs = kv
+                .value()
+                .values()
+                .filter(|ptr| !ptr.is_dropped())
+                .filter_map(|ptr| {
+                    self.tx_map
+                        .get(ptr.hash())
+                        .map(|stx| (ptr.nonce, stx.value().clone()))
+                })
+                .collect::<AccountTxs>();
+
+            if !txs.is_empty() {
+                pending.insert(*kv.key(), txs);
+            }
+        }
+
+        let queued = self
+            .queued
+            .lock()
+            .txs
+            .iter()
+            .filter(|(sender, _)| is_wanted(sender))
+            .map(|(sender, txs)| (*sender, txs.clone()))
+            .collect();
+
+        let system = self
+            .sys_tx_bucket
+            .txs()
+            .into_iter()
+            .filter(|stx| is_wanted(&stx.sender))
+            .collect();
+
+        MemPoolContent {
+            pending,
+            queued,
+            system,
+        }
     }
 
     pub fn reach_limit(&self) -> Result<usize, usize> {
@@ -167,16 +398,23 @@ impl PriorityPool {
 
         match self.tx_map.get(hash).map(|r| r.clone()) {
             Some(tx) => Some(tx),
-            None => self.sys_tx_bucket.get_tx_by_hash(hash),
+            None => self
+                .sys_tx_bucket
+                .get_tx_by_hash(hash)
+                .or_else(|| self.queued.lock().get(hash)),
         }
     }
 
-    pub fn flush<F: Fn(&SignedTransaction) -> bool>(
+    pub fn flush<F, N>(
         &self,
         hashes: &[Hash],
         nonce_check: F,
+        account_nonce: N,
         number: BlockNumber,
-    ) {
+    ) where
+        F: Fn(&SignedTransaction) -> bool,
+        N: Fn(&H160) -> Option<U256>,
+    {
         let _flushing = self.flush_lock.write();
         self.occupied_nonce.clear();
         let mut reduce_len = 0;
@@ -186,6 +424,25 @@ impl PriorityPool {
         if reduce_len != 0 {
             self.stock_len.fetch_sub(reduce_len, Ordering::AcqRel);
         }
+
+        self.flush_queued(account_nonce);
+    }
+
+    /// Drop the queued transactions whose nonce has been used, and promote the
+    /// ones whose gap is filled by the new block.
+    fn flush_queued<N: Fn(&H160) -> Option<U256>>(&self, account_nonce: N) {
+        let senders = self.queued.lock().senders();
+
+        for sender in senders.iter() {
+            let account_nonce = match account_nonce(sender) {
+                Some(nonce) => nonce,
+                None => continue,
+            };
+
+            self.queued.lock().remove_stale(sender, account_nonce);
+            let (next_nonce, pending_count) = self.next_nonce(sender, account_nonce);
+            self.promote(sender, next_nonce, pending_count);
+        }
     }
 
     fn flush_inner<F: Fn(&SignedTransaction) -> bool>(
@@ -195,7 +452,6 @@ impl PriorityPool {
         reduce_len: &mut usize,
         number: BlockNumber,
     ) {
-        let mut q = self.real_queue.lock();
         let mut timeout_gap = self.timeout_gap.lock();
 
         for hash in hashes {
@@ -204,7 +460,7 @@ impl PriorityPool {
             }
         }
 
-        for tx_ptr in q.drain().chain(pop_all_item(Arc::clone(&self.co_queue))) {
+        for tx_ptr in pop_all_item(Arc::clone(&self.co_queue)) {
             if tx_ptr.is_dropped() && self.tx_map.remove(tx_ptr.hash()).is_some() {
                 *reduce_len += 1;
             }
@@ -233,7 +489,6 @@ impl PriorityPool {
         for tx in self.tx_map.iter().map(|kv| kv.value().clone()) {
             let tx_wrapper = TxWrapper::from(tx);
             self.occupy_nonce(tx_wrapper.ptr());
-            q.push(tx_wrapper.ptr());
             ptr.insert(tx_wrapper.hash());
         }
     }
@@ -249,11 +504,6 @@ impl PriorityPool {
         }
     }
 
-    #[cfg(test)]
-    pub fn real_queue_len(&self) -> usize {
-        self.real_queue.lock().len()
-    }
-
     #[cfg(test)]
     pub fn system_script_queue_len(&self) -> usize {
         self.sys_tx_bucket.len()
@@ -284,18 +534,25 @@ impl SystemScriptTxBucket {
             .is_none()
     }
 
-    pub fn package(&self) -> Vec<Hash> {
+    pub fn package(&self) -> Vec<(Hash, U256)> {
         self.tx_buckets
             .iter()
             .map(|kv| {
                 kv.value()
                     .first_key_value()
-                    .map(|(hash, _tx)| *hash)
+                    .map(|(hash, tx)| (*hash, tx.transaction.unsigned.gas_limit))
                     .unwrap()
             })
             .collect()
     }
 
+    pub fn txs(&self) -> Vec<SignedTransaction> {
+        self.tx_buckets
+            .iter()
+            .flat_map(|kv| kv.value().values().cloned().collect::<Vec<_>>())
+            .collect()
+    }
+
     pub fn get_tx_by_hash(&self, hash: &Hash) -> Option<SignedTransaction> {
         if let Some(data) = self.hash_data_map.get(hash) {
             if let Some(tx_map) = self.tx_buckets.get(data.value()) {
@@ -332,6 +589,121 @@ impl SystemScriptTxBucket {
     }
 }
 
+/// The transactions which can not be executed until the gap before their
+/// nonces is filled.
+#[derive(Default)]
+struct QueuedTxs {
+    txs:    HashMap<H160, BTreeMap<U256, SignedTransaction>>,
+    hashes: HashMap<Hash, (H160, U256)>,
+}
+
+impl QueuedTxs {
+    fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    fn account_len(&self, sender: &H160) -> usize {
+        self.txs
+            .get(sender)
+            .map(|txs| txs.len())
+            .unwrap_or_default()
+    }
+
+    fn contains(&self, hash: &Hash) -> bool {
+        self.hashes.contains_key(hash)
+    }
+
+    fn get(&self, hash: &Hash) -> Option<SignedTransaction> {
+        let (sender, nonce) = self.hashes.get(hash)?;
+        self.get_by_nonce(sender, nonce).cloned()
+    }
+
+    fn get_by_nonce(&self, sender: &H160, nonce: &U256) -> Option<&SignedTransaction> {
+        self.txs.get(sender).and_then(|txs| txs.get(nonce))
+    }
+
+    fn senders(&self) -> Vec<H160> {
+        self.txs.keys().copied().collect()
+    }
+
+    fn insert(&mut self, stx: SignedTransaction) {
+        let sender = stx.sender;
+        let nonce = stx.transaction.unsigned.nonce;
+        self.hashes.insert(stx.transaction.hash, (sender, nonce));
+
+        if let Some(old) = self.txs.entry(sender).or_default().insert(nonce, stx) {
+            self.hashes.remove(&old.transaction.hash);
+        }
+    }
+
+    fn remove(&mut self, sender: &H160, nonce: &U256) -> Option<SignedTransaction> {
+        let txs = self.txs.get_mut(sender)?;
+        let stx = txs.remove(nonce)?;
+        if txs.is_empty() {
+            self.txs.remove(sender);
+        }
+
+        self.hashes.remove(&stx.transaction.hash);
+        Some(stx)
+    }
+
+    fn remove_stale(&mut self, sender: &H160, account_nonce: U256) {
+        let stale = match self.txs.get(sender) {
+            Some(txs) => txs
+                .range(..account_nonce)
+                .map(|(nonce, _)| *nonce)
+                .collect::<Vec<_>>(),
+            None => Vec::new(),
+        };
+
+        for nonce in stale.iter() {
+            self.remove(sender, nonce);
+        }
+    }
+
+    /// Remove and return at most `limit` transactions with consecutive nonces
+    /// from the given one.
+    fn take_from(
+        &mut self,
+        sender: &H160,
+        from_nonce: U256,
+        limit: usize,
+    ) -> Vec<SignedTransaction> {
+        let mut ret = Vec::new();
+        let mut nonce = from_nonce;
+
+        while ret.len() < limit {
+            match self.remove(sender, &nonce) {
+                Some(stx) => ret.push(stx),
+                None => break,
+            }
+            nonce += U256::one();
+        }
+
+        ret
+    }
+
+    /// Find the transaction with the lowest gas price, the one with the
+    /// highest nonce wins a tie since it is the farthest from execution.
+    fn lowest_price(&self) -> Option<(H160, U256, U256)> {
+        self.txs
+            .iter()
+            .flat_map(|(sender, txs)| {
+                txs.iter()
+                    .map(move |(nonce, stx)| (*sender, *nonce, stx.transaction.unsigned.gas_price))
+            })
+            .min_by(|a, b| a.2.cmp(&b.2).then(b.1.cmp(&a.1)))
+    }
+}
+
+fn bump_price(price: U256, price_bump: u64) -> U256 {
+    let bump = price
+        .checked_mul(price_bump.into())
+        .map(|p| p / 100)
+        .unwrap_or(U256::MAX);
+    price.saturating_add(bump)
+}
+
 fn pop_all_item<T>(queue: Arc<ArrayQueue<T>>) -> impl Iterator<Item = T> {
     (0..queue.len()).map(move |_| queue.pop().unwrap())
 }
@@ -148,7 +148,7 @@ async fn test_flush() {
     let remove_hashes: Vec<Hash> = txs.iter().map(|tx| tx.transaction.hash).collect();
     exec_flush(remove_hashes, Arc::clone(&mempool)).await;
     assert_eq!(mempool.get_tx_cache().len(), 432);
-    assert_eq!(mempool.get_tx_cache().real_queue_len(), 432);
+    assert_eq!(mempool.get_tx_cache().content(None).pending_len(), 432);
 }
 
 #[tokio::test(flavor = "multi_thread")]
@@ -218,6 +218,289 @@ async fn test_ensure_order_txs() {
     ensure_order_txs!(50, 50, 50);
 }
 
+#[tokio::test]
+async fn test_replace_by_fee() {
+    let mempool = Arc::new(default_mempool().await);
+    let priv_key = Secp256k1RecoverablePrivateKey::generate(&mut OsRng);
+
+    let origin = mock_priced_signed_tx(&priv_key, 0, 1, 100, 100);
+    let other = mock_priced_signed_tx(&priv_key, 1, 1, 100, 100);
+    mempool
+        .insert(Context::new(), origin.clone())
+        .await
+        .unwrap();
+    mempool.insert(Context::new(), other.clone()).await.unwrap();
+    assert_eq!(mempool.len(), 2);
+
+    // bump less than 10% on gas price or priority fee
+    for (gas_price, priority_fee) in [(100, 100), (109, 110), (110, 109)] {
+        let tx = mock_priced_signed_tx(&priv_key, 0, 1, gas_price, priority_fee);
+        let err = mempool
+            .insert(Context::new(), tx.clone())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("ReplacementUnderpriced"));
+        assert!(!mempool.get_tx_cache().contains(&tx.transaction.hash));
+    }
+    assert_eq!(mempool.len(), 2);
+
+    // speed up
+    let speed_up = mock_priced_signed_tx(&priv_key, 0, 1, 110, 110);
+    mempool
+        .insert(Context::new(), speed_up.clone())
+        .await
+        .unwrap();
+    assert_eq!(mempool.len(), 2);
+    assert!(!mempool.get_tx_cache().contains(&origin.transaction.hash));
+    assert!(mempool.get_tx_cache().contains(&speed_up.transaction.hash));
+
+    // cancel the speed up one
+    let cancel = mock_priced_signed_tx(&priv_key, 0, 1, 200, 200);
+    mempool
+        .insert(Context::new(), cancel.clone())
+        .await
+        .unwrap();
+    assert_eq!(mempool.len(), 2);
+    assert!(!mempool.get_tx_cache().contains(&speed_up.transaction.hash));
+
+    let package_txs = exec_package(Arc::clone(&mempool), CYCLE_LIMIT.into(), TX_NUM_LIMIT).await;
+    assert_eq!(
+        package_txs.into_iter().collect::<HashSet<_>>(),
+        [cancel.transaction.hash, other.transaction.hash]
+            .into_iter()
+            .collect::<HashSet<_>>()
+    );
+
+    // the replaced one is gone after flush
+    exec_flush(vec![other.transaction.hash], Arc::clone(&mempool)).await;
+    assert_eq!(mempool.len(), 1);
+    assert_eq!(mempool.get_tx_cache().content(None).pending_len(), 1);
+}
+
+#[tokio::test]
+async fn test_package_gas_limit() {
+    let mempool = Arc::new(default_mempool().await);
+    let (key_a, key_b, key_c) = (
+        Secp256k1RecoverablePrivateKey::generate(&mut OsRng),
+        Secp256k1RecoverablePrivateKey::generate(&mut OsRng),
+        Secp256k1RecoverablePrivateKey::generate(&mut OsRng),
+    );
+
+    let a_0 = mock_priced_signed_tx(&key_a, 0, 30, 10, 10);
+    let a_1 = mock_priced_signed_tx(&key_a, 1, 30, 100, 100);
+    let b_0 = mock_priced_signed_tx(&key_b, 0, 60, 50, 50);
+    let c_0 = mock_priced_signed_tx(&key_c, 0, 10, 5, 5);
+    let hash = |tx: &SignedTransaction| tx.transaction.hash;
+    concurrent_insert(
+        vec![a_0.clone(), a_1.clone(), b_0.clone(), c_0.clone()],
+        Arc::clone(&mempool),
+    )
+    .await;
+
+    // a_0 does not fit, so a_1 is skipped as well and the smaller c_0 is taken
+    let package_txs = exec_package(Arc::clone(&mempool), 70u64.into(), TX_NUM_LIMIT).await;
+    assert_eq!(package_txs, vec![hash(&b_0), hash(&c_0)]);
+
+    // a_1 is the highest price, but it has to wait for a_0
+    let package_txs = exec_package(Arc::clone(&mempool), 100u64.into(), TX_NUM_LIMIT).await;
+    assert_eq!(package_txs, vec![hash(&b_0), hash(&a_0), hash(&c_0)]);
+
+    let package_txs = exec_package(Arc::clone(&mempool), 200u64.into(), TX_NUM_LIMIT).await;
+    assert_eq!(package_txs, vec![
+        hash(&b_0),
+        hash(&a_0),
+        hash(&a_1),
+        hash(&c_0)
+    ]);
+
+    let package_txs = exec_package(Arc::clone(&mempool), 200u64.into(), 2).await;
+    assert_eq!(package_txs, vec![hash(&b_0), hash(&a_0)]);
+}
+
+#[tokio::test]
+async fn test_queued_txs() {
+    let mempool = Arc::new(default_mempool().await);
+    let priv_key = Secp256k1RecoverablePrivateKey::generate(&mut OsRng);
+    let txs = (0..4)
+        .map(|nonce| mock_priced_signed_tx(&priv_key, nonce, 1, 10, 10))
+        .collect::<Vec<_>>();
+    let hashes = txs.iter().map(|tx| tx.transaction.hash).collect::<Vec<_>>();
+
+    // the nonce 1 is missing
+    for tx in [&txs[0], &txs[2], &txs[3]] {
+        mempool.insert(Context::new(), tx.clone()).await.unwrap();
+    }
+    assert_eq!(mempool.len(), 1);
+    assert_eq!(mempool.queued_len(), 2);
+    assert!(mempool.get_tx_cache().contains(&hashes[3]));
+    let package_txs = exec_package(Arc::clone(&mempool), CYCLE_LIMIT.into(), TX_NUM_LIMIT).await;
+    assert_eq!(package_txs, vec![hashes[0]]);
+
+    // fill the gap
+    mempool
+        .insert(Context::new(), txs[1].clone())
+        .await
+        .unwrap();
+    assert_eq!(mempool.len(), 4);
+    assert_eq!(mempool.queued_len(), 0);
+    let package_txs = exec_package(Arc::clone(&mempool), CYCLE_LIMIT.into(), TX_NUM_LIMIT).await;
+    assert_eq!(package_txs, hashes);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_promote_queued_txs_on_flush() {
+    let mempool = Arc::new(default_mempool().await);
+    let priv_key = Secp256k1RecoverablePrivateKey::generate(&mut OsRng);
+    let txs = (1..3)
+        .map(|nonce| mock_priced_signed_tx(&priv_key, nonce, 1, 10, 10))
+        .collect::<Vec<_>>();
+    let sender = txs[0].sender;
+    mempool.get_adapter().set_account_nonce(sender, 0u64.into());
+    concurrent_insert(txs.clone(), Arc::clone(&mempool)).await;
+    assert_eq!(mempool.len(), 0);
+    assert_eq!(mempool.queued_len(), 2);
+
+    // the nonce 0 is committed by another node
+    mempool.get_adapter().set_account_nonce(sender, 1u64.into());
+    exec_flush(vec![], Arc::clone(&mempool)).await;
+    assert_eq!(mempool.len(), 2);
+    assert_eq!(mempool.queued_len(), 0);
+
+    // the queued tx whose nonce is used is dropped
+    let tx = mock_priced_signed_tx(&priv_key, 4, 1, 10, 10);
+    mempool.insert(Context::new(), tx.clone()).await.unwrap();
+    assert_eq!(mempool.queued_len(), 1);
+    mempool.get_adapter().set_account_nonce(sender, 5u64.into());
+    exec_flush(vec![], Arc::clone(&mempool)).await;
+    assert_eq!(mempool.queued_len(), 0);
+    assert!(!mempool.get_tx_cache().contains(&tx.transaction.hash));
+}
+
+#[tokio::test]
+async fn test_queued_limits() {
+    let mempool = Arc::new(default_mempool().await);
+    let priv_key = Secp256k1RecoverablePrivateKey::generate(&mut OsRng);
+    let txs = (0..13)
+        .map(|nonce| mock_priced_signed_tx(&priv_key, nonce, 1, 10, 10))
+        .collect::<Vec<_>>();
+
+    // the txs beyond the pending limit of an account are queued
+    for tx in txs[..12].iter() {
+        mempool.insert(Context::new(), tx.clone()).await.unwrap();
+    }
+    assert_eq!(mempool.len(), PENDING_PER_ACCOUNT);
+    assert_eq!(mempool.queued_len(), QUEUED_PER_ACCOUNT);
+
+    // reach the queued limit of an account
+    let err = mempool
+        .insert(Context::new(), txs[12].clone())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("ExceedAccountLimit"));
+
+    // fill up the queued tier with gapped txs of other accounts
+    for _ in 0..QUEUED_SIZE - QUEUED_PER_ACCOUNT {
+        let priv_key = Secp256k1RecoverablePrivateKey::generate(&mut OsRng);
+        let tx = mock_priced_signed_tx(&priv_key, 1, 1, 10, 10);
+        mempool
+            .get_adapter()
+            .set_account_nonce(tx.sender, 0u64.into());
+        mempool.insert(Context::new(), tx).await.unwrap();
+    }
+    assert_eq!(mempool.queued_len(), QUEUED_SIZE);
+
+    // a cheaper one is rejected
+    let priv_key = Secp256k1RecoverablePrivateKey::generate(&mut OsRng);
+    let tx = mock_priced_signed_tx(&priv_key, 1, 1, 5, 5);
+    mempool
+        .get_adapter()
+        .set_account_nonce(tx.sender, 0u64.into());
+    let err = mempool.insert(Context::new(), tx).await.unwrap_err();
+    assert!(err.to_string().contains("ReachLimit"));
+
+    // a more expensive one evicts the lowest priced and the farthest one
+    let tx = mock_priced_signed_tx(&priv_key, 1, 1, 20, 20);
+    mempool.insert(Context::new(), tx.clone()).await.unwrap();
+    assert_eq!(mempool.queued_len(), QUEUED_SIZE);
+    assert!(mempool.get_tx_cache().contains(&tx.transaction.hash));
+    assert!(!mempool.get_tx_cache().contains(&txs[11].transaction.hash));
+}
+
+#[tokio::test]
+async fn test_mempool_content() {
+    let mempool = Arc::new(default_mempool().await);
+    let priv_key = Secp256k1RecoverablePrivateKey::generate(&mut OsRng);
+    let txs = (0..3)
+        .map(|nonce| mock_priced_signed_tx(&priv_key, nonce, 1, 10, 10))
+        .collect::<Vec<_>>();
+    let sender = txs[0].sender;
+    let other_tx = default_mock_txs(1).pop().unwrap();
+    let sys_tx = mock_sys_txs(1).pop().unwrap();
+
+    for tx in [&txs[0], &txs[2], &other_tx, &sys_tx] {
+        mempool.insert(Context::new(), tx.clone()).await.unwrap();
+    }
+
+    let content = mempool.get_content(Context::new(), None).await.unwrap();
+    assert_eq!(content.pending_len(), 2);
+    assert_eq!(content.queued_len(), 1);
+    assert_eq!(content.pending[&sender][&U256::zero()], txs[0]);
+    assert_eq!(content.queued[&sender][&U256::from(2u64)], txs[2]);
+    assert!(content.pending.contains_key(&other_tx.sender));
+    assert_eq!(content.system, vec![sys_tx]);
+
+    let content = mempool
+        .get_content(Context::new(), Some(sender))
+        .await
+        .unwrap();
+    assert_eq!(content.pending.len(), 1);
+    assert_eq!(content.queued.len(), 1);
+    assert!(content.system.is_empty());
+}
+
+#[tokio::test]
+async fn test_subscribe_new_txs() {
+    let mempool = Arc::new(default_mempool().await);
+    let mut new_txs = mempool.subscribe_new_txs();
+    let txs = default_mock_txs(2);
+
+    for tx in txs.iter() {
+        mempool.insert(Context::new(), tx.clone()).await.unwrap();
+    }
+    // a duplicated one is not published again
+    mempool
+        .insert(Context::new(), txs[0].clone())
+        .await
+        .unwrap();
+
+    assert_eq!(new_txs.recv().await.unwrap(), txs[0]);
+    assert_eq!(new_txs.recv().await.unwrap(), txs[1]);
+    assert!(new_txs.try_recv().is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_mempool_journal() {
+    let path = "./free-space/mempool/journal_replay";
+    let _ = std::fs::remove_dir_all(path);
+    let txs = default_mock_txs(3);
+    let hashes = txs.iter().map(|tx| tx.transaction.hash).collect::<Vec<_>>();
+
+    let mempool = Arc::new(default_mempool().await.with_journal(path).await.unwrap());
+    concurrent_insert(txs, Arc::clone(&mempool)).await;
+    exec_flush(vec![hashes[0]], Arc::clone(&mempool)).await;
+    assert_eq!(mempool.len(), 2);
+    drop(mempool);
+
+    // the txs which are not committed survive the restart
+    let mempool = default_mempool().await.with_journal(path).await.unwrap();
+    assert_eq!(mempool.len(), 2);
+    assert!(!mempool.get_tx_cache().contains(&hashes[0]));
+    assert!(mempool.get_tx_cache().contains(&hashes[1]));
+    assert!(mempool.get_tx_cache().contains(&hashes[2]));
+
+    std::fs::remove_dir_all(path).unwrap();
+}
+
 #[rustfmt::skip]
 /// Bench in Intel(R) Core(TM) i7-4770HQ CPU @ 2.20GHz (8 x 2200):
 /// test tests::mempool::bench_check_sig             ... bench:   2,881,140 ns/iter (+/- 907,215)
@@ -322,7 +605,7 @@ fn bench_package(b: &mut Bencher) {
     runtime.block_on(concurrent_insert(txs, Arc::clone(&mempool)));
     std::thread::sleep(std::time::Duration::from_secs(1));
 
-    assert_eq!(mempool.get_tx_cache().real_queue_len(), 20_000);
+    assert_eq!(mempool.get_tx_cache().content(None).pending_len(), 20_000);
 
     b.iter(|| {
         runtime.block_on(exec_package(
@@ -16,7 +16,7 @@ use core_executor::NATIVE_TOKEN_ISSUE_ADDRESS;
 use protocol::traits::{Context, MemPool, MemPoolAdapter};
 use protocol::types::{
     public_to_address, recover_intact_pub_key, Bytes, Hash, Public, SignedTransaction, Transaction,
-    TransactionAction, UnverifiedTransaction, H256, U256,
+    TransactionAction, UnverifiedTransaction, H160, H256, U256,
 };
 use protocol::{async_trait, tokio, ProtocolResult};
 
@@ -29,17 +29,27 @@ const POOL_SIZE: usize = 100_000;
 const MAX_TX_SIZE: u64 = 1024; // 1KB
 const TIMEOUT: u64 = 1000;
 const TIMEOUT_GAP: u64 = 100;
+const PRICE_BUMP: u64 = 10;
+const PENDING_PER_ACCOUNT: usize = 8;
+const QUEUED_SIZE: usize = 8;
+const QUEUED_PER_ACCOUNT: usize = 4;
 
 pub struct HashMemPoolAdapter {
-    network_txs: DashMap<Hash, SignedTransaction>,
+    network_txs:    DashMap<Hash, SignedTransaction>,
+    account_nonces: DashMap<H160, U256>,
 }
 
 impl HashMemPoolAdapter {
     fn new() -> HashMemPoolAdapter {
         HashMemPoolAdapter {
-            network_txs: DashMap::new(),
+            network_txs:    DashMap::new(),
+            account_nonces: DashMap::new(),
         }
     }
+
+    fn set_account_nonce(&self, address: H160, nonce: U256) {
+        self.account_nonces.insert(address, nonce);
+    }
 }
 
 #[async_trait]
@@ -72,11 +82,24 @@ impl MemPoolAdapter for HashMemPoolAdapter {
     async fn check_authorization(
         &self,
         _ctx: Context,
-        _tx: &SignedTransaction,
+        tx: &SignedTransaction,
     ) -> ProtocolResult<()> {
+        // The first transaction of an account unknown to the test continues
+        // its nonce.
+        self.account_nonces
+            .entry(tx.sender)
+            .or_insert(tx.transaction.unsigned.nonce);
         Ok(())
     }
 
+    async fn get_account_nonce(&self, _ctx: Context, address: &H160) -> ProtocolResult<U256> {
+        Ok(self
+            .account_nonces
+            .get(address)
+            .map(|nonce| *nonce.value())
+            .unwrap_or_default())
+    }
+
     async fn check_transaction(&self, _ctx: Context, tx: &SignedTransaction) -> ProtocolResult<()> {
         check_hash(tx)?;
         check_sig(tx)
@@ -144,7 +167,17 @@ async fn new_mempool(
     _max_tx_size: u64,
 ) -> MemPoolImpl<HashMemPoolAdapter> {
     let adapter = HashMemPoolAdapter::new();
-    let mempool = MemPoolImpl::new(pool_size, 20, adapter, vec![]).await;
+    let mempool = MemPoolImpl::new(
+        pool_size,
+        20,
+        PRICE_BUMP,
+        PENDING_PER_ACCOUNT,
+        QUEUED_SIZE,
+        QUEUED_PER_ACCOUNT,
+        adapter,
+        vec![],
+    )
+    .await;
     mempool
 }
 
@@ -317,6 +350,39 @@ fn mock_signed_tx(
     }
 }
 
+fn mock_priced_signed_tx(
+    priv_key: &Secp256k1RecoverablePrivateKey,
+    nonce: u64,
+    gas_limit: u64,
+    gas_price: u64,
+    priority_fee: u64,
+) -> SignedTransaction {
+    let mut raw = mock_transaction(nonce, false);
+    raw.gas_limit = gas_limit.into();
+    raw.gas_price = gas_price.into();
+    raw.max_priority_fee_per_gas = priority_fee.into();
+    let mut tx = UnverifiedTransaction {
+        unsigned:  raw,
+        signature: None,
+        chain_id:  random::<u64>(),
+        hash:      Default::default(),
+    };
+
+    let signature =
+        Secp256k1Recoverable::sign_message(tx.signature_hash().as_bytes(), &priv_key.to_bytes())
+            .unwrap()
+            .to_bytes();
+    tx.signature = Some(signature.into());
+
+    let pub_key = Public::from_slice(&priv_key.pub_key().to_uncompressed_bytes()[1..65]);
+
+    SignedTransaction {
+        transaction: tx.calc_hash(),
+        sender:      public_to_address(&pub_key),
+        public:      Some(pub_key),
+    }
+}
+
 fn mock_system_script_signed_tx(
     priv_key: &Secp256k1RecoverablePrivateKey,
     pub_key: &Secp256k1RecoverablePublicKey,
@@ -33,6 +33,7 @@ impl TxWrapper {
 pub struct TxDigest {
     pub hash:      Hash,
     pub gas_price: U256,
+    pub gas_limit: U256,
     pub nonce:     U256,
     pub sender:    H160,
 
@@ -72,6 +73,7 @@ impl From<&SignedTransaction> for TxDigest {
         TxDigest {
             hash:       stx.transaction.hash,
             gas_price:  stx.transaction.unsigned.gas_price,
+            gas_limit:  stx.transaction.unsigned.gas_limit,
             nonce:      stx.transaction.unsigned.nonce,
             sender:     stx.sender,
             is_dropped: AtomicBool::new(false),
@@ -113,6 +115,7 @@ mod tests {
         Arc::new(TxDigest {
             hash:       rand_hash(),
             gas_price:  gas_price.into(),
+            gas_limit:  U256::one(),
             nonce:      nonce.into(),
             sender:     H160::default(),
             is_dropped: AtomicBool::new(false),
@@ -22,7 +22,7 @@ where
         addr: H160,
         data: Vec<u8>,
     ) -> ProtocolResult<TxResp> {
-        let mut backend = AxonExecutorAdapter::from_root(
+        let mut backend = AxonExecutorAdapter::without_block_hashes(
             header.state_root,
             Arc::clone(&self.trie_db),
             Arc::clone(&self.storage),
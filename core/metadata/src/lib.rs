@@ -17,7 +17,7 @@ use parking_lot::RwLock;
 
 use protocol::traits::{Context, MetadataControl, MetadataControlAdapter};
 use protocol::types::{
-    ExitReason, Hash, Header, Hex, Metadata, MetadataVersion, ValidatorExtend, H160,
+    Evidence, ExitReason, Hash, Header, Hex, Metadata, MetadataVersion, ValidatorExtend, H160,
 };
 use protocol::{Display, ProtocolError, ProtocolErrorKind, ProtocolResult};
 
@@ -30,6 +30,7 @@ lazy_static::lazy_static! {
 pub struct MetadataController<Adapter> {
     adapter:          Arc<Adapter>,
     metadata_cache:   RwLock<BTreeMap<Epoch, Metadata>>,
+    evidences:        RwLock<BTreeMap<Epoch, Vec<Evidence>>>,
     metadata_address: H160,
 }
 
@@ -54,6 +55,7 @@ where
         let mut cache = self.metadata_cache.write();
         cache.retain(|&k, _| k < boundary);
         cache.insert(epoch, metadata);
+        self.evidences.write().retain(|&k, _| k >= boundary);
 
         common_apm::metrics::network::NETWORK_TAGGED_CONSENSUS_PEERS.set(consensus_count as i64);
 
@@ -80,6 +82,47 @@ where
             .cloned()
             .unwrap()
     }
+
+    fn record_evidence(&self, _ctx: Context, evidence: Evidence) {
+        let epoch = self.calc_epoch(evidence.height);
+        let mut evidences = self.evidences.write();
+        let list = evidences.entry(epoch).or_insert_with(Vec::new);
+
+        if !list.contains(&evidence) {
+            list.push(evidence);
+        }
+    }
+
+    fn get_evidences(&self, _ctx: Context, epoch: u64) -> Vec<Evidence> {
+        self.evidences
+            .read()
+            .get(&epoch)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn get_offenders(&self, ctx: Context, epoch: u64) -> Vec<H160> {
+        let evidences = self.get_evidences(ctx, epoch);
+        if evidences.is_empty() {
+            return Vec::new();
+        }
+
+        let cache = self.metadata_cache.read();
+        let metadata = match cache.get(&epoch) {
+            Some(metadata) => metadata,
+            None => return Vec::new(),
+        };
+
+        metadata
+            .verifier_list
+            .iter()
+            .filter(|v| {
+                let pub_key = v.pub_key.as_bytes();
+                evidences.iter().any(|e| e.voter == pub_key)
+            })
+            .map(|v| v.address)
+            .collect()
+    }
 }
 
 impl<Adapter: MetadataControlAdapter> MetadataController<Adapter> {
@@ -88,6 +131,7 @@ impl<Adapter: MetadataControlAdapter> MetadataController<Adapter> {
         MetadataController {
             adapter,
             metadata_cache: RwLock::new(BTreeMap::new()),
+            evidences: RwLock::new(BTreeMap::new()),
             metadata_address,
         }
     }
@@ -56,6 +56,33 @@ async fn test_need_change_metadata() {
     assert!(!ctl.need_change_metadata(99_999_999));
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_get_offenders() {
+    let handle = TestHandle::new(4).await;
+    let ctl = handle.metadata_controller(TEST_EPOCH_LEN);
+    let metadata = ctl
+        .get_metadata(Context::new(), &mock_header(0, handle.state_root))
+        .unwrap();
+    let verifier = metadata.verifier_list[0].clone();
+    assert!(ctl.get_offenders(Context::new(), 0).is_empty());
+
+    let evidence = Evidence::new(1, 0, 2, verifier.pub_key.as_bytes(), vote(1), vote(2));
+    ctl.record_evidence(Context::new(), evidence.clone());
+    ctl.record_evidence(Context::new(), evidence.clone());
+
+    assert_eq!(ctl.get_evidences(Context::new(), 0), vec![evidence]);
+    assert_eq!(ctl.get_offenders(Context::new(), 0), vec![verifier.address]);
+    assert!(ctl.get_evidences(Context::new(), 1).is_empty());
+}
+
+fn vote(hash: u8) -> EvidenceVote {
+    EvidenceVote {
+        block_hash: Bytes::from(vec![hash; 32]),
+        signature:  Bytes::new(),
+        voters:     Vec::new(),
+    }
+}
+
 #[test]
 fn test_calc_epoch() {
     EPOCH_LEN.swap(Arc::new(100u64));
@@ -12,9 +12,10 @@ use core_storage::{adapter::rocks::RocksAdapter, ImplStorage};
 use protocol::codec::ProtocolCodec;
 use protocol::traits::{CommonStorage, Context, Executor, Storage};
 use protocol::types::{
-    Account, Address, Bytes, Header, Hex, Metadata, MetadataVersion, Proposal, Public, RichBlock,
-    SignatureComponents, SignedTransaction, Transaction, TransactionAction, UnverifiedTransaction,
-    ValidatorExtend, H160, H256, NIL_DATA, RLP_NULL, U256,
+    Account, Address, Bytes, Evidence, EvidenceVote, Header, Hex, Metadata, MetadataVersion,
+    Proposal, Public, RichBlock, SignatureComponents, SignedTransaction, Transaction,
+    TransactionAction, UnverifiedTransaction, ValidatorExtend, H160, H256, NIL_DATA, RLP_NULL,
+    U256,
 };
 
 use crate::{calc_epoch, metadata_abi as abi, MetadataAdapterImpl, MetadataController, EPOCH_LEN};
@@ -40,8 +41,13 @@ impl TestHandle {
             Default::default(),
         )
         .unwrap();
-        let trie_db =
-            RocksTrieDB::new(path + &salt.to_string() + "/trie", Default::default(), 50).unwrap();
+        let trie_db = RocksTrieDB::new(
+            path + &salt.to_string() + "/trie",
+            Default::default(),
+            50,
+            Default::default(),
+        )
+        .unwrap();
 
         let mut handle = TestHandle {
             storage:    Arc::new(ImplStorage::new(Arc::new(storage_adapter))),
@@ -81,6 +87,7 @@ impl TestHandle {
             Arc::clone(&self.storage),
             proposal.into(),
         )
+        .await
         .unwrap();
 
         let resp = executor.exec(&mut backend, genesis.txs.clone());
@@ -116,7 +123,7 @@ impl TestHandle {
     }
 
     pub fn exec(&mut self, txs: Vec<SignedTransaction>) {
-        let mut backend = AxonExecutorAdapter::from_root(
+        let mut backend = AxonExecutorAdapter::without_block_hashes(
             self.state_root,
             Arc::clone(&self.trie_db),
             Arc::clone(&self.storage),
@@ -210,6 +210,22 @@ impl NetworkConfig {
         self
     }
 
+    pub fn peer_soft_ban(mut self, timeout: Option<u64>) -> Self {
+        if let Some(timeout) = timeout {
+            self.peer_soft_ban = Duration::from_secs(timeout);
+        }
+
+        self
+    }
+
+    pub fn peer_fatal_ban(mut self, timeout: Option<u64>) -> Self {
+        if let Some(timeout) = timeout {
+            self.peer_fatal_ban = Duration::from_secs(timeout);
+        }
+
+        self
+    }
+
     pub fn peer_store_dir(mut self, path: PathBuf) -> Self {
         self.peer_store_path = path;
         self
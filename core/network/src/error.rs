@@ -135,6 +135,12 @@ pub enum NetworkError {
     #[display(fmt = "unsupported peer address {}", _0)]
     UnexpectedPeerAddr(String),
 
+    #[display(fmt = "no address of peer {} is known", _0)]
+    UnknownPeer(String),
+
+    #[display(fmt = "{} is not banned", _0)]
+    NotBanned(String),
+
     #[display(fmt = "unknown endpoint scheme {}", _0)]
     UnexpectedScheme(String),
 
@@ -1,4 +1,7 @@
+use ipnetwork::IpNetwork;
 use parking_lot::{Mutex, RwLock};
+use protocol::traits::TrustFeedback;
+use protocol::types::{NetworkBan, NetworkNodeInfo, NetworkPeer};
 use std::{
     collections::{HashMap, HashSet},
     sync::Arc,
@@ -7,6 +10,7 @@ use tentacle::{
     multiaddr::Multiaddr, secio::PeerId, utils::extract_peer_id, ProtocolId, SessionId,
 };
 
+use self::peer_store::{Behaviour, PeerScoreConfig};
 pub use self::{
     peer_store::{AddrInfo, PeerStore},
     registry::{Online, PeerInfo},
@@ -21,6 +25,7 @@ pub struct PeerManager {
     online:           RwLock<Online>,
     peer_store:       RwLock<PeerStore>,
     bootstraps:       HashMap<PeerId, Multiaddr>,
+    static_peers:     RwLock<HashMap<PeerId, Multiaddr>>,
     chain_id:         Mutex<String>,
     pub public_addrs: RwLock<HashSet<Multiaddr>>,
     config:           Arc<NetworkConfig>,
@@ -41,13 +46,18 @@ impl PeerManager {
             }
             b
         };
+        let mut peer_store = PeerStore::load_from_dir_or_default(config.peer_store_path.clone());
+        peer_store.set_score_config(PeerScoreConfig {
+            ban_timeout_ms: config.peer_soft_ban.as_millis() as u64,
+            ..Default::default()
+        });
+
         PeerManager {
             online: RwLock::new(Online::default()),
-            peer_store: RwLock::new(PeerStore::load_from_dir_or_default(
-                config.peer_store_path.clone(),
-            )),
+            peer_store: RwLock::new(peer_store),
             chain_id: Mutex::new(String::new()),
             bootstraps,
+            static_peers: RwLock::new(HashMap::new()),
             public_addrs: RwLock::new(HashSet::new()),
             config,
             consensus_list: RwLock::new(HashSet::new()),
@@ -104,6 +114,17 @@ impl PeerManager {
         unconnected
     }
 
+    pub fn unconnected_static_peers(&self) -> Vec<Multiaddr> {
+        let online = self.online.read();
+
+        self.static_peers
+            .read()
+            .iter()
+            .filter(|(id, _)| !online.peers.contains_key(id))
+            .map(|(_, addr)| addr.clone())
+            .collect()
+    }
+
     pub fn connected_consensus_peer(&self) -> Vec<Multiaddr> {
         let online = self.online.read();
         let mut list = Vec::new();
@@ -235,6 +256,7 @@ impl PeerManager {
     pub fn always_allow(&self, addr: &Multiaddr) -> bool {
         let peer_id = extract_peer_id(addr).unwrap();
         self.bootstraps.contains_key(&peer_id)
+            || self.static_peers.read().contains_key(&peer_id)
             || self.consensus_list.read().contains(&peer_id)
             || self.with_peer_store(|peer_store| !peer_store.is_addr_banned(addr))
     }
@@ -256,22 +278,165 @@ impl PeerManager {
         if let Some(info) = self.online.read().peers.get(peer_id) {
             self.peer_store
                 .write()
-                .ban_addr(&info.addr, timeout, ban_reason)
+                .ban_addr(&info.addr, timeout, ban_reason);
+            return Some(info.session_id);
         }
         None
     }
 
+    /// Ban the peer by its id and all its known addresses, whether it is
+    /// connected or not. Return whether any address of the peer is known.
+    pub fn ban_peer(&self, peer_id: &PeerId, timeout: u64, ban_reason: String) -> bool {
+        let mut addrs = Vec::new();
+        if let Some(info) = self.online.read().peers.get(peer_id) {
+            addrs.push(info.addr.clone());
+        }
+        if let Some(addr) = self.static_peers.read().get(peer_id) {
+            addrs.push(addr.clone());
+        }
+
+        self.with_peer_store_mut(|peer_store| {
+            peer_store.ban_peer(peer_id, addrs, timeout, ban_reason)
+        })
+    }
+
     pub fn ban_session_id(&self, session_id: SessionId, timeout: u64, ban_reason: String) {
-        let addr = self.online.read().peers.values().find_map(|info| {
+        if let Some(addr) = self.session_addr(session_id) {
+            self.peer_store.write().ban_addr(&addr, timeout, ban_reason)
+        }
+    }
+
+    /// Apply the feedback to the score of the peer on the session, return
+    /// whether the session should be disconnected.
+    pub fn report(&self, session_id: SessionId, feedback: &TrustFeedback) -> bool {
+        let behaviour = match feedback {
+            TrustFeedback::Fatal(_) => None,
+            TrustFeedback::Worse(_) => Some(Behaviour::Worse),
+            TrustFeedback::Bad(_) => Some(Behaviour::Bad),
+            TrustFeedback::Good => Some(Behaviour::Good),
+            TrustFeedback::Neutral => return false,
+        };
+        let addr = match self.session_addr(session_id) {
+            Some(addr) => addr,
+            None => return false,
+        };
+
+        let banned = match behaviour {
+            Some(behaviour) => self.peer_store.write().report(&addr, behaviour).is_banned(),
+            None => {
+                self.peer_store.write().ban_addr(
+                    &addr,
+                    self.config.peer_fatal_ban.as_millis() as u64,
+                    feedback.to_string(),
+                );
+                true
+            }
+        };
+
+        if banned {
+            log::warn!("ban peer {} on session {}: {}", addr, session_id, feedback);
+            common_apm::metrics::network::NETWORK_BANNED_PEERS.inc();
+        }
+        banned
+    }
+
+    /// Keep connecting to the peer until it is removed
+    pub fn add_static_peer(&self, peer_id: PeerId, addr: Multiaddr) {
+        self.with_peer_store_mut(|peer_store| peer_store.add_outbound_addr(addr.clone()));
+        self.static_peers.write().insert(peer_id, addr);
+    }
+
+    /// Forget the peer, return its session if it is connected
+    pub fn remove_peer(&self, peer_id: &PeerId) -> Option<SessionId> {
+        self.static_peers.write().remove(peer_id);
+        self.with_peer_store_mut(|peer_store| peer_store.remove_peer_addrs(peer_id));
+        self.session_id(peer_id)
+    }
+
+    pub fn unban_network(&self, network: &IpNetwork) -> bool {
+        self.with_peer_store_mut(|peer_store| peer_store.unban_network(network))
+    }
+
+    pub fn unban_peer(&self, peer_id: &PeerId) -> bool {
+        self.with_peer_store_mut(|peer_store| peer_store.unban_peer(peer_id))
+    }
+
+    /// The session of the peer if it is connected
+    pub fn session_id(&self, peer_id: &PeerId) -> Option<SessionId> {
+        self.online
+            .read()
+            .peers
+            .get(peer_id)
+            .map(|info| info.session_id)
+    }
+
+    pub fn peer_infos(&self) -> Vec<NetworkPeer> {
+        let online = self.online.read();
+        let peer_store = self.peer_store.read();
+        let consensus_list = self.consensus_list.read();
+        let static_peers = self.static_peers.read();
+
+        online
+            .peers
+            .iter()
+            .map(|(id, info)| {
+                let tags = [
+                    ("consensus", consensus_list.contains(id)),
+                    ("bootstrap", self.bootstraps.contains_key(id)),
+                    ("static", static_peers.contains_key(id)),
+                ];
+
+                NetworkPeer {
+                    peer_id:        id.to_base58(),
+                    connected_addr: info.addr.to_string(),
+                    listen_addrs:   info.listens.iter().map(ToString::to_string).collect(),
+                    inbound:        info.session_type.is_inbound(),
+                    score:          peer_store.peer_score(id).unwrap_or_default(),
+                    tags:           tags
+                        .iter()
+                        .filter(|(_, tagged)| *tagged)
+                        .map(|(tag, _)| tag.to_string())
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+
+    pub fn node_info(&self) -> NetworkNodeInfo {
+        let mut listen_addrs = vec![self.config.default_listen.to_string()];
+        listen_addrs.extend(self.public_addrs.read().iter().map(ToString::to_string));
+
+        let now_ms = faketime::unix_time_as_millis();
+        let banned = self.with_peer_store(|peer_store| {
+            peer_store
+                .ban_list()
+                .get_banned_addrs()
+                .into_iter()
+                .filter(|banned| banned.ban_until > now_ms)
+                .map(|banned| NetworkBan {
+                    address:   banned.address.to_string(),
+                    ban_until: banned.ban_until,
+                    reason:    banned.ban_reason,
+                })
+                .collect()
+        });
+
+        NetworkNodeInfo {
+            peer_id: self.local_peer_id().to_base58(),
+            listen_addrs,
+            chain_id: self.chain_id(),
+            connected_peers: self.with_registry(|reg| reg.peers.len()),
+            banned,
+        }
+    }
+
+    fn session_addr(&self, session_id: SessionId) -> Option<Multiaddr> {
+        self.online.read().peers.values().find_map(|info| {
             if info.session_id == session_id {
                 Some(info.addr.clone())
             } else {
                 None
             }
-        });
-
-        if let Some(addr) = addr {
-            self.peer_store.write().ban_addr(&addr, timeout, ban_reason)
-        }
+        })
     }
 }
@@ -4,7 +4,10 @@ use faketime::unix_time_as_millis;
 use ipnetwork::IpNetwork;
 use std::collections::HashMap;
 use std::net::IpAddr;
-use tentacle::{multiaddr::Multiaddr, utils::multiaddr_to_socketaddr};
+use tentacle::{
+    multiaddr::Multiaddr,
+    utils::{extract_peer_id, multiaddr_to_socketaddr},
+};
 
 pub(crate) const CLEAR_INTERVAL_COUNTER: usize = 1024;
 
@@ -39,9 +42,24 @@ impl BanList {
         }
     }
 
-    /// Unban address
-    pub fn unban_network(&mut self, ip_network: &IpNetwork) {
-        self.inner.remove(ip_network);
+    /// Unban address, return whether it is in the list
+    pub fn unban_network(&mut self, ip_network: &IpNetwork) -> bool {
+        self.inner.remove(ip_network).is_some()
+    }
+
+    /// Unban all the addresses banned along with the peer, return whether any
+    /// is in the list
+    pub fn unban_peer(&mut self, peer_id: &str) -> bool {
+        let count = self.inner.len();
+        self.inner
+            .retain(|_, banned_addr| banned_addr.peer_id.as_deref() != Some(peer_id));
+        self.inner.len() != count
+    }
+
+    fn is_peer_banned_until(&self, peer_id: &str, now_ms: u64) -> bool {
+        self.inner.values().any(|banned_addr| {
+            banned_addr.ban_until.gt(&now_ms) && banned_addr.peer_id.as_deref() == Some(peer_id)
+        })
     }
 
     fn is_ip_banned_until(&self, ip: IpAddr, now_ms: u64) -> bool {
@@ -63,11 +81,15 @@ impl BanList {
         self.is_ip_banned_until(ip.to_owned(), now_ms)
     }
 
-    /// Whether the address is banned
+    /// Whether the address is banned, by its ip or its peer id
     pub fn is_addr_banned(&self, addr: &Multiaddr) -> bool {
+        let now_ms = unix_time_as_millis();
         multiaddr_to_socketaddr(addr)
-            .map(|socket_addr| self.is_ip_banned(&socket_addr.ip()))
+            .map(|socket_addr| self.is_ip_banned_until(socket_addr.ip(), now_ms))
             .unwrap_or_default()
+            || extract_peer_id(addr)
+                .map(|peer_id| self.is_peer_banned_until(&peer_id.to_base58(), now_ms))
+                .unwrap_or_default()
     }
 
     /// Get banned address list
@@ -49,6 +49,28 @@ impl Default for PeerScoreConfig {
     }
 }
 
+/// The behaviours reported by the upper modules, which shift the peer score
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Behaviour {
+    /// Send valid message
+    Good,
+    /// Send useless or oversized message
+    Bad,
+    /// Send invalid message
+    Worse,
+}
+
+impl Behaviour {
+    /// The score change of the behaviour
+    pub fn score(self) -> Score {
+        match self {
+            Behaviour::Good => 1,
+            Behaviour::Bad => -10,
+            Behaviour::Worse => -30,
+        }
+    }
+}
+
 /// Peer Status
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum Status {
@@ -4,8 +4,8 @@ use crate::{
         addr_manager::Manager,
         ban_list::BanList,
         types::{ip_to_network, AddrInfo, BannedAddr, PeerInfo},
-        Group, Multiaddr, PeerScoreConfig, ReportResult, Status, ADDR_COUNT_LIMIT, ADDR_TIMEOUT_MS,
-        ADDR_TRY_TIMEOUT_MS, DIAL_INTERVAL,
+        Behaviour, Group, Multiaddr, PeerScoreConfig, ReportResult, Score, Status,
+        ADDR_COUNT_LIMIT, ADDR_TIMEOUT_MS, ADDR_TRY_TIMEOUT_MS, DIAL_INTERVAL,
     },
 };
 use ipnetwork::IpNetwork;
@@ -41,10 +41,21 @@ impl PeerStore {
         }
     }
 
+    /// Set scoring configuration
+    pub fn set_score_config(&mut self, score_config: PeerScoreConfig) {
+        self.score_config = score_config;
+    }
+
     /// this method will assume peer is connected, which implies address is
     /// "verified".
     pub fn add_connected_peer(&mut self, addr: Multiaddr, session_type: SessionType) {
         let now_ms = faketime::unix_time_as_millis();
+        // A reconnected peer keeps the score of its address
+        let score = self
+            .addr_manager
+            .get(&addr)
+            .map(|peer_addr| peer_addr.score)
+            .unwrap_or(self.score_config.default_score);
         match self
             .peers
             .entry(extract_peer_id(&addr).expect("connected addr should have peer id"))
@@ -56,7 +67,7 @@ impl PeerStore {
                 peer.session_type = session_type;
             }
             Entry::Vacant(entry) => {
-                let peer = PeerInfo::new(addr, session_type, now_ms);
+                let peer = PeerInfo::new(addr, session_type, now_ms, score);
                 entry.insert(peer);
             }
         }
@@ -95,19 +106,34 @@ impl PeerStore {
         &mut self.addr_manager
     }
 
-    /// Report peer behaviours
-    pub fn report(&mut self, addr: &Multiaddr) -> ReportResult {
+    /// Report peer behaviours, the peer is banned once its score drops below
+    /// the ban score
+    pub fn report(&mut self, addr: &Multiaddr, behaviour: Behaviour) -> ReportResult {
+        let peer_id = match extract_peer_id(addr) {
+            Some(peer_id) => peer_id,
+            None => return ReportResult::Ok,
+        };
+        let peer = match self.peers.get_mut(&peer_id) {
+            Some(peer) => peer,
+            None => return ReportResult::Ok,
+        };
+        let score = peer
+            .score
+            .saturating_add(behaviour.score())
+            .min(self.score_config.default_score);
+        peer.score = score;
+
         if let Some(peer_addr) = self.addr_manager.get_mut(addr) {
-            let score = peer_addr.score.saturating_add(0);
             peer_addr.score = score;
-            if score < self.score_config.ban_score {
-                self.ban_addr(
-                    addr,
-                    self.score_config.ban_timeout_ms,
-                    "report behaviour".to_string(),
-                );
-                return ReportResult::Banned;
-            }
+        }
+
+        if score < self.score_config.ban_score {
+            self.ban_addr(
+                addr,
+                self.score_config.ban_timeout_ms,
+                format!("report behaviour {:?}", behaviour),
+            );
+            return ReportResult::Banned;
         }
         ReportResult::Ok
     }
@@ -117,6 +143,19 @@ impl PeerStore {
         extract_peer_id(addr).and_then(|peer_id| self.peers.remove(&peer_id))
     }
 
+    /// Get the score of a connected peer
+    pub fn peer_score(&self, peer_id: &PeerId) -> Option<Score> {
+        self.peers.get(peer_id).map(|peer| peer.score)
+    }
+
+    /// Remove all addresses of the peer
+    #[allow(clippy::mutable_key_type)]
+    pub fn remove_peer_addrs(&mut self, peer_id: &PeerId) {
+        for addr in self.addr_manager.get_by_peer_id(peer_id, |_| true) {
+            self.addr_manager.remove(&addr);
+        }
+    }
+
     /// Get peer status
     pub fn peer_status(&self, peer_id: &PeerId) -> Status {
         if self.peers.contains_key(peer_id) {
@@ -213,10 +252,50 @@ impl PeerStore {
             ban_until: now_ms + timeout_ms,
             created_at: now_ms,
             ban_reason,
+            peer_id: None,
         };
         self.mut_ban_list().ban(ban_addr);
     }
 
+    /// Ban the given addresses and all the known ones of the peer, whether it
+    /// is connected or not. The bans are recorded with the peer id, so that
+    /// the peer is refused from any address and the bans can be lifted by the
+    /// id. Return whether any address of the peer is known.
+    #[allow(clippy::mutable_key_type)]
+    pub(crate) fn ban_peer(
+        &mut self,
+        peer_id: &PeerId,
+        mut addrs: Vec<Multiaddr>,
+        timeout_ms: u64,
+        ban_reason: String,
+    ) -> bool {
+        addrs.extend(self.addr_manager.get_by_peer_id(peer_id, |_| true));
+        if let Some(peer) = self.peers.get(peer_id) {
+            addrs.push(peer.connected_addr.clone());
+        }
+
+        let networks = addrs
+            .iter()
+            .filter_map(multiaddr_to_socketaddr)
+            .map(|socket_addr| ip_to_network(socket_addr.ip()))
+            .collect::<HashSet<_>>();
+        let now_ms = faketime::unix_time_as_millis();
+        for network in networks.iter() {
+            self.mut_ban_list().ban(BannedAddr {
+                address:    *network,
+                ban_until:  now_ms + timeout_ms,
+                created_at: now_ms,
+                ban_reason: ban_reason.clone(),
+                peer_id:    Some(peer_id.to_base58()),
+            });
+        }
+        for addr in addrs.iter() {
+            self.addr_manager.remove(addr);
+        }
+
+        !networks.is_empty()
+    }
+
     /// Whether the address is banned
     pub fn is_addr_banned(&self, addr: &Multiaddr) -> bool {
         self.ban_list().is_addr_banned(addr)
@@ -232,6 +311,17 @@ impl PeerStore {
         &mut self.ban_list
     }
 
+    /// Unban a network, return whether it is banned
+    pub fn unban_network(&mut self, network: &IpNetwork) -> bool {
+        self.ban_list.unban_network(network)
+    }
+
+    /// Unban the addresses banned along with the peer, return whether any is
+    /// banned
+    pub fn unban_peer(&mut self, peer_id: &PeerId) -> bool {
+        self.ban_list.unban_peer(&peer_id.to_base58())
+    }
+
     /// Clear ban list
     pub fn clear_ban_list(&mut self) {
         std::mem::take(&mut self.ban_list);
@@ -322,3 +412,143 @@ impl PeerStore {
         self.peers.len()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use tentacle::{
+        multiaddr::Multiaddr,
+        secio::{PeerId, SecioKeyPair},
+        service::SessionType,
+        utils::multiaddr_to_socketaddr,
+        SessionId,
+    };
+
+    use super::{ip_to_network, Behaviour, PeerStore};
+    use crate::config::NetworkConfig;
+    use crate::peer_manager::{PeerInfo, PeerManager};
+
+    fn mock_peer(ip: &str) -> (PeerId, Multiaddr) {
+        let peer_id = SecioKeyPair::secp256k1_generated().peer_id();
+        let addr = format!("/ip4/{}/tcp/2337/p2p/{}", ip, peer_id.to_base58())
+            .parse()
+            .unwrap();
+        (peer_id, addr)
+    }
+
+    #[test]
+    fn test_report_ban_peer() {
+        let peer_id = SecioKeyPair::secp256k1_generated().peer_id();
+        let addr: Multiaddr = format!("/ip4/10.0.0.1/tcp/2337/p2p/{}", peer_id.to_base58())
+            .parse()
+            .unwrap();
+        let mut store = PeerStore::default();
+        store.add_connected_peer(addr.clone(), SessionType::Inbound);
+
+        assert!(store.report(&addr, Behaviour::Good).is_ok());
+        assert!(store.report(&addr, Behaviour::Worse).is_ok());
+        assert!(store.report(&addr, Behaviour::Worse).is_ok());
+        assert!(!store.is_addr_banned(&addr));
+
+        assert!(store.report(&addr, Behaviour::Worse).is_banned());
+        assert!(store.is_addr_banned(&addr));
+    }
+
+    #[test]
+    fn test_unban_network() {
+        let (_, addr) = mock_peer("10.0.0.2");
+        let network = ip_to_network(multiaddr_to_socketaddr(&addr).unwrap().ip());
+        let mut store = PeerStore::default();
+        store.ban_addr(&addr, 60_000, "test".to_string());
+        assert!(store.is_addr_banned(&addr));
+
+        assert!(store.unban_network(&network));
+        assert!(!store.is_addr_banned(&addr));
+        assert!(!store.unban_network(&network));
+    }
+
+    #[test]
+    fn test_ban_offline_peer() {
+        let manager = PeerManager::new(Arc::new(NetworkConfig::new()));
+        let (peer_id, addr) = mock_peer("10.0.0.5");
+        let other_addr: Multiaddr = format!("/ip4/10.0.0.6/tcp/2337/p2p/{}", peer_id.to_base58())
+            .parse()
+            .unwrap();
+        let (unknown_id, _) = mock_peer("10.0.0.7");
+
+        manager.add_static_peer(peer_id.clone(), addr.clone());
+        assert_eq!(manager.session_id(&peer_id), None);
+        assert!(manager.ban_peer(&peer_id, 60_000, "test".to_string()));
+        assert!(!manager.ban_peer(&unknown_id, 60_000, "test".to_string()));
+
+        // the peer is refused from any address
+        assert!(manager.with_peer_store(|store| store.is_addr_banned(&addr)));
+        assert!(manager.with_peer_store(|store| store.is_addr_banned(&other_addr)));
+        assert_eq!(manager.node_info().banned.len(), 1);
+
+        assert!(manager.unban_peer(&peer_id));
+        assert!(!manager.unban_peer(&peer_id));
+        assert!(!manager.with_peer_store(|store| store.is_addr_banned(&addr)));
+        assert!(!manager.with_peer_store(|store| store.is_addr_banned(&other_addr)));
+    }
+
+    #[test]
+    fn test_static_peer() {
+        let manager = PeerManager::new(Arc::new(NetworkConfig::new()));
+        let (peer_id, addr) = mock_peer("10.0.0.3");
+
+        manager.add_static_peer(peer_id.clone(), addr.clone());
+        assert_eq!(manager.unconnected_static_peers(), vec![addr.clone()]);
+        assert!(manager.with_peer_store(|store| store.addr_manager().get(&addr).is_some()));
+
+        let session_id = SessionId::new(1);
+        manager.register(PeerInfo::mock(
+            addr.clone(),
+            session_id,
+            SessionType::Outbound,
+        ));
+        assert!(manager.unconnected_static_peers().is_empty());
+
+        let infos = manager.peer_infos();
+        assert_eq!(infos.len(), 1);
+        assert_eq!(infos[0].peer_id, peer_id.to_base58());
+        assert_eq!(infos[0].connected_addr, addr.to_string());
+        assert!(!infos[0].inbound);
+        assert_eq!(infos[0].tags, vec!["static".to_string()]);
+
+        // the session is left to the caller to disconnect
+        assert_eq!(manager.remove_peer(&peer_id), Some(session_id));
+        assert!(manager.with_peer_store(|store| store.addr_manager().get(&addr).is_none()));
+        assert!(manager.peer_infos()[0].tags.is_empty());
+
+        manager.unregister(&addr);
+        assert!(manager.peer_infos().is_empty());
+        assert!(manager.unconnected_static_peers().is_empty());
+        assert_eq!(manager.remove_peer(&peer_id), None);
+    }
+
+    #[test]
+    fn test_node_info() {
+        let config = Arc::new(NetworkConfig::new());
+        let manager = PeerManager::new(Arc::clone(&config));
+        manager.set_chain_id("0x5".to_string());
+
+        let (_, addr) = mock_peer("10.0.0.4");
+        let network = ip_to_network(multiaddr_to_socketaddr(&addr).unwrap().ip());
+        manager.with_peer_store_mut(|store| store.ban_addr(&addr, 60_000, "test".to_string()));
+
+        let info = manager.node_info();
+        assert_eq!(info.peer_id, config.secio_keypair.peer_id().to_base58());
+        assert_eq!(info.listen_addrs, vec![config.default_listen.to_string()]);
+        assert_eq!(info.chain_id, "0x5");
+        assert_eq!(info.connected_peers, 0);
+        assert_eq!(info.banned.len(), 1);
+        assert_eq!(info.banned[0].address, network.to_string());
+        assert_eq!(info.banned[0].reason, "test");
+
+        assert!(manager.unban_network(&network));
+        assert!(!manager.unban_network(&network));
+        assert!(manager.node_info().banned.is_empty());
+    }
+}
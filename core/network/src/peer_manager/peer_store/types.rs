@@ -16,6 +16,8 @@ pub struct PeerInfo {
     pub session_type:         SessionType,
     /// Connected time
     pub last_connected_at_ms: u64,
+    /// Score about this peer
+    pub score:                Score,
 }
 
 impl PeerInfo {
@@ -24,11 +26,13 @@ impl PeerInfo {
         connected_addr: Multiaddr,
         session_type: SessionType,
         last_connected_at_ms: u64,
+        score: Score,
     ) -> Self {
         PeerInfo {
             connected_addr,
             session_type,
             last_connected_at_ms,
+            score,
         }
     }
 }
@@ -116,6 +120,9 @@ pub struct BannedAddr {
     pub ban_reason: String,
     /// Ban time
     pub created_at: u64,
+    /// The peer banned by its id along with the address
+    #[serde(default)]
+    pub peer_id:    Option<String>,
 }
 
 /// Convert multiaddr to IpNetwork
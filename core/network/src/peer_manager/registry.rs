@@ -28,6 +28,18 @@ impl PeerInfo {
         }
     }
 
+    #[cfg(test)]
+    pub fn mock(addr: Multiaddr, session_id: SessionId, session_type: SessionType) -> Self {
+        PeerInfo {
+            addr,
+            session_id,
+            opened_protocols: Default::default(),
+            session_type,
+            listens: Vec::new(),
+            reuse: false,
+        }
+    }
+
     pub fn insert_protocol(&mut self, id: ProtocolId) {
         self.opened_protocols.insert(id);
     }
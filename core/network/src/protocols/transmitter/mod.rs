@@ -70,14 +70,23 @@ impl ServiceProtocol for TransmitterProtocol {
 
         // let host = remote_peer.connected_addr.host.to_owned();
         let route_fut = self.router.route_message(remote_peer.clone(), recv_msg);
+        let peer_manager = Arc::clone(&self.peer_manager);
+        let control = context.control().clone();
         spawn(async move {
             common_apm::metrics::network::NETWORK_RECEIVED_MESSAGE_IN_PROCESSING_GUAGE.inc();
             common_apm::metrics::network::NETWORK_RECEIVED_PEER_ID_MESSAGE_IN_PROCESSING_GUAGE_VEC
                 .with_label_values(&[&peer_id])
                 .inc();
 
-            if let Err(err) = route_fut.await {
-                log::warn!("route message from {:?} failed: {:?}", remote_peer, err);
+            match route_fut.await {
+                Ok(feedback) => {
+                    if peer_manager.report(remote_peer.session_id, &feedback) {
+                        let _ignore = control.disconnect(remote_peer.session_id).await;
+                    }
+                }
+                Err(err) => {
+                    log::warn!("route message from {:?} failed: {:?}", remote_peer, err)
+                }
             }
 
             common_apm::metrics::network::NETWORK_RECEIVED_MESSAGE_IN_PROCESSING_GUAGE.dec();
@@ -22,7 +22,7 @@ pub trait Reactor: Send + Sync {
         context: RouterContext,
         endpoint: Endpoint,
         network_message: NetworkMessage,
-    ) -> ProtocolResult<()>;
+    ) -> ProtocolResult<TrustFeedback>;
 }
 
 pub struct MessageReactor<M: MessageCodec, H: MessageHandler<Message = M>> {
@@ -46,7 +46,7 @@ impl<M: MessageCodec, H: MessageHandler<Message = M>> Reactor for MessageReactor
         context: RouterContext,
         endpoint: Endpoint,
         mut network_message: NetworkMessage,
-    ) -> ProtocolResult<()> {
+    ) -> ProtocolResult<TrustFeedback> {
         let ctx = Context::new()
             .set_session_id(context.remote_peer.session_id)
             .set_remote_peer_id(context.remote_peer.peer_id.clone())
@@ -61,15 +61,21 @@ impl<M: MessageCodec, H: MessageHandler<Message = M>> Reactor for MessageReactor
         };
 
         let session_id = context.remote_peer.session_id;
-        let _feedback = match endpoint.scheme() {
+        let feedback = match endpoint.scheme() {
             EndpointScheme::Gossip => {
                 let raw_context = Bytes::from(network_message.content);
-                let content = M::decode_msg(raw_context)?;
+                let content = match M::decode_msg(raw_context) {
+                    Ok(content) => content,
+                    Err(e) => return Ok(TrustFeedback::Worse(e.to_string())),
+                };
                 self.msg_handler.process(ctx, content).await
             }
             EndpointScheme::RpcCall => {
                 let raw_context = Bytes::from(network_message.content);
-                let content = M::decode_msg(raw_context)?;
+                let content = match M::decode_msg(raw_context) {
+                    Ok(content) => content,
+                    Err(e) => return Ok(TrustFeedback::Worse(e.to_string())),
+                };
                 let rpc_endpoint = RpcEndpoint::try_from(endpoint)?;
 
                 let ctx = ctx.set_rpc_id(rpc_endpoint.rpc_id().value());
@@ -100,7 +106,7 @@ impl<M: MessageCodec, H: MessageHandler<Message = M>> Reactor for MessageReactor
                         full_url,
                         context.remote_peer
                     );
-                    return Ok(());
+                    return Ok(TrustFeedback::Neutral);
                 }
 
                 let rpc_id = rpc_endpoint.rpc_id().value();
@@ -110,12 +116,11 @@ impl<M: MessageCodec, H: MessageHandler<Message = M>> Reactor for MessageReactor
                     log::warn!("network: reactor: {} rpc dropped on {}", session_id, end);
                 }
 
-                return Ok(());
+                return Ok(TrustFeedback::Neutral);
             }
         };
 
-        // context.report_feedback(feedback);
-        Ok(())
+        Ok(feedback)
     }
 }
 
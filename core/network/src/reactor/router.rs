@@ -4,7 +4,7 @@ use std::sync::Arc;
 
 use derive_more::Display;
 use parking_lot::RwLock;
-use protocol::traits::{MessageCodec, MessageHandler};
+use protocol::traits::{MessageCodec, MessageHandler, TrustFeedback};
 use protocol::{types::Bytes, ProtocolResult};
 use tentacle::context::ProtocolContextMutRef;
 use tentacle::secio::PeerId;
@@ -99,13 +99,16 @@ impl MessageRouter {
         &self,
         remote_peer: RemotePeer,
         recv_msg: ReceivedMessage,
-    ) -> impl Future<Output = ProtocolResult<()>> {
+    ) -> impl Future<Output = ProtocolResult<TrustFeedback>> {
         let reactor_map = Arc::clone(&self.reactor_map);
         let router_context = RouterContext::new(remote_peer, Arc::clone(&self.rpc_map));
         // let raw_data_size = recv_msg.data.len();
 
         async move {
-            let network_message = { NetworkMessage::decode(recv_msg.data)? };
+            let network_message = match NetworkMessage::decode(recv_msg.data) {
+                Ok(network_message) => network_message,
+                Err(e) => return Ok(TrustFeedback::Worse(e.to_string())),
+            };
             common_apm::metrics::network::on_network_message_received(&network_message.url);
 
             let endpoint = network_message.url.parse::<Endpoint>()?;
@@ -4,6 +4,7 @@ use std::os::unix::io::{FromRawFd, IntoRawFd};
 use std::os::windows::io::{FromRawSocket, IntoRawSocket};
 use std::{collections::HashSet, sync::Arc, time::Duration};
 
+use ipnetwork::IpNetwork;
 use rand::prelude::IteratorRandom;
 use tentacle::{
     builder::ServiceBuilder,
@@ -27,7 +28,7 @@ use protocol::{
         Context, Gossip, MessageCodec, MessageHandler, Network, PeerTag, PeerTrust, Priority, Rpc,
         TrustFeedback,
     },
-    types::Bytes,
+    types::{Bytes, NetworkNodeInfo, NetworkPeer},
     ProtocolResult,
 };
 
@@ -42,6 +43,7 @@ use crate::{
         SupportProtocols, TransmitterProtocol,
     },
     reactor::MessageRouter,
+    traits::NetworkContext,
 };
 
 #[derive(Clone)]
@@ -114,7 +116,20 @@ impl Rpc for NetworkServiceHandle {
 }
 
 impl PeerTrust for NetworkServiceHandle {
-    fn report(&self, _ctx: Context, _feedback: TrustFeedback) {}
+    fn report(&self, ctx: Context, feedback: TrustFeedback) {
+        // Only the feedback on a message from a remote peer has a session
+        let session_id = match ctx.session_id() {
+            Ok(session_id) => session_id,
+            Err(_) => return,
+        };
+
+        if self.gossip.peer_manager.report(session_id, &feedback) {
+            let sender = self.gossip.transmitter.clone();
+            tokio::spawn(async move {
+                let _ignore = sender.disconnect(session_id).await;
+            });
+        }
+    }
 }
 
 impl Network for NetworkServiceHandle {
@@ -186,6 +201,81 @@ impl Network for NetworkServiceHandle {
             .peer_manager
             .with_registry(|reg| reg.peers.len()))
     }
+
+    fn peers(&self, _ctx: Context) -> ProtocolResult<Vec<NetworkPeer>> {
+        Ok(self.gossip.peer_manager.peer_infos())
+    }
+
+    fn node_info(&self, _ctx: Context) -> ProtocolResult<NetworkNodeInfo> {
+        Ok(self.gossip.peer_manager.node_info())
+    }
+
+    fn add_peer(&self, _ctx: Context, addr: &str) -> ProtocolResult<()> {
+        let addr = addr
+            .parse::<Multiaddr>()
+            .map_err(|_| NetworkError::UnexpectedPeerAddr(addr.to_owned()))?;
+        let peer_id = match extract_peer_id(&addr) {
+            Some(id) if id != self.gossip.peer_manager.local_peer_id() => id,
+            _ => return Err(NetworkError::UnexpectedPeerAddr(addr.to_string()).into()),
+        };
+
+        // The peer is dialed on the next round of the service
+        self.gossip.peer_manager.add_static_peer(peer_id, addr);
+        Ok(())
+    }
+
+    fn remove_peer(&self, _ctx: Context, peer_id: &str) -> ProtocolResult<()> {
+        let peer_id = peer_id
+            .parse::<PeerId>()
+            .map_err(|_| NetworkError::InvalidPeerId)?;
+
+        if let Some(id) = self.gossip.peer_manager.remove_peer(&peer_id) {
+            let sender = self.gossip.transmitter.clone();
+            tokio::spawn(async move {
+                let _ignore = sender.disconnect(id).await;
+            });
+        }
+        Ok(())
+    }
+
+    fn ban_peer(&self, _ctx: Context, peer_id: &str, duration: u64) -> ProtocolResult<()> {
+        let id = peer_id
+            .parse::<PeerId>()
+            .map_err(|_| NetworkError::InvalidPeerId)?;
+        let peer_manager = &self.gossip.peer_manager;
+        if !peer_manager.ban_peer(
+            &id,
+            Duration::from_secs(duration).as_millis() as u64,
+            "ban from admin".to_string(),
+        ) {
+            return Err(NetworkError::UnknownPeer(peer_id.to_owned()).into());
+        }
+
+        if let Some(session_id) = peer_manager.session_id(&id) {
+            let sender = self.gossip.transmitter.clone();
+            tokio::spawn(async move {
+                let _ignore = sender.disconnect(session_id).await;
+            });
+        }
+        Ok(())
+    }
+
+    fn unban_peer(&self, _ctx: Context, peer: &str) -> ProtocolResult<()> {
+        let unbanned = match peer.parse::<PeerId>() {
+            Ok(id) => self.gossip.peer_manager.unban_peer(&id),
+            Err(_) => {
+                let network = peer
+                    .parse::<IpNetwork>()
+                    .map_err(|_| NetworkError::UnexpectedPeerAddr(peer.to_owned()))?;
+                self.gossip.peer_manager.unban_network(&network)
+            }
+        };
+
+        if !unbanned {
+            return Err(NetworkError::NotBanned(peer.to_owned()).into());
+        }
+        Ok(())
+    }
 }
 
 pub struct NetworkService {
@@ -510,6 +600,14 @@ impl NetworkService {
         }
     }
 
+    async fn try_dial_static(&mut self) {
+        let addrs = self.peer_mgr_handle.unconnected_static_peers();
+
+        for addr in addrs {
+            self.dial_identify(addr).await;
+        }
+    }
+
     #[allow(clippy::unnecessary_to_owned)]
     pub async fn run(mut self) {
         if let Some(mut net) = self.net.take() {
@@ -533,6 +631,7 @@ impl NetworkService {
             tokio::select! {
                 _ = interval.tick() => {
                     self.try_dial_consensus().await;
+                    self.try_dial_static().await;
                     self.try_dial_peers().await;
                     self.try_dial_feeler().await;
                     self.try_dial_observed_addr().await;
@@ -1,6 +1,12 @@
 #![allow(clippy::mutable_key_type)]
 
-use std::{collections::HashMap, convert::TryFrom, panic, sync::Arc, thread, time::Duration};
+mod maintenance;
+mod snapshot;
+
+use std::{
+    collections::HashMap, convert::TryFrom, future::Future, panic, path::Path, sync::Arc, thread,
+    time::Duration,
+};
 
 use backtrace::Backtrace;
 #[cfg(all(
@@ -15,19 +21,21 @@ use {
 
 use common_apm::metrics::mempool::{MEMPOOL_CO_QUEUE_LEN, MEMPOOL_LEN_GAUGE};
 use common_apm::{server::run_prometheus_server, tracing::global_tracer_register};
-use common_config_parser::types::Config;
+use common_config_parser::types::{Config, ExecutorMode};
 use common_crypto::{
     BlsPrivateKey, BlsPublicKey, PublicKey, Secp256k1, Secp256k1PrivateKey,
     Secp256k1RecoverablePrivateKey, ToPublicKey, UncompressedPublicKey,
 };
 use core_api::{jsonrpc::run_jsonrpc_server, DefaultAPIAdapter};
 use core_consensus::message::{
-    ChokeMessageHandler, ProposalMessageHandler, PullBlockRpcHandler, PullProofRpcHandler,
-    PullTxsRpcHandler, QCMessageHandler, RemoteHeightMessageHandler, VoteMessageHandler,
-    BROADCAST_HEIGHT, END_GOSSIP_AGGREGATED_VOTE, END_GOSSIP_SIGNED_CHOKE,
+    ChokeMessageHandler, EvidenceMessageHandler, ProposalMessageHandler, PullBlockRpcHandler,
+    PullCodeRpcHandler, PullProofRpcHandler, PullStateRpcHandler, PullTxsRpcHandler,
+    QCMessageHandler, RemoteHeightMessageHandler, VoteMessageHandler, BROADCAST_HEIGHT,
+    END_GOSSIP_AGGREGATED_VOTE, END_GOSSIP_EVIDENCE, END_GOSSIP_SIGNED_CHOKE,
     END_GOSSIP_SIGNED_PROPOSAL, END_GOSSIP_SIGNED_VOTE, RPC_RESP_SYNC_PULL_BLOCK,
-    RPC_RESP_SYNC_PULL_PROOF, RPC_RESP_SYNC_PULL_TXS, RPC_SYNC_PULL_BLOCK, RPC_SYNC_PULL_PROOF,
-    RPC_SYNC_PULL_TXS,
+    RPC_RESP_SYNC_PULL_CODE, RPC_RESP_SYNC_PULL_PROOF, RPC_RESP_SYNC_PULL_STATE,
+    RPC_RESP_SYNC_PULL_TXS, RPC_SYNC_PULL_BLOCK, RPC_SYNC_PULL_CODE, RPC_SYNC_PULL_PROOF,
+    RPC_SYNC_PULL_STATE, RPC_SYNC_PULL_TXS,
 };
 use core_consensus::status::{CurrentStatus, StatusAgent};
 use core_consensus::{
@@ -35,7 +43,7 @@ use core_consensus::{
     OverlordConsensusAdapter, OverlordSynchronization, SignedTxsWAL,
 };
 use core_cross_client::DefaultCrossAdapter;
-use core_executor::{AxonExecutor, AxonExecutorAdapter, MPTTrie, RocksTrieDB};
+use core_executor::{AxonExecutor, AxonExecutorAdapter, MPTTrie, RocksTrieDB, TriePruner};
 use core_interoperation::InteroperationImpl;
 use core_mempool::{
     DefaultMemPoolAdapter, MemPoolImpl, NewTxsHandler, PullTxsHandler, END_GOSSIP_NEW_TXS,
@@ -46,7 +54,8 @@ use core_network::{
     observe_listen_port_occupancy, NetworkConfig, NetworkService, PeerId, PeerIdExt,
 };
 use core_rpc_client::RpcClient;
-use core_storage::{adapter::rocks::RocksAdapter, ImplStorage};
+use core_storage::adapter::rocks::{CategoryStats, RocksAdapter};
+use core_storage::ImplStorage;
 use protocol::codec::{hex_decode, ProtocolCodec};
 use protocol::lazy::{CHAIN_ID, CURRENT_STATE_ROOT};
 #[cfg(unix)]
@@ -54,9 +63,10 @@ use protocol::tokio::signal::unix as os_impl;
 use protocol::tokio::{runtime::Builder as RuntimeBuilder, sync::Mutex as AsyncMutex, time::sleep};
 use protocol::traits::{
     CommonStorage, Context, Executor, MemPool, MetadataControl, Network, NodeInfo, Storage,
+    StorageCategory,
 };
 use protocol::types::{
-    Account, Address, MerkleRoot, Proposal, RichBlock, Validator, NIL_DATA, RLP_NULL,
+    Account, Address, BlockNumber, MerkleRoot, Proposal, RichBlock, Validator, NIL_DATA, RLP_NULL,
 };
 use protocol::{tokio, Display, From, ProtocolError, ProtocolErrorKind, ProtocolResult};
 
@@ -103,6 +113,78 @@ impl Axon {
         })
     }
 
+    /// Initialize the data directory with the genesis block without running
+    /// the node.
+    pub fn init(mut self) -> ProtocolResult<()> {
+        Self::block_on(async move { self.create_genesis().await })
+    }
+
+    /// Export the state at the given height, or the latest height if not
+    /// given, into a snapshot file. The node must be stopped.
+    pub fn export_snapshot(
+        config: &Config,
+        height: Option<BlockNumber>,
+        path: &Path,
+    ) -> ProtocolResult<()> {
+        Self::block_on(snapshot::export(config, height, path))
+    }
+
+    /// Bootstrap an empty node from a snapshot file, the node syncs from the
+    /// block next to the snapshot once it runs.
+    pub fn import_snapshot(config: &Config, path: &Path) -> ProtocolResult<()> {
+        Self::block_on(snapshot::import(config, path))
+    }
+
+    /// Rewind the chain to the given block whose state must still be
+    /// available. The node must be stopped.
+    pub fn rollback(config: &Config, to: BlockNumber) -> ProtocolResult<()> {
+        Self::block_on(maintenance::rollback(config, to))
+    }
+
+    /// Collect the estimated key count and size of each storage category.
+    pub fn db_stats(config: &Config) -> ProtocolResult<Vec<(StorageCategory, CategoryStats)>> {
+        maintenance::db_stats(config)
+    }
+
+    /// Check the links and proofs of all stored blocks, return the number of
+    /// the latest block.
+    pub fn verify_chain(config: &Config) -> ProtocolResult<BlockNumber> {
+        Self::block_on(maintenance::verify_chain(config))
+    }
+
+    /// Index the logs of the blocks stored before the log index was built,
+    /// return the number of the first indexed block. The node must be
+    /// stopped.
+    pub fn reindex_logs(config: &Config) -> ProtocolResult<BlockNumber> {
+        Self::block_on(maintenance::reindex_logs(config))
+    }
+
+    /// Export the blocks in the range into an RLP block stream, return the
+    /// number of exported blocks.
+    pub fn export_blocks(
+        config: &Config,
+        from: BlockNumber,
+        to: Option<BlockNumber>,
+        path: &Path,
+    ) -> ProtocolResult<u64> {
+        Self::block_on(maintenance::export_blocks(config, from, to, path))
+    }
+
+    /// Execute and save the blocks of an RLP block stream on top of the local
+    /// chain, return the number of imported blocks.
+    pub fn import_blocks(config: &Config, path: &Path) -> ProtocolResult<u64> {
+        Self::block_on(maintenance::import_blocks(config, path))
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let rt = RuntimeBuilder::new_multi_thread()
+            .enable_all()
+            .build()
+            .expect("new tokio runtime");
+
+        rt.block_on(future)
+    }
+
     pub async fn create_genesis(&mut self) -> ProtocolResult<()> {
         // Init Block db
         let path_block = self.config.data_path_for_block();
@@ -110,8 +192,18 @@ impl Axon {
         let storage = Arc::new(ImplStorage::new(rocks_adapter));
 
         match storage.get_latest_block(Context::new()).await {
-            Ok(_) => {
+            Ok(latest) => {
                 log::info!("The Genesis block has been initialized.");
+
+                // The blocks stored before the log index was built.
+                if storage.get_log_index_start().await?.is_none() {
+                    let start = latest.header.number + 1;
+                    storage.set_log_index_start(start).await?;
+                    log::warn!(
+                        "The logs before block {} are not indexed, run reindex-logs to index them",
+                        start
+                    );
+                }
                 return Ok(());
             }
             Err(e) => {
@@ -127,6 +219,7 @@ impl Axon {
             path_state,
             self.config.rocksdb.clone(),
             self.config.executor.triedb_cache_size,
+            self.config.executor.mode,
         )?);
         let mut mpt = MPTTrie::new(Arc::clone(&trie_db));
 
@@ -150,7 +243,8 @@ impl Axon {
             trie_db,
             Arc::clone(&storage),
             proposal.into(),
-        )?;
+        )
+        .await?;
         let resp = executor.exec(&mut backend, self.genesis.txs.clone());
 
         self.state_root = resp.state_root;
@@ -167,6 +261,7 @@ impl Axon {
         storage
             .insert_block(Context::new(), self.genesis.block.clone())
             .await?;
+        storage.set_log_index_start(0).await?;
         storage
             .insert_transactions(
                 Context::new(),
@@ -227,8 +322,8 @@ impl Axon {
             //     config.network.trust_interval_duration,
             //     config.network.trust_max_history_duration,
             // )?
-            // .peer_soft_ban(config.network.soft_ban_duration)
-            // .peer_fatal_ban(config.network.fatal_ban_duration)
+            .peer_soft_ban(config.network.soft_ban_duration)
+            .peer_fatal_ban(config.network.fatal_ban_duration)
             // .rpc_timeout(config.network.rpc_timeout)
             .ping_interval(config.network.ping_interval)
             // .selfcheck_interval(config.network.selfcheck_interval)
@@ -256,8 +351,13 @@ impl Axon {
             path_state,
             config.rocksdb.clone(),
             config.executor.triedb_cache_size,
+            config.executor.mode,
         )?);
 
+        if config.executor.mode == ExecutorMode::Pruned {
+            TriePruner::new(Arc::clone(&trie_db), config.executor.prune_keep_blocks).spawn();
+        }
+
         #[cfg(all(
             not(target_env = "msvc"),
             not(target_os = "macos"),
@@ -298,6 +398,18 @@ impl Axon {
 
         let metadata = metadata_controller.get_metadata(Context::new(), &current_block.header)?;
 
+        // Recover the evidences of double signing in the last two epochs
+        let evidences = storage
+            .get_evidences(
+                Context::new(),
+                metadata.version.start.saturating_sub(self.config.epoch_len),
+                current_block.header.number + 1,
+            )
+            .await?;
+        for evidence in evidences.into_iter() {
+            metadata_controller.record_evidence(Context::new(), evidence);
+        }
+
         let ckb_client = RpcClient::new(
             &self.config.cross_client.ckb_uri,
             &self.config.cross_client.mercury_uri,
@@ -324,15 +436,23 @@ impl Axon {
             config.mempool.broadcast_txs_size,
             config.mempool.broadcast_txs_interval,
         );
-        let mempool = Arc::new(
-            MemPoolImpl::new(
-                config.mempool.pool_size as usize,
-                config.mempool.timeout_gap,
-                mempool_adapter,
-                current_stxs.clone(),
-            )
-            .await,
-        );
+        let mut mempool = MemPoolImpl::new(
+            config.mempool.pool_size as usize,
+            config.mempool.timeout_gap,
+            config.mempool.price_bump,
+            config.mempool.pending_per_account as usize,
+            config.mempool.queued_size as usize,
+            config.mempool.queued_per_account as usize,
+            mempool_adapter,
+            current_stxs.clone(),
+        )
+        .await;
+        if config.mempool.journal {
+            mempool = mempool
+                .with_journal(config.data_path_for_mempool_journal())
+                .await?;
+        }
+        let mempool = Arc::new(mempool);
 
         let monitor_mempool = Arc::clone(&mempool);
         tokio::spawn(async move {
@@ -405,7 +525,8 @@ impl Axon {
                 Arc::clone(&trie_db),
                 Arc::clone(&storage),
                 proposal.into(),
-            )?;
+            )
+            .await?;
             let _resp = executor.exec(&mut backend, current_stxs.clone());
             let block_hash = current_header.hash();
 
@@ -497,6 +618,7 @@ impl Axon {
 
         let synchronization = Arc::new(OverlordSynchronization::<_>::new(
             config.consensus.sync_txs_chunk_size,
+            config.consensus.snap_sync,
             consensus_adapter,
             status_agent.clone(),
             lock,
@@ -529,6 +651,10 @@ impl Axon {
             END_GOSSIP_SIGNED_CHOKE,
             ChokeMessageHandler::new(Arc::clone(&overlord_consensus)),
         )?;
+        network_service.register_endpoint_handler(
+            END_GOSSIP_EVIDENCE,
+            EvidenceMessageHandler::new(Arc::clone(&overlord_consensus)),
+        )?;
         network_service.register_endpoint_handler(
             BROADCAST_HEIGHT,
             RemoteHeightMessageHandler::new(Arc::clone(&synchronization)),
@@ -547,9 +673,21 @@ impl Axon {
             RPC_SYNC_PULL_TXS,
             PullTxsRpcHandler::new(Arc::new(network_service.handle()), Arc::clone(&storage)),
         )?;
+
+        network_service.register_endpoint_handler(
+            RPC_SYNC_PULL_STATE,
+            PullStateRpcHandler::new(Arc::new(network_service.handle()), Arc::clone(&trie_db)),
+        )?;
+
+        network_service.register_endpoint_handler(
+            RPC_SYNC_PULL_CODE,
+            PullCodeRpcHandler::new(Arc::new(network_service.handle()), Arc::clone(&storage)),
+        )?;
         network_service.register_rpc_response(RPC_RESP_SYNC_PULL_BLOCK)?;
         network_service.register_rpc_response(RPC_RESP_SYNC_PULL_PROOF)?;
         network_service.register_rpc_response(RPC_RESP_SYNC_PULL_TXS)?;
+        network_service.register_rpc_response(RPC_RESP_SYNC_PULL_STATE)?;
+        network_service.register_rpc_response(RPC_RESP_SYNC_PULL_CODE)?;
 
         let network_handle = network_service.handle();
 
@@ -562,6 +700,7 @@ impl Axon {
             Arc::clone(&storage),
             Arc::clone(&trie_db),
             Arc::new(network_handle),
+            Arc::clone(&metadata_controller),
         ));
         let _handles = run_jsonrpc_server(self.config.clone(), api_adapter).await?;
 
@@ -0,0 +1,536 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::{path::Path, sync::Arc};
+
+use rlp::{Decodable, DecoderError, Encodable, Prototype, Rlp, RlpStream};
+
+use common_config_parser::types::Config;
+use core_consensus::{
+    engine::generate_receipts_and_logs,
+    util::{digest_signed_transactions, verify_proof_with_metadata},
+    ConsensusWal, SignedTxsWAL,
+};
+use core_executor::{AxonExecutor, AxonExecutorAdapter, JournalTrieDB, RocksTrieDB};
+use core_metadata::{MetadataAdapterImpl, MetadataController};
+use core_storage::adapter::rocks::{CategoryStats, RocksAdapter};
+use core_storage::ImplStorage;
+use protocol::traits::{
+    CommonStorage, Context, Executor, MetadataControl, Storage, StorageCategory,
+};
+use protocol::types::{
+    Block, BlockNumber, ExecResp, Header, MerkleRoot, Proof, Proposal, SignedTransaction, RLP_NULL,
+};
+use protocol::{Display, ProtocolError, ProtocolErrorKind, ProtocolResult};
+
+use crate::snapshot::{open_storage, open_trie_db};
+
+/// A record larger than this is treated as a corrupted length prefix.
+const MAX_RECORD_SIZE: usize = 256 << 20;
+
+const CATEGORIES: [StorageCategory; 9] = [
+    StorageCategory::Block,
+    StorageCategory::BlockHeader,
+    StorageCategory::Receipt,
+    StorageCategory::SignedTransaction,
+    StorageCategory::Wal,
+    StorageCategory::HashHeight,
+    StorageCategory::Code,
+    StorageCategory::LogIndex,
+    StorageCategory::Evidence,
+];
+
+/// Rewind the chain to the given block. The blocks above and the consensus
+/// WALs of their heights are removed so that the node replays them from peers
+/// once it runs.
+pub async fn rollback(config: &Config, to: BlockNumber) -> ProtocolResult<()> {
+    let storage = open_storage(config)?;
+    let trie_db = open_trie_db(config)?;
+    let ctx = Context::new();
+
+    let latest = storage.get_latest_block_header(ctx.clone()).await?;
+    if to >= latest.number {
+        return Err(MaintenanceError::InvalidRollback {
+            to,
+            latest: latest.number,
+        }
+        .into());
+    }
+
+    let block = storage
+        .get_block(ctx.clone(), to)
+        .await?
+        .ok_or(MaintenanceError::MissingBlock(to))?;
+    // The stale nodes journaled by a block belong to the state of its parent.
+    let pruned = matches!(trie_db.pruned_number()?, Some(number) if to < number);
+    let state_root = block.header.state_root;
+    if pruned
+        || (state_root != RLP_NULL
+            && !cita_trie::DB::contains(trie_db.as_ref(), state_root.as_bytes()).unwrap_or(false))
+    {
+        return Err(MaintenanceError::StateNotAvailable(to).into());
+    }
+
+    // The proof of a block is carried by its next block.
+    let proof = storage
+        .get_block_header(ctx.clone(), to + 1)
+        .await?
+        .ok_or(MaintenanceError::MissingBlock(to + 1))?
+        .proof;
+
+    // Move the latest pointers first, so that an interrupted rollback still
+    // leaves a consistent chain behind.
+    storage.update_latest_proof(ctx.clone(), proof).await?;
+    storage.set_latest_block(ctx.clone(), block).await?;
+
+    for number in (to + 1..=latest.number).rev() {
+        storage.remove_block(ctx.clone(), number).await?;
+    }
+
+    // The overlord state and the proposed transactions above the new latest
+    // block must not be restored once the node runs.
+    ConsensusWal::new(config.data_path_for_consensus_wal()).clear()?;
+    SignedTxsWAL::new(config.data_path_for_txs_wal()).remove_from(to + 1)?;
+
+    log::info!(
+        "[maintenance] roll back from block {} to {}, state root {:?}",
+        latest.number,
+        to,
+        state_root
+    );
+
+    Ok(())
+}
+
+/// Collect the estimated key count and size of each storage category.
+pub fn db_stats(config: &Config) -> ProtocolResult<Vec<(StorageCategory, CategoryStats)>> {
+    let adapter = RocksAdapter::new(config.data_path_for_block(), config.rocksdb.clone())?;
+
+    CATEGORIES
+        .iter()
+        .map(|category| Ok((*category, adapter.category_stats(*category)?)))
+        .collect()
+}
+
+/// Check that each stored block links to its parent by `prev_hash` and that
+/// its proof is signed by the verifiers of its epoch. Return the number of the
+/// latest verified block.
+pub async fn verify_chain(config: &Config) -> ProtocolResult<BlockNumber> {
+    let storage = Arc::new(open_storage(config)?);
+    let trie_db = open_trie_db(config)?;
+    let metadata = MetadataController::new(
+        Arc::new(MetadataAdapterImpl::new(Arc::clone(&storage), trie_db)),
+        config.metadata_contract_address.into(),
+        config.epoch_len,
+    );
+    let ctx = Context::new();
+
+    let latest = storage.get_latest_block_header(ctx.clone()).await?;
+    let mut parent = storage
+        .get_block(ctx.clone(), 0)
+        .await?
+        .ok_or(MaintenanceError::MissingBlock(0))?;
+
+    for number in 1..=latest.number {
+        let block = storage
+            .get_block(ctx.clone(), number)
+            .await?
+            .ok_or(MaintenanceError::MissingBlock(number))?;
+        if block.header.number != number || block.header.prev_hash != parent.header_hash() {
+            return Err(MaintenanceError::UnlinkedBlock(number).into());
+        }
+
+        // The proof of a block is carried by its next block.
+        verify_proof(&metadata, &latest, &parent, &block.header.proof)?;
+
+        if number % 10_000 == 0 {
+            log::info!("[maintenance] verified block {}", number);
+        }
+        parent = block;
+    }
+
+    let latest_proof = storage.get_latest_proof(ctx).await?;
+    verify_proof(&metadata, &latest, &parent, &latest_proof)?;
+
+    Ok(latest.number)
+}
+
+/// Index the logs of the blocks stored before the log index was built, from
+/// the first indexed block down to the genesis. The progress is saved after
+/// each block so that an interrupted reindex resumes. Return the number of the
+/// first indexed block.
+pub async fn reindex_logs(config: &Config) -> ProtocolResult<BlockNumber> {
+    let storage = open_storage(config)?;
+    let ctx = Context::new();
+
+    let mut start = match storage.get_log_index_start().await? {
+        Some(start) => start,
+        None => return Ok(0),
+    };
+
+    while start > 0 {
+        let number = start - 1;
+        let block = storage
+            .get_block(ctx.clone(), number)
+            .await?
+            .ok_or(MaintenanceError::MissingBlock(number))?;
+        let receipts = storage
+            .get_receipts(ctx.clone(), number, &block.tx_hashes)
+            .await?
+            .into_iter()
+            .collect::<Option<Vec<_>>>();
+
+        match receipts {
+            Some(receipts) => storage.insert_log_index(&receipts, number).await?,
+            // The receipts of the genesis block are not stored, neither indexed
+            // by a new node.
+            None if number == 0 => (),
+            None => return Err(MaintenanceError::MissingReceipts(number).into()),
+        }
+        storage.set_log_index_start(number).await?;
+        start = number;
+
+        if number % 10_000 == 0 {
+            log::info!("[maintenance] reindexed the logs of block {}", number);
+        }
+    }
+
+    Ok(start)
+}
+
+/// Write the blocks in the range as a stream of RLP records, each of which
+/// holds a block, its transactions and its proof. Return the number of
+/// exported blocks.
+pub async fn export_blocks(
+    config: &Config,
+    from: BlockNumber,
+    to: Option<BlockNumber>,
+    path: &Path,
+) -> ProtocolResult<u64> {
+    let storage = open_storage(config)?;
+    let ctx = Context::new();
+
+    let latest = storage.get_latest_block_header(ctx.clone()).await?;
+    let to = to.unwrap_or(latest.number);
+    if from > to || to > latest.number {
+        return Err(MaintenanceError::InvalidRange {
+            from,
+            to,
+            latest: latest.number,
+        }
+        .into());
+    }
+
+    let file = File::create(path).map_err(MaintenanceError::Io)?;
+    let mut writer = BufWriter::new(file);
+
+    for number in from..=to {
+        let block = storage
+            .get_block(ctx.clone(), number)
+            .await?
+            .ok_or(MaintenanceError::MissingBlock(number))?;
+        let txs = storage
+            .get_transactions(ctx.clone(), number, &block.tx_hashes)
+            .await?
+            .into_iter()
+            .collect::<Option<Vec<_>>>()
+            .ok_or(MaintenanceError::MissingBlock(number))?;
+        let proof = if number == latest.number {
+            storage.get_latest_proof(ctx.clone()).await?
+        } else {
+            storage
+                .get_block_header(ctx.clone(), number + 1)
+                .await?
+                .ok_or(MaintenanceError::MissingBlock(number + 1))?
+                .proof
+        };
+
+        writer
+            .write_all(&rlp::encode(&BlockRecord { block, txs, proof }))
+            .map_err(MaintenanceError::Io)?;
+    }
+
+    writer.flush().map_err(MaintenanceError::Io)?;
+
+    Ok(to - from + 1)
+}
+
+/// Execute and save the blocks in a stream written by [`export_blocks`] on top
+/// of the local chain. The blocks already stored are skipped. Return the number
+/// of imported blocks.
+pub async fn import_blocks(config: &Config, path: &Path) -> ProtocolResult<u64> {
+    let storage = Arc::new(open_storage(config)?);
+    let trie_db = open_trie_db(config)?;
+    let metadata = MetadataController::new(
+        Arc::new(MetadataAdapterImpl::new(
+            Arc::clone(&storage),
+            Arc::clone(&trie_db),
+        )),
+        config.metadata_contract_address.into(),
+        config.epoch_len,
+    );
+    let ctx = Context::new();
+
+    let file = File::open(path).map_err(MaintenanceError::Io)?;
+    let mut reader = BufReader::new(file);
+    let mut latest = storage.get_latest_block(ctx.clone()).await?;
+    let mut imported = 0;
+
+    while let Some(BlockRecord { block, txs, proof }) = read_record(&mut reader)? {
+        let header = &block.header;
+        let number = header.number;
+        if number <= latest.header.number {
+            continue;
+        }
+
+        if number != latest.header.number + 1 || header.prev_hash != latest.header_hash() {
+            return Err(MaintenanceError::UnlinkedBlock(number).into());
+        }
+        if digest_signed_transactions(&txs) != header.signed_txs_hash {
+            return Err(MaintenanceError::InvalidSignedTxsHash(number).into());
+        }
+
+        let resp = exec(&trie_db, &storage, latest.header.state_root, &block, &txs).await?;
+        if resp.state_root != header.state_root {
+            return Err(MaintenanceError::InvalidStateRoot {
+                number,
+                expect: header.state_root,
+                actual: resp.state_root,
+            }
+            .into());
+        }
+        if resp.receipt_root != header.receipts_root {
+            return Err(MaintenanceError::InvalidReceiptsRoot(number).into());
+        }
+
+        // The verifiers are read from the state just executed.
+        let current_metadata = metadata.get_metadata(ctx.clone(), header)?;
+        verify_proof_with_metadata(&block, &proof, &current_metadata)?;
+
+        let (receipts, _logs) =
+            generate_receipts_and_logs(number, block.header_hash(), header.state_root, &txs, &resp);
+        storage
+            .insert_transactions(ctx.clone(), number, txs)
+            .await?;
+        storage
+            .insert_receipts(ctx.clone(), number, receipts)
+            .await?;
+        storage.update_latest_proof(ctx.clone(), proof).await?;
+        storage.insert_block(ctx.clone(), block.clone()).await?;
+
+        latest = block;
+        imported += 1;
+    }
+
+    Ok(imported)
+}
+
+async fn exec(
+    trie_db: &Arc<RocksTrieDB>,
+    storage: &Arc<ImplStorage<RocksAdapter>>,
+    last_state_root: MerkleRoot,
+    block: &Block,
+    txs: &[SignedTransaction],
+) -> ProtocolResult<ExecResp> {
+    let journal = Arc::new(JournalTrieDB::new(Arc::clone(trie_db)));
+    let mut backend = AxonExecutorAdapter::from_root(
+        last_state_root,
+        Arc::clone(&journal),
+        Arc::clone(storage),
+        Proposal::from(block.clone()).into(),
+    )
+    .await?;
+    let resp = AxonExecutor::default().exec(&mut backend, txs.to_vec());
+
+    journal.commit(block.header.number)?;
+    Ok(resp)
+}
+
+/// The metadata of all epochs is kept by the metadata contract, so it is read
+/// from the latest state which is available in both executor modes.
+fn verify_proof<MT: MetadataControl>(
+    metadata: &MT,
+    latest: &Header,
+    block: &Block,
+    proof: &Proof,
+) -> ProtocolResult<()> {
+    // The genesis block is agreed by the community rather than by a proof.
+    if block.header.number == 0 {
+        return Ok(());
+    }
+
+    let query = Header {
+        state_root: latest.state_root,
+        ..block.header.clone()
+    };
+    let current_metadata = metadata.get_metadata(Context::new(), &query)?;
+    verify_proof_with_metadata(block, proof, &current_metadata)
+}
+
+fn read_record<R: Read>(reader: &mut R) -> Result<Option<BlockRecord>, MaintenanceError> {
+    let mut record = vec![0u8];
+    match reader.read_exact(&mut record) {
+        Ok(()) => (),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(MaintenanceError::Io(e)),
+    }
+
+    let len = match record[0] {
+        prefix @ 0xc0..=0xf7 => (prefix - 0xc0) as usize,
+        prefix @ 0xf8..=0xff => {
+            let mut len_bytes = vec![0u8; (prefix - 0xf7) as usize];
+            reader
+                .read_exact(&mut len_bytes)
+                .map_err(MaintenanceError::Io)?;
+            record.extend_from_slice(&len_bytes);
+            len_bytes.iter().fold(0usize, |len, b| {
+                len.saturating_mul(256).saturating_add(*b as usize)
+            })
+        }
+        _ => return Err(MaintenanceError::Decode(DecoderError::RlpExpectedToBeList)),
+    };
+    if len > MAX_RECORD_SIZE {
+        return Err(MaintenanceError::RecordTooLarge(len));
+    }
+
+    let head_len = record.len();
+    record.resize(head_len + len, 0);
+    reader
+        .read_exact(&mut record[head_len..])
+        .map_err(MaintenanceError::Io)?;
+
+    rlp::decode(&record)
+        .map(Some)
+        .map_err(MaintenanceError::Decode)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct BlockRecord {
+    block: Block,
+    txs:   Vec<SignedTransaction>,
+    proof: Proof,
+}
+
+impl Encodable for BlockRecord {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(3)
+            .append(&self.block)
+            .append_list(&self.txs)
+            .append(&self.proof);
+    }
+}
+
+impl Decodable for BlockRecord {
+    fn decode(r: &Rlp) -> Result<Self, DecoderError> {
+        match r.prototype()? {
+            Prototype::List(3) => Ok(BlockRecord {
+                block: r.val_at(0)?,
+                txs:   r.list_at(1)?,
+                proof: r.val_at(2)?,
+            }),
+            _ => Err(DecoderError::RlpExpectedToBeList),
+        }
+    }
+}
+
+#[derive(Debug, Display)]
+pub enum MaintenanceError {
+    #[display(fmt = "block stream io error {:?}", _0)]
+    Io(io::Error),
+
+    #[display(fmt = "decode block record error {:?}", _0)]
+    Decode(DecoderError),
+
+    #[display(fmt = "block record of {} bytes is too large", _0)]
+    RecordTooLarge(usize),
+
+    #[display(
+        fmt = "cannot roll back to block {}, the latest block is {}",
+        to,
+        latest
+    )]
+    InvalidRollback {
+        to:     BlockNumber,
+        latest: BlockNumber,
+    },
+
+    #[display(
+        fmt = "invalid block range [{}, {}], the latest block is {}",
+        from,
+        to,
+        latest
+    )]
+    InvalidRange {
+        from:   BlockNumber,
+        to:     BlockNumber,
+        latest: BlockNumber,
+    },
+
+    #[display(fmt = "cannot get block {}", _0)]
+    MissingBlock(BlockNumber),
+
+    #[display(fmt = "the state of block {} is not available", _0)]
+    StateNotAvailable(BlockNumber),
+
+    #[display(fmt = "cannot get the receipts of block {}", _0)]
+    MissingReceipts(BlockNumber),
+
+    #[display(fmt = "block {} does not link to its parent", _0)]
+    UnlinkedBlock(BlockNumber),
+
+    #[display(fmt = "the signed transactions hash of block {} mismatch", _0)]
+    InvalidSignedTxsHash(BlockNumber),
+
+    #[display(
+        fmt = "Check invalid state root of block {}, expect {:?} get {:?}",
+        number,
+        expect,
+        actual
+    )]
+    InvalidStateRoot {
+        number: BlockNumber,
+        expect: MerkleRoot,
+        actual: MerkleRoot,
+    },
+
+    #[display(fmt = "the receipts root of block {} mismatch", _0)]
+    InvalidReceiptsRoot(BlockNumber),
+}
+
+impl std::error::Error for MaintenanceError {}
+
+impl From<MaintenanceError> for ProtocolError {
+    fn from(error: MaintenanceError) -> ProtocolError {
+        ProtocolError::new(ProtocolErrorKind::Main, Box::new(error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_record_stream() {
+        let mut first = BlockRecord {
+            block: Block::default(),
+            txs:   vec![],
+            proof: Proof::default(),
+        };
+        first.block.header.number = 1;
+        first.block.header.extra_data = vec![7u8; 100].into();
+        let mut second = first.clone();
+        second.block.header.number = 2;
+
+        let mut buf = rlp::encode(&first).to_vec();
+        buf.extend_from_slice(&rlp::encode(&second));
+
+        let mut reader = buf.as_slice();
+        assert_eq!(read_record(&mut reader).unwrap(), Some(first));
+        assert_eq!(read_record(&mut reader).unwrap(), Some(second));
+        assert_eq!(read_record(&mut reader).unwrap(), None);
+
+        let mut truncated = &buf[..buf.len() - 1];
+        read_record(&mut truncated).unwrap();
+        read_record(&mut truncated).unwrap();
+        assert!(read_record(&mut truncated).is_err());
+    }
+}
@@ -0,0 +1,647 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::{path::Path, sync::Arc};
+
+use rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream};
+
+use common_config_parser::types::Config;
+use core_executor::{MPTTrie, RocksTrieDB, BLOCK_HASH_HISTORY};
+use core_storage::{adapter::rocks::RocksAdapter, ImplStorage};
+use protocol::codec::ProtocolCodec;
+use protocol::traits::{CommonStorage, Context, Storage};
+use protocol::types::{
+    Account, Block, BlockNumber, Hasher, MerkleRoot, Proof, SignedTransaction, H160, NIL_DATA,
+    RLP_NULL,
+};
+use protocol::{Display, ProtocolError, ProtocolErrorKind, ProtocolResult};
+
+const SNAPSHOT_MAGIC: &[u8; 8] = b"AXONSNAP";
+const SNAPSHOT_VERSION: u32 = 2;
+/// The max number of items in a chunk.
+const CHUNK_ITEMS: usize = 4096;
+/// A chunk larger than this is treated as a corrupted length prefix.
+const MAX_CHUNK_SIZE: usize = 256 << 20;
+const CHECKSUM_LEN: usize = 32;
+
+/// Export the state at the given height into a snapshot file. The file is a
+/// sequence of chunks, each of which is prefixed by its length and followed by
+/// the keccak checksum of its payload. The first chunk is the block at the
+/// height with the blocks before it that `BLOCKHASH` can read, then come the
+/// accounts, each followed by its storage slots and code, and the last chunk
+/// counts the exported items.
+pub async fn export(
+    config: &Config,
+    height: Option<BlockNumber>,
+    path: &Path,
+) -> ProtocolResult<()> {
+    let storage = open_storage(config)?;
+    let trie_db = open_trie_db(config)?;
+    let ctx = Context::new();
+
+    let latest = storage.get_latest_block_header(ctx.clone()).await?;
+    let height = height.unwrap_or(latest.number);
+    if height > latest.number {
+        return Err(SnapshotError::HeightNotCommitted {
+            height,
+            latest: latest.number,
+        }
+        .into());
+    }
+
+    let block = storage
+        .get_block(ctx.clone(), height)
+        .await?
+        .ok_or(SnapshotError::MissingBlock(height))?;
+    let txs = storage
+        .get_transactions(ctx.clone(), height, &block.tx_hashes)
+        .await?
+        .into_iter()
+        .collect::<Option<Vec<_>>>()
+        .ok_or(SnapshotError::MissingBlock(height))?;
+    // The proof of a block is carried by its next block.
+    let proof = if height == latest.number {
+        storage.get_latest_proof(ctx.clone()).await?
+    } else {
+        storage
+            .get_block_header(ctx.clone(), height + 1)
+            .await?
+            .ok_or(SnapshotError::MissingBlock(height + 1))?
+            .proof
+    };
+
+    let mut ancestors = Vec::new();
+    for number in height.saturating_sub(BLOCK_HASH_HISTORY)..height {
+        let ancestor = storage
+            .get_block(ctx.clone(), number)
+            .await?
+            .ok_or(SnapshotError::MissingBlock(number))?;
+        ancestors.push(ancestor);
+    }
+
+    let state_root = block.header.state_root;
+    if !cita_trie::DB::contains(trie_db.as_ref(), state_root.as_bytes()).unwrap_or(false) {
+        return Err(SnapshotError::StateNotAvailable(height).into());
+    }
+
+    let file = File::create(path).map_err(SnapshotError::Io)?;
+    let mut writer = SnapshotWriter::new(BufWriter::new(file))?;
+    writer.write_chunk(&SnapshotChunk::Head(SnapshotHead {
+        version: SNAPSHOT_VERSION,
+        block,
+        txs,
+        proof,
+        ancestors,
+    }))?;
+
+    let mut summary = SnapshotSummary::default();
+    let mut items = Vec::with_capacity(CHUNK_ITEMS);
+    let state_trie = MPTTrie::from_root(state_root, Arc::clone(&trie_db))?;
+
+    for (key, value) in state_trie.iter() {
+        let address = H160::from_slice(&key);
+        let account = <Account as ProtocolCodec>::decode(value)?;
+        let storage_root = account.storage_root;
+        let code_hash = account.code_hash;
+
+        items.push(SnapshotItem::Account(address, account));
+        summary.accounts += 1;
+
+        if storage_root != RLP_NULL {
+            let storage_trie = MPTTrie::from_root(storage_root, Arc::clone(&trie_db))?;
+            for (key, value) in storage_trie.iter() {
+                items.push(SnapshotItem::Storage(key, value));
+                summary.slots += 1;
+                writer.flush_items(&mut items, false)?;
+            }
+        }
+
+        if code_hash != NIL_DATA {
+            let code = storage
+                .get_code_by_hash(ctx.clone(), &code_hash)
+                .await?
+                .ok_or(SnapshotError::MissingCode(address))?;
+            items.push(SnapshotItem::Code(code.to_vec()));
+            summary.codes += 1;
+        }
+
+        writer.flush_items(&mut items, false)?;
+    }
+
+    writer.flush_items(&mut items, true)?;
+    writer.write_chunk(&SnapshotChunk::End(summary.clone()))?;
+    writer.finish()?;
+
+    log::info!(
+        "[snapshot] export the state of block {} to {:?}, {:?}",
+        height,
+        path,
+        summary
+    );
+
+    Ok(())
+}
+
+/// Rebuild the state trie and the block storage head from a snapshot file, so
+/// that the node starts syncing from the block next to the snapshot. The data
+/// directory must be empty.
+pub async fn import(config: &Config, path: &Path) -> ProtocolResult<()> {
+    let storage = open_storage(config)?;
+    let ctx = Context::new();
+
+    if storage.get_latest_block_header(ctx.clone()).await.is_ok() {
+        return Err(SnapshotError::DataExists.into());
+    }
+
+    let trie_db = open_trie_db(config)?;
+    let file = File::open(path).map_err(SnapshotError::Io)?;
+    let mut reader = SnapshotReader::new(BufReader::new(file))?;
+
+    let head = match reader.read_chunk()? {
+        SnapshotChunk::Head(head) => head,
+        _ => return Err(SnapshotError::UnexpectedChunk.into()),
+    };
+    if head.version != SNAPSHOT_VERSION {
+        return Err(SnapshotError::UnsupportedVersion(head.version).into());
+    }
+    verify_ancestors(&head)?;
+
+    let mut state_trie = MPTTrie::new(Arc::clone(&trie_db));
+    let mut pending: Option<(H160, Account, MPTTrie<RocksTrieDB>)> = None;
+    let mut summary = SnapshotSummary::default();
+
+    let expect_summary = loop {
+        let items = match reader.read_chunk()? {
+            SnapshotChunk::Items(items) => items,
+            SnapshotChunk::End(expect) => break expect,
+            SnapshotChunk::Head(_) => return Err(SnapshotError::UnexpectedChunk.into()),
+        };
+
+        for item in items.into_iter() {
+            match item {
+                SnapshotItem::Account(address, account) => {
+                    if let Some(prev) = pending.take() {
+                        commit_account(&mut state_trie, prev)?;
+                    }
+
+                    pending = Some((address, account, MPTTrie::new(Arc::clone(&trie_db))));
+                    summary.accounts += 1;
+                }
+                SnapshotItem::Storage(key, value) => {
+                    let (_, _, storage_trie) =
+                        pending.as_mut().ok_or(SnapshotError::UnexpectedChunk)?;
+                    storage_trie.insert(&key, &value)?;
+                    summary.slots += 1;
+                }
+                SnapshotItem::Code(code) => {
+                    let (address, account, _) =
+                        pending.as_ref().ok_or(SnapshotError::UnexpectedChunk)?;
+                    let code_hash = Hasher::digest(&code);
+                    if code_hash != account.code_hash {
+                        return Err(SnapshotError::InvalidCode(*address).into());
+                    }
+
+                    storage
+                        .insert_code(ctx.clone(), (*address).into(), code_hash, code.into())
+                        .await?;
+                    summary.codes += 1;
+                }
+            }
+        }
+    };
+
+    if let Some(prev) = pending.take() {
+        commit_account(&mut state_trie, prev)?;
+    }
+
+    if summary != expect_summary {
+        return Err(SnapshotError::Truncated {
+            expect: expect_summary,
+            actual: summary,
+        }
+        .into());
+    }
+
+    let header = &head.block.header;
+    let state_root = state_trie.commit()?;
+    if state_root != header.state_root {
+        return Err(SnapshotError::InvalidStateRoot {
+            expect: header.state_root,
+            actual: state_root,
+        }
+        .into());
+    }
+
+    let number = header.number;
+    storage
+        .insert_transactions(ctx.clone(), number, head.txs)
+        .await?;
+    storage.update_latest_proof(ctx.clone(), head.proof).await?;
+    for ancestor in head.ancestors.into_iter() {
+        storage.set_block(ctx.clone(), ancestor).await?;
+    }
+    storage.insert_block(ctx, head.block).await?;
+    // The receipts of the blocks in the snapshot are not available.
+    storage.set_log_index_start(number + 1).await?;
+
+    log::info!(
+        "[snapshot] import the state of block {} from {:?}, {:?}",
+        number,
+        path,
+        summary
+    );
+
+    Ok(())
+}
+
+/// The ancestors must be the consecutive blocks right before the head block,
+/// linked by their hashes.
+fn verify_ancestors(head: &SnapshotHead) -> Result<(), SnapshotError> {
+    let number = head.block.header.number;
+    let expect = number.min(BLOCK_HASH_HISTORY);
+    if head.ancestors.len() as u64 != expect {
+        return Err(SnapshotError::InvalidAncestors(number));
+    }
+
+    let mut child = &head.block;
+    for ancestor in head.ancestors.iter().rev() {
+        if ancestor.header.number + 1 != child.header.number
+            || ancestor.header_hash() != child.header.prev_hash
+        {
+            return Err(SnapshotError::InvalidAncestors(ancestor.header.number));
+        }
+        child = ancestor;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn open_storage(config: &Config) -> ProtocolResult<ImplStorage<RocksAdapter>> {
+    let rocks_adapter = Arc::new(RocksAdapter::new(
+        config.data_path_for_block(),
+        config.rocksdb.clone(),
+    )?);
+    Ok(ImplStorage::new(rocks_adapter))
+}
+
+pub(crate) fn open_trie_db(config: &Config) -> ProtocolResult<Arc<RocksTrieDB>> {
+    Ok(Arc::new(RocksTrieDB::new(
+        config.data_path_for_state(),
+        config.rocksdb.clone(),
+        config.executor.triedb_cache_size,
+        config.executor.mode,
+    )?))
+}
+
+fn commit_account(
+    state_trie: &mut MPTTrie<RocksTrieDB>,
+    (address, account, mut storage_trie): (H160, Account, MPTTrie<RocksTrieDB>),
+) -> ProtocolResult<()> {
+    let storage_root = storage_trie.commit()?;
+    if storage_root != account.storage_root {
+        return Err(SnapshotError::InvalidStorageRoot(address).into());
+    }
+
+    state_trie.insert(address.as_bytes(), &account.encode()?)
+}
+
+struct SnapshotWriter<W: Write> {
+    inner: W,
+}
+
+impl<W: Write> SnapshotWriter<W> {
+    fn new(mut inner: W) -> Result<Self, SnapshotError> {
+        inner.write_all(SNAPSHOT_MAGIC).map_err(SnapshotError::Io)?;
+        Ok(SnapshotWriter { inner })
+    }
+
+    fn write_chunk(&mut self, chunk: &SnapshotChunk) -> Result<(), SnapshotError> {
+        let payload = rlp::encode(chunk);
+        let checksum = Hasher::digest(&payload);
+
+        self.inner
+            .write_all(&(payload.len() as u32).to_be_bytes())
+            .and_then(|_| self.inner.write_all(&payload))
+            .and_then(|_| self.inner.write_all(checksum.as_bytes()))
+            .map_err(SnapshotError::Io)
+    }
+
+    /// Write the items as a chunk once there are enough of them, or at once if
+    /// `force` is set.
+    fn flush_items(
+        &mut self,
+        items: &mut Vec<SnapshotItem>,
+        force: bool,
+    ) -> Result<(), SnapshotError> {
+        if items.is_empty() || (!force && items.len() < CHUNK_ITEMS) {
+            return Ok(());
+        }
+
+        let chunk = SnapshotChunk::Items(std::mem::take(items));
+        self.write_chunk(&chunk)
+    }
+
+    fn finish(mut self) -> Result<(), SnapshotError> {
+        self.inner.flush().map_err(SnapshotError::Io)
+    }
+}
+
+struct SnapshotReader<R: Read> {
+    inner: R,
+}
+
+impl<R: Read> SnapshotReader<R> {
+    fn new(mut inner: R) -> Result<Self, SnapshotError> {
+        let mut magic = [0u8; 8];
+        inner.read_exact(&mut magic).map_err(SnapshotError::Io)?;
+        if &magic != SNAPSHOT_MAGIC {
+            return Err(SnapshotError::InvalidMagic);
+        }
+
+        Ok(SnapshotReader { inner })
+    }
+
+    fn read_chunk(&mut self) -> Result<SnapshotChunk, SnapshotError> {
+        let mut len = [0u8; 4];
+        self.inner.read_exact(&mut len).map_err(SnapshotError::Io)?;
+        let len = u32::from_be_bytes(len) as usize;
+        if len > MAX_CHUNK_SIZE {
+            return Err(SnapshotError::ChunkTooLarge(len));
+        }
+
+        let mut payload = vec![0u8; len];
+        let mut checksum = [0u8; CHECKSUM_LEN];
+        self.inner
+            .read_exact(&mut payload)
+            .and_then(|_| self.inner.read_exact(&mut checksum))
+            .map_err(SnapshotError::Io)?;
+
+        if Hasher::digest(&payload).as_bytes() != checksum {
+            return Err(SnapshotError::ChecksumMismatch);
+        }
+
+        rlp::decode(&payload).map_err(SnapshotError::Decode)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct SnapshotHead {
+    version:   u32,
+    block:     Block,
+    txs:       Vec<SignedTransaction>,
+    proof:     Proof,
+    /// The blocks before `block` whose hashes can be read by `BLOCKHASH`.
+    ancestors: Vec<Block>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum SnapshotItem {
+    Account(H160, Account),
+    Storage(Vec<u8>, Vec<u8>),
+    Code(Vec<u8>),
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SnapshotSummary {
+    accounts: u64,
+    slots:    u64,
+    codes:    u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum SnapshotChunk {
+    Head(SnapshotHead),
+    Items(Vec<SnapshotItem>),
+    End(SnapshotSummary),
+}
+
+impl Encodable for SnapshotItem {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        match self {
+            SnapshotItem::Account(address, account) => {
+                s.begin_list(3).append(&0u8).append(address).append(account);
+            }
+            SnapshotItem::Storage(key, value) => {
+                s.begin_list(3).append(&1u8).append(key).append(value);
+            }
+            SnapshotItem::Code(code) => {
+                s.begin_list(2).append(&2u8).append(code);
+            }
+        }
+    }
+}
+
+impl Decodable for SnapshotItem {
+    fn decode(r: &Rlp) -> Result<Self, DecoderError> {
+        match r.val_at::<u8>(0)? {
+            0 => Ok(SnapshotItem::Account(r.val_at(1)?, r.val_at(2)?)),
+            1 => Ok(SnapshotItem::Storage(r.val_at(1)?, r.val_at(2)?)),
+            2 => Ok(SnapshotItem::Code(r.val_at(1)?)),
+            _ => Err(DecoderError::Custom("invalid snapshot item")),
+        }
+    }
+}
+
+impl Encodable for SnapshotChunk {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        match self {
+            SnapshotChunk::Head(head) => {
+                s.begin_list(6)
+                    .append(&0u8)
+                    .append(&head.version)
+                    .append(&head.block)
+                    .append_list::<SignedTransaction, _>(&head.txs)
+                    .append(&head.proof)
+                    .append_list::<Block, _>(&head.ancestors);
+            }
+            SnapshotChunk::Items(items) => {
+                s.begin_list(2)
+                    .append(&1u8)
+                    .append_list::<SnapshotItem, _>(items);
+            }
+            SnapshotChunk::End(summary) => {
+                s.begin_list(4)
+                    .append(&2u8)
+                    .append(&summary.accounts)
+                    .append(&summary.slots)
+                    .append(&summary.codes);
+            }
+        }
+    }
+}
+
+impl Decodable for SnapshotChunk {
+    fn decode(r: &Rlp) -> Result<Self, DecoderError> {
+        match r.val_at::<u8>(0)? {
+            0 => Ok(SnapshotChunk::Head(SnapshotHead {
+                version:   r.val_at(1)?,
+                block:     r.val_at(2)?,
+                txs:       r.list_at(3)?,
+                proof:     r.val_at(4)?,
+                ancestors: r.list_at(5)?,
+            })),
+            1 => Ok(SnapshotChunk::Items(r.list_at(1)?)),
+            2 => Ok(SnapshotChunk::End(SnapshotSummary {
+                accounts: r.val_at(1)?,
+                slots:    r.val_at(2)?,
+                codes:    r.val_at(3)?,
+            })),
+            _ => Err(DecoderError::Custom("invalid snapshot chunk")),
+        }
+    }
+}
+
+#[derive(Debug, Display)]
+pub enum SnapshotError {
+    #[display(fmt = "snapshot io error {:?}", _0)]
+    Io(io::Error),
+
+    #[display(fmt = "decode snapshot chunk error {:?}", _0)]
+    Decode(DecoderError),
+
+    #[display(fmt = "not a snapshot file")]
+    InvalidMagic,
+
+    #[display(fmt = "unsupported snapshot version {}", _0)]
+    UnsupportedVersion(u32),
+
+    #[display(fmt = "snapshot chunk of {} bytes is too large", _0)]
+    ChunkTooLarge(usize),
+
+    #[display(fmt = "snapshot chunk checksum mismatch")]
+    ChecksumMismatch,
+
+    #[display(fmt = "unexpected snapshot chunk")]
+    UnexpectedChunk,
+
+    #[display(fmt = "snapshot is truncated, expect {:?} get {:?}", expect, actual)]
+    Truncated {
+        expect: SnapshotSummary,
+        actual: SnapshotSummary,
+    },
+
+    #[display(
+        fmt = "block {} is not committed, the latest block is {}",
+        height,
+        latest
+    )]
+    HeightNotCommitted {
+        height: BlockNumber,
+        latest: BlockNumber,
+    },
+
+    #[display(fmt = "cannot get block {}", _0)]
+    MissingBlock(BlockNumber),
+
+    #[display(fmt = "cannot get the code of {:?}", _0)]
+    MissingCode(H160),
+
+    #[display(fmt = "the state of block {} is not available", _0)]
+    StateNotAvailable(BlockNumber),
+
+    #[display(fmt = "the ancestor block {} does not link to the snapshot block", _0)]
+    InvalidAncestors(BlockNumber),
+
+    #[display(fmt = "the data directory is not empty")]
+    DataExists,
+
+    #[display(fmt = "the code of {:?} does not match its hash", _0)]
+    InvalidCode(H160),
+
+    #[display(fmt = "the storage root of {:?} does not match", _0)]
+    InvalidStorageRoot(H160),
+
+    #[display(fmt = "Check invalid state root, expect {:?} get {:?}", expect, actual)]
+    InvalidStateRoot {
+        expect: MerkleRoot,
+        actual: MerkleRoot,
+    },
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl From<SnapshotError> for ProtocolError {
+    fn from(error: SnapshotError) -> ProtocolError {
+        ProtocolError::new(ProtocolErrorKind::Main, Box::new(error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_chunk_roundtrip() {
+        let items = vec![
+            SnapshotItem::Account(H160::repeat_byte(1), Account {
+                nonce:        1u64.into(),
+                balance:      100u64.into(),
+                storage_root: RLP_NULL,
+                code_hash:    NIL_DATA,
+            }),
+            SnapshotItem::Storage(vec![1u8; 32], vec![2u8; 32]),
+            SnapshotItem::Code(vec![0x60, 0x00]),
+        ];
+
+        let mut buf = Vec::new();
+        let mut writer = SnapshotWriter::new(&mut buf).unwrap();
+        writer
+            .write_chunk(&SnapshotChunk::Items(items.clone()))
+            .unwrap();
+        writer
+            .write_chunk(&SnapshotChunk::End(SnapshotSummary {
+                accounts: 1,
+                slots:    1,
+                codes:    1,
+            }))
+            .unwrap();
+        writer.finish().unwrap();
+
+        let mut reader = SnapshotReader::new(buf.as_slice()).unwrap();
+        assert_eq!(reader.read_chunk().unwrap(), SnapshotChunk::Items(items));
+        assert!(matches!(
+            reader.read_chunk().unwrap(),
+            SnapshotChunk::End(SnapshotSummary { accounts: 1, .. })
+        ));
+
+        // Flip a byte of the payload.
+        buf[SNAPSHOT_MAGIC.len() + 8] ^= 0xff;
+        let mut reader = SnapshotReader::new(buf.as_slice()).unwrap();
+        assert!(matches!(
+            reader.read_chunk(),
+            Err(SnapshotError::ChecksumMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_verify_ancestors() {
+        let mut blocks: Vec<Block> = Vec::new();
+        for number in 0..=BLOCK_HASH_HISTORY + 1 {
+            let mut block = Block::default();
+            block.header.number = number;
+            if let Some(parent) = blocks.last() {
+                block.header.prev_hash = parent.header_hash();
+            }
+            blocks.push(block);
+        }
+
+        let block = blocks.pop().unwrap();
+        let start = blocks.len() - BLOCK_HASH_HISTORY as usize;
+        let mut head = SnapshotHead {
+            version: SNAPSHOT_VERSION,
+            block,
+            txs: Vec::new(),
+            proof: Proof::default(),
+            ancestors: blocks[start..].to_vec(),
+        };
+        assert!(verify_ancestors(&head).is_ok());
+
+        // Miss the oldest ancestor.
+        head.ancestors.remove(0);
+        assert!(verify_ancestors(&head).is_err());
+
+        // Break the hash link.
+        head.ancestors = blocks[start..].to_vec();
+        head.ancestors[10].header.timestamp += 1;
+        assert!(verify_ancestors(&head).is_err());
+    }
+}
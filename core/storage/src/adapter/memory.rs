@@ -1,4 +1,4 @@
-use std::collections::{hash_map, HashMap};
+use std::collections::HashMap;
 use std::error::Error;
 use std::marker::PhantomData;
 use std::sync::Arc;
@@ -36,7 +36,7 @@ impl Default for MemoryAdapter {
 }
 
 pub struct MemoryIterator<'a, S: StorageSchema> {
-    inner: hash_map::Iter<'a, Vec<u8>, Vec<u8>>,
+    inner: std::vec::IntoIter<(&'a Vec<u8>, &'a Vec<u8>)>,
     pin_s: PhantomData<S>,
 }
 
@@ -57,9 +57,12 @@ impl<'a, S: StorageSchema> Iterator for MemoryIterator<'a, S> {
     }
 }
 
+/// Iterate the keys from the prefix on in order, the same as the prefix
+/// iterator of RocksDB without a prefix extractor.
 pub struct MemoryIntoIterator<'a, S: StorageSchema> {
-    inner: parking_lot::RwLockReadGuard<'a, HashMap<String, Category>>,
-    pin_s: PhantomData<S>,
+    inner:  parking_lot::RwLockReadGuard<'a, HashMap<String, Category>>,
+    prefix: Vec<u8>,
+    pin_s:  PhantomData<S>,
 }
 
 impl<'a, 'b: 'a, S: StorageSchema> IntoIterator for &'b MemoryIntoIterator<'a, S> {
@@ -67,12 +70,17 @@ impl<'a, 'b: 'a, S: StorageSchema> IntoIterator for &'b MemoryIntoIterator<'a, S
     type Item = ProtocolResult<(<S as StorageSchema>::Key, <S as StorageSchema>::Value)>;
 
     fn into_iter(self) -> Self::IntoIter {
+        let mut pairs = self
+            .inner
+            .get(&S::category().to_string())
+            .expect("impossible, already ensure we have category in prepare_iter")
+            .iter()
+            .filter(|(key, _)| key.as_slice() >= self.prefix.as_slice())
+            .collect::<Vec<_>>();
+        pairs.sort_unstable_by(|a, b| a.0.cmp(b.0));
+
         Box::new(MemoryIterator {
-            inner: self
-                .inner
-                .get(&S::category().to_string())
-                .expect("impossible, already ensure we have category in prepare_iter")
-                .iter(),
+            inner: pairs.into_iter(),
             pin_s: PhantomData::<S>,
         })
     }
@@ -192,7 +200,7 @@ impl StorageAdapter for MemoryAdapter {
 
     fn prepare_iter<'a, 'b: 'a, S: StorageSchema + 'static, P: AsRef<[u8]> + 'a>(
         &'b self,
-        _prefix: &P,
+        prefix: &P,
     ) -> ProtocolResult<Box<dyn IntoIteratorByRef<S> + 'a>> {
         {
             self.db
@@ -202,8 +210,9 @@ impl StorageAdapter for MemoryAdapter {
         }
 
         Ok(Box::new(MemoryIntoIterator {
-            inner: self.db.read(),
-            pin_s: PhantomData::<S>,
+            inner:  self.db.read(),
+            prefix: prefix.as_ref().to_vec(),
+            pin_s:  PhantomData::<S>,
         }))
     }
 }
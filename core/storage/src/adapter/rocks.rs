@@ -4,7 +4,9 @@ use std::path::Path;
 use std::sync::Arc;
 use std::{fs, io};
 
-use rocksdb::ops::{DeleteCF, GetCF, GetColumnFamilys, IterateCF, OpenCF, PutCF, WriteOps};
+use rocksdb::ops::{
+    DeleteCF, GetCF, GetColumnFamilys, GetPropertyCF, IterateCF, OpenCF, PutCF, WriteOps,
+};
 use rocksdb::{
     ColumnFamily, ColumnFamilyDescriptor, DBIterator, FullOptions, Options, WriteBatch, DB,
 };
@@ -39,6 +41,8 @@ impl RocksAdapter {
             map_category(StorageCategory::Wal),
             map_category(StorageCategory::HashHeight),
             map_category(StorageCategory::Code),
+            map_category(StorageCategory::LogIndex),
+            map_category(StorageCategory::Evidence),
         ];
 
         let (mut opts, cf_descriptors) = if let Some(ref file) = config.options_file {
@@ -80,6 +84,34 @@ impl RocksAdapter {
     pub fn inner_db(&self) -> Arc<DB> {
         Arc::clone(&self.db)
     }
+
+    /// Return the estimated number of keys and the size of the SST files of a
+    /// category. Data still in memtables is not counted.
+    pub fn category_stats(&self, category: StorageCategory) -> ProtocolResult<CategoryStats> {
+        let name = map_category(category);
+        let column = self
+            .db
+            .cf_handle(name)
+            .ok_or(RocksAdapterError::CategoryNotFound(name))?;
+        let property = |key: &str| -> ProtocolResult<u64> {
+            Ok(self
+                .db
+                .property_int_value_cf(column, key)
+                .map_err(RocksAdapterError::from)?
+                .unwrap_or_default())
+        };
+
+        Ok(CategoryStats {
+            keys: property("rocksdb.estimate-num-keys")?,
+            size: property("rocksdb.total-sst-files-size")?,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CategoryStats {
+    pub keys: u64,
+    pub size: u64,
 }
 
 macro_rules! db {
@@ -294,6 +326,8 @@ const C_RECEIPTS: &str = "c4";
 const C_WALS: &str = "c5";
 const C_HASH_HEIGHT_MAP: &str = "c6";
 const C_EVM_CODE_MAP: &str = "c7";
+const C_LOG_INDEX: &str = "c8";
+const C_EVIDENCE: &str = "c9";
 
 fn map_category(c: StorageCategory) -> &'static str {
     match c {
@@ -304,6 +338,8 @@ fn map_category(c: StorageCategory) -> &'static str {
         StorageCategory::Wal => C_WALS,
         StorageCategory::HashHeight => C_HASH_HEIGHT_MAP,
         StorageCategory::Code => C_EVM_CODE_MAP,
+        StorageCategory::LogIndex => C_LOG_INDEX,
+        StorageCategory::Evidence => C_EVIDENCE,
     }
 }
 
@@ -5,7 +5,7 @@ mod cache;
 #[cfg(test)]
 mod tests;
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::convert::From;
 use std::error::Error;
 use std::str::FromStr;
@@ -22,8 +22,8 @@ use protocol::traits::{
     StorageSchema,
 };
 use protocol::types::{
-    Block, BlockNumber, Bytes, DBBytes, Hash, Hasher, Header, Proof, Receipt, SignedTransaction,
-    H256,
+    Block, BlockNumber, Bytes, DBBytes, Evidence, Hash, Hasher, Header, Proof, Receipt,
+    SignedTransaction, H160, H256,
 };
 use protocol::{
     async_trait, tokio, Display, From, ProtocolError, ProtocolErrorKind, ProtocolResult,
@@ -37,6 +37,7 @@ lazy_static::lazy_static! {
     pub static ref LATEST_BLOCK_KEY: Hash = Hasher::digest(Bytes::from("latest_hash"));
     pub static ref LATEST_PROOF_KEY: Hash = Hasher::digest(Bytes::from("latest_proof"));
     pub static ref OVERLORD_WAL_KEY: Hash = Hasher::digest(Bytes::from("overlord_wal"));
+    pub static ref LOG_INDEX_START_KEY: Hash = Hasher::digest(Bytes::from("log_index_start"));
 }
 
 macro_rules! get_cache {
@@ -187,6 +188,98 @@ impl<Adapter: StorageAdapter> ImplStorage<Adapter> {
 
         Ok(())
     }
+
+    /// Index the block by the addresses and the topics of its logs, with the
+    /// number of the logs having each of them. The address of a log is the
+    /// sender of its transaction, the same as the one returned by the API.
+    pub async fn insert_log_index(
+        &self,
+        receipts: &[Receipt],
+        block_number: BlockNumber,
+    ) -> ProtocolResult<()> {
+        let counts = log_index_counts(receipts);
+        if counts.is_empty() {
+            return Ok(());
+        }
+
+        let (keys, vals) = counts
+            .into_iter()
+            .map(|(term, count)| {
+                (
+                    LogIndexKey::new(term, block_number),
+                    StorageBatchModify::Insert(count),
+                )
+            })
+            .unzip();
+
+        self.adapter
+            .batch_modify::<LogIndexSchema>(keys, vals)
+            .await
+    }
+
+    async fn remove_log_index(
+        &self,
+        receipts: &[Receipt],
+        block_number: BlockNumber,
+    ) -> ProtocolResult<()> {
+        let counts = log_index_counts(receipts);
+        if counts.is_empty() {
+            return Ok(());
+        }
+
+        let (keys, vals) = counts
+            .into_keys()
+            .map(|term| {
+                (
+                    LogIndexKey::new(term, block_number),
+                    StorageBatchModify::Remove,
+                )
+            })
+            .unzip();
+
+        self.adapter
+            .batch_modify::<LogIndexSchema>(keys, vals)
+            .await
+    }
+
+    /// The number of the first block whose logs are indexed. The blocks
+    /// stored before the log index was built are indexed by reindexing.
+    pub async fn get_log_index_start(&self) -> ProtocolResult<Option<BlockNumber>> {
+        get!(self, *LOG_INDEX_START_KEY, LogIndexStartSchema)
+    }
+
+    pub async fn set_log_index_start(&self, number: BlockNumber) -> ProtocolResult<()> {
+        self.adapter
+            .insert::<LogIndexStartSchema>(*LOG_INDEX_START_KEY, number)
+            .await
+    }
+
+    /// Collect the numbers of the blocks in `[from, to]` indexed by any of the
+    /// terms. The keys of a term are ordered by the block number, so the
+    /// iteration starts at `from` and stops after `to`.
+    fn get_log_index(
+        &self,
+        terms: impl Iterator<Item = LogTerm>,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> ProtocolResult<BTreeSet<BlockNumber>> {
+        let mut numbers = BTreeSet::new();
+
+        for term in terms {
+            let prefix = LogIndexKey::new(term, from).encode()?;
+            let prepare_iter = self.adapter.prepare_iter::<LogIndexSchema, _>(&prefix)?;
+
+            for item in prepare_iter.ref_to_iter() {
+                let (key, _) = item?;
+                if key.term != term.to_bytes() || key.block_number() > to {
+                    break;
+                }
+                numbers.insert(key.block_number());
+            }
+        }
+
+        Ok(numbers)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -313,6 +406,90 @@ impl FromStr for CommonHashKey {
 
 pub type BlockKey = CommonPrefix;
 
+const LOG_TERM_LEN: usize = 33;
+
+/// A term the logs are searched by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LogTerm {
+    Address(H160),
+    Topic(H256),
+}
+
+impl LogTerm {
+    /// A tag byte followed by the address padded to 32 bytes, or the topic.
+    fn to_bytes(self) -> [u8; LOG_TERM_LEN] {
+        let mut bytes = [0u8; LOG_TERM_LEN];
+        match self {
+            LogTerm::Address(address) => {
+                bytes[0] = 0;
+                bytes[13..].copy_from_slice(address.as_bytes());
+            }
+            LogTerm::Topic(topic) => {
+                bytes[0] = 1;
+                bytes[1..].copy_from_slice(topic.as_bytes());
+            }
+        }
+        bytes
+    }
+}
+
+/// Count the logs of the receipts by their addresses and topics.
+fn log_index_counts(receipts: &[Receipt]) -> HashMap<LogTerm, u64> {
+    let mut counts = HashMap::new();
+    for receipt in receipts.iter().filter(|r| !r.logs.is_empty()) {
+        *counts.entry(LogTerm::Address(receipt.sender)).or_insert(0) += receipt.logs.len() as u64;
+
+        for log in receipt.logs.iter() {
+            for topic in log.topics.iter().collect::<HashSet<_>>() {
+                *counts.entry(LogTerm::Topic(*topic)).or_insert(0) += 1;
+            }
+        }
+    }
+
+    counts
+}
+
+#[derive(Debug, Clone)]
+pub struct LogIndexKey {
+    term:         [u8; LOG_TERM_LEN],
+    block_number: [u8; 8], // BigEndian
+}
+
+impl LogIndexKey {
+    pub fn new(term: LogTerm, block_number: BlockNumber) -> Self {
+        LogIndexKey {
+            term:         term.to_bytes(),
+            block_number: block_number.to_be_bytes(),
+        }
+    }
+
+    pub fn block_number(&self) -> BlockNumber {
+        u64::from_be_bytes(self.block_number)
+    }
+}
+
+impl ProtocolCodec for LogIndexKey {
+    fn encode(&self) -> ProtocolResult<Bytes> {
+        let mut key = Vec::with_capacity(LOG_TERM_LEN + 8);
+        key.extend_from_slice(&self.term);
+        key.extend_from_slice(&self.block_number);
+
+        Ok(Bytes::from(key))
+    }
+
+    fn decode<B: AsRef<[u8]>>(bytes: B) -> ProtocolResult<Self> {
+        let bytes = bytes.as_ref();
+        debug_assert!(bytes.len() == LOG_TERM_LEN + 8);
+
+        let mut term = [0u8; LOG_TERM_LEN];
+        term.copy_from_slice(&bytes[0..LOG_TERM_LEN]);
+        let mut block_number = [0u8; 8];
+        block_number.copy_from_slice(&bytes[LOG_TERM_LEN..LOG_TERM_LEN + 8]);
+
+        Ok(LogIndexKey { term, block_number })
+    }
+}
+
 impl_storage_schema_for!(
     TransactionSchema,
     CommonHashKey,
@@ -336,6 +513,9 @@ impl_storage_schema_for!(LatestProofSchema, Hash, Proof, Block);
 impl_storage_schema_for!(OverlordWalSchema, Hash, Bytes, Wal);
 impl_storage_schema_for!(EvmCodeSchema, Hash, Bytes, Code);
 impl_storage_schema_for!(EvmCodeAddressSchema, Hash, Hash, Code);
+impl_storage_schema_for!(LogIndexSchema, LogIndexKey, u64, LogIndex);
+impl_storage_schema_for!(LogIndexStartSchema, Hash, u64, Block);
+impl_storage_schema_for!(EvidenceSchema, CommonHashKey, Evidence, Evidence);
 
 #[async_trait]
 impl<Adapter: StorageAdapter> CommonStorage for ImplStorage<Adapter> {
@@ -385,10 +565,65 @@ impl<Adapter: StorageAdapter> CommonStorage for ImplStorage<Adapter> {
         Ok(())
     }
 
-    async fn remove_block(&self, _ctx: Context, height: u64) -> ProtocolResult<()> {
+    /// Remove the block at the given height together with its header, hash
+    /// index, transactions, receipts and log index.
+    async fn remove_block(&self, ctx: Context, height: u64) -> ProtocolResult<()> {
+        let block = match self.get_block(ctx.clone(), height).await? {
+            Some(block) => block,
+            None => return Ok(()),
+        };
+        let block_hash = block.header_hash();
+        let hashes = block.tx_hashes;
+
+        let receipts = self
+            .get_receipts(ctx, height, &hashes)
+            .await?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        self.remove_log_index(&receipts, height).await?;
+        let keys = hashes
+            .iter()
+            .map(|hash| CommonHashKey::new(height, *hash))
+            .collect::<Vec<_>>();
+
+        self.adapter
+            .batch_modify::<TransactionSchema>(
+                keys.clone(),
+                keys.iter().map(|_| StorageBatchModify::Remove).collect(),
+            )
+            .await?;
+        self.adapter
+            .batch_modify::<ReceiptSchema>(
+                keys.clone(),
+                keys.iter().map(|_| StorageBatchModify::Remove).collect(),
+            )
+            .await?;
+        self.adapter
+            .batch_modify::<TxHashNumberSchema>(
+                hashes.clone(),
+                hashes.iter().map(|_| StorageBatchModify::Remove).collect(),
+            )
+            .await?;
+        self.adapter
+            .remove::<BlockHashNumberSchema>(block_hash)
+            .await?;
+        self.adapter
+            .remove::<BlockHeaderSchema>(BlockKey::new(height))
+            .await?;
         self.adapter
             .remove::<BlockSchema>(BlockKey::new(height))
-            .await
+            .await?;
+
+        self.cache.blocks.lock().pop(&height);
+        self.cache.headers.lock().pop(&height);
+        self.cache.block_numbers.lock().pop(&block_hash);
+        for hash in hashes.iter() {
+            self.cache.transactions.lock().pop(hash);
+            self.cache.receipts.lock().pop(hash);
+        }
+
+        Ok(())
     }
 
     async fn get_latest_block(&self, _ctx: Context) -> ProtocolResult<Block> {
@@ -596,6 +831,7 @@ impl<Adapter: StorageAdapter> Storage for ImplStorage<Adapter> {
         block_height: u64,
         receipts: Vec<Receipt>,
     ) -> ProtocolResult<()> {
+        self.insert_log_index(&receipts, block_height).await?;
         self.batch_insert_receipts(receipts, block_height).await?;
 
         Ok(())
@@ -702,6 +938,89 @@ impl<Adapter: StorageAdapter> Storage for ImplStorage<Adapter> {
         Ok(hashes.iter().map(|h| found.remove(h)).collect::<Vec<_>>())
     }
 
+    #[trace_span(kind = "storage")]
+    async fn get_log_block_numbers(
+        &self,
+        ctx: Context,
+        from: BlockNumber,
+        to: BlockNumber,
+        addresses: &[H160],
+        topics: &[H256],
+    ) -> ProtocolResult<Vec<BlockNumber>> {
+        if from > to {
+            return Ok(Vec::new());
+        }
+
+        if !addresses.is_empty() || !topics.is_empty() {
+            if let Some(start) = self.get_log_index_start().await? {
+                if from < start {
+                    return Err(StorageError::LogsNotIndexed { from, start }.into());
+                }
+            }
+        }
+
+        let inst = Instant::now();
+        let by_address = (!addresses.is_empty())
+            .then(|| self.get_log_index(addresses.iter().map(|a| LogTerm::Address(*a)), from, to))
+            .transpose()?;
+        let by_topic = (!topics.is_empty())
+            .then(|| self.get_log_index(topics.iter().map(|t| LogTerm::Topic(*t)), from, to))
+            .transpose()?;
+        on_storage_get_cf(
+            StorageCategory::LogIndex,
+            inst.elapsed(),
+            (addresses.len() + topics.len()) as f64,
+        );
+
+        let numbers = match (by_address, by_topic) {
+            (Some(by_address), Some(by_topic)) => {
+                by_address.intersection(&by_topic).copied().collect()
+            }
+            (Some(numbers), None) | (None, Some(numbers)) => numbers.into_iter().collect(),
+            (None, None) => (from..=to).collect(),
+        };
+
+        Ok(numbers)
+    }
+
+    #[trace_span(kind = "storage")]
+    async fn insert_evidence(&self, ctx: Context, evidence: Evidence) -> ProtocolResult<()> {
+        let key = CommonHashKey::new(evidence.height, evidence.hash());
+        self.adapter.insert::<EvidenceSchema>(key, evidence).await
+    }
+
+    #[trace_span(kind = "storage")]
+    async fn get_evidences(
+        &self,
+        ctx: Context,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> ProtocolResult<Vec<Evidence>> {
+        if from > to {
+            return Ok(Vec::new());
+        }
+
+        let inst = Instant::now();
+        let prefix = CommonPrefix::new(from);
+        let prepare_iter = self.adapter.prepare_iter::<EvidenceSchema, _>(&prefix)?;
+
+        let mut evidences = Vec::new();
+        for item in prepare_iter.ref_to_iter() {
+            let (key, evidence) = item?;
+            if key.height() > to {
+                break;
+            }
+            evidences.push(evidence);
+        }
+        on_storage_get_cf(
+            StorageCategory::Evidence,
+            inst.elapsed(),
+            evidences.len() as f64,
+        );
+
+        Ok(evidences)
+    }
+
     async fn update_latest_proof(&self, _ctx: Context, proof: Proof) -> ProtocolResult<()> {
         self.adapter
             .insert::<LatestProofSchema>(*LATEST_PROOF_KEY, proof.clone())
@@ -729,6 +1048,16 @@ pub enum StorageError {
 
     #[display(fmt = "decode batch value")]
     BatchDecode,
+
+    #[display(
+        fmt = "the logs from block {} are not indexed, the first indexed block is {}",
+        from,
+        start
+    )]
+    LogsNotIndexed {
+        from:  BlockNumber,
+        start: BlockNumber,
+    },
 }
 
 impl Error for StorageError {}
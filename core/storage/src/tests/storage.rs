@@ -5,7 +5,7 @@ use std::sync::Arc;
 use test::Bencher;
 
 use protocol::traits::{CommonStorage, Context, Storage};
-use protocol::types::Hasher;
+use protocol::types::{Bytes, Evidence, EvidenceVote, Hasher, Log, H160, H256};
 
 use crate::adapter::memory::MemoryAdapter;
 use crate::tests::{get_random_bytes, mock_block, mock_proof, mock_receipt, mock_signed_tx};
@@ -31,6 +31,39 @@ fn test_storage_block_insert() {
     assert_eq!(height, block.unwrap().header.number);
 }
 
+#[test]
+fn test_storage_block_remove() {
+    let storage = ImplStorage::new(Arc::new(MemoryAdapter::new()));
+
+    let height = 100;
+    let mut block = mock_block(height, Hasher::digest(get_random_bytes(10)));
+    let stx = mock_signed_tx();
+    block.tx_hashes = vec![stx.transaction.hash];
+    let block_hash = block.header_hash();
+    let mut receipt = mock_receipt(stx.transaction.hash);
+    receipt.logs = vec![Log {
+        address: receipt.sender,
+        topics:  vec![H256::from_low_u64_be(1)],
+        data:    Vec::new(),
+    }];
+
+    exec!(storage.insert_block(Context::new(), block));
+    exec!(storage.insert_transactions(Context::new(), height, vec![stx.clone()]));
+    exec!(storage.insert_receipts(Context::new(), height, vec![receipt.clone()]));
+    exec!(storage.remove_block(Context::new(), height));
+
+    assert!(exec!(storage.get_block(Context::new(), height)).is_none());
+    assert!(exec!(storage.get_block_header(Context::new(), height)).is_none());
+    assert!(exec!(storage.get_block_by_hash(Context::new(), &block_hash)).is_none());
+    assert!(
+        exec!(storage.get_transaction_by_hash(Context::new(), &stx.transaction.hash)).is_none()
+    );
+    assert!(
+        exec!(storage.get_log_block_numbers(Context::new(), 0, height, &[receipt.sender], &[]))
+            .is_empty()
+    );
+}
+
 #[test]
 fn test_storage_receipts_insert() {
     let storage = ImplStorage::new(Arc::new(MemoryAdapter::new()));
@@ -57,6 +90,93 @@ fn test_storage_receipts_insert() {
     }
 }
 
+#[test]
+fn test_storage_evidence() {
+    let storage = ImplStorage::new(Arc::new(MemoryAdapter::new()));
+    let evidence = |height: u64, voter: &'static [u8]| {
+        let vote = |hash: u8| EvidenceVote {
+            block_hash: Bytes::from(vec![hash; 32]),
+            signature:  get_random_bytes(96),
+            voters:     vec![Bytes::from_static(voter)],
+        };
+        Evidence::new(height, 0, 1, Bytes::from_static(voter), vote(1), vote(2))
+    };
+
+    let evidences = vec![
+        evidence(10, b"alice"),
+        evidence(12, b"bob"),
+        evidence(12, b"carol"),
+    ];
+    for e in evidences.iter() {
+        exec!(storage.insert_evidence(Context::new(), e.clone()));
+    }
+
+    let search = |from, to| exec!(storage.get_evidences(Context::new(), from, to));
+    assert_eq!(search(0, 100).len(), 3);
+    assert_eq!(search(0, 11), vec![evidences[0].clone()]);
+    assert_eq!(search(11, 12).len(), 2);
+    assert!(search(13, 100).is_empty());
+    assert!(search(12, 10).is_empty());
+}
+
+#[test]
+fn test_storage_log_index() {
+    let storage = ImplStorage::new(Arc::new(MemoryAdapter::new()));
+    let (alice, bob) = (H160::from_low_u64_be(1), H160::from_low_u64_be(2));
+    let (transfer, approval) = (H256::from_low_u64_be(3), H256::from_low_u64_be(4));
+
+    let receipt_with_log = |sender: H160, topics: Vec<H256>| {
+        let mut receipt = mock_receipt(Hasher::digest(get_random_bytes(10)));
+        receipt.sender = sender;
+        receipt.logs = vec![Log {
+            address: sender,
+            topics,
+            data: Vec::new(),
+        }];
+        receipt
+    };
+
+    let blocks = vec![
+        vec![receipt_with_log(alice, vec![transfer])],
+        vec![
+            receipt_with_log(bob, vec![approval]),
+            mock_receipt(Hasher::digest(get_random_bytes(10))),
+        ],
+        vec![receipt_with_log(alice, vec![approval])],
+        vec![mock_receipt(Hasher::digest(get_random_bytes(10)))],
+    ];
+    for (number, receipts) in (10..).zip(blocks) {
+        exec!(storage.insert_receipts(Context::new(), number, receipts));
+    }
+
+    let search = |from, to, addresses: &[H160], topics: &[H256]| {
+        exec!(storage.get_log_block_numbers(Context::new(), from, to, addresses, topics))
+    };
+
+    assert_eq!(search(0, 100, &[alice], &[]), vec![10, 12]);
+    assert_eq!(search(11, 100, &[alice], &[]), vec![12]);
+    assert_eq!(search(0, 11, &[alice], &[]), vec![10]);
+    assert_eq!(search(0, 100, &[alice, bob], &[]), vec![10, 11, 12]);
+    assert_eq!(search(0, 100, &[], &[approval]), vec![11, 12]);
+    assert_eq!(search(0, 100, &[alice], &[approval]), vec![12]);
+    assert_eq!(search(0, 100, &[bob], &[transfer]), Vec::<u64>::new());
+    assert_eq!(search(10, 13, &[], &[]), vec![10, 11, 12, 13]);
+    assert_eq!(search(13, 10, &[], &[]), Vec::<u64>::new());
+
+    // The blocks before the first indexed one are not searched by criteria.
+    exec!(storage.set_log_index_start(11));
+    assert!(futures::executor::block_on(storage.get_log_block_numbers(
+        Context::new(),
+        10,
+        100,
+        &[alice],
+        &[]
+    ))
+    .is_err());
+    assert_eq!(search(11, 100, &[alice], &[]), vec![12]);
+    assert_eq!(search(10, 11, &[], &[]), vec![10, 11]);
+}
+
 #[test]
 fn test_storage_transactions_insert() {
     let storage = ImplStorage::new(Arc::new(MemoryAdapter::new()));
@@ -0,0 +1,79 @@
+use rlp::{Decodable, DecoderError, Encodable, Prototype, Rlp, RlpStream};
+
+use crate::types::{Bytes, Evidence, EvidenceVote};
+
+impl Encodable for EvidenceVote {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(3)
+            .append(&self.block_hash)
+            .append(&self.signature)
+            .append_list::<Bytes, _>(&self.voters);
+    }
+}
+
+impl Decodable for EvidenceVote {
+    fn decode(r: &Rlp) -> Result<Self, DecoderError> {
+        match r.prototype()? {
+            Prototype::List(3) => Ok(EvidenceVote {
+                block_hash: r.val_at(0)?,
+                signature:  r.val_at(1)?,
+                voters:     r.list_at(2)?,
+            }),
+            _ => Err(DecoderError::RlpExpectedToBeList),
+        }
+    }
+}
+
+impl Encodable for Evidence {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(6)
+            .append(&self.height)
+            .append(&self.round)
+            .append(&self.vote_type)
+            .append(&self.voter)
+            .append(&self.first)
+            .append(&self.second);
+    }
+}
+
+impl Decodable for Evidence {
+    fn decode(r: &Rlp) -> Result<Self, DecoderError> {
+        match r.prototype()? {
+            Prototype::List(6) => Ok(Evidence {
+                height:    r.val_at(0)?,
+                round:     r.val_at(1)?,
+                vote_type: r.val_at(2)?,
+                voter:     r.val_at(3)?,
+                first:     r.val_at(4)?,
+                second:    r.val_at(5)?,
+            }),
+            _ => Err(DecoderError::RlpExpectedToBeList),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evidence_codec() {
+        let vote = |hash: &'static [u8]| EvidenceVote {
+            block_hash: Bytes::from_static(hash),
+            signature:  Bytes::from_static(b"signature"),
+            voters:     vec![Bytes::from_static(b"alice"), Bytes::from_static(b"bob")],
+        };
+        let evidence = Evidence::new(
+            10,
+            1,
+            2,
+            Bytes::from_static(b"alice"),
+            vote(b"second"),
+            vote(b"first"),
+        );
+
+        assert_eq!(evidence.first.block_hash, Bytes::from_static(b"first"));
+        let decoded: Evidence = rlp::decode(&rlp::encode(&evidence)).unwrap();
+        assert_eq!(decoded, evidence);
+    }
+}
@@ -1,7 +1,9 @@
 pub mod block;
 pub mod error;
+pub mod evidence;
 pub mod executor;
 pub mod receipt;
+pub mod snap;
 pub mod transaction;
 
 use rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream};
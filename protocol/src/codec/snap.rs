@@ -0,0 +1,124 @@
+use rlp::{Decodable, DecoderError, Encodable, Prototype, Rlp, RlpStream};
+
+use crate::types::{Bytes, CodeRequest, Hash, StateEntry, StateRange, StateRangeRequest};
+
+impl Encodable for StateRangeRequest {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(3)
+            .append(&self.root)
+            .append(&self.start)
+            .append(&self.limit);
+    }
+}
+
+impl Decodable for StateRangeRequest {
+    fn decode(r: &Rlp) -> Result<Self, DecoderError> {
+        match r.prototype()? {
+            Prototype::List(3) => Ok(StateRangeRequest {
+                root:  r.val_at(0)?,
+                start: r.val_at(1)?,
+                limit: r.val_at(2)?,
+            }),
+            _ => Err(DecoderError::RlpExpectedToBeList),
+        }
+    }
+}
+
+impl Encodable for StateEntry {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(2).append(&self.key).append(&self.value);
+    }
+}
+
+impl Decodable for StateEntry {
+    fn decode(r: &Rlp) -> Result<Self, DecoderError> {
+        match r.prototype()? {
+            Prototype::List(2) => Ok(StateEntry {
+                key:   r.val_at(0)?,
+                value: r.val_at(1)?,
+            }),
+            _ => Err(DecoderError::RlpExpectedToBeList),
+        }
+    }
+}
+
+impl Encodable for StateRange {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(4)
+            .append_list(&self.entries)
+            .append(&self.more)
+            .append_list::<Bytes, _>(&self.first_proof)
+            .append_list::<Bytes, _>(&self.last_proof);
+    }
+}
+
+impl Decodable for StateRange {
+    fn decode(r: &Rlp) -> Result<Self, DecoderError> {
+        match r.prototype()? {
+            Prototype::List(4) => Ok(StateRange {
+                entries:     r.list_at(0)?,
+                more:        r.val_at(1)?,
+                first_proof: r.list_at(2)?,
+                last_proof:  r.list_at(3)?,
+            }),
+            _ => Err(DecoderError::RlpExpectedToBeList),
+        }
+    }
+}
+
+impl Encodable for CodeRequest {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(1).append_list::<Hash, _>(&self.hashes);
+    }
+}
+
+impl Decodable for CodeRequest {
+    fn decode(r: &Rlp) -> Result<Self, DecoderError> {
+        match r.prototype()? {
+            Prototype::List(1) => Ok(CodeRequest {
+                hashes: r.list_at(0)?,
+            }),
+            _ => Err(DecoderError::RlpExpectedToBeList),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_range_codec() {
+        let range = StateRange {
+            entries:     vec![
+                StateEntry {
+                    key:   Bytes::from(vec![1u8; 32]),
+                    value: Bytes::from(vec![2u8; 70]),
+                },
+                StateEntry {
+                    key:   Bytes::from(vec![3u8; 32]),
+                    value: Bytes::new(),
+                },
+            ],
+            more:        true,
+            first_proof: vec![Bytes::from(vec![4u8; 100])],
+            last_proof:  vec![Bytes::from(vec![5u8; 80]), Bytes::from(vec![6u8; 10])],
+        };
+        let decode: StateRange = rlp::decode(&rlp::encode(&range)).unwrap();
+        assert_eq!(range, decode);
+
+        let req = StateRangeRequest {
+            root:  Hash::repeat_byte(7),
+            start: Bytes::from(vec![8u8; 32]),
+            limit: 16,
+        };
+        let decode: StateRangeRequest = rlp::decode(&rlp::encode(&req)).unwrap();
+        assert_eq!(req, decode);
+
+        let req = CodeRequest {
+            hashes: vec![Hash::repeat_byte(9), Hash::zero()],
+        };
+        let decode: CodeRequest = rlp::decode(&rlp::encode(&req)).unwrap();
+        assert_eq!(req, decode);
+    }
+}
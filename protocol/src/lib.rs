@@ -44,6 +44,12 @@ pub struct ProtocolError {
     error: Box<dyn Error + Send>,
 }
 
+impl ProtocolError {
+    pub fn kind(&self) -> &ProtocolErrorKind {
+        &self.kind
+    }
+}
+
 impl From<ProtocolError> for Box<dyn Error + Send> {
     fn from(error: ProtocolError) -> Self {
         Box::new(error) as Box<dyn Error + Send>
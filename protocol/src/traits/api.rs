@@ -1,6 +1,8 @@
+use crate::tokio::sync::broadcast::Receiver;
 use crate::types::{
-    Account, Block, BlockNumber, Bytes, Hash, Header, Proposal, Receipt, SignedTransaction, TxResp,
-    H160, U256,
+    AccessList, Account, AccountProof, Block, BlockNumber, Bytes, Hash, Header, MemPoolContent,
+    NetworkNodeInfo, NetworkPeer, Proposal, Receipt, SignedTransaction, TraceResult, TracerType,
+    TxResp, H160, H256, U256,
 };
 use crate::{async_trait, traits::Context, ProtocolResult};
 
@@ -39,6 +41,17 @@ pub trait APIAdapter: Send + Sync {
         tx_hashes: &[Hash],
     ) -> ProtocolResult<Vec<Option<Receipt>>>;
 
+    /// Return the numbers of the blocks in `[from, to]` that may have logs
+    /// emitted by one of the addresses and with one of the topics.
+    async fn get_log_block_numbers(
+        &self,
+        ctx: Context,
+        from: BlockNumber,
+        to: BlockNumber,
+        addresses: &[H160],
+        topics: &[H256],
+    ) -> ProtocolResult<Vec<BlockNumber>>;
+
     async fn get_transaction_by_hash(
         &self,
         ctx: Context,
@@ -61,20 +74,64 @@ pub trait APIAdapter: Send + Sync {
 
     async fn get_pending_tx_count(&self, ctx: Context, address: H160) -> ProtocolResult<U256>;
 
+    async fn get_mempool_content(
+        &self,
+        ctx: Context,
+        from: Option<H160>,
+    ) -> ProtocolResult<MemPoolContent>;
+
+    fn subscribe_pending_txs(&self) -> Receiver<SignedTransaction>;
+
+    /// Subscribe to the numbers of the blocks committed from now on.
+    fn subscribe_new_blocks(&self) -> Receiver<BlockNumber>;
+
+    /// Get the gas limit in the metadata of the given block, which must be in
+    /// the current or the next epoch.
+    fn get_gas_limit(&self, ctx: Context, number: BlockNumber) -> u64;
+
+    #[allow(clippy::too_many_arguments)]
     async fn evm_call(
         &self,
         ctx: Context,
         from: Option<H160>,
         to: Option<H160>,
         data: Vec<u8>,
+        gas_limit: u64,
+        access_list: AccessList,
         state_root: Hash,
         proposal: Proposal,
     ) -> ProtocolResult<TxResp>;
 
+    /// Make the call and return the addresses and storage slots it accesses,
+    /// together with the response of the call made with that access list.
+    #[allow(clippy::too_many_arguments)]
+    async fn create_access_list(
+        &self,
+        ctx: Context,
+        from: Option<H160>,
+        to: Option<H160>,
+        data: Vec<u8>,
+        gas_limit: u64,
+        state_root: Hash,
+        proposal: Proposal,
+    ) -> ProtocolResult<(AccessList, TxResp)>;
+
     async fn get_code_by_hash(&self, ctx: Context, hash: &Hash) -> ProtocolResult<Option<Bytes>>;
 
     async fn peer_count(&self, ctx: Context) -> ProtocolResult<U256>;
 
+    async fn get_peers(&self, ctx: Context) -> ProtocolResult<Vec<NetworkPeer>>;
+
+    async fn get_node_info(&self, ctx: Context) -> ProtocolResult<NetworkNodeInfo>;
+
+    async fn add_peer(&self, ctx: Context, addr: &str) -> ProtocolResult<()>;
+
+    async fn remove_peer(&self, ctx: Context, peer_id: &str) -> ProtocolResult<()>;
+
+    async fn ban_peer(&self, ctx: Context, peer_id: &str, duration: u64) -> ProtocolResult<()>;
+
+    async fn unban_peer(&self, ctx: Context, peer: &str) -> ProtocolResult<()>;
+
     async fn get_storage_at(
         &self,
         ctx: Context,
@@ -82,4 +139,37 @@ pub trait APIAdapter: Send + Sync {
         position: U256,
         state_root: Hash,
     ) -> ProtocolResult<Bytes>;
+
+    /// Return the Merkle proofs of the account and the given storage slots in
+    /// the state with the given root.
+    async fn get_proof(
+        &self,
+        ctx: Context,
+        address: H160,
+        storage_keys: Vec<H256>,
+        state_root: Hash,
+    ) -> ProtocolResult<AccountProof>;
+
+    /// Replay the block with the given number on top of its parent state and
+    /// trace its transactions. If `tx_hash` is set, only that transaction is
+    /// traced.
+    async fn trace_block(
+        &self,
+        ctx: Context,
+        number: BlockNumber,
+        tx_hash: Option<Hash>,
+        tracer: TracerType,
+    ) -> ProtocolResult<Vec<(Hash, TraceResult)>>;
+
+    #[allow(clippy::too_many_arguments)]
+    async fn trace_call(
+        &self,
+        ctx: Context,
+        from: Option<H160>,
+        to: Option<H160>,
+        data: Vec<u8>,
+        state_root: Hash,
+        proposal: Proposal,
+        tracer: TracerType,
+    ) -> ProtocolResult<TraceResult>;
 }
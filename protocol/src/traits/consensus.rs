@@ -1,10 +1,15 @@
 use std::collections::HashMap;
 
 use crate::types::{
-    Address, Block, BlockNumber, Bytes, ExecResp, Hash, Header, Hex, Log, MerkleRoot, Metadata,
-    Proof, Proposal, Receipt, SignedTransaction, Validator, U256,
+    Address, Block, BlockNumber, Bytes, Evidence, ExecResp, Hash, Header, Hex, Log, MerkleRoot,
+    Metadata, Proof, Proposal, Receipt, SignedTransaction, StateEntry, StateRange,
+    StateRangeRequest, Validator, H160, H256, U256,
+};
+use crate::{
+    async_trait,
+    traits::{Context, TrustFeedback},
+    ProtocolResult,
 };
-use crate::{async_trait, traits::Context, ProtocolResult};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum MessageTarget {
@@ -32,6 +37,9 @@ pub trait Consensus: Send + Sync {
 
     /// Network set a received signed choke to consensus.
     async fn set_choke(&self, ctx: Context, choke: Vec<u8>) -> ProtocolResult<()>;
+
+    /// Network set a received evidence of double signing to consensus.
+    async fn set_evidence(&self, ctx: Context, evidence: Evidence) -> ProtocolResult<()>;
 }
 
 #[async_trait]
@@ -74,6 +82,41 @@ pub trait SynchronizationAdapter: CommonConsensusAdapter + Send + Sync {
         ctx: Context,
         number: BlockNumber,
     ) -> ProtocolResult<Proof>;
+
+    /// Pull a range of the trie entries with Merkle proofs from other nodes.
+    async fn get_state_range_from_remote(
+        &self,
+        ctx: Context,
+        req: StateRangeRequest,
+    ) -> ProtocolResult<StateRange>;
+
+    /// Pull contract codes corresponding to the given code hashes from other
+    /// nodes.
+    async fn get_codes_from_remote(
+        &self,
+        ctx: Context,
+        hashes: &[Hash],
+    ) -> ProtocolResult<Vec<Bytes>>;
+
+    /// Insert the entries into the local trie with the given root and return
+    /// the new root.
+    fn save_state_range(
+        &self,
+        ctx: Context,
+        root: MerkleRoot,
+        entries: &[StateEntry],
+    ) -> ProtocolResult<MerkleRoot>;
+
+    async fn save_code(
+        &self,
+        ctx: Context,
+        address: H256,
+        code_hash: Hash,
+        code: Bytes,
+    ) -> ProtocolResult<()>;
+
+    /// Report the behaviour of the peer that serves the synchronization.
+    fn report_peer(&self, ctx: Context, feedback: TrustFeedback);
 }
 
 #[async_trait]
@@ -83,6 +126,10 @@ pub trait CommonConsensusAdapter: Send + Sync {
 
     async fn save_proof(&self, ctx: Context, proof: Proof) -> ProtocolResult<()>;
 
+    /// Save a verified evidence of double signing to the database and record
+    /// it for the metadata of the next epoch.
+    async fn save_evidence(&self, ctx: Context, evidence: Evidence) -> ProtocolResult<()>;
+
     /// Save some signed transactions to the database.
     async fn save_signed_txs(
         &self,
@@ -140,6 +187,8 @@ pub trait CommonConsensusAdapter: Send + Sync {
 
     async fn broadcast_number(&self, ctx: Context, height: u64) -> ProtocolResult<()>;
 
+    async fn broadcast_evidence(&self, ctx: Context, evidence: Evidence) -> ProtocolResult<()>;
+
     fn set_args(&self, context: Context, state_root: MerkleRoot, gas_limit: u64, max_tx_size: u64);
 
     fn tag_consensus(&self, ctx: Context, peer_ids: Vec<Bytes>) -> ProtocolResult<()>;
@@ -212,4 +261,12 @@ pub trait ConsensusAdapter: CommonConsensusAdapter + Send + Sync {
     async fn pull_block(&self, ctx: Context, number: u64, end: &str) -> ProtocolResult<Block>;
 
     async fn verify_txs(&self, ctx: Context, number: u64, txs: &[Hash]) -> ProtocolResult<()>;
+
+    /// Get the balances of the given accounts in the state of the given root.
+    fn get_balances(
+        &self,
+        ctx: Context,
+        state_root: MerkleRoot,
+        addresses: &[H160],
+    ) -> ProtocolResult<Vec<U256>>;
 }
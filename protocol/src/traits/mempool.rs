@@ -1,4 +1,5 @@
-use crate::types::{BlockNumber, Hash, MerkleRoot, SignedTransaction, H160, U256};
+use crate::tokio::sync::broadcast::Receiver;
+use crate::types::{BlockNumber, Hash, MemPoolContent, MerkleRoot, SignedTransaction, H160, U256};
 use crate::{async_trait, traits::Context, ProtocolResult};
 
 #[async_trait]
@@ -35,7 +36,19 @@ pub trait MemPool: Send + Sync {
 
     async fn get_tx_count_by_address(&self, ctx: Context, address: H160) -> ProtocolResult<usize>;
 
+    /// Get a snapshot of the transactions in the pool, only the ones sent by
+    /// `from` if it is given. It does not change the pool.
+    async fn get_content(&self, ctx: Context, from: Option<H160>)
+        -> ProtocolResult<MemPoolContent>;
+
     fn set_args(&self, context: Context, state_root: MerkleRoot, gas_limit: u64, max_tx_size: u64);
+
+    /// Subscribe to the transactions admitted into the pool from now on.
+    fn subscribe_new_txs(&self) -> Receiver<SignedTransaction>;
+
+    /// Subscribe to the numbers of the blocks committed from now on, each of
+    /// which is sent once the pool is flushed with the block.
+    fn subscribe_committed(&self) -> Receiver<BlockNumber>;
 }
 
 #[async_trait]
@@ -57,6 +70,10 @@ pub trait MemPoolAdapter: Send + Sync {
     async fn check_authorization(&self, ctx: Context, tx: &SignedTransaction)
         -> ProtocolResult<()>;
 
+    /// Get the nonce expected by the next transaction of the address at the
+    /// latest state.
+    async fn get_account_nonce(&self, ctx: Context, address: &H160) -> ProtocolResult<U256>;
+
     async fn check_transaction(&self, ctx: Context, tx: &SignedTransaction) -> ProtocolResult<()>;
 
     async fn check_storage_exist(&self, ctx: Context, tx_hash: &Hash) -> ProtocolResult<()>;
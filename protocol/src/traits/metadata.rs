@@ -1,4 +1,4 @@
-use crate::types::{Header, Metadata, TxResp, H160};
+use crate::types::{Evidence, Header, Metadata, TxResp, H160};
 use crate::{traits::Context, ProtocolResult};
 
 pub trait MetadataControl: Sync + Send {
@@ -11,6 +11,17 @@ pub trait MetadataControl: Sync + Send {
     fn get_metadata(&self, ctx: Context, header: &Header) -> ProtocolResult<Metadata>;
 
     fn get_metadata_unchecked(&self, ctx: Context, block_number: u64) -> Metadata;
+
+    /// Record the evidence of a validator that signed two conflicting votes
+    /// in the epoch of the votes.
+    fn record_evidence(&self, ctx: Context, evidence: Evidence);
+
+    fn get_evidences(&self, ctx: Context, epoch: u64) -> Vec<Evidence>;
+
+    /// Return the addresses of the verifiers that double signed in the epoch,
+    /// the metadata appended for the next epoch should remove or penalise
+    /// them.
+    fn get_offenders(&self, ctx: Context, epoch: u64) -> Vec<H160>;
 }
 
 pub trait MetadataControlAdapter: Sync + Send {
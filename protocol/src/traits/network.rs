@@ -3,7 +3,7 @@ use std::{
     hash::{Hash, Hasher},
 };
 
-use crate::types::Bytes;
+use crate::types::{Bytes, NetworkNodeInfo, NetworkPeer};
 use crate::{async_trait, codec::ProtocolCodec, traits::Context, Display, ProtocolResult};
 
 #[derive(Clone, Debug, Copy)]
@@ -148,6 +148,25 @@ pub trait Network: Send + Sync {
     fn untag(&self, ctx: Context, peer_id: Bytes, tag: &PeerTag) -> ProtocolResult<()>;
     fn tag_consensus(&self, ctx: Context, peer_ids: Vec<Bytes>) -> ProtocolResult<()>;
     fn peer_count(&self, ctx: Context) -> ProtocolResult<usize>;
+
+    fn peers(&self, ctx: Context) -> ProtocolResult<Vec<NetworkPeer>>;
+
+    fn node_info(&self, ctx: Context) -> ProtocolResult<NetworkNodeInfo>;
+
+    /// Keep connecting to the peer at the multiaddr, which must end with the
+    /// peer id.
+    fn add_peer(&self, ctx: Context, addr: &str) -> ProtocolResult<()>;
+
+    /// Forget the addresses of the peer and disconnect it.
+    fn remove_peer(&self, ctx: Context, peer_id: &str) -> ProtocolResult<()>;
+
+    /// Ban the peer and the IPs of its known addresses for the given seconds,
+    /// and disconnect it if it is connected.
+    fn ban_peer(&self, ctx: Context, peer_id: &str, duration: u64) -> ProtocolResult<()>;
+
+    /// Lift the bans on a peer by its id, or on an IP network listed in the
+    /// node info.
+    fn unban_peer(&self, ctx: Context, peer: &str) -> ProtocolResult<()>;
 }
 
 pub trait PeerTrust: Send + Sync {
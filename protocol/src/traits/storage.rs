@@ -1,4 +1,7 @@
-use crate::types::{Block, Bytes, Hash, Header, Proof, Receipt, SignedTransaction, H256};
+use crate::types::{
+    Block, BlockNumber, Bytes, Evidence, Hash, Header, Proof, Receipt, SignedTransaction, H160,
+    H256,
+};
 use crate::{async_trait, codec::ProtocolCodec, traits::Context, Display, ProtocolResult};
 
 #[derive(Debug, Copy, Clone, Display)]
@@ -10,6 +13,8 @@ pub enum StorageCategory {
     Wal,
     HashHeight,
     Code,
+    LogIndex,
+    Evidence,
 }
 
 pub type StorageIterator<'a, S> = Box<
@@ -111,6 +116,30 @@ pub trait Storage: CommonStorage {
         hashes: &[Hash],
     ) -> ProtocolResult<Vec<Option<Receipt>>>;
 
+    /// Return the numbers of the blocks in `[from, to]` that have logs
+    /// matching the criteria, which are looked up in the log index. A block
+    /// matches if it has a log emitted by one of the addresses and a log with
+    /// one of the topics, an empty criterion matches every block.
+    async fn get_log_block_numbers(
+        &self,
+        ctx: Context,
+        from: BlockNumber,
+        to: BlockNumber,
+        addresses: &[H160],
+        topics: &[H256],
+    ) -> ProtocolResult<Vec<BlockNumber>>;
+
+    /// Save the evidence of a validator that signed two conflicting votes.
+    async fn insert_evidence(&self, ctx: Context, evidence: Evidence) -> ProtocolResult<()>;
+
+    /// Return the evidences of the votes with heights in `[from, to]`.
+    async fn get_evidences(
+        &self,
+        ctx: Context,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> ProtocolResult<Vec<Evidence>>;
+
     async fn update_latest_proof(&self, ctx: Context, proof: Proof) -> ProtocolResult<()>;
 
     async fn get_latest_proof(&self, ctx: Context) -> ProtocolResult<Proof>;
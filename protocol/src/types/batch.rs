@@ -29,6 +29,7 @@ macro_rules! batch_msg_type {
 
 batch_msg_type!(BatchSignedTxs, SignedTransaction);
 batch_msg_type!(BatchBlocks, Block);
+batch_msg_type!(BatchCodes, Bytes);
 
 #[cfg(test)]
 mod tests {
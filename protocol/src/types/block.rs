@@ -10,6 +10,11 @@ pub type BlockNumber = u64;
 
 pub const MAX_BLOCK_GAS_LIMIT: u64 = 30_000_000;
 pub const BASE_FEE_PER_GAS: u64 = 0x539;
+/// The bound divisor of the base fee, which limits the change of the base fee
+/// to 12.5% per block.
+pub const BASE_FEE_CHANGE_DENOMINATOR: u64 = 8;
+/// The gas target of a block is the gas limit divided by the multiplier.
+pub const ELASTICITY_MULTIPLIER: u64 = 2;
 
 #[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
 pub struct Proposal {
@@ -150,6 +155,27 @@ impl Header {
     pub fn size(&self) -> usize {
         self.encode().unwrap().len()
     }
+
+    /// Calculate the base fee per gas of the next block by EIP-1559, the gas
+    /// target is half of the given gas limit in metadata.
+    pub fn next_base_fee_per_gas(&self, gas_limit: u64) -> U256 {
+        let gas_target = U256::from(gas_limit / ELASTICITY_MULTIPLIER);
+        let base_fee = self.base_fee_per_gas;
+
+        if gas_target.is_zero() || self.gas_used == gas_target {
+            return base_fee;
+        }
+
+        if self.gas_used > gas_target {
+            let delta =
+                base_fee * (self.gas_used - gas_target) / gas_target / BASE_FEE_CHANGE_DENOMINATOR;
+            base_fee + delta.max(U256::one())
+        } else {
+            let delta =
+                base_fee * (gas_target - self.gas_used) / gas_target / BASE_FEE_CHANGE_DENOMINATOR;
+            base_fee.saturating_sub(delta)
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
@@ -181,6 +207,35 @@ mod tests {
             .as_millis() as u64
     }
 
+    #[test]
+    fn test_next_base_fee_per_gas() {
+        let mut header = Header {
+            base_fee_per_gas: 1_000_000_000u64.into(),
+            gas_used: 15_000_000u64.into(),
+            ..Default::default()
+        };
+        assert_eq!(
+            header.next_base_fee_per_gas(30_000_000),
+            1_000_000_000u64.into()
+        );
+
+        header.gas_used = 30_000_000u64.into();
+        assert_eq!(
+            header.next_base_fee_per_gas(30_000_000),
+            1_125_000_000u64.into()
+        );
+
+        header.gas_used = 0u64.into();
+        assert_eq!(
+            header.next_base_fee_per_gas(30_000_000),
+            875_000_000u64.into()
+        );
+
+        header.base_fee_per_gas = 1u64.into();
+        header.gas_used = 20_000_000u64.into();
+        assert_eq!(header.next_base_fee_per_gas(30_000_000), 2u64.into());
+    }
+
     #[test]
     fn print_genesis() {
         let genesis = RichBlock {
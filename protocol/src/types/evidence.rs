@@ -0,0 +1,56 @@
+use crate::types::{Bytes, Hash, Hasher};
+
+/// A vote signed by a single validator, or a quorum certificate aggregated
+/// from the signatures of several validators. The voters are the addresses
+/// used by overlord, which are the public keys of the validators.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EvidenceVote {
+    pub block_hash: Bytes,
+    pub signature:  Bytes,
+    pub voters:     Vec<Bytes>,
+}
+
+/// The proof that a validator signed two votes for different blocks with the
+/// same height, round and vote type. The vote type is `1` for prevote and `2`
+/// for precommit, the same as overlord.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Evidence {
+    pub height:    u64,
+    pub round:     u64,
+    pub vote_type: u8,
+    pub voter:     Bytes,
+    pub first:     EvidenceVote,
+    pub second:    EvidenceVote,
+}
+
+impl Evidence {
+    /// The two votes are ordered by the block hash so that the evidence found
+    /// by different nodes has the same hash.
+    pub fn new(
+        height: u64,
+        round: u64,
+        vote_type: u8,
+        voter: Bytes,
+        a: EvidenceVote,
+        b: EvidenceVote,
+    ) -> Self {
+        let (first, second) = if a.block_hash <= b.block_hash {
+            (a, b)
+        } else {
+            (b, a)
+        };
+
+        Evidence {
+            height,
+            round,
+            vote_type,
+            voter,
+            first,
+            second,
+        }
+    }
+
+    pub fn hash(&self) -> Hash {
+        Hasher::digest(rlp::encode(self))
+    }
+}
@@ -1,8 +1,17 @@
 pub use ethereum::{AccessList, AccessListItem, Account};
 pub use evm::{backend::Log, Config, ExitError, ExitFatal, ExitReason, ExitRevert, ExitSucceed};
 
+use std::sync::Arc;
+
+use cita_trie::{MemoryDB, PatriciaTrie, Trie};
+use hasher::HasherKeccak;
+
 use crate::codec::ProtocolCodec;
-use crate::types::{Hash, Hasher, Header, MerkleRoot, Proposal, H160, U256};
+use crate::types::{
+    Bytes, Hash, Hasher, Header, MerkleRoot, Proposal, TypesError, H160, H256, NIL_DATA, RLP_NULL,
+    U256,
+};
+use crate::ProtocolResult;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ExecResp {
@@ -73,3 +82,97 @@ impl From<&Header> for ExecutorContext {
         }
     }
 }
+
+/// The Merkle proofs of an account and some of its storage slots, see
+/// EIP-1186. The account trie is keyed by the address and the storage trie by
+/// the slot, neither key is hashed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccountProof {
+    pub address:       H160,
+    pub account:       Account,
+    pub account_proof: Vec<Bytes>,
+    pub storage_proof: Vec<StorageProof>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StorageProof {
+    pub key:   H256,
+    pub value: H256,
+    pub proof: Vec<Bytes>,
+}
+
+impl AccountProof {
+    /// Verify the proofs against the given state root. An absent account is
+    /// proved as an empty one and an absent slot as zero.
+    pub fn verify(&self, state_root: MerkleRoot) -> ProtocolResult<()> {
+        let account =
+            match verify_trie_proof(state_root, self.address.as_bytes(), &self.account_proof)? {
+                Some(raw) => Account::decode(raw)?,
+                None => Account {
+                    nonce:        U256::zero(),
+                    balance:      U256::zero(),
+                    storage_root: RLP_NULL,
+                    code_hash:    NIL_DATA,
+                },
+            };
+        if account != self.account {
+            return Err(TypesError::InvalidStateProof(format!(
+                "account {:?} mismatches",
+                self.address
+            ))
+            .into());
+        }
+
+        for slot in self.storage_proof.iter() {
+            let value = verify_trie_proof(account.storage_root, slot.key.as_bytes(), &slot.proof)?
+                .map(|raw| {
+                    if raw.len() == H256::len_bytes() {
+                        Ok(H256::from_slice(&raw))
+                    } else {
+                        Err(TypesError::InvalidStateProof(format!(
+                            "storage value {:?} of {:?}",
+                            raw, slot.key
+                        )))
+                    }
+                })
+                .transpose()?
+                .unwrap_or_default();
+
+            if value != slot.value {
+                return Err(TypesError::InvalidStateProof(format!(
+                    "storage {:?} mismatches",
+                    slot.key
+                ))
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Verify the proof of a key in the trie with the given root and return the
+/// value, or `None` if the proof shows the key is absent.
+fn verify_trie_proof(
+    root: MerkleRoot,
+    key: &[u8],
+    proof: &[Bytes],
+) -> ProtocolResult<Option<Bytes>> {
+    // An empty trie has no node to prove anything with.
+    if root == RLP_NULL {
+        return if proof.is_empty() {
+            Ok(None)
+        } else {
+            Err(TypesError::InvalidStateProof("proof of an empty trie".to_string()).into())
+        };
+    }
+
+    let hasher = Arc::new(HasherKeccak::new());
+    let trie = PatriciaTrie::new(Arc::new(MemoryDB::new(true)), hasher);
+    let proof = proof.iter().map(|node| node.to_vec()).collect();
+
+    Ok(trie
+        .verify_proof(root.as_bytes(), key, proof)
+        .map_err(|e| TypesError::InvalidStateProof(format!("{:?}", e)))?
+        .map(Bytes::from))
+}
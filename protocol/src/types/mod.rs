@@ -4,22 +4,31 @@ pub use batch::*;
 pub use block::*;
 pub use bytes::{Buf, BufMut, Bytes, BytesMut};
 pub use ckb_client::*;
+pub use evidence::*;
 pub use evm::{backend::*, ExitRevert, ExitSucceed};
 pub use executor::{
-    AccessList, AccessListItem, Account, Config, ExecResp, ExecutorContext, ExitReason, TxResp,
+    AccessList, AccessListItem, Account, AccountProof, Config, ExecResp, ExecutorContext,
+    ExitError, ExitReason, StorageProof, TxResp,
 };
 pub use interoperation::VMResp;
+pub use network::*;
 pub use primitive::*;
 pub use receipt::*;
+pub use snap::*;
+pub use trace::*;
 pub use transaction::*;
 
 pub mod batch;
 pub mod block;
 pub mod ckb_client;
+pub mod evidence;
 pub mod executor;
 pub mod interoperation;
+pub mod network;
 pub mod primitive;
 pub mod receipt;
+pub mod snap;
+pub mod trace;
 pub mod transaction;
 
 use std::error::Error;
@@ -62,6 +71,9 @@ pub enum TypesError {
 
     #[display(fmt = "Crypto error {:?}", _0)]
     Crypto(CryptoError),
+
+    #[display(fmt = "Invalid state proof: {}", _0)]
+    InvalidStateProof(String),
 }
 
 impl Error for TypesError {}
@@ -0,0 +1,30 @@
+/// A connected peer as seen by the peer manager. The peer ids are in base58
+/// and the addresses are multiaddrs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NetworkPeer {
+    pub peer_id:        String,
+    pub connected_addr: String,
+    pub listen_addrs:   Vec<String>,
+    pub inbound:        bool,
+    pub score:          i32,
+    /// The tags such as `consensus`, `bootstrap` and `static`.
+    pub tags:           Vec<String>,
+}
+
+/// An IP network banned from connecting, with the unix time in milliseconds
+/// the ban ends.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NetworkBan {
+    pub address:   String,
+    pub ban_until: u64,
+    pub reason:    String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NetworkNodeInfo {
+    pub peer_id:         String,
+    pub listen_addrs:    Vec<String>,
+    pub chain_id:        String,
+    pub connected_peers: usize,
+    pub banned:          Vec<NetworkBan>,
+}
@@ -0,0 +1,48 @@
+use crate::types::{Bytes, Hash, MerkleRoot};
+
+/// The maximum number of entries a peer returns for one state range request.
+pub const MAX_STATE_RANGE_ENTRIES: u64 = 1024;
+/// The maximum number of codes a peer returns for one code request.
+pub const MAX_CODE_REQUEST_HASHES: usize = 64;
+
+/// Ask a peer for the key-value pairs of the trie with the given root, in key
+/// order, starting from `start` (inclusive).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StateRangeRequest {
+    pub root:  MerkleRoot,
+    pub start: Bytes,
+    pub limit: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StateEntry {
+    pub key:   Bytes,
+    pub value: Bytes,
+}
+
+/// A continuous range of trie entries. The first and the last entries come with
+/// Merkle proofs against the requested root so that a forged range is rejected
+/// before it is written.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StateRange {
+    pub entries:     Vec<StateEntry>,
+    pub more:        bool,
+    pub first_proof: Vec<Bytes>,
+    pub last_proof:  Vec<Bytes>,
+}
+
+impl StateRange {
+    pub fn first_key(&self) -> Option<&Bytes> {
+        self.entries.first().map(|e| &e.key)
+    }
+
+    pub fn last_key(&self) -> Option<&Bytes> {
+        self.entries.last().map(|e| &e.key)
+    }
+}
+
+/// Ask a peer for the contract codes with the given code hashes.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CodeRequest {
+    pub hashes: Vec<Hash>,
+}
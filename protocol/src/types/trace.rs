@@ -0,0 +1,92 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{Hex, H160, H256, U256};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StructLoggerConfig {
+    pub disable_storage: bool,
+    pub disable_stack:   bool,
+    pub enable_memory:   bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TracerType {
+    StructLog(StructLoggerConfig),
+    Call { only_top_call: bool },
+    Prestate,
+}
+
+impl Default for TracerType {
+    fn default() -> Self {
+        TracerType::StructLog(StructLoggerConfig::default())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum TraceResult {
+    StructLog(StructLogResult),
+    Call(CallFrame),
+    Prestate(BTreeMap<H160, PrestateAccount>),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLogResult {
+    pub gas:          u64,
+    pub failed:       bool,
+    pub return_value: Hex,
+    pub struct_logs:  Vec<StructLog>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLog {
+    pub pc:       u64,
+    pub op:       String,
+    pub gas:      u64,
+    pub gas_cost: u64,
+    pub depth:    u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stack:    Option<Vec<U256>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory:   Option<Vec<Hex>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage:  Option<BTreeMap<H256, H256>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error:    Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CallFrame {
+    #[serde(rename = "type")]
+    pub call_type: String,
+    pub from:      H160,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to:        Option<H160>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value:     Option<U256>,
+    pub gas:       U256,
+    pub gas_used:  U256,
+    pub input:     Hex,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output:    Option<Hex>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error:     Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub calls:     Vec<CallFrame>,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PrestateAccount {
+    pub balance: U256,
+    pub nonce:   U256,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code:    Option<Hex>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub storage: BTreeMap<H256, H256>,
+}
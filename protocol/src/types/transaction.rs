@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 pub use ethereum::{
     AccessList, AccessListItem, EIP1559TransactionMessage as TransactionMessage, TransactionAction,
     TransactionRecoveryId, TransactionSignature,
@@ -167,6 +169,30 @@ impl SignedTransaction {
     }
 }
 
+/// The transactions of a sender in the mempool, keyed by nonce.
+pub type AccountTxs = BTreeMap<U256, SignedTransaction>;
+
+/// A snapshot of the transactions in the mempool. The pending ones are ready
+/// to be packaged while the queued ones wait for the gap before their nonces
+/// to be filled. System script transactions are packaged regardless of nonce
+/// so they are listed apart.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MemPoolContent {
+    pub pending: BTreeMap<H160, AccountTxs>,
+    pub queued:  BTreeMap<H160, AccountTxs>,
+    pub system:  Vec<SignedTransaction>,
+}
+
+impl MemPoolContent {
+    pub fn pending_len(&self) -> usize {
+        self.pending.values().map(|txs| txs.len()).sum()
+    }
+
+    pub fn queued_len(&self) -> usize {
+        self.queued.values().map(|txs| txs.len()).sum()
+    }
+}
+
 pub fn public_to_address(public: &Public) -> H160 {
     let hash = Hasher::digest(public);
     let mut ret = H160::zero();